@@ -31,13 +31,14 @@ use proc_macro::{LexError, TokenStream};
 /// Defines an intercom interface.
 ///
 /// ```rust,ignore
-/// #[com_interface(IID, base?)]
+/// #[com_interface(IID, base?, dual?)]
 /// trait Foo { /* ... */ }
 /// ```
 ///
 /// - `IID` - A unique ID of the interface used to query for it. Must be either
 ///           a valid GUID or `AUTO_GUID` specifier.
 /// - `base` - Base interface. Defaults to `IUnknown` if not specified.
+/// - `dual` - Optional flag that exposes the interface through `IDispatch`.
 ///
 /// Associated types: `trait`, `impl Struct`
 ///
@@ -48,6 +49,12 @@ use proc_macro::{LexError, TokenStream};
 /// Each interface automatically inherits from the base `IUnknown` interface,
 /// which provides the clients a way to perform reference counting and the
 /// ability to query for other interfaces the object might implement.
+///
+/// Interfaces marked `dual` inherit from `IDispatch` instead and can be called
+/// by late bound clients, such as scripting languages, in addition to the
+/// virtual table. The dispatch IDs follow the member order starting from 1.
+/// The arguments and return values are converted through `Variant`. Dual
+/// interfaces cannot specify a `base`.
 #[proc_macro_attribute]
 pub fn com_interface(attr: TokenStream, tokens: TokenStream) -> TokenStream
 {
@@ -57,6 +64,66 @@ pub fn com_interface(attr: TokenStream, tokens: TokenStream) -> TokenStream
     }
 }
 
+/// Declares the interfaces of an IDL file.
+///
+/// ```rust,ignore
+/// com_import!("path/to/file.idl");
+/// ```
+///
+/// - `path` - Path of the IDL file relative to the crate root.
+///
+/// The macro declares each object interface of the file as a
+/// `#[com_interface]` trait with the IID of the interface as the `com_iid`.
+/// The interfaces deriving from `IDispatch` are declared `dual` and the
+/// interfaces deriving from other interfaces of the file use them as the
+/// `base`. The methods take `&self` and the methods returning `HRESULT`
+/// return the `[out]` and `[retval]` parameters in a `ComResult`.
+///
+/// The IDL types are mapped to the intercom types, such as `BSTR` to `&str`
+/// and `String`, `VARIANT` to `Variant` and interface pointers to `ComItf`
+/// and `ComRc`. `[unique]` interface pointers are wrapped in an `Option`.
+/// Declarations other than interfaces and the constructs that have no
+/// intercom counterpart, such as conformant arrays and `[in, out]`
+/// parameters, are reported as errors.
+#[proc_macro]
+pub fn com_import(args: TokenStream) -> TokenStream
+{
+    match expand_com_import(args) {
+        Ok(t) => t,
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Exposes an interface method as a property accessor.
+///
+/// ```rust,ignore
+/// #[com_interface]
+/// trait IFoo {
+///     #[com_property]
+///     fn get_value(&self) -> ComResult<u32>;
+///
+///     #[com_property]
+///     fn set_value(&mut self, value: u32) -> ComResult<()>;
+/// }
+/// ```
+///
+/// Associated types: methods of `#[com_interface]` traits and impls
+///
+/// Getters take no arguments and return the value in a `Result`. Setters take
+/// the value as the only argument and return a `Result` with no value. The
+/// property is named after the method without the `get_`, `set_` or `put_`
+/// prefix and the getter and the setter of the same property share the name.
+///
+/// The type library describes the methods as `propget` and `propput`
+/// accessors and the dual interfaces serve them through
+/// `DISPATCH_PROPERTYGET` and `DISPATCH_PROPERTYPUT`. The attribute itself
+/// leaves the method unchanged.
+#[proc_macro_attribute]
+pub fn com_property(_attr: TokenStream, tokens: TokenStream) -> TokenStream
+{
+    tokens
+}
+
 /// Defines a COM class that implements one or more COM interfaces.
 ///
 /// ```rust,ignore
@@ -68,12 +135,42 @@ pub fn com_interface(attr: TokenStream, tokens: TokenStream) -> TokenStream
 ///             to specify the class when they want to construct an object.
 ///             The value must be a valid GUID, `AUTO_GUID` or `NO_GUID`.
 /// - `interfaces` - Any number of interfaces that the class implements.
+/// - `factory = path` - Optional function used to construct the instances.
+/// - `allocator = path` - Optional static `ComAllocator` for the instances.
+/// - `threading = "model"` - Optional concurrency policy for the instances.
+/// - `aggregatable = true` - Optional flag that allows aggregating the class.
 ///
 /// Associated types: `struct`, `enum`
 ///
 /// If the `CLSID` is specified as `NO_GUID`, the class cannot be constructed
 /// by the clients. It can still be returned as a return value from other
 /// intercom methods.
+///
+/// Classes that are not declared `aggregatable = true` refuse to be
+/// aggregated by other COM objects with `CLASS_E_NOAGGREGATION`.
+///
+/// Calls rejected by the threading model of the class are reported through
+/// the `ComResult` or `HRESULT` of the method. Methods that cannot return an
+/// error abort the process instead. See `intercom::threading` for details.
+///
+/// The class factory constructs the instances with `Default::default()`
+/// unless a `factory` function is specified. The function has the signature
+/// `fn(intercom::Variant) -> ComResult<S>` and receives the arguments given
+/// to `IIntercomClassFactoryEx::create_instance_with`, or `Variant::None`
+/// when the instance is created through `IClassFactory`. Errors returned by
+/// the function are reported to the client as the creation result. The
+/// function is used even if the class also implements `Default`.
+///
+/// The instances are allocated from the heap unless an `allocator` is
+/// specified. The allocator must be a `static` implementing
+/// `intercom::allocator::ComAllocator`, such as a `PoolAllocator`.
+///
+/// The `threading` model is one of `"single"`, `"mutex"` or `"free"`, the
+/// last being the default. Single threaded instances reject calls from other
+/// than the creating thread with `RPC_E_WRONG_THREAD` and mutex instances
+/// serialize the calls. Calls that would alias a `&mut self` method, such as
+/// reentrant calls through callbacks, fail with `E_ILLEGAL_METHOD_CALL` in
+/// every model. See `intercom::threading` for details.
 #[proc_macro_attribute]
 pub fn com_class(attr: TokenStream, tokens: TokenStream) -> TokenStream
 {
@@ -112,10 +209,22 @@ pub fn com_module(args: TokenStream) -> TokenStream
 /// - `libid` - A unique ID that specifies the current intercom library.
 ///             Optional, the libid is generated randomly if omitted.
 /// - `items` - List of items contained in this library.
+/// - `on_unload` - Optional function invoked when the library can be unloaded.
 ///
 /// The macro results in the implementation of the object creation
 /// infrastructure that allows external clients to load the library and
 /// instantiate the specified types.
+///
+/// The generated `DllCanUnloadNow` reports that the library can be unloaded
+/// once it has no live objects or server locks left. The `on_unload`
+/// function is invoked the first time this happens. COM polls
+/// `DllCanUnloadNow` periodically and may keep the library loaded even after
+/// it has reported that it can be unloaded, so the library must remain
+/// usable after `on_unload` returns. The function is invoked again only if
+/// the library creates objects or acquires server locks in between. A panic
+/// in the function is reported to the panic handler and returned as the
+/// result of `DllCanUnloadNow`, which keeps the library loaded, and the
+/// function is invoked again on the next poll.
 #[proc_macro]
 pub fn com_library(args: TokenStream) -> TokenStream
 {
@@ -126,6 +235,9 @@ pub fn com_library(args: TokenStream) -> TokenStream
 }
 
 /// Derives the implementation of the trait ForeignType for a type.
+///
+/// Enums are described as enum references and other types as struct
+/// references in the type library.
 #[proc_macro_derive(ForeignType)]
 pub fn named_type_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 {
@@ -136,6 +248,16 @@ pub fn named_type_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStr
 }
 
 /// Derives the implementation of the trait ExternType for a type.
+///
+/// For `#[repr(C)]` structs with named fields the derive also describes the
+/// struct in the type library. The struct is included in the library with a
+/// `struct` item in `com_library!`.
+///
+/// Enums without fields are passed as their discriminants, using the integer
+/// type given with `#[repr(...)]` or `i32` by default. Unknown discriminants
+/// are rejected with `E_INVALIDARG` so the enums can only be used in methods
+/// that return `ComResult`. The enum is included in the library with an
+/// `enum` item in `com_library!`.
 #[proc_macro_derive(ExternType)]
 pub fn derive_extern_type(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 {
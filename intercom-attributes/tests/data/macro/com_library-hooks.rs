@@ -6,6 +6,8 @@ fn custom_load() -> ComResult<()>
     Ok(())
 }
 
+fn custom_unload() {}
+
 fn custom_register() -> ComResult<()>
 {
     Ok(())
@@ -19,6 +21,7 @@ fn custom_unregister() -> ComResult<()>
 com_library!(
     libid = "00000000-0000-0000-0000-000000000000",
     on_load = custom_load,
+    on_unload = custom_unload,
     on_register = custom_register,
     on_unregister = custom_unregister,
 );
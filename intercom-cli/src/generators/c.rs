@@ -0,0 +1,596 @@
+//! Enables the generation of C header files for using intercom libraries
+//! from C and from the languages that bind to C.
+//!
+//! The header declares the interfaces as structs of virtual table pointers
+//! in the same way the COBJMACROS style Windows SDK headers do. The
+//! declarations stay within C89 so that the header works with the oldest
+//! toolchains. The toolchains without `<stdint.h>` get the fixed width
+//! integer types from fallback typedefs.
+
+use std::borrow::Cow;
+use std::io::Write;
+
+use super::cpp::guid_as_struct;
+use super::GeneratorError;
+use super::{doc_lines, pascal_case, LibraryContext, ModelOptions, TypeSystemOptions};
+
+use intercom::typelib::{
+    CoClass, Enum, Interface, InterfaceVariant, Method, MethodKind, Primitive, StringEncoding,
+    Struct, TypeDesc, TypeInfo, TypeLib,
+};
+
+use handlebars::Handlebars;
+use serde_derive::Serialize;
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CLibrary
+{
+    pub lib_name: String,
+    pub enums: Vec<CEnum>,
+    pub structs: Vec<CStruct>,
+    pub interfaces: Vec<CInterface>,
+    pub coclasses: Vec<CClass>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CEnum
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub base: String,
+    pub variants: Vec<CEnumVariant>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CEnumVariant
+{
+    pub name: String,
+    pub value: i64,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CStruct
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub size: u32,
+    pub fields: Vec<CField>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CField
+{
+    pub name: String,
+    pub field_type: String,
+    pub offset: u32,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CInterface
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub iid_struct: String,
+
+    /// The virtual table grouped by the interface that declares the methods,
+    /// starting from IUnknown.
+    pub vtbl: Vec<CVtblSection>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CVtblSection
+{
+    pub name: String,
+    pub this_type: String,
+    pub methods: Vec<CMethod>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CMethod
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub ret_type: String,
+    pub args: Vec<CArg>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CArg
+{
+    pub name: String,
+    pub arg_type: String,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CClass
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub clsid_struct: String,
+}
+
+impl CLibrary
+{
+    fn try_from(lib: TypeLib, opts: &ModelOptions) -> Result<Self, GeneratorError>
+    {
+        let ctx = LibraryContext::from(&lib);
+
+        let mut enums = vec![];
+        let mut coclasses = vec![];
+        for t in &lib.types {
+            match t {
+                TypeInfo::Class(cls) => coclasses.push(CClass::from(cls.as_ref())),
+                TypeInfo::Enum(e) => enums.push(CEnum::from(e.as_ref())),
+
+                // The library context lists the structs and the interfaces in
+                // dependency order.
+                TypeInfo::Struct(_) | TypeInfo::Interface(_) => {}
+            }
+        }
+        let interfaces = ctx
+            .interfaces
+            .iter()
+            .map(|itf| CInterface::gather(itf, opts, &ctx))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<CInterface>>();
+        let structs = ctx
+            .structs
+            .iter()
+            .map(|s| CStruct::from(s, opts, &ctx))
+            .collect();
+
+        Ok(Self {
+            lib_name: lib.name.to_string(),
+            enums,
+            structs,
+            interfaces,
+            coclasses,
+        })
+    }
+}
+
+impl CEnum
+{
+    fn from(e: &Enum) -> Self
+    {
+        CEnum {
+            name: e.name.to_string(),
+            doc: comment_lines(&e.doc),
+            base: primitive_type(&e.ty).to_string(),
+            variants: e
+                .variants
+                .iter()
+                .map(|v| CEnumVariant {
+                    name: format!("{}_{}", e.name, v.name),
+                    value: v.value,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CStruct
+{
+    fn from(s: &Struct, opts: &ModelOptions, ctx: &LibraryContext) -> Self
+    {
+        // The struct layout doesn't depend on the type system. Interface
+        // pointers in the fields refer to the first type system.
+        let ts_opts = &opts.type_systems[0];
+        CStruct {
+            name: s.name.to_string(),
+            doc: comment_lines(&s.doc),
+            size: s.size,
+            fields: s
+                .fields
+                .iter()
+                .map(|f| CField {
+                    name: identifier(&f.name),
+                    field_type: type_desc_name(&f.ty, ts_opts, ctx),
+                    offset: f.offset,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CInterface
+{
+    fn gather(
+        itf: &Interface,
+        opts: &ModelOptions,
+        ctx: &LibraryContext,
+    ) -> Result<Vec<Self>, GeneratorError>
+    {
+        opts.type_systems
+            .iter()
+            .filter_map(|ts_opts| {
+                itf.variants
+                    .iter()
+                    .find(|v| v.as_ref().ts == ts_opts.ts)
+                    .map(|v| CInterface::try_from(itf, v.as_ref(), ts_opts, ctx))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    fn try_from(
+        itf: &Interface,
+        itf_variant: &InterfaceVariant,
+        ts_opts: &TypeSystemOptions,
+        ctx: &LibraryContext,
+    ) -> Result<Self, GeneratorError>
+    {
+        let name = final_name(itf, ts_opts);
+        Ok(Self {
+            doc: comment_lines(&itf.doc),
+            iid_struct: guid_as_struct(&itf_variant.iid),
+            vtbl: Self::vtbl(&name, itf, itf_variant, ts_opts, ctx)?,
+            name,
+        })
+    }
+
+    /// Lists the virtual table methods of the interface and its base
+    /// interfaces.
+    fn vtbl(
+        this_type: &str,
+        itf: &Interface,
+        itf_variant: &InterfaceVariant,
+        ts_opts: &TypeSystemOptions,
+        ctx: &LibraryContext,
+    ) -> Result<Vec<CVtblSection>, GeneratorError>
+    {
+        // Dual interfaces derive from IDispatch regardless of the declared
+        // base.
+        let base = match itf.options.dual {
+            true => Some("IDispatch"),
+            false => itf_variant.base.as_ref().map(|b| b.as_ref()),
+        };
+        let mut sections = match base {
+            None | Some("IUnknown") => vec![iunknown(this_type)],
+            Some("IDispatch") => vec![iunknown(this_type), idispatch(this_type)],
+            Some(base) => {
+                let base_itf = ctx
+                    .itfs_by_name
+                    .get(base)
+                    .ok_or_else(|| format!("Unknown base interface '{}'", base))?;
+                let base_variant = base_itf
+                    .variants
+                    .iter()
+                    .find(|v| v.ts == ts_opts.ts)
+                    .ok_or_else(|| {
+                        format!("Base interface '{}' has no {:?} variant", base, ts_opts.ts)
+                    })?;
+                Self::vtbl(this_type, base_itf, base_variant, ts_opts, ctx)?
+            }
+        };
+
+        sections.push(CVtblSection {
+            name: final_name(itf, ts_opts),
+            this_type: this_type.to_string(),
+            methods: itf_variant
+                .methods
+                .iter()
+                .map(|m| CMethod::from(m.as_ref(), ts_opts, ctx))
+                .collect(),
+        });
+        Ok(sections)
+    }
+}
+
+impl CMethod
+{
+    fn from(method: &Method, opts: &TypeSystemOptions, ctx: &LibraryContext) -> Self
+    {
+        // The property accessors follow the MIDL naming.
+        let name = match method.kind {
+            MethodKind::Method => pascal_case(&method.name),
+            MethodKind::PropertyGet => format!("get_{}", pascal_case(&method.name)),
+            MethodKind::PropertyPut => format!("put_{}", pascal_case(&method.name)),
+        };
+        // The parameter descriptions follow the method documentation as
+        // Doxygen commands.
+        let mut doc = comment_lines(&method.doc);
+        let params = method
+            .parameters
+            .iter()
+            .filter_map(|arg| arg.doc.as_ref().map(|doc| (identifier(&arg.name), doc)))
+            .map(|(name, doc)| format!("@param {} {}", name, doc.replace("*/", "* /")))
+            .collect::<Vec<_>>();
+        if !doc.is_empty() && !params.is_empty() {
+            doc.push(String::new());
+        }
+        doc.extend(params);
+
+        Self {
+            name,
+            doc,
+            ret_type: type_desc_name(&method.return_type.ty, opts, ctx),
+            args: method
+                .parameters
+                .iter()
+                .map(|arg| CArg {
+                    name: identifier(&arg.name),
+                    arg_type: type_desc_name(&arg.ty, opts, ctx),
+                })
+                .collect(),
+        }
+    }
+
+    /// Declares a method of the built-in interfaces.
+    fn builtin(name: &str, ret_type: &str, args: &[(&str, &str)]) -> Self
+    {
+        Self {
+            name: name.to_string(),
+            doc: vec![],
+            ret_type: ret_type.to_string(),
+            args: args
+                .iter()
+                .map(|(arg_type, name)| CArg {
+                    name: name.to_string(),
+                    arg_type: arg_type.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CClass
+{
+    fn from(cls: &CoClass) -> Self
+    {
+        CClass {
+            name: cls.name.to_string(),
+            doc: comment_lines(&cls.doc),
+            clsid_struct: guid_as_struct(&cls.clsid),
+        }
+    }
+}
+
+fn iunknown(this_type: &str) -> CVtblSection
+{
+    CVtblSection {
+        name: "IUnknown".to_string(),
+        this_type: this_type.to_string(),
+        methods: vec![
+            CMethod::builtin(
+                "QueryInterface",
+                "HRESULT",
+                &[("const IID*", "riid"), ("void**", "ppvObject")],
+            ),
+            CMethod::builtin("AddRef", "uint32_t", &[]),
+            CMethod::builtin("Release", "uint32_t", &[]),
+        ],
+    }
+}
+
+fn idispatch(this_type: &str) -> CVtblSection
+{
+    CVtblSection {
+        name: "IDispatch".to_string(),
+        this_type: this_type.to_string(),
+        methods: vec![
+            CMethod::builtin("GetTypeInfoCount", "HRESULT", &[("uint32_t*", "pctinfo")]),
+            CMethod::builtin(
+                "GetTypeInfo",
+                "HRESULT",
+                &[
+                    ("uint32_t", "iTInfo"),
+                    ("uint32_t", "lcid"),
+                    ("void**", "ppTInfo"),
+                ],
+            ),
+            CMethod::builtin(
+                "GetIDsOfNames",
+                "HRESULT",
+                &[
+                    ("const IID*", "riid"),
+                    ("uint16_t**", "rgszNames"),
+                    ("uint32_t", "cNames"),
+                    ("uint32_t", "lcid"),
+                    ("int32_t*", "rgDispId"),
+                ],
+            ),
+            CMethod::builtin(
+                "Invoke",
+                "HRESULT",
+                &[
+                    ("int32_t", "dispIdMember"),
+                    ("const IID*", "riid"),
+                    ("uint32_t", "lcid"),
+                    ("uint16_t", "wFlags"),
+                    ("void*", "pDispParams"),
+                    ("VARIANT*", "pVarResult"),
+                    ("void*", "pExcepInfo"),
+                    ("uint32_t*", "puArgErr"),
+                ],
+            ),
+        ],
+    }
+}
+
+fn final_name(itf: &Interface, opts: &TypeSystemOptions) -> String
+{
+    let base_name = if itf.options.class_impl_interface {
+        Cow::from(format!("I{}", itf.name))
+    } else {
+        itf.name.clone()
+    };
+
+    match opts.use_full_name {
+        true => format!("{}_{:?}", base_name, opts.ts),
+        false => base_name.to_string(),
+    }
+}
+
+fn type_desc_name(ty: &TypeDesc, opts: &TypeSystemOptions, ctx: &LibraryContext) -> String
+{
+    match ty {
+        TypeDesc::Void => "void".to_string(),
+        TypeDesc::Primitive(Primitive::Hresult) => "HRESULT".to_string(),
+        TypeDesc::Primitive(p) => primitive_type(p.name()).to_string(),
+        TypeDesc::String(StringEncoding::Utf16) => "BSTR".to_string(),
+        TypeDesc::String(StringEncoding::Utf8) => "char*".to_string(),
+        TypeDesc::Variant => "VARIANT".to_string(),
+        TypeDesc::Interface { name, .. } => type_name(name, 1, opts, ctx),
+        TypeDesc::Struct(name) | TypeDesc::Enum(name) => type_name(name, 0, opts, ctx),
+        TypeDesc::SafeArray(_) => "SAFEARRAY*".to_string(),
+        TypeDesc::Array {
+            element: pointee, ..
+        }
+        | TypeDesc::Pointer { pointee, .. } => {
+            format!("{}*", type_desc_name(pointee, opts, ctx))
+        }
+        TypeDesc::Nullable(inner) => type_desc_name(inner, opts, ctx),
+    }
+}
+
+fn type_name(ty: &str, indirection: u32, opts: &TypeSystemOptions, ctx: &LibraryContext) -> String
+{
+    let base_name = match ctx.itfs_by_name.get(ty) {
+        Some(itf) => final_name(itf, opts),
+        None => ty.to_string(),
+    };
+
+    format!("{}{}", base_name, "*".repeat(indirection as usize))
+}
+
+/// Gets the C type of the primitive type.
+///
+/// Other names are returned as they are.
+fn primitive_type(name: &str) -> &str
+{
+    match name {
+        // C89 has no boolean type. Rust bool is a single byte.
+        "bool" => "uint8_t",
+        "i8" => "int8_t",
+        "u8" => "uint8_t",
+        "i16" => "int16_t",
+        "u16" => "uint16_t",
+        "i32" => "int32_t",
+        "u32" => "uint32_t",
+        "i64" => "int64_t",
+        "u64" => "uint64_t",
+        "isize" => "ptrdiff_t",
+        "usize" => "size_t",
+        "f32" => "float",
+        "f64" => "double",
+        other => other,
+    }
+}
+
+/// Makes the name a valid C identifier.
+fn identifier(name: &str) -> String
+{
+    #[rustfmt::skip]
+    const KEYWORDS: &[&str] = &[
+        "auto", "char", "default", "double", "float", "goto", "int", "long", "register", "short",
+        "signed", "sizeof", "switch", "typedef", "union", "unsigned", "void", "volatile",
+    ];
+    // Names with leading underscores are reserved for the implementation.
+    let name = match name.trim_start_matches('_') {
+        "" => "value",
+        name => name,
+    };
+    match KEYWORDS.contains(&name) {
+        true => format!("{}_", name),
+        false => name.to_string(),
+    }
+}
+
+/// Splits the documentation into lines that can be placed in a block
+/// comment.
+fn comment_lines(doc: &Option<Cow<'static, str>>) -> Vec<String>
+{
+    doc_lines(doc)
+        .into_iter()
+        .map(|line| line.replace("*/", "* /"))
+        .collect()
+}
+
+/// Generates the C header.
+///
+/// - `out` - The writer to use for output.
+pub fn write(lib: TypeLib, opts: ModelOptions, out: &mut dyn Write) -> Result<(), GeneratorError>
+{
+    let mut reg = Handlebars::new();
+    reg.register_template_string("c", include_str!("c.hbs"))
+        .expect("Error in the built-in C template.");
+
+    let c_model = CLibrary::try_from(lib, &opts)?;
+    let rendered = reg
+        .render("c", &c_model)
+        .expect("Rendering a valid ComCrate to C failed");
+    write!(out, "{}", rendered)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::generators::test::{assert_snapshot, sample_lib, sample_options};
+
+    #[test]
+    fn c_snapshot()
+    {
+        let mut output = vec![];
+        write(sample_lib(), sample_options(), &mut output).unwrap();
+
+        assert_snapshot("sample_lib.h", &output);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn header_compiles_as_c89()
+    {
+        let header = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/snapshots/sample_lib.h");
+        let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+        // Both the <stdint.h> types and the fallback typedefs must be valid.
+        for defines in &[&[][..], &["-DINTERCOM_NO_STDINT"][..]] {
+            let status = std::process::Command::new(&cc)
+                .args(["-std=c89", "-pedantic", "-Wall", "-Wextra", "-Werror"])
+                .args(["-fsyntax-only", "-x", "c"])
+                .args(defines.iter())
+                .arg(&header)
+                .status()
+                .expect("Failed to run the C compiler");
+            assert!(status.success(), "{:?} failed to compile as C89", defines);
+        }
+    }
+
+    #[test]
+    fn derived_interfaces_repeat_the_base_vtbl()
+    {
+        let lib = CLibrary::try_from(sample_lib(), &sample_options()).unwrap();
+        let itf = lib
+            .interfaces
+            .iter()
+            .find(|itf| itf.name == "IAdvancedCalculator_Raw")
+            .unwrap();
+
+        let sections = itf
+            .vtbl
+            .iter()
+            .map(|s| {
+                let methods = s
+                    .methods
+                    .iter()
+                    .map(|m| m.name.as_str())
+                    .collect::<Vec<_>>();
+                format!("{}: {}", s.name, methods.join(", "))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sections,
+            vec![
+                "IUnknown: QueryInterface, AddRef, Release",
+                "ICalculator_Raw: Add, Sum, Name",
+                "IAdvancedCalculator_Raw: Power, get_Precision, put_Precision",
+            ]
+        );
+    }
+}
@@ -7,10 +7,11 @@ use std::borrow::Cow;
 use std::io::Write;
 
 use super::GeneratorError;
-use super::{pascal_case, LibraryContext, ModelOptions, TypeSystemOptions};
+use super::{doc_lines, pascal_case, LibraryContext, ModelOptions, TypeSystemOptions};
 
 use intercom::typelib::{
-    Arg, CoClass, Direction, Interface, InterfaceVariant, Method, TypeInfo, TypeLib,
+    Arg, CoClass, Direction, Enum, Interface, InterfaceVariant, Method, MethodKind, Primitive,
+    StringEncoding, Struct, TypeDesc, TypeInfo, TypeLib,
 };
 
 use handlebars::Handlebars;
@@ -20,15 +21,51 @@ use serde_derive::Serialize;
 pub struct CppLibrary
 {
     pub lib_name: String,
+    pub enums: Vec<CppEnum>,
+    pub structs: Vec<CppStruct>,
     pub interfaces: Vec<CppInterface>,
     pub coclass_count: usize,
     pub coclasses: Vec<CppClass>,
 }
 
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CppEnum
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub base: String,
+    pub variants: Vec<CppEnumVariant>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CppEnumVariant
+{
+    pub name: String,
+    pub value: i64,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CppStruct
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub size: u32,
+    pub fields: Vec<CppField>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CppField
+{
+    pub name: String,
+    pub field_type: String,
+    pub offset: u32,
+}
+
 #[derive(PartialEq, Eq, Serialize, Debug)]
 pub struct CppInterface
 {
     pub name: String,
+    pub doc: Vec<String>,
     pub iid_struct: String,
     pub base: Option<String>,
     pub methods: Vec<CppMethod>,
@@ -38,6 +75,7 @@ pub struct CppInterface
 pub struct CppMethod
 {
     pub name: String,
+    pub doc: Vec<String>,
     pub ret_type: String,
     pub args: Vec<CppArg>,
 }
@@ -53,6 +91,7 @@ pub struct CppArg
 pub struct CppClass
 {
     pub name: String,
+    pub doc: Vec<String>,
     pub clsid_struct: String,
     pub interface_count: usize,
     pub interfaces: Vec<String>,
@@ -64,23 +103,36 @@ impl CppLibrary
     {
         let ctx = LibraryContext::from(&lib);
 
-        let mut interfaces = vec![];
+        let mut enums = vec![];
         let mut coclasses = vec![];
         for t in &lib.types {
             match t {
                 TypeInfo::Class(cls) => coclasses.push(CppClass::from(cls.as_ref(), opts, &ctx)),
-                TypeInfo::Interface(itf) => {
-                    interfaces.push(CppInterface::gather(itf.as_ref(), opts, &ctx)?)
-                }
+                TypeInfo::Enum(e) => enums.push(CppEnum::from(e.as_ref())),
+
+                // The library context lists the structs and the interfaces in
+                // dependency order.
+                TypeInfo::Struct(_) | TypeInfo::Interface(_) => {}
             }
         }
-        let interfaces = interfaces
+        let interfaces = ctx
+            .interfaces
+            .iter()
+            .map(|itf| CppInterface::gather(itf, opts, &ctx))
+            .collect::<Result<Vec<_>, _>>()?
             .into_iter()
             .flatten()
             .collect::<Vec<CppInterface>>();
+        let structs = ctx
+            .structs
+            .iter()
+            .map(|s| CppStruct::from(s, opts, &ctx))
+            .collect();
 
         Ok(Self {
             lib_name: lib.name.to_string(),
+            enums,
+            structs,
             interfaces,
             coclass_count: coclasses.len(),
             coclasses,
@@ -88,6 +140,50 @@ impl CppLibrary
     }
 }
 
+impl CppEnum
+{
+    fn from(e: &Enum) -> Self
+    {
+        CppEnum {
+            name: e.name.to_string(),
+            doc: doc_lines(&e.doc),
+            base: e.ty.to_string(),
+            variants: e
+                .variants
+                .iter()
+                .map(|v| CppEnumVariant {
+                    name: v.name.to_string(),
+                    value: v.value,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CppStruct
+{
+    fn from(s: &Struct, opts: &ModelOptions, ctx: &LibraryContext) -> Self
+    {
+        // The struct layout doesn't depend on the type system. Interface
+        // pointers in the fields refer to the first type system.
+        let ts_opts = &opts.type_systems[0];
+        CppStruct {
+            name: s.name.to_string(),
+            doc: doc_lines(&s.doc),
+            size: s.size,
+            fields: s
+                .fields
+                .iter()
+                .map(|f| CppField {
+                    name: f.name.to_string(),
+                    field_type: CppArg::type_desc_name(&f.ty, ts_opts, ctx),
+                    offset: f.offset,
+                })
+                .collect(),
+        }
+    }
+}
+
 impl CppInterface
 {
     fn gather(
@@ -116,8 +212,14 @@ impl CppInterface
     {
         Ok(Self {
             name: Self::final_name(itf, ts_opts),
+            doc: doc_lines(&itf.doc),
             iid_struct: guid_as_struct(&itf_variant.iid),
-            base: Some("IUnknown".to_string()),
+            base: itf_variant.base.as_ref().map(|base| {
+                ctx.itfs_by_name
+                    .get(base.as_ref())
+                    .map(|base_itf| Self::final_name(base_itf, ts_opts))
+                    .unwrap_or_else(|| base.to_string())
+            }),
             methods: itf_variant
                 .methods
                 .iter()
@@ -149,8 +251,29 @@ impl CppMethod
         ctx: &LibraryContext,
     ) -> Result<Self, GeneratorError>
     {
+        // The property accessors follow the MIDL naming.
+        let name = match method.kind {
+            MethodKind::Method => pascal_case(&method.name),
+            MethodKind::PropertyGet => format!("get_{}", pascal_case(&method.name)),
+            MethodKind::PropertyPut => format!("put_{}", pascal_case(&method.name)),
+        };
+        // The parameter descriptions follow the method documentation as
+        // Doxygen commands.
+        let mut doc = doc_lines(&method.doc);
+        let params = method
+            .parameters
+            .iter()
+            .filter_map(|arg| arg.doc.as_ref().map(|doc| (&arg.name, doc)))
+            .map(|(name, doc)| format!("@param {} {}", name, doc))
+            .collect::<Vec<_>>();
+        if !doc.is_empty() && !params.is_empty() {
+            doc.push(String::new());
+        }
+        doc.extend(params);
+
         Ok(Self {
-            name: pascal_case(&method.name),
+            name,
+            doc,
             ret_type: CppArg::cpp_type(&method.return_type, opts, ctx),
             args: method
                 .parameters
@@ -191,22 +314,44 @@ impl CppArg
     }
 
     fn cpp_type(arg: &Arg, opts: &TypeSystemOptions, ctx: &LibraryContext) -> String
+    {
+        Self::type_desc_name(&arg.ty, opts, ctx)
+    }
+
+    fn type_desc_name(ty: &TypeDesc, opts: &TypeSystemOptions, ctx: &LibraryContext) -> String
+    {
+        match ty {
+            TypeDesc::Void => "void".to_string(),
+            TypeDesc::Primitive(Primitive::Hresult) => "intercom::HRESULT".to_string(),
+            TypeDesc::Primitive(p) => p.name().to_string(),
+            TypeDesc::String(StringEncoding::Utf16) => "BSTR".to_string(),
+            TypeDesc::String(StringEncoding::Utf8) => "char*".to_string(),
+            TypeDesc::Variant => "Variant".to_string(),
+            TypeDesc::Interface { name, .. } => Self::type_name(name, 1, opts, ctx),
+            TypeDesc::Struct(name) | TypeDesc::Enum(name) => Self::type_name(name, 0, opts, ctx),
+            TypeDesc::SafeArray(_) => "intercom::SAFEARRAY*".to_string(),
+            TypeDesc::Array {
+                element: pointee, ..
+            }
+            | TypeDesc::Pointer { pointee, .. } => {
+                format!("{}*", Self::type_desc_name(pointee, opts, ctx))
+            }
+            TypeDesc::Nullable(inner) => Self::type_desc_name(inner, opts, ctx),
+        }
+    }
+
+    fn type_name(
+        ty: &str,
+        indirection: u32,
+        opts: &TypeSystemOptions,
+        ctx: &LibraryContext,
+    ) -> String
     {
         let base_name = ctx
             .itfs_by_name
-            .get(arg.ty.as_ref())
+            .get(ty)
             .map(|itf| CppInterface::final_name(itf, opts))
-            .unwrap_or_else(|| arg.ty.to_string());
-        let indirection = match arg.direction {
-            Direction::In | Direction::Return => arg.indirection_level,
-            Direction::Out | Direction::Retval => arg.indirection_level + 1,
-        };
-
-        let base_name = match base_name.as_ref() {
-            "std::ffi::c_void" => "void".to_string(),
-            "HRESULT" => "intercom::HRESULT".to_string(),
-            other => other.to_string(),
-        };
+            .unwrap_or_else(|| ty.to_string());
 
         format!("{}{}", base_name, "*".repeat(indirection as usize))
     }
@@ -231,6 +376,7 @@ impl CppClass
             .collect::<Vec<_>>();
         CppClass {
             name: cls.name.to_string(),
+            doc: doc_lines(&cls.doc),
             clsid_struct: guid_as_struct(&cls.clsid),
             interface_count: interfaces.len(),
             interfaces,
@@ -281,3 +427,19 @@ pub fn guid_as_struct(g: &intercom::GUID) -> String
             g.data4[0], g.data4[1], g.data4[2], g.data4[3],
             g.data4[4], g.data4[5], g.data4[6], g.data4[7] )
 }
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::generators::test::{assert_snapshot, sample_lib, sample_options};
+
+    #[test]
+    fn cpp_header_snapshot()
+    {
+        let mut output = vec![];
+        write(sample_lib(), sample_options(), Some(&mut output), None).unwrap();
+
+        assert_snapshot("sample_lib.hpp", &output);
+    }
+}
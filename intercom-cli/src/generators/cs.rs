@@ -0,0 +1,1014 @@
+//! Enables the generation of C# interop source for using intercom libraries
+//! from .NET projects.
+//!
+//! The interfaces are declared as `[ComImport]` interfaces, which the .NET
+//! runtime marshals through the built-in COM support on Windows. The
+//! optional `ComWrappers` bindings call the virtual tables directly, which
+//! works on the platforms without the built-in COM support as well.
+
+use std::borrow::Cow;
+use std::io::Write;
+
+use super::GeneratorError;
+use super::{
+    doc_lines, member_indices, pascal_case, LibraryContext, ModelOptions, TypeSystemOptions,
+};
+
+use intercom::typelib::{
+    Arg, CoClass, Direction, Enum, Interface, InterfaceVariant, Method, MethodKind, Primitive,
+    StringEncoding, Struct, TypeDesc, TypeInfo, TypeLib,
+};
+
+use handlebars::Handlebars;
+use serde_derive::Serialize;
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CsLibrary
+{
+    pub lib_name: String,
+    pub namespace: String,
+    pub com_wrappers: bool,
+    pub enums: Vec<CsEnum>,
+    pub structs: Vec<CsStruct>,
+    pub interfaces: Vec<CsInterface>,
+    pub coclasses: Vec<CsClass>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CsEnum
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub base: String,
+    pub variants: Vec<CsEnumVariant>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CsEnumVariant
+{
+    pub name: String,
+    pub value: i64,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CsStruct
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub size: u32,
+    pub fields: Vec<CsField>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CsField
+{
+    pub name: String,
+    pub field_type: String,
+    pub attributes: String,
+    pub offset: u32,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CsInterface
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub iid: String,
+    pub interface_type: &'static str,
+    pub base: Option<String>,
+    pub methods: Vec<CsMethod>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug, Clone)]
+pub struct CsMethod
+{
+    pub name: String,
+    pub doc: Vec<String>,
+
+    /// Inherited methods are declared again as the COM interop doesn't
+    /// include the base interface methods in the virtual table.
+    pub inherited: bool,
+    pub dispid: Option<usize>,
+    pub preserve_sig: bool,
+    pub ret_type: String,
+    pub ret_attributes: String,
+    pub args: Vec<CsArg>,
+
+    /// Body of the `ComWrappers` proxy method.
+    pub proxy: Vec<String>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug, Clone)]
+pub struct CsArg
+{
+    pub name: String,
+    pub arg_type: String,
+    pub attributes: String,
+    pub modifier: &'static str,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CsClass
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub clsid: String,
+    pub default_interface: Option<CsDefaultInterface>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct CsDefaultInterface
+{
+    pub name: String,
+    pub iid: String,
+}
+
+impl CsLibrary
+{
+    fn try_from(
+        lib: TypeLib,
+        opts: &ModelOptions,
+        com_wrappers: bool,
+    ) -> Result<Self, GeneratorError>
+    {
+        let ctx = LibraryContext::from(&lib);
+
+        let mut enums = vec![];
+        let mut coclasses = vec![];
+        for t in &lib.types {
+            match t {
+                TypeInfo::Class(cls) => coclasses.push(CsClass::from(cls.as_ref(), opts, &ctx)),
+                TypeInfo::Enum(e) => enums.push(CsEnum::from(e.as_ref())),
+
+                // The library context lists the structs and the interfaces in
+                // dependency order.
+                TypeInfo::Struct(_) | TypeInfo::Interface(_) => {}
+            }
+        }
+
+        // The derived interfaces repeat the methods of the base interfaces so
+        // the bases must be processed first.
+        let mut interfaces: Vec<CsInterface> = vec![];
+        for itf in &ctx.interfaces {
+            for ts_opts in &opts.type_systems {
+                let variant = match itf.variants.iter().find(|v| v.as_ref().ts == ts_opts.ts) {
+                    Some(variant) => variant,
+                    None => continue,
+                };
+                let cs_itf =
+                    CsInterface::try_from(itf, variant.as_ref(), ts_opts, &interfaces, &ctx)?;
+                interfaces.push(cs_itf);
+            }
+        }
+
+        let structs = ctx
+            .structs
+            .iter()
+            .map(|s| CsStruct::from(s, opts, &ctx))
+            .collect();
+
+        Ok(Self {
+            lib_name: lib.name.to_string(),
+            namespace: pascal_case(&lib.name),
+            com_wrappers,
+            enums,
+            structs,
+            interfaces,
+            coclasses,
+        })
+    }
+}
+
+impl CsEnum
+{
+    fn from(e: &Enum) -> Self
+    {
+        CsEnum {
+            name: e.name.to_string(),
+            doc: xml_doc(&e.doc, &[]),
+            base: primitive_name(&e.ty).to_string(),
+            variants: e
+                .variants
+                .iter()
+                .map(|v| CsEnumVariant {
+                    name: v.name.to_string(),
+                    value: v.value,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CsStruct
+{
+    fn from(s: &Struct, opts: &ModelOptions, ctx: &LibraryContext) -> Self
+    {
+        // The struct layout doesn't depend on the type system. Interface
+        // pointers in the fields refer to the first type system.
+        let ts_opts = &opts.type_systems[0];
+        CsStruct {
+            name: s.name.to_string(),
+            doc: xml_doc(&s.doc, &[]),
+            size: s.size,
+            fields: s
+                .fields
+                .iter()
+                .map(|f| {
+                    // The structs are copied as they are so the pointers are
+                    // left for the user to deal with.
+                    let ty = CsType::from(&f.ty, &[], ts_opts, ctx);
+                    CsField {
+                        name: identifier(&f.name),
+                        attributes: ty.attributes(""),
+                        field_type: ty.name,
+                        offset: f.offset,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CsInterface
+{
+    fn try_from(
+        itf: &Interface,
+        itf_variant: &InterfaceVariant,
+        ts_opts: &TypeSystemOptions,
+        previous: &[CsInterface],
+        ctx: &LibraryContext,
+    ) -> Result<Self, GeneratorError>
+    {
+        let name = final_name(itf, ts_opts);
+
+        // Dual interfaces derive from IDispatch, which the runtime implements
+        // based on the interface type.
+        let (interface_type, base, mut methods) = match itf.options.dual {
+            true => ("InterfaceIsDual", None, vec![]),
+            false => match itf_variant.base.as_ref().map(|b| b.as_ref()) {
+                None | Some("IUnknown") => ("InterfaceIsIUnknown", None, vec![]),
+                Some("IDispatch") => ("InterfaceIsIDispatch", None, vec![]),
+                Some(base) => {
+                    let base_name = ctx
+                        .itfs_by_name
+                        .get(base)
+                        .map(|base_itf| final_name(base_itf, ts_opts))
+                        .ok_or_else(|| format!("Unknown base interface '{}'", base))?;
+                    let base_itf = previous
+                        .iter()
+                        .find(|prev| prev.name == base_name)
+                        .ok_or_else(|| format!("Unknown base interface '{}'", base))?;
+                    let inherited = base_itf
+                        .methods
+                        .iter()
+                        .map(|m| CsMethod {
+                            inherited: true,
+                            ..m.clone()
+                        })
+                        .collect();
+                    ("InterfaceIsIUnknown", Some(base_name), inherited)
+                }
+            },
+        };
+
+        // The virtual table starts with the IUnknown or the IDispatch methods.
+        let first_slot = match interface_type {
+            "InterfaceIsIUnknown" => 3,
+            _ => 7,
+        };
+        let members = member_indices(&itf_variant.methods);
+        for (method, member) in itf_variant.methods.iter().zip(members) {
+            let slot = first_slot + methods.len();
+            let dispid = match itf.options.dual {
+                true => Some(member + 1),
+                false => None,
+            };
+            methods.push(CsMethod::try_from(
+                method.as_ref(),
+                slot,
+                dispid,
+                ts_opts,
+                ctx,
+            )?);
+        }
+
+        // The proxy bodies refer to the pointer of this interface, also for
+        // the inherited methods.
+        let this = format!("var __this = GetInterface(typeof({}).GUID);", name);
+        for method in &mut methods {
+            if method.proxy.len() > 1 {
+                method.proxy[0] = this.clone();
+            }
+        }
+
+        Ok(Self {
+            name,
+            doc: xml_doc(&itf.doc, &[]),
+            iid: format!("{:-X}", itf_variant.iid),
+            interface_type,
+            base,
+            methods,
+        })
+    }
+}
+
+impl CsMethod
+{
+    fn try_from(
+        method: &Method,
+        slot: usize,
+        dispid: Option<usize>,
+        opts: &TypeSystemOptions,
+        ctx: &LibraryContext,
+    ) -> Result<Self, GeneratorError>
+    {
+        // The property accessors follow the MIDL naming.
+        let name = match method.kind {
+            MethodKind::Method => pascal_case(&method.name),
+            MethodKind::PropertyGet => format!("get_{}", pascal_case(&method.name)),
+            MethodKind::PropertyPut => format!("put_{}", pascal_case(&method.name)),
+        };
+        let param_docs = method
+            .parameters
+            .iter()
+            .filter_map(|arg| {
+                arg.doc
+                    .as_ref()
+                    .map(|doc| (arg.name.as_ref(), doc.as_ref()))
+            })
+            .collect::<Vec<_>>();
+
+        // Failed HRESULTs are turned into exceptions unless the method returns
+        // something else.
+        let preserve_sig = method.return_type.ty != TypeDesc::Primitive(Primitive::Hresult);
+        let (params, retval) = match method.parameters.split_last() {
+            Some((last, rest)) if !preserve_sig && last.direction == Direction::Retval => {
+                (rest, Some(last))
+            }
+            _ => (&method.parameters[..], None),
+        };
+
+        let ret = match (preserve_sig, retval) {
+            (true, _) => CsType::from(&method.return_type.ty, &[], opts, ctx),
+            (false, Some(retval)) => CsType::from(pointee(&retval.ty), &[], opts, ctx),
+            (false, None) => CsType::blittable("void"),
+        };
+        let args = params
+            .iter()
+            .map(|arg| CsArg::try_from(arg, &method.parameters, opts, ctx))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let proxy = proxy_body(
+            slot,
+            preserve_sig,
+            &ret,
+            retval.is_some(),
+            params,
+            &method.parameters,
+            opts,
+            ctx,
+        );
+        Ok(Self {
+            name,
+            doc: xml_doc(&method.doc, &param_docs),
+            inherited: false,
+            dispid,
+            preserve_sig,
+            ret_attributes: ret.attributes("return: ").trim_end().to_string(),
+            ret_type: ret.name,
+            args,
+            proxy,
+        })
+    }
+}
+
+impl CsArg
+{
+    fn try_from(
+        arg: &Arg,
+        all_args: &[Arg],
+        opts: &TypeSystemOptions,
+        ctx: &LibraryContext,
+    ) -> Result<Self, GeneratorError>
+    {
+        if arg.direction == Direction::Return {
+            return Err("Direction::Return is invalid direction for arguments"
+                .to_string()
+                .into());
+        }
+
+        let (modifier, ty) = Self::parameter_type(arg, all_args, opts, ctx);
+        Ok(Self {
+            name: identifier(&arg.name),
+            attributes: ty.attributes(""),
+            arg_type: ty.name,
+            modifier,
+        })
+    }
+
+    /// Gets the modifier and the type of the parameter.
+    fn parameter_type(
+        arg: &Arg,
+        all_args: &[Arg],
+        opts: &TypeSystemOptions,
+        ctx: &LibraryContext,
+    ) -> (&'static str, CsType)
+    {
+        match (&arg.ty, arg.direction) {
+            (TypeDesc::Pointer { pointee, .. }, _) if **pointee == TypeDesc::Void => {
+                ("", CsType::blittable("IntPtr"))
+            }
+            (TypeDesc::Pointer { pointee, .. }, _) if is_array(pointee) => {
+                (modifier(arg), CsType::blittable("IntPtr"))
+            }
+            (TypeDesc::Pointer { pointee, .. }, _) => {
+                (modifier(arg), CsType::from(pointee, all_args, opts, ctx))
+            }
+            (ty, _) => ("", CsType::from(ty, all_args, opts, ctx)),
+        }
+    }
+}
+
+impl CsClass
+{
+    fn from(cls: &CoClass, opts: &ModelOptions, ctx: &LibraryContext) -> Self
+    {
+        // The coclass interface represents the default interface of the first
+        // type system the same way the imported type libraries do.
+        let ts_opts = &opts.type_systems[0];
+        let default_interface = cls.interfaces.first().and_then(|itf_ref| {
+            let itf = ctx.itfs_by_ref.get(itf_ref.name.as_ref())?;
+            let variant = itf.variants.iter().find(|v| v.as_ref().ts == ts_opts.ts)?;
+            Some(CsDefaultInterface {
+                name: final_name(itf, ts_opts),
+                iid: format!("{:-X}", variant.iid),
+            })
+        });
+        CsClass {
+            name: cls.name.to_string(),
+            doc: xml_doc(&cls.doc, &[]),
+            clsid: format!("{:-X}", cls.clsid),
+            default_interface,
+        }
+    }
+}
+
+/// How the proxies pass the values through the virtual table.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Conversion
+{
+    /// The value is passed as it is.
+    Blittable,
+    Bool,
+    VariantBool,
+    Date,
+    Bstr,
+    Utf8,
+    Interface,
+
+    /// The proxies don't support the type.
+    Unsupported,
+}
+
+/// C# representation of a type.
+struct CsType
+{
+    name: String,
+    marshal_as: Option<String>,
+    native: String,
+    conversion: Conversion,
+
+    /// Expression for the IID of the interface types.
+    iid: String,
+}
+
+impl CsType
+{
+    fn new(name: &str, marshal_as: Option<&str>, native: &str, conversion: Conversion) -> Self
+    {
+        CsType {
+            name: name.to_string(),
+            marshal_as: marshal_as.map(str::to_string),
+            native: native.to_string(),
+            conversion,
+            iid: String::new(),
+        }
+    }
+
+    fn blittable(name: &str) -> Self
+    {
+        Self::new(name, None, name, Conversion::Blittable)
+    }
+
+    fn unsupported(name: &str, marshal_as: String) -> Self
+    {
+        CsType {
+            marshal_as: Some(marshal_as),
+            ..Self::new(name, None, "IntPtr", Conversion::Unsupported)
+        }
+    }
+
+    fn from(ty: &TypeDesc, all_args: &[Arg], opts: &TypeSystemOptions, ctx: &LibraryContext)
+        -> Self
+    {
+        match ty {
+            TypeDesc::Void => Self::blittable("void"),
+            TypeDesc::Primitive(Primitive::Bool) => {
+                Self::new("bool", Some("UnmanagedType.U1"), "byte", Conversion::Bool)
+            }
+            TypeDesc::Primitive(Primitive::VariantBool) => Self::new(
+                "bool",
+                Some("UnmanagedType.VariantBool"),
+                "short",
+                Conversion::VariantBool,
+            ),
+            TypeDesc::Primitive(Primitive::Date) => {
+                Self::new("DateTime", None, "double", Conversion::Date)
+            }
+            TypeDesc::Primitive(p) => Self::blittable(primitive_name(p.name())),
+            TypeDesc::String(StringEncoding::Utf16) => Self::new(
+                "string",
+                Some("UnmanagedType.BStr"),
+                "IntPtr",
+                Conversion::Bstr,
+            ),
+            TypeDesc::String(StringEncoding::Utf8) => Self::new(
+                "string",
+                Some("UnmanagedType.LPUTF8Str"),
+                "IntPtr",
+                Conversion::Utf8,
+            ),
+            TypeDesc::Variant => Self::unsupported("object", "UnmanagedType.Struct".to_string()),
+            TypeDesc::Interface { name, iid } => match ctx.itfs_by_name.get(name.as_ref()) {
+                Some(itf) => {
+                    let name = final_name(itf, opts);
+                    CsType {
+                        iid: format!("typeof({}).GUID", name),
+                        ..Self::new(&name, None, "IntPtr", Conversion::Interface)
+                    }
+                }
+
+                // The interfaces outside the library are passed as plain
+                // objects.
+                None => {
+                    let marshal_as = match name.as_ref() {
+                        "IDispatch" => "UnmanagedType.IDispatch",
+                        _ => "UnmanagedType.IUnknown",
+                    };
+                    CsType {
+                        iid: format!("new Guid(\"{:-X}\")", iid),
+                        ..Self::new("object", Some(marshal_as), "IntPtr", Conversion::Interface)
+                    }
+                }
+            },
+            TypeDesc::Struct(name) | TypeDesc::Enum(name) => Self::named(name),
+            TypeDesc::SafeArray(element) => {
+                let element_type = Self::from(element, &[], opts, ctx);
+                Self::unsupported(
+                    &format!("{}[]", element_type.name),
+                    format!(
+                        "UnmanagedType.SafeArray, SafeArraySubType = VarEnum.{}",
+                        var_enum(element)
+                    ),
+                )
+            }
+            TypeDesc::Array { element, length } => {
+                let element_type = Self::from(element, &[], opts, ctx);
+                let marshal_as = match all_args.iter().position(|arg| arg.name == *length) {
+                    Some(idx) => format!("UnmanagedType.LPArray, SizeParamIndex = {}", idx),
+                    None => "UnmanagedType.LPArray".to_string(),
+                };
+                Self::unsupported(&format!("{}[]", element_type.name), marshal_as)
+            }
+            TypeDesc::Pointer { .. } => Self::blittable("IntPtr"),
+            TypeDesc::Nullable(inner) => Self::from(inner, all_args, opts, ctx),
+        }
+    }
+
+    /// Gets the type referred by the name.
+    fn named(name: &str) -> Self
+    {
+        match name {
+            "GUID" => Self::blittable("Guid"),
+            name => Self::blittable(name),
+        }
+    }
+
+    /// Gets the MarshalAs attribute for the type.
+    fn attributes(&self, target: &str) -> String
+    {
+        match &self.marshal_as {
+            Some(marshal_as) => format!("[{}MarshalAs({})] ", target, marshal_as),
+            None => String::new(),
+        }
+    }
+
+    /// Converts the C# value for the native call.
+    ///
+    /// Returns the statements before the call, the argument and the
+    /// statements after the call.
+    fn to_native(&self, value: &str) -> (Option<String>, String, Option<String>)
+    {
+        let native = format!("__{}", value.trim_start_matches('@'));
+        match self.conversion {
+            Conversion::Blittable | Conversion::Unsupported => (None, value.to_string(), None),
+            Conversion::Bool => (None, format!("(byte)({} ? 1 : 0)", value), None),
+            Conversion::VariantBool => (None, format!("(short)({} ? -1 : 0)", value), None),
+            Conversion::Date => (None, format!("{}.ToOADate()", value), None),
+            Conversion::Bstr => (
+                Some(format!("var {} = Marshal.StringToBSTR({});", native, value)),
+                native.clone(),
+                Some(format!("Marshal.FreeBSTR({});", native)),
+            ),
+            Conversion::Utf8 => (
+                Some(format!(
+                    "var {} = Marshal.StringToCoTaskMemUTF8({});",
+                    native, value
+                )),
+                native.clone(),
+                Some(format!("Marshal.FreeCoTaskMem({});", native)),
+            ),
+            Conversion::Interface => (
+                Some(format!(
+                    "var {} = Activation.GetInterface({}, {});",
+                    native, value, self.iid
+                )),
+                native.clone(),
+                Some(format!("Activation.Release({});", native)),
+            ),
+        }
+    }
+
+    /// Converts the native value the callee returned.
+    fn converted_result(&self, native: &str) -> String
+    {
+        match self.conversion {
+            Conversion::Blittable | Conversion::Unsupported => native.to_string(),
+            Conversion::Bool | Conversion::VariantBool => format!("{} != 0", native),
+            Conversion::Date => format!("DateTime.FromOADate({})", native),
+            Conversion::Bstr => format!("Activation.TakeBstr({})", native),
+            Conversion::Utf8 => format!("Activation.TakeUtf8({})", native),
+            Conversion::Interface => format!("({})Activation.GetObject({})", self.name, native),
+        }
+    }
+}
+
+/// Builds the body of the `ComWrappers` proxy method.
+///
+/// The first line is replaced with the interface lookup once the interface
+/// that declares the method is known.
+#[allow(clippy::too_many_arguments)]
+fn proxy_body(
+    slot: usize,
+    preserve_sig: bool,
+    ret: &CsType,
+    has_retval: bool,
+    params: &[Arg],
+    all_args: &[Arg],
+    opts: &TypeSystemOptions,
+    ctx: &LibraryContext,
+) -> Vec<String>
+{
+    let unsupported = |ty: &str| {
+        vec![format!(
+            "throw new NotSupportedException(\"Passing {} is not supported.\");",
+            ty
+        )]
+    };
+
+    let mut before = vec![];
+    let mut natives = vec!["IntPtr".to_string()];
+    let mut call_args = vec!["__this".to_string()];
+    let mut after = vec![];
+    let mut outputs = vec![];
+    for arg in params {
+        let (modifier, ty) = CsArg::parameter_type(arg, all_args, opts, ctx);
+        if ty.conversion == Conversion::Unsupported {
+            return unsupported(&ty.name);
+        }
+        let name = identifier(&arg.name);
+        match modifier {
+            "" => {
+                let (pre, value, post) = ty.to_native(&name);
+                before.extend(pre);
+                natives.push(ty.native.clone());
+                call_args.push(value);
+                after.extend(post);
+            }
+            "out " => {
+                let native = format!("__{}", name.trim_start_matches('@'));
+                before.push(format!("{} {} = default;", ty.native, native));
+                natives.push(format!("{}*", ty.native));
+                call_args.push(format!("&{}", native));
+                outputs.push(format!("{} = {};", name, ty.converted_result(&native)));
+            }
+            _ => return unsupported(&format!("{} by reference", ty.name)),
+        }
+    }
+
+    if ret.conversion == Conversion::Unsupported {
+        return unsupported(&ret.name);
+    }
+    if has_retval {
+        before.push(format!("{} __retval = default;", ret.native));
+        natives.push(format!("{}*", ret.native));
+        call_args.push("&__retval".to_string());
+    }
+    natives.push(match preserve_sig {
+        true => ret.native.clone(),
+        false => "int".to_string(),
+    });
+
+    let call = format!(
+        "((delegate* unmanaged[Stdcall]<{}>)(*(IntPtr**)__this)[{}])({})",
+        natives.join(", "),
+        slot,
+        call_args.join(", ")
+    );
+
+    // The placeholder is replaced with the interface lookup.
+    let mut body = vec![String::new()];
+    body.extend(before);
+    match (preserve_sig, ret.name.as_ref()) {
+        (true, "void") => body.push(format!("{};", call)),
+        (true, _) => body.push(format!("var __result = {};", call)),
+        (false, _) => body.push(format!("var __hr = {};", call)),
+    }
+    body.extend(after);
+    if !preserve_sig {
+        body.push("Marshal.ThrowExceptionForHR(__hr);".to_string());
+    }
+    body.extend(outputs);
+    match (preserve_sig, has_retval) {
+        (true, _) if ret.name != "void" => {
+            body.push(format!("return {};", ret.converted_result("__result")))
+        }
+        (false, true) => body.push(format!("return {};", ret.converted_result("__retval"))),
+        _ => {}
+    }
+    body
+}
+
+fn final_name(itf: &Interface, opts: &TypeSystemOptions) -> String
+{
+    let base_name = if itf.options.class_impl_interface {
+        Cow::from(format!("I{}", itf.name))
+    } else {
+        itf.name.clone()
+    };
+
+    match opts.use_full_name {
+        true => format!("{}_{:?}", base_name, opts.ts),
+        false => base_name.to_string(),
+    }
+}
+
+/// Gets the C# name of the Rust primitive type.
+///
+/// Other names are returned as they are.
+fn primitive_name(name: &str) -> &str
+{
+    match name {
+        "bool" => "bool",
+        "i8" => "sbyte",
+        "u8" => "byte",
+        "i16" => "short",
+        "u16" => "ushort",
+        "i32" => "int",
+        "u32" => "uint",
+        "i64" => "long",
+        "u64" => "ulong",
+        "isize" => "IntPtr",
+        "usize" => "UIntPtr",
+        "f32" => "float",
+        "f64" => "double",
+        "HRESULT" => "int",
+        other => other,
+    }
+}
+
+/// Gets the variant type of the safe array elements.
+fn var_enum(ty: &TypeDesc) -> &'static str
+{
+    match ty {
+        TypeDesc::Primitive(p) => match p {
+            Primitive::Bool | Primitive::VariantBool => "VT_BOOL",
+            Primitive::I8 => "VT_I1",
+            Primitive::U8 => "VT_UI1",
+            Primitive::I16 => "VT_I2",
+            Primitive::U16 => "VT_UI2",
+            Primitive::I32 => "VT_I4",
+            Primitive::U32 => "VT_UI4",
+            Primitive::I64 => "VT_I8",
+            Primitive::U64 => "VT_UI8",
+            Primitive::ISize => "VT_INT",
+            Primitive::USize => "VT_UINT",
+            Primitive::F32 => "VT_R4",
+            Primitive::F64 => "VT_R8",
+            Primitive::Hresult => "VT_ERROR",
+            Primitive::Date => "VT_DATE",
+        },
+        TypeDesc::String(_) => "VT_BSTR",
+        TypeDesc::Interface { name, .. } if name == "IDispatch" => "VT_DISPATCH",
+        TypeDesc::Interface { .. } => "VT_UNKNOWN",
+        TypeDesc::Struct(_) => "VT_RECORD",
+        TypeDesc::Enum(_) => "VT_I4",
+        TypeDesc::Nullable(inner) => var_enum(inner),
+        _ => "VT_VARIANT",
+    }
+}
+
+fn pointee(ty: &TypeDesc) -> &TypeDesc
+{
+    match ty {
+        TypeDesc::Pointer { pointee, .. } => pointee,
+        other => other,
+    }
+}
+
+fn is_array(ty: &TypeDesc) -> bool
+{
+    match ty {
+        TypeDesc::Array { .. } => true,
+        TypeDesc::Nullable(inner) => is_array(inner),
+        _ => false,
+    }
+}
+
+/// Gets the C# modifier of the pointer parameters.
+fn modifier(arg: &Arg) -> &'static str
+{
+    match arg.direction {
+        Direction::Out | Direction::Retval => "out ",
+        _ => "ref ",
+    }
+}
+
+/// Escapes the C# keywords used as identifiers.
+fn identifier(name: &str) -> String
+{
+    #[rustfmt::skip]
+    const KEYWORDS: &[&str] = &[
+        "base", "bool", "byte", "case", "catch", "char", "checked", "class", "decimal", "default",
+        "delegate", "double", "event", "explicit", "extern", "finally", "fixed", "float", "foreach",
+        "goto", "implicit", "in", "int", "interface", "internal", "is", "lock", "long", "namespace",
+        "new", "null", "object", "operator", "out", "override", "params", "private", "protected",
+        "public", "readonly", "ref", "sbyte", "sealed", "short", "sizeof", "stackalloc", "string",
+        "switch", "this", "throw", "typeof", "uint", "ulong", "unchecked", "unsafe", "ushort",
+        "using", "virtual", "void", "volatile",
+    ];
+    match KEYWORDS.contains(&name) {
+        true => format!("@{}", name),
+        false => name.to_string(),
+    }
+}
+
+/// Formats the documentation as the lines of an XML documentation comment.
+///
+/// The first paragraph is the summary and the rest are the remarks.
+fn xml_doc(doc: &Option<Cow<'static, str>>, params: &[(&str, &str)]) -> Vec<String>
+{
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let lines = doc_lines(doc)
+        .iter()
+        .map(|line| escape(line))
+        .collect::<Vec<_>>();
+    let summary_len = lines
+        .iter()
+        .position(|line| line.is_empty())
+        .unwrap_or(lines.len());
+    let (summary, remarks) = lines.split_at(summary_len);
+    let remarks = match remarks.iter().position(|line| !line.is_empty()) {
+        Some(start) => &remarks[start..],
+        None => &[],
+    };
+
+    let mut xml = vec![];
+    if !summary.is_empty() {
+        xml.push("<summary>".to_string());
+        xml.extend_from_slice(summary);
+        xml.push("</summary>".to_string());
+    }
+    if !remarks.is_empty() {
+        xml.push("<remarks>".to_string());
+        xml.extend_from_slice(remarks);
+        xml.push("</remarks>".to_string());
+    }
+    for (name, doc) in params {
+        xml.push(format!(
+            "<param name=\"{}\">{}</param>",
+            name,
+            escape(&doc.split_whitespace().collect::<Vec<_>>().join(" "))
+        ));
+    }
+    xml
+}
+
+/// Generates the C# source.
+///
+/// - `com_wrappers` - Include the `ComWrappers` based activation.
+/// - `out` - The writer to use for output.
+pub fn write(
+    lib: TypeLib,
+    opts: ModelOptions,
+    com_wrappers: bool,
+    out: &mut dyn Write,
+) -> Result<(), GeneratorError>
+{
+    let mut reg = Handlebars::new();
+    reg.register_template_string("cs", include_str!("cs.hbs"))
+        .expect("Error in the built-in C# template.");
+
+    let cs_model = CsLibrary::try_from(lib, &opts, com_wrappers)?;
+    let rendered = reg
+        .render("cs", &cs_model)
+        .expect("Rendering a valid ComCrate to C# failed");
+    write!(out, "{}", rendered)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::generators::test::{assert_snapshot, sample_lib};
+    use intercom::type_system::TypeSystemName;
+
+    fn automation_options() -> ModelOptions
+    {
+        ModelOptions {
+            type_systems: vec![TypeSystemOptions {
+                ts: TypeSystemName::Automation,
+                use_full_name: true,
+            }],
+        }
+    }
+
+    #[test]
+    fn cs_snapshot()
+    {
+        let mut output = vec![];
+        write(sample_lib(), automation_options(), false, &mut output).unwrap();
+
+        assert_snapshot("sample_lib.cs", &output);
+    }
+
+    #[test]
+    fn cs_com_wrappers_snapshot()
+    {
+        let mut output = vec![];
+        write(sample_lib(), automation_options(), true, &mut output).unwrap();
+
+        assert_snapshot("sample_lib_com_wrappers.cs", &output);
+    }
+
+    #[test]
+    fn derived_interfaces_repeat_the_base_methods()
+    {
+        let model = CsLibrary::try_from(sample_lib(), &automation_options(), true).unwrap();
+        let derived = &model.interfaces[1];
+        assert_eq!(derived.base.as_deref(), Some("ICalculator_Automation"));
+
+        let methods = derived
+            .methods
+            .iter()
+            .map(|m| (m.name.as_ref(), m.inherited))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            methods,
+            vec![
+                ("Add", true),
+                ("Sum", true),
+                ("Name", true),
+                ("Power", false),
+                ("get_Precision", false),
+                ("put_Precision", false),
+            ]
+        );
+
+        // The inherited proxies call through the derived interface.
+        assert_eq!(
+            derived.methods[0].proxy[0],
+            "var __this = GetInterface(typeof(IAdvancedCalculator_Automation).GUID);"
+        );
+    }
+
+    #[test]
+    fn retvals_become_return_values()
+    {
+        let model = CsLibrary::try_from(sample_lib(), &automation_options(), false).unwrap();
+        let name = &model.interfaces[0].methods[2];
+        assert!(!name.preserve_sig);
+        assert_eq!(name.ret_type, "string");
+        assert_eq!(
+            name.ret_attributes,
+            "[return: MarshalAs(UnmanagedType.BStr)]"
+        );
+        assert!(name.args.is_empty());
+    }
+}
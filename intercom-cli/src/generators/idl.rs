@@ -4,13 +4,17 @@ use std::borrow::Cow;
 use std::io::Write;
 
 use super::GeneratorError;
-use super::{pascal_case, LibraryContext, ModelOptions, TypeSystemOptions};
+use super::{
+    help_string, is_in_out_array, member_indices, pascal_case, LibraryContext, ModelOptions,
+    TypeSystemOptions,
+};
 
 use handlebars::Handlebars;
 use serde_derive::Serialize;
 
 use intercom::typelib::{
-    Arg, CoClass, Direction, Interface, InterfaceVariant, Method, TypeInfo, TypeLib,
+    Arg, CoClass, Direction, Enum, Interface, InterfaceVariant, Method, MethodKind, StringEncoding,
+    Struct, TypeDesc, TypeInfo, TypeLib,
 };
 
 #[derive(Debug, Serialize)]
@@ -18,15 +22,49 @@ struct IdlLibrary
 {
     pub lib_name: String,
     pub lib_id: String,
+    pub enums: Vec<IdlEnum>,
+    pub structs: Vec<IdlStruct>,
     pub interfaces: Vec<IdlInterface>,
     pub coclasses: Vec<IdlClass>,
 }
 
+#[derive(Debug, Serialize)]
+struct IdlEnum
+{
+    pub name: String,
+    pub help: Option<String>,
+    pub variants: Vec<IdlEnumVariant>,
+}
+
+#[derive(Debug, Serialize)]
+struct IdlEnumVariant
+{
+    pub name: String,
+    pub value: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct IdlStruct
+{
+    pub name: String,
+    pub help: Option<String>,
+    pub fields: Vec<IdlField>,
+}
+
+#[derive(Debug, Serialize)]
+struct IdlField
+{
+    pub name: String,
+    pub field_type: String,
+}
+
 #[derive(Debug, Serialize)]
 struct IdlInterface
 {
     name: String,
+    help: Option<String>,
     base: Option<String>,
+    dual: bool,
     iid: String,
     methods: Vec<IdlMethod>,
 }
@@ -36,6 +74,8 @@ struct IdlMethod
 {
     pub name: String,
     pub idx: usize,
+    pub property: Option<&'static str>,
+    pub help: Option<String>,
     pub ret_type: String,
     pub args: Vec<IdlArg>,
 }
@@ -53,6 +93,7 @@ struct IdlClass
 {
     pub name: String,
     pub clsid: String,
+    pub help: Option<String>,
     pub interfaces: Vec<String>,
 }
 
@@ -62,30 +103,86 @@ impl IdlLibrary
     {
         let ctx = LibraryContext::from(&lib);
 
-        let mut interfaces = vec![];
+        let mut enums = vec![];
         let mut coclasses = vec![];
         for t in &lib.types {
             match t {
                 TypeInfo::Class(cls) => coclasses.push(IdlClass::from(cls.as_ref(), opts, &ctx)),
-                TypeInfo::Interface(itf) => {
-                    interfaces.push(IdlInterface::gather(itf.as_ref(), opts, &ctx)?)
-                }
+                TypeInfo::Enum(e) => enums.push(IdlEnum::from(e.as_ref())),
+
+                // The library context lists the structs and the interfaces in
+                // dependency order.
+                TypeInfo::Struct(_) | TypeInfo::Interface(_) => {}
             }
         }
-        let interfaces = interfaces
+        let interfaces = ctx
+            .interfaces
+            .iter()
+            .map(|itf| IdlInterface::gather(itf, opts, &ctx))
+            .collect::<Result<Vec<_>, _>>()?
             .into_iter()
             .flatten()
             .collect::<Vec<IdlInterface>>();
+        let structs = ctx
+            .structs
+            .iter()
+            .map(|s| IdlStruct::from(s, opts, &ctx))
+            .collect();
 
         Ok(Self {
             lib_name: pascal_case(lib.name),
             lib_id: format!("{:-X}", lib.libid),
+            enums,
+            structs,
             interfaces,
             coclasses,
         })
     }
 }
 
+impl IdlEnum
+{
+    fn from(e: &Enum) -> Self
+    {
+        // The IDL enum values share a single scope so the variant names are
+        // prefixed with the enum name.
+        IdlEnum {
+            name: e.name.to_string(),
+            help: help_string(&e.doc),
+            variants: e
+                .variants
+                .iter()
+                .map(|v| IdlEnumVariant {
+                    name: format!("{}_{}", e.name, v.name),
+                    value: v.value,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl IdlStruct
+{
+    fn from(s: &Struct, opts: &ModelOptions, ctx: &LibraryContext) -> Self
+    {
+        // The struct layout doesn't depend on the type system. Interface
+        // pointers in the fields refer to the first type system.
+        let ts_opts = &opts.type_systems[0];
+        IdlStruct {
+            name: s.name.to_string(),
+            help: help_string(&s.doc),
+            fields: s
+                .fields
+                .iter()
+                .map(|f| IdlField {
+                    name: f.name.to_string(),
+                    field_type: IdlArg::type_desc_name(&f.ty, ts_opts, ctx),
+                })
+                .collect(),
+        }
+    }
+}
+
 impl IdlInterface
 {
     fn gather(
@@ -112,15 +209,28 @@ impl IdlInterface
         ctx: &LibraryContext,
     ) -> Result<Self, GeneratorError>
     {
+        // The dispatch IDs of the dual interfaces start from one as zero is
+        // reserved for the default member.
+        let first_id = match itf.options.dual {
+            true => 1,
+            false => 0,
+        };
         Ok(Self {
             name: Self::final_name(itf, ts_opts),
+            help: help_string(&itf.doc),
             iid: format!("{:-X}", itf_variant.iid),
-            base: Some("IUnknown".to_string()),
+            base: itf_variant.base.as_ref().map(|base| {
+                ctx.itfs_by_name
+                    .get(base.as_ref())
+                    .map(|base_itf| Self::final_name(base_itf, ts_opts))
+                    .unwrap_or_else(|| base.to_string())
+            }),
+            dual: itf.options.dual,
             methods: itf_variant
                 .methods
                 .iter()
-                .enumerate()
-                .map(|(i, m)| IdlMethod::try_from(i, m.as_ref(), ts_opts, ctx))
+                .zip(member_indices(&itf_variant.methods))
+                .map(|(m, i)| IdlMethod::try_from(first_id + i, m.as_ref(), ts_opts, ctx))
                 .collect::<Result<Vec<_>, _>>()?,
         })
     }
@@ -152,6 +262,12 @@ impl IdlMethod
         Ok(Self {
             name: pascal_case(&method.name),
             idx,
+            property: match method.kind {
+                MethodKind::Method => None,
+                MethodKind::PropertyGet => Some("propget"),
+                MethodKind::PropertyPut => Some("propput"),
+            },
+            help: help_string(&method.doc),
             ret_type: IdlArg::idl_type(&method.return_type, opts, ctx),
             args: method
                 .parameters
@@ -172,6 +288,11 @@ impl IdlArg
     {
         let mut attrs = vec![];
         match arg.direction {
+            // The arrays modified in place are passed back to the caller.
+            Direction::In if is_in_out_array(&arg.ty) => {
+                attrs.push("in");
+                attrs.push("out");
+            }
             Direction::In => attrs.push("in"),
             Direction::Out => attrs.push("out"),
             Direction::Retval => {
@@ -185,6 +306,25 @@ impl IdlArg
             }
         }
 
+        // Output arrays are allocated by the callee so the size applies to
+        // the pointer behind the out parameter.
+        let (value_ty, size_is) = match &arg.ty {
+            TypeDesc::Pointer { pointee, .. } => match &**pointee {
+                TypeDesc::Array { length, .. } => {
+                    (&**pointee, Some(format!("size_is(, *{})", length)))
+                }
+                other => (other, None),
+            },
+            TypeDesc::Array { length, .. } => (&arg.ty, Some(format!("size_is({})", length))),
+            other => (other, None),
+        };
+        if let Some(size_is) = &size_is {
+            attrs.push(size_is);
+        }
+        if let TypeDesc::String(StringEncoding::Utf8) = value_ty {
+            attrs.push("string");
+        }
+
         Ok(Self {
             name: arg.name.to_string(),
             arg_type: Self::idl_type(arg, opts, ctx),
@@ -193,21 +333,45 @@ impl IdlArg
     }
 
     fn idl_type(arg: &Arg, opts: &TypeSystemOptions, ctx: &LibraryContext) -> String
+    {
+        Self::type_desc_name(&arg.ty, opts, ctx)
+    }
+
+    fn type_desc_name(ty: &TypeDesc, opts: &TypeSystemOptions, ctx: &LibraryContext) -> String
+    {
+        match ty {
+            TypeDesc::Void => "void".to_string(),
+            TypeDesc::Primitive(p) => p.name().to_string(),
+            TypeDesc::String(StringEncoding::Utf16) => "BSTR".to_string(),
+            TypeDesc::String(StringEncoding::Utf8) => "char*".to_string(),
+            TypeDesc::Variant => "VARIANT".to_string(),
+            TypeDesc::Interface { name, .. } => Self::type_name(name, 1, opts, ctx),
+            TypeDesc::Struct(name) | TypeDesc::Enum(name) => Self::type_name(name, 0, opts, ctx),
+            TypeDesc::SafeArray(element) => {
+                format!("SAFEARRAY({})", Self::type_desc_name(element, opts, ctx))
+            }
+            TypeDesc::Array {
+                element: pointee, ..
+            }
+            | TypeDesc::Pointer { pointee, .. } => {
+                format!("{}*", Self::type_desc_name(pointee, opts, ctx))
+            }
+            TypeDesc::Nullable(inner) => Self::type_desc_name(inner, opts, ctx),
+        }
+    }
+
+    fn type_name(
+        ty: &str,
+        indirection: u32,
+        opts: &TypeSystemOptions,
+        ctx: &LibraryContext,
+    ) -> String
     {
         let base_name = ctx
             .itfs_by_name
-            .get(arg.ty.as_ref())
+            .get(ty)
             .map(|itf| IdlInterface::final_name(itf, opts))
-            .unwrap_or_else(|| arg.ty.to_string());
-        let indirection = match arg.direction {
-            Direction::In | Direction::Return => arg.indirection_level,
-            Direction::Out | Direction::Retval => arg.indirection_level + 1,
-        };
-
-        let base_name = match base_name.as_ref() {
-            "std::ffi::c_void" => "void".to_string(),
-            other => other.to_string(),
-        };
+            .unwrap_or_else(|| ty.to_string());
 
         format!("{}{}", base_name, "*".repeat(indirection as usize))
     }
@@ -233,6 +397,7 @@ impl IdlClass
         IdlClass {
             name: cls.name.to_string(),
             clsid: format!("{:-X}", cls.clsid),
+            help: help_string(&cls.doc),
             interfaces,
         }
     }
@@ -260,3 +425,19 @@ pub fn write(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::generators::test::{assert_snapshot, sample_lib, sample_options};
+
+    #[test]
+    fn idl_snapshot()
+    {
+        let mut output = vec![];
+        write(sample_lib(), sample_options(), &mut output).unwrap();
+
+        assert_snapshot("sample_lib.idl", &output);
+    }
+}
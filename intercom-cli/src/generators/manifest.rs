@@ -0,0 +1,157 @@
+//! Enables the generation of side-by-side assembly manifests for
+//! registration free COM.
+
+use std::io::Write;
+
+use super::pascal_case;
+use super::GeneratorError;
+
+use handlebars::Handlebars;
+use serde_derive::Serialize;
+
+use intercom::typelib::{CoClass, TypeInfo, TypeLib};
+
+#[derive(Debug, Serialize)]
+struct ManifestLibrary
+{
+    pub lib_name: String,
+    pub lib_id: String,
+    pub lib_version: String,
+    pub assembly_version: String,
+    pub file_name: String,
+    pub coclasses: Vec<ManifestClass>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestClass
+{
+    pub clsid: String,
+    pub progid: String,
+    pub threading_model: String,
+}
+
+impl ManifestLibrary
+{
+    fn try_from(lib: TypeLib, file_name: &str) -> Result<Self, GeneratorError>
+    {
+        let lib_name = pascal_case(&lib.name);
+        let coclasses = lib
+            .types
+            .iter()
+            .filter_map(|t| match t {
+                TypeInfo::Class(cls) => Some(ManifestClass::from(cls.as_ref(), &lib_name)),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Self {
+            assembly_version: Self::assembly_version(&lib.version)?,
+            lib_version: lib.version.to_string(),
+            lib_id: lib.libid.to_string(),
+            file_name: file_name.to_string(),
+            lib_name,
+            coclasses,
+        })
+    }
+
+    /// Converts the type library version into the four part assembly version.
+    fn assembly_version(version: &str) -> Result<String, GeneratorError>
+    {
+        let mut parts = version
+            .split('.')
+            .map(|part| {
+                part.parse::<u16>()
+                    .map(|_| part)
+                    .map_err(|_| format!("Invalid library version '{}'", version))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if parts.len() > 4 {
+            return Err(format!("Invalid library version '{}'", version).into());
+        }
+        parts.resize(4, "0");
+        Ok(parts.join("."))
+    }
+}
+
+impl ManifestClass
+{
+    fn from(cls: &CoClass, lib_name: &str) -> Self
+    {
+        ManifestClass {
+            clsid: cls.clsid.to_string(),
+            progid: format!("{}.{}", lib_name, cls.name),
+
+            // Matches the ThreadingModel value used by the registry based
+            // registration.
+            threading_model: cls.threading_model.registry_value().to_string(),
+        }
+    }
+}
+
+/// Generates the manifest content.
+///
+/// - `file_name` - The file name of the library the manifest describes.
+/// - `out` - The writer to use for output.
+pub fn write(lib: TypeLib, file_name: &str, out: &mut dyn Write) -> Result<(), GeneratorError>
+{
+    let mut reg = Handlebars::new();
+    reg.register_template_string("manifest", include_str!("manifest.hbs"))
+        .expect("Error in the built-in manifest template.");
+
+    let manifest_model = ManifestLibrary::try_from(lib, file_name)?;
+
+    let rendered = reg
+        .render("manifest", &manifest_model)
+        .expect("Rendering a valid ComCrate to manifest failed");
+    write!(out, "{}", rendered)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::generators::test::{assert_snapshot, sample_lib};
+
+    #[test]
+    fn manifest_snapshot()
+    {
+        let mut output = vec![];
+        write(sample_lib(), "sample_lib.dll", &mut output).unwrap();
+
+        assert_snapshot("sample_lib.manifest", &output);
+    }
+
+    #[test]
+    fn assembly_version()
+    {
+        assert_eq!(ManifestLibrary::assembly_version("0.1").unwrap(), "0.1.0.0");
+        assert_eq!(
+            ManifestLibrary::assembly_version("1.2.3.4").unwrap(),
+            "1.2.3.4"
+        );
+        assert!(ManifestLibrary::assembly_version("1.2.3.4.5").is_err());
+        assert!(ManifestLibrary::assembly_version("1.x").is_err());
+    }
+
+    #[test]
+    fn threading_model()
+    {
+        use intercom::threading::ThreadingModel;
+
+        let model_of = |model| {
+            let cls = CoClass::__new(
+                "Class".into(),
+                intercom::GUID::zero_guid(),
+                vec![],
+                model,
+                None,
+            );
+            ManifestClass::from(&cls, "lib").threading_model
+        };
+        assert_eq!(model_of(ThreadingModel::Single), "Apartment");
+        assert_eq!(model_of(ThreadingModel::Mutex), "Both");
+        assert_eq!(model_of(ThreadingModel::Free), "Both");
+    }
+}
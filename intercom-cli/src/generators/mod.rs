@@ -1,10 +1,12 @@
 //! Generators for file formats that can be derived from the intercom
 //! libraries.
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use intercom::type_system::TypeSystemName;
-use intercom::typelib::{Interface, TypeInfo, TypeLib};
+use intercom::typelib::{Interface, Method, PointerDirection, Struct, TypeDesc, TypeInfo, TypeLib};
+use intercom::ComBox;
 
 /// A common error type for all the generators.
 #[derive(Fail, Debug)]
@@ -48,6 +50,14 @@ pub struct LibraryContext<'a>
 {
     pub itfs_by_ref: HashMap<String, &'a Interface>,
     pub itfs_by_name: HashMap<String, &'a Interface>,
+
+    /// The interfaces ordered so that the base interfaces come before the
+    /// interfaces that derive from them.
+    pub interfaces: Vec<&'a Interface>,
+
+    /// The structs ordered so that the structs embedded in the fields come
+    /// before the structs that contain them.
+    pub structs: Vec<&'a Struct>,
 }
 
 impl<'a> LibraryContext<'a>
@@ -78,11 +88,70 @@ impl<'a> LibraryContext<'a>
                 )
             })
             .collect();
+        let interfaces: Vec<&Interface> = lib
+            .types
+            .iter()
+            .filter_map(|t| match t {
+                TypeInfo::Interface(itf) => Some(&**(itf.as_ref())),
+                _ => None,
+            })
+            .collect();
+        let mut ordered_interfaces = vec![];
+        for itf in &interfaces {
+            order_interface(itf, &interfaces, &mut ordered_interfaces);
+        }
+        let structs: Vec<&Struct> = lib
+            .types
+            .iter()
+            .filter_map(|t| match t {
+                TypeInfo::Struct(s) => Some(&**(s.as_ref())),
+                _ => None,
+            })
+            .collect();
+        let mut ordered = vec![];
+        for s in &structs {
+            order_struct(s, &structs, &mut ordered);
+        }
         LibraryContext {
             itfs_by_name,
             itfs_by_ref,
+            interfaces: ordered_interfaces,
+            structs: ordered,
+        }
+    }
+}
+
+/// Adds the interface to the list after its base interfaces.
+fn order_interface<'a>(itf: &'a Interface, all: &[&'a Interface], ordered: &mut Vec<&'a Interface>)
+{
+    if ordered.iter().any(|o| o.name == itf.name) {
+        return;
+    }
+
+    for base in itf.variants.iter().filter_map(|v| v.as_ref().base.as_ref()) {
+        if let Some(dependency) = all.iter().find(|d| d.name == *base) {
+            order_interface(dependency, all, ordered);
         }
     }
+    ordered.push(itf);
+}
+
+/// Adds the struct to the list after the structs it embeds.
+fn order_struct<'a>(s: &'a Struct, all: &[&'a Struct], ordered: &mut Vec<&'a Struct>)
+{
+    if ordered.iter().any(|o| o.name == s.name) {
+        return;
+    }
+
+    // Pointers don't require the full definition.
+    for field in &s.fields {
+        if let TypeDesc::Struct(name) = &field.ty {
+            if let Some(dependency) = all.iter().find(|d| &d.name == name) {
+                order_struct(dependency, all, ordered);
+            }
+        }
+    }
+    ordered.push(s);
 }
 
 /// Convert the Rust identifier from `snake_case` to `PascalCase`
@@ -118,5 +187,448 @@ pub fn pascal_case<T: AsRef<str>>(input: T) -> String
     output
 }
 
+/// Convert the `PascalCase` name to `snake_case`.
+pub fn snake_case(input: &str) -> String
+{
+    let mut output = String::new();
+    for (idx, c) in input.chars().enumerate() {
+        if c.is_uppercase() && idx > 0 {
+            output.push('_');
+        }
+        output.extend(c.to_lowercase());
+    }
+    output
+}
+
+/// Gets the index of the member each method belongs to.
+///
+/// The accessors of a property share the member, which is used as the
+/// dispatch ID of the methods.
+pub fn member_indices(methods: &[ComBox<Method>]) -> Vec<usize>
+{
+    let mut members: Vec<&str> = vec![];
+    methods
+        .iter()
+        .map(|m| match members.iter().position(|name| *name == m.name) {
+            Some(idx) => idx,
+            None => {
+                members.push(&m.name);
+                members.len() - 1
+            }
+        })
+        .collect()
+}
+
+/// Splits the documentation into the lines of a comment block.
+pub fn doc_lines(doc: &Option<Cow<'static, str>>) -> Vec<String>
+{
+    doc.iter()
+        .flat_map(|doc| doc.lines())
+        .map(|line| line.trim_end().to_string())
+        .collect()
+}
+
+/// Gets the summary paragraph of the documentation.
+///
+/// The help strings are single line so the lines of the summary are joined.
+pub fn summary(doc: &Option<Cow<'static, str>>) -> Option<String>
+{
+    Some(
+        doc.as_ref()?
+            .lines()
+            .map(str::trim)
+            .take_while(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Gets the summary paragraph of the documentation as an escaped string.
+pub fn help_string(doc: &Option<Cow<'static, str>>) -> Option<String>
+{
+    let summary = summary(doc)?;
+    Some(summary.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Checks whether the type is an `[in, out]` `SAFEARRAY` pointer.
+///
+/// The Automation type system passes the mutable slices back to the caller
+/// through these.
+pub fn is_in_out_array(ty: &TypeDesc) -> bool
+{
+    match ty {
+        TypeDesc::Pointer {
+            pointee,
+            direction: PointerDirection::InOut,
+        } => matches!(**pointee, TypeDesc::SafeArray(_)),
+        _ => false,
+    }
+}
+
+pub mod c;
 pub mod cpp;
+pub mod cs;
 pub mod idl;
+pub mod manifest;
+pub mod python;
+pub mod rust;
+pub mod tlb;
+
+#[cfg(test)]
+pub mod test
+{
+    use super::{member_indices, LibraryContext, ModelOptions, TypeSystemOptions};
+    use intercom::threading::ThreadingModel;
+    use intercom::type_system::TypeSystemName;
+    use intercom::typelib::*;
+    use intercom::{ComBox, GUID};
+    use std::path::PathBuf;
+
+    /// Builds a small type library for the generator snapshot tests.
+    pub fn sample_lib() -> TypeLib
+    {
+        let arg = |name: &'static str, ty: TypeDesc, direction| Arg {
+            name: name.into(),
+            ty,
+            direction,
+            doc: None,
+        };
+        let i32_ty = TypeDesc::Primitive(Primitive::I32);
+        let f64_ty = TypeDesc::Primitive(Primitive::F64);
+        let variant = |ts, iid| {
+            // The Raw type system passes the slices as conformant arrays.
+            let values = match ts {
+                TypeSystemName::Automation => vec![arg(
+                    "values",
+                    TypeDesc::SafeArray(Box::new(i32_ty.clone())),
+                    Direction::In,
+                )],
+                TypeSystemName::Raw => vec![
+                    arg(
+                        "values",
+                        TypeDesc::array(i32_ty.clone(), "values_len"),
+                        Direction::In,
+                    ),
+                    arg(
+                        "values_len",
+                        TypeDesc::Primitive(Primitive::U32),
+                        Direction::In,
+                    ),
+                ],
+            };
+            let encoding = match ts {
+                TypeSystemName::Automation => StringEncoding::Utf16,
+                TypeSystemName::Raw => StringEncoding::Utf8,
+            };
+            ComBox::new(InterfaceVariant {
+                ts,
+                iid: GUID::parse(iid).unwrap(),
+                methods: vec![
+                    ComBox::new(Method {
+                        name: "add".into(),
+                        kind: MethodKind::Method,
+                        return_type: arg("", i32_ty.clone(), Direction::Return),
+                        parameters: vec![
+                            Arg {
+                                doc: Some("The first operand.".into()),
+                                ..arg("a", i32_ty.clone(), Direction::In)
+                            },
+                            arg("b", i32_ty.clone(), Direction::In),
+                        ],
+                        doc: Some("Adds the \"a\" and the \"b\".\n\nWraps on overflow.".into()),
+                    }),
+                    ComBox::new(Method {
+                        name: "sum".into(),
+                        kind: MethodKind::Method,
+                        return_type: arg("", i32_ty.clone(), Direction::Return),
+                        parameters: values,
+                        doc: None,
+                    }),
+                    ComBox::new(Method {
+                        name: "name".into(),
+                        kind: MethodKind::Method,
+                        return_type: arg(
+                            "",
+                            TypeDesc::Primitive(Primitive::Hresult),
+                            Direction::Return,
+                        ),
+                        parameters: vec![arg(
+                            "__out",
+                            TypeDesc::pointer(TypeDesc::String(encoding), PointerDirection::Out),
+                            Direction::Retval,
+                        )],
+                        doc: None,
+                    }),
+                ],
+                base: Some("IUnknown".into()),
+            })
+        };
+        let hresult_ty = TypeDesc::Primitive(Primitive::Hresult);
+        let u32_ty = TypeDesc::Primitive(Primitive::U32);
+        let derived_variant = |ts, iid| {
+            ComBox::new(InterfaceVariant {
+                ts,
+                iid: GUID::parse(iid).unwrap(),
+                methods: vec![
+                    ComBox::new(Method {
+                        name: "power".into(),
+                        kind: MethodKind::Method,
+                        return_type: arg("", f64_ty.clone(), Direction::Return),
+                        parameters: vec![
+                            arg("value", f64_ty.clone(), Direction::In),
+                            arg("exponent", i32_ty.clone(), Direction::In),
+                        ],
+                        doc: None,
+                    }),
+                    ComBox::new(Method {
+                        name: "precision".into(),
+                        kind: MethodKind::PropertyGet,
+                        return_type: arg("", hresult_ty.clone(), Direction::Return),
+                        parameters: vec![arg(
+                            "__out",
+                            TypeDesc::pointer(u32_ty.clone(), PointerDirection::Out),
+                            Direction::Retval,
+                        )],
+                        doc: Some("Number of significant digits in the results.".into()),
+                    }),
+                    ComBox::new(Method {
+                        name: "precision".into(),
+                        kind: MethodKind::PropertyPut,
+                        return_type: arg("", hresult_ty.clone(), Direction::Return),
+                        parameters: vec![arg("digits", u32_ty.clone(), Direction::In)],
+                        doc: None,
+                    }),
+                ],
+                base: Some("ICalculator".into()),
+            })
+        };
+
+        TypeLib::__new(
+            "sample_lib".into(),
+            GUID::parse("00000001-0000-0000-0000-000000000000").unwrap(),
+            "0.1".into(),
+            vec![
+                TypeInfo::Class(ComBox::new(CoClass::__new(
+                    "Calculator".into(),
+                    GUID::parse("00000002-0000-0000-0000-000000000000").unwrap(),
+                    vec![InterfaceRef {
+                        name: "ICalculator".into(),
+                        iid_automation: GUID::parse("00000003-0000-0000-0000-000000000000")
+                            .unwrap(),
+                        iid_raw: GUID::parse("00000004-0000-0000-0000-000000000000").unwrap(),
+                    }],
+                    ThreadingModel::Free,
+                    Some("Calculator that works on\nthe integers.".into()),
+                ))),
+                TypeInfo::Enum(ComBox::new(Enum {
+                    name: "Rounding".into(),
+                    ty: "u32".into(),
+                    variants: vec![
+                        EnumVariant {
+                            name: "Down".into(),
+                            value: 0,
+                        },
+                        EnumVariant {
+                            name: "Nearest".into(),
+                            value: 5,
+                        },
+                    ],
+                    doc: Some("Rounding of the results.".into()),
+                })),
+                TypeInfo::Struct(ComBox::new(Struct {
+                    name: "Point".into(),
+                    size: 16,
+                    fields: vec![
+                        Field {
+                            name: "x".into(),
+                            ty: TypeDesc::Primitive(Primitive::F64),
+                            offset: 0,
+                        },
+                        Field {
+                            name: "y".into(),
+                            ty: TypeDesc::Primitive(Primitive::F64),
+                            offset: 8,
+                        },
+                    ],
+                    doc: None,
+                })),
+                TypeInfo::Interface(ComBox::new(Interface {
+                    name: "ICalculator".into(),
+                    variants: vec![
+                        variant(
+                            TypeSystemName::Automation,
+                            "00000003-0000-0000-0000-000000000000",
+                        ),
+                        variant(TypeSystemName::Raw, "00000004-0000-0000-0000-000000000000"),
+                    ],
+                    options: InterfaceOptions::default(),
+                    doc: Some("Basic arithmetic.".into()),
+                })),
+                TypeInfo::Interface(ComBox::new(Interface {
+                    name: "IAdvancedCalculator".into(),
+                    variants: vec![
+                        derived_variant(
+                            TypeSystemName::Automation,
+                            "00000005-0000-0000-0000-000000000000",
+                        ),
+                        derived_variant(
+                            TypeSystemName::Raw,
+                            "00000006-0000-0000-0000-000000000000",
+                        ),
+                    ],
+                    options: InterfaceOptions::default(),
+                    doc: None,
+                })),
+            ],
+        )
+    }
+
+    #[test]
+    fn base_interfaces_come_first()
+    {
+        let lib = sample_lib();
+        let ctx = LibraryContext::from(&lib);
+        let names = ctx
+            .interfaces
+            .iter()
+            .map(|i| i.name.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["ICalculator", "IAdvancedCalculator"]);
+    }
+
+    #[test]
+    fn property_accessors_share_the_member()
+    {
+        let lib = sample_lib();
+        let ctx = LibraryContext::from(&lib);
+        let itf = ctx.itfs_by_name["IAdvancedCalculator"];
+        assert_eq!(member_indices(&itf.variants[0].methods), vec![0, 1, 1]);
+    }
+
+    #[test]
+    fn structs_are_ordered_by_dependencies()
+    {
+        let field = |name: &'static str, ty| Field {
+            name: name.into(),
+            ty,
+            offset: 0,
+        };
+        let point = || TypeDesc::Struct("Point".into());
+        let f64_ty = || TypeDesc::Primitive(Primitive::F64);
+        let lib = TypeLib::__new(
+            "lib".into(),
+            GUID::parse("00000001-0000-0000-0000-000000000000").unwrap(),
+            "0.1".into(),
+            vec![
+                TypeInfo::Struct(ComBox::new(Struct {
+                    name: "Line".into(),
+                    size: 32,
+                    fields: vec![field("start", point()), field("end", point())],
+                    doc: None,
+                })),
+                TypeInfo::Struct(ComBox::new(Struct {
+                    name: "Node".into(),
+                    size: 16,
+                    fields: vec![
+                        field(
+                            "next",
+                            TypeDesc::pointer(
+                                TypeDesc::Struct("Node".into()),
+                                PointerDirection::InOut,
+                            ),
+                        ),
+                        field("value", TypeDesc::pointer(point(), PointerDirection::In)),
+                    ],
+                    doc: None,
+                })),
+                TypeInfo::Struct(ComBox::new(Struct {
+                    name: "Point".into(),
+                    size: 16,
+                    fields: vec![field("x", f64_ty()), field("y", f64_ty())],
+                    doc: None,
+                })),
+            ],
+        );
+
+        let ctx = LibraryContext::from(&lib);
+        let names = ctx
+            .structs
+            .iter()
+            .map(|s| s.name.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Point", "Line", "Node"]);
+    }
+
+    /// Model options that include both type systems.
+    pub fn sample_options() -> ModelOptions
+    {
+        ModelOptions {
+            type_systems: vec![
+                TypeSystemOptions {
+                    ts: TypeSystemName::Automation,
+                    use_full_name: true,
+                },
+                TypeSystemOptions {
+                    ts: TypeSystemName::Raw,
+                    use_full_name: true,
+                },
+            ],
+        }
+    }
+
+    /// Compares the generator output against the stored snapshot.
+    ///
+    /// Setting the `INTERCOM_UPDATE_SNAPSHOTS` environment variable overwrites
+    /// the stored snapshot with the current output instead.
+    pub fn assert_snapshot(name: &str, actual: &[u8])
+    {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/snapshots")
+            .join(name);
+        let actual = String::from_utf8_lossy(actual).replace("\r\n", "\n");
+
+        if std::env::var_os("INTERCOM_UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &actual).unwrap();
+            return;
+        }
+
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("Missing snapshot {}", path.display()))
+            .replace("\r\n", "\n");
+        assert!(
+            expected == actual,
+            "Snapshot {} differs. Actual output:\n{}",
+            name,
+            actual
+        );
+    }
+
+    /// Compares the binary generator output against the stored snapshot.
+    ///
+    /// The snapshot is updated the same way as the text snapshots.
+    pub fn assert_binary_snapshot(name: &str, actual: &[u8])
+    {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/snapshots")
+            .join(name);
+
+        if std::env::var_os("INTERCOM_UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let expected =
+            std::fs::read(&path).unwrap_or_else(|_| panic!("Missing snapshot {}", path.display()));
+        if let Some(offset) = expected.iter().zip(actual).position(|(e, a)| e != a) {
+            panic!("Snapshot {} differs at offset {:#x}", name, offset);
+        }
+        assert_eq!(
+            expected.len(),
+            actual.len(),
+            "Snapshot {} size differs",
+            name
+        );
+    }
+}
@@ -0,0 +1,794 @@
+//! Enables the generation of ctypes bindings for using intercom libraries
+//! from Python scripts.
+//!
+//! The bindings declare the virtual tables as ctypes structures and wrap the
+//! interfaces in classes that convert the arguments, raise the failed
+//! HRESULTs as exceptions and release the references when collected.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Write;
+
+use super::GeneratorError;
+use super::{doc_lines, pascal_case, snake_case, LibraryContext, ModelOptions, TypeSystemOptions};
+
+use intercom::typelib::{
+    CoClass, Direction, Enum, Interface, InterfaceVariant, Method, MethodKind, Primitive,
+    StringEncoding, Struct, TypeDesc, TypeInfo, TypeLib,
+};
+
+use handlebars::Handlebars;
+use serde_derive::Serialize;
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct PyLibrary
+{
+    pub lib_name: String,
+    pub class_name: String,
+    pub allocator: Option<String>,
+    pub error_store: Option<String>,
+    pub enums: Vec<PyEnum>,
+    pub structs: Vec<PyStruct>,
+    pub interfaces: Vec<PyInterface>,
+    pub coclasses: Vec<PyClass>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct PyEnum
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub variants: Vec<PyEnumVariant>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct PyEnumVariant
+{
+    pub name: String,
+    pub value: i64,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct PyStruct
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub size: u32,
+    pub fields: Vec<PyField>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct PyField
+{
+    pub name: String,
+    pub field_type: String,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct PyInterface
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub iid: String,
+    pub base: String,
+    pub base_vtbl: String,
+    pub vtbl: Vec<PyVtblEntry>,
+    pub methods: Vec<PyMethod>,
+    pub properties: Vec<PyProperty>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct PyVtblEntry
+{
+    pub name: String,
+    pub prototype: String,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct PyMethod
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub params: String,
+    pub body: Vec<String>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct PyProperty
+{
+    pub name: String,
+    pub accessors: String,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct PyClass
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub clsid: String,
+    pub create_name: String,
+    pub default_interface: String,
+}
+
+/// Names of the types the bindings refer to.
+struct PyContext<'a>
+{
+    lib: &'a LibraryContext<'a>,
+
+    /// ctypes types of the enums by the enum name.
+    enums: HashMap<String, String>,
+}
+
+impl PyLibrary
+{
+    fn try_from(lib: TypeLib, opts: &ModelOptions) -> Result<Self, GeneratorError>
+    {
+        let lib_ctx = LibraryContext::from(&lib);
+        let mut ctx = PyContext {
+            lib: &lib_ctx,
+            enums: HashMap::new(),
+        };
+
+        let mut enums = vec![];
+        let mut coclasses = vec![];
+        for t in &lib.types {
+            match t {
+                TypeInfo::Class(cls) => coclasses.extend(PyClass::from(cls.as_ref(), opts, &ctx)),
+                TypeInfo::Enum(e) => {
+                    ctx.enums
+                        .insert(e.name.to_string(), primitive_ctype(&e.ty).to_string());
+                    enums.push(PyEnum::from(e.as_ref()));
+                }
+
+                // The library context lists the structs and the interfaces in
+                // dependency order.
+                TypeInfo::Struct(_) | TypeInfo::Interface(_) => {}
+            }
+        }
+
+        let interfaces = lib_ctx
+            .interfaces
+            .iter()
+            .map(|itf| PyInterface::gather(itf, opts, &ctx))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<PyInterface>>();
+        let structs = lib_ctx
+            .structs
+            .iter()
+            .map(|s| PyStruct::from(s, &ctx))
+            .collect();
+
+        // The intercom runtime classes manage the memory and the errors.
+        let runtime_class = |name: &str| {
+            lib.types.iter().find_map(|t| match t {
+                TypeInfo::Class(cls) if cls.name == name => Some(format!("{:-X}", cls.clsid)),
+                _ => None,
+            })
+        };
+
+        Ok(Self {
+            lib_name: lib.name.to_string(),
+            class_name: pascal_case(&lib.name),
+            allocator: runtime_class("Allocator"),
+            error_store: runtime_class("ErrorStore"),
+            enums,
+            structs,
+            interfaces,
+            coclasses,
+        })
+    }
+}
+
+impl PyEnum
+{
+    fn from(e: &Enum) -> Self
+    {
+        PyEnum {
+            name: e.name.to_string(),
+            doc: docstring(&e.doc, &[]),
+            variants: e
+                .variants
+                .iter()
+                .map(|v| PyEnumVariant {
+                    name: identifier(&v.name),
+                    value: v.value,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PyStruct
+{
+    fn from(s: &Struct, ctx: &PyContext) -> Self
+    {
+        PyStruct {
+            name: s.name.to_string(),
+            doc: docstring(&s.doc, &[]),
+            size: s.size,
+            fields: s
+                .fields
+                .iter()
+                .map(|f| PyField {
+                    name: f.name.to_string(),
+                    field_type: ctype(&f.ty, ctx),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PyInterface
+{
+    fn gather(
+        itf: &Interface,
+        opts: &ModelOptions,
+        ctx: &PyContext,
+    ) -> Result<Vec<Self>, GeneratorError>
+    {
+        opts.type_systems
+            .iter()
+            .filter_map(|ts_opts| {
+                itf.variants
+                    .iter()
+                    .find(|v| v.as_ref().ts == ts_opts.ts)
+                    .map(|v| PyInterface::try_from(itf, v.as_ref(), ts_opts, ctx))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    fn try_from(
+        itf: &Interface,
+        itf_variant: &InterfaceVariant,
+        ts_opts: &TypeSystemOptions,
+        ctx: &PyContext,
+    ) -> Result<Self, GeneratorError>
+    {
+        // Dual interfaces derive from IDispatch regardless of the declared
+        // base.
+        let base = match itf.options.dual {
+            true => Some("IDispatch"),
+            false => itf_variant.base.as_ref().map(|b| b.as_ref()),
+        };
+        let (base, base_vtbl) = match base {
+            None | Some("IUnknown") => ("Unknown".to_string(), "IUnknownVtbl".to_string()),
+            Some("IDispatch") => ("Unknown".to_string(), "IDispatchVtbl".to_string()),
+            Some(base) => {
+                let base_itf = ctx
+                    .lib
+                    .itfs_by_name
+                    .get(base)
+                    .ok_or_else(|| format!("Unknown base interface '{}'", base))?;
+                let base_name = final_name(base_itf, ts_opts);
+                (base_name.clone(), format!("{}Vtbl", base_name))
+            }
+        };
+
+        let mut properties: Vec<(String, Option<String>, Option<String>)> = vec![];
+        for method in itf_variant
+            .methods
+            .iter()
+            .filter(|m| m.kind != MethodKind::Method)
+        {
+            let name = identifier(&method.name);
+            let accessor = Some(method_name(method));
+            let idx = match properties.iter().position(|p| p.0 == name) {
+                Some(idx) => idx,
+                None => {
+                    properties.push((name, None, None));
+                    properties.len() - 1
+                }
+            };
+            match method.kind {
+                MethodKind::PropertyGet => properties[idx].1 = accessor,
+                _ => properties[idx].2 = accessor,
+            }
+        }
+
+        Ok(Self {
+            name: final_name(itf, ts_opts),
+            doc: docstring(&itf.doc, &[]),
+            iid: format!("{:-X}", itf_variant.iid),
+            base,
+            base_vtbl,
+            vtbl: itf_variant
+                .methods
+                .iter()
+                .map(|m| PyVtblEntry::from(m.as_ref(), ctx))
+                .collect(),
+            methods: itf_variant
+                .methods
+                .iter()
+                .map(|m| PyMethod::try_from(m.as_ref(), ts_opts, ctx))
+                .collect::<Result<Vec<_>, _>>()?,
+            properties: properties
+                .into_iter()
+                .map(|(name, getter, setter)| PyProperty {
+                    name,
+                    accessors: match setter {
+                        Some(setter) => {
+                            format!("{}, {}", getter.as_deref().unwrap_or("None"), setter)
+                        }
+                        None => getter.unwrap_or_default(),
+                    },
+                })
+                .collect(),
+        })
+    }
+}
+
+impl PyVtblEntry
+{
+    fn from(method: &Method, ctx: &PyContext) -> Self
+    {
+        // The virtual table entries follow the MIDL naming.
+        let name = match method.kind {
+            MethodKind::Method => pascal_case(&method.name),
+            MethodKind::PropertyGet => format!("get_{}", pascal_case(&method.name)),
+            MethodKind::PropertyPut => format!("put_{}", pascal_case(&method.name)),
+        };
+        let mut types = vec![
+            ctype(&method.return_type.ty, ctx),
+            "ctypes.c_void_p".to_string(),
+        ];
+        types.extend(method.parameters.iter().map(|arg| ctype(&arg.ty, ctx)));
+        PyVtblEntry {
+            name,
+            prototype: format!("FUNCTYPE({})", types.join(", ")),
+        }
+    }
+}
+
+impl PyMethod
+{
+    fn try_from(
+        method: &Method,
+        opts: &TypeSystemOptions,
+        ctx: &PyContext,
+    ) -> Result<Self, GeneratorError>
+    {
+        let vtbl_entry = PyVtblEntry::from(method, ctx);
+
+        // The array lengths are taken from the arrays.
+        let lengths = method
+            .parameters
+            .iter()
+            .filter_map(|arg| match &arg.ty {
+                TypeDesc::Array { length, .. } if arg.direction == Direction::In => {
+                    Some((length.as_ref(), identifier(&arg.name)))
+                }
+                _ => None,
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut params = vec!["self".to_string()];
+        let mut before = vec![];
+        let mut call_args = vec!["self._ptr".to_string()];
+        let mut outputs = vec![];
+        for arg in &method.parameters {
+            let name = identifier(&arg.name);
+            let temp = format!("_{}", name);
+            match arg.direction {
+                Direction::In => {
+                    if let Some(array) = lengths.get(arg.name.as_ref()) {
+                        call_args.push(format!("_len({})", array));
+                        continue;
+                    }
+                    params.push(name.clone());
+                    match Value::of(&arg.ty, opts, ctx) {
+                        Value::VariantBool => call_args.push(format!("-1 if {} else 0", name)),
+                        Value::Bstr => {
+                            before.push(format!("{} = _BStr({})", temp, name));
+                            call_args.push(format!("{}.value", temp));
+                        }
+                        Value::Utf8 => call_args.push(format!("_utf8({})", name)),
+                        Value::Interface(cls) => {
+                            before.push(format!("{} = _as_interface({}, {})", temp, name, cls));
+                            call_args.push(format!("_pointer({})", temp));
+                        }
+                        Value::Array(element) => {
+                            before.push(format!("{} = _array({}, {})", temp, element, name));
+                            call_args.push(temp);
+                        }
+                        _ => call_args.push(name),
+                    }
+                }
+                Direction::Out | Direction::Retval => {
+                    let pointee = match &arg.ty {
+                        TypeDesc::Pointer { pointee, .. } => pointee.as_ref(),
+                        other => other,
+                    };
+                    // The simple ctypes types hold the Python value while the
+                    // structures are returned as they are.
+                    let out_type = ctype(pointee, ctx);
+                    let value = match out_type.starts_with("ctypes.c_") || out_type == "HRESULT" {
+                        true => format!("{}.value", temp),
+                        false => temp.clone(),
+                    };
+                    before.push(format!("{} = {}()", temp, out_type));
+                    call_args.push(format!("ctypes.byref({})", temp));
+                    outputs.push(Value::of(pointee, opts, ctx).convert(&value));
+                }
+                Direction::Return => {
+                    return Err("Direction::Return is invalid direction for arguments"
+                        .to_string()
+                        .into());
+                }
+            }
+        }
+
+        let call = format!("self._vtbl.{}({})", vtbl_entry.name, call_args.join(", "));
+        let mut body = before;
+        match &method.return_type.ty {
+            TypeDesc::Primitive(Primitive::Hresult) => {
+                body.push(format!("self._lib.check({})", call));
+            }
+            TypeDesc::Void => body.push(call),
+            ty => {
+                body.push(format!("_result = {}", call));
+                outputs.insert(0, Value::of(ty, opts, ctx).convert("_result"));
+            }
+        }
+        match outputs.len() {
+            0 => {}
+            1 => body.push(format!("return {}", outputs[0])),
+            _ => body.push(format!("return {}", outputs.join(", "))),
+        }
+
+        // The bindings cannot convert the SAFEARRAYs or the VARIANTs so the
+        // methods using them raise instead of passing raw pointers around.
+        let unsupported = std::iter::once(&method.return_type)
+            .chain(&method.parameters)
+            .find_map(|arg| unsupported_type(&arg.ty));
+        if let Some(ty) = unsupported {
+            body = vec![format!(
+                "raise NotImplementedError(\"{} uses {} values which the Python bindings do not support\")",
+                method_name(method),
+                ty
+            )];
+        }
+
+        let param_docs = method
+            .parameters
+            .iter()
+            .filter(|arg| !lengths.contains_key(arg.name.as_ref()))
+            .filter_map(|arg| {
+                arg.doc
+                    .as_ref()
+                    .map(|doc| (identifier(&arg.name), doc.as_ref()))
+            })
+            .collect::<Vec<_>>();
+        Ok(Self {
+            name: method_name(method),
+            doc: docstring(&method.doc, &param_docs),
+            params: params.join(", "),
+            body,
+        })
+    }
+}
+
+impl PyClass
+{
+    fn from(cls: &CoClass, opts: &ModelOptions, ctx: &PyContext) -> Option<Self>
+    {
+        // The classes are created through their first interface by default.
+        let ts_opts = &opts.type_systems[0];
+        let itf = ctx
+            .lib
+            .itfs_by_ref
+            .get(cls.interfaces.first()?.name.as_ref())?;
+        Some(PyClass {
+            name: cls.name.to_string(),
+            doc: docstring(&cls.doc, &[]),
+            clsid: format!("{:-X}", cls.clsid),
+            create_name: format!("create_{}", snake_case(&cls.name)),
+            default_interface: final_name(itf, ts_opts),
+        })
+    }
+}
+
+/// Python representation of the values that need converting.
+enum Value
+{
+    Plain,
+    VariantBool,
+    Bstr,
+    Utf8,
+    Interface(String),
+    Array(String),
+}
+
+impl Value
+{
+    fn of(ty: &TypeDesc, opts: &TypeSystemOptions, ctx: &PyContext) -> Self
+    {
+        match ty {
+            TypeDesc::Primitive(Primitive::VariantBool) => Value::VariantBool,
+            TypeDesc::String(StringEncoding::Utf16) => Value::Bstr,
+            TypeDesc::String(StringEncoding::Utf8) => Value::Utf8,
+            TypeDesc::Interface { name, .. } => match ctx.lib.itfs_by_name.get(name.as_ref()) {
+                Some(itf) => Value::Interface(final_name(itf, opts)),
+                None => Value::Interface("Unknown".to_string()),
+            },
+            TypeDesc::Array { element, .. } => Value::Array(ctype(element, ctx)),
+            TypeDesc::Nullable(inner) => Value::of(inner, opts, ctx),
+            _ => Value::Plain,
+        }
+    }
+
+    /// Converts the value the callee returned.
+    fn convert(&self, value: &str) -> String
+    {
+        match self {
+            Value::Plain | Value::Array(_) => value.to_string(),
+            Value::VariantBool => format!("{} != 0", value),
+            Value::Bstr => format!("self._lib.take_bstr({})", value),
+            Value::Utf8 => format!("self._lib.take_string({})", value),
+            Value::Interface(cls) => format!("self._lib.wrap({}, {})", cls, value),
+        }
+    }
+}
+
+fn final_name(itf: &Interface, opts: &TypeSystemOptions) -> String
+{
+    let base_name = if itf.options.class_impl_interface {
+        Cow::from(format!("I{}", itf.name))
+    } else {
+        itf.name.clone()
+    };
+
+    match opts.use_full_name {
+        true => format!("{}_{:?}", base_name, opts.ts),
+        false => base_name.to_string(),
+    }
+}
+
+/// Gets the name of the type the bindings cannot convert.
+fn unsupported_type(ty: &TypeDesc) -> Option<&'static str>
+{
+    match ty {
+        TypeDesc::SafeArray(_) => Some("SAFEARRAY"),
+        TypeDesc::Variant => Some("VARIANT"),
+        TypeDesc::Array { element: inner, .. }
+        | TypeDesc::Pointer { pointee: inner, .. }
+        | TypeDesc::Nullable(inner) => unsupported_type(inner),
+        _ => None,
+    }
+}
+
+/// Gets the name of the wrapper method.
+fn method_name(method: &Method) -> String
+{
+    match method.kind {
+        MethodKind::Method => identifier(&method.name),
+        MethodKind::PropertyGet => format!("get_{}", method.name),
+        MethodKind::PropertyPut => format!("put_{}", method.name),
+    }
+}
+
+/// Gets the ctypes type of the value.
+fn ctype(ty: &TypeDesc, ctx: &PyContext) -> String
+{
+    match ty {
+        TypeDesc::Void => "None".to_string(),
+        TypeDesc::Primitive(p) => primitive_ctype(p.name()).to_string(),
+        TypeDesc::String(_) | TypeDesc::Interface { .. } | TypeDesc::SafeArray(_) => {
+            "ctypes.c_void_p".to_string()
+        }
+        TypeDesc::Variant => "VARIANT".to_string(),
+        TypeDesc::Struct(name) | TypeDesc::Enum(name) => named_ctype(name, ctx),
+        TypeDesc::Array {
+            element: pointee, ..
+        }
+        | TypeDesc::Pointer { pointee, .. } => pointer_ctype(&ctype(pointee, ctx)),
+        TypeDesc::Nullable(inner) => ctype(inner, ctx),
+    }
+}
+
+/// Gets the ctypes type of the named type.
+fn named_ctype(name: &str, ctx: &PyContext) -> String
+{
+    match ctx.enums.get(name) {
+        Some(ctype) => ctype.to_string(),
+        None => name.to_string(),
+    }
+}
+
+fn pointer_ctype(pointee: &str) -> String
+{
+    match pointee {
+        "None" => "ctypes.c_void_p".to_string(),
+        pointee => format!("ctypes.POINTER({})", pointee),
+    }
+}
+
+/// Gets the ctypes type of the primitive type.
+///
+/// Other names are returned as they are.
+fn primitive_ctype(name: &str) -> &str
+{
+    match name {
+        "bool" => "ctypes.c_bool",
+        "i8" => "ctypes.c_int8",
+        "u8" => "ctypes.c_uint8",
+        "i16" | "VARIANT_BOOL" => "ctypes.c_int16",
+        "u16" => "ctypes.c_uint16",
+        "i32" => "ctypes.c_int32",
+        "u32" => "ctypes.c_uint32",
+        "i64" => "ctypes.c_int64",
+        "u64" => "ctypes.c_uint64",
+        "isize" => "ctypes.c_ssize_t",
+        "usize" => "ctypes.c_size_t",
+        "f32" => "ctypes.c_float",
+        "f64" | "DATE" => "ctypes.c_double",
+        "HRESULT" => "HRESULT",
+        other => other,
+    }
+}
+
+/// Makes the name a valid Python identifier.
+///
+/// The leading underscores would make the names private and the keywords
+/// get an underscore suffix.
+fn identifier(name: &str) -> String
+{
+    const KEYWORDS: &[&str] = &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is",
+        "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with",
+        "yield",
+    ];
+    let name = name.trim_start_matches('_');
+    match KEYWORDS.contains(&name) {
+        true => format!("{}_", name),
+        false => name.to_string(),
+    }
+}
+
+/// Formats the documentation as the lines of a docstring.
+fn docstring(doc: &Option<Cow<'static, str>>, params: &[(String, &str)]) -> Vec<String>
+{
+    let mut lines = doc_lines(doc)
+        .iter()
+        .map(|line| line.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\""))
+        .collect::<Vec<_>>();
+    if !lines.is_empty() && !params.is_empty() {
+        lines.push(String::new());
+    }
+    lines.extend(params.iter().map(|(name, doc)| {
+        let doc = doc.split_whitespace().collect::<Vec<_>>().join(" ");
+        format!(":param {}: {}", name, doc.replace('\\', "\\\\"))
+    }));
+
+    match lines.len() {
+        0 => lines,
+        1 => vec![format!("\"\"\"{}\"\"\"", lines[0])],
+        _ => {
+            lines[0] = format!("\"\"\"{}", lines[0]);
+            lines.push("\"\"\"".to_string());
+            lines
+        }
+    }
+}
+
+/// Generates the Python module.
+///
+/// - `out` - The writer to use for output.
+pub fn write(lib: TypeLib, opts: ModelOptions, out: &mut dyn Write) -> Result<(), GeneratorError>
+{
+    let mut reg = Handlebars::new();
+    reg.register_template_string("python", include_str!("python.hbs"))
+        .expect("Error in the built-in Python template.");
+
+    let py_model = PyLibrary::try_from(lib, &opts)?;
+    let rendered = reg
+        .render("python", &py_model)
+        .expect("Rendering a valid ComCrate to Python failed");
+    write!(out, "{}", rendered)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::generators::test::{assert_snapshot, sample_lib, sample_options};
+    use intercom::typelib::{Arg, PointerDirection};
+
+    #[test]
+    fn python_snapshot()
+    {
+        let mut output = vec![];
+        write(sample_lib(), sample_options(), &mut output).unwrap();
+
+        assert_snapshot("sample_lib.py", &output);
+    }
+
+    #[test]
+    fn array_lengths_are_taken_from_the_arrays()
+    {
+        let model = PyLibrary::try_from(sample_lib(), &sample_options()).unwrap();
+        let raw = model
+            .interfaces
+            .iter()
+            .find(|itf| itf.name == "ICalculator_Raw")
+            .unwrap();
+        let sum = &raw.methods[1];
+        assert_eq!(sum.params, "self, values");
+        assert_eq!(
+            sum.body,
+            vec![
+                "_values = _array(ctypes.c_int32, values)",
+                "_result = self._vtbl.Sum(self._ptr, _values, _len(values))",
+                "return _result",
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_types_raise()
+    {
+        let model = PyLibrary::try_from(sample_lib(), &sample_options()).unwrap();
+        let automation = &model.interfaces[0];
+        assert_eq!(automation.name, "ICalculator_Automation");
+        let sum = &automation.methods[1];
+        assert_eq!(sum.params, "self, values");
+        assert_eq!(
+            sum.body,
+            vec![
+                "raise NotImplementedError(\"sum uses SAFEARRAY values which the Python \
+                 bindings do not support\")"
+            ]
+        );
+
+        let lib = sample_lib();
+        let lib_ctx = LibraryContext::from(&lib);
+        let ctx = PyContext {
+            lib: &lib_ctx,
+            enums: HashMap::new(),
+        };
+        let method = Method {
+            name: "value".into(),
+            kind: MethodKind::Method,
+            return_type: Arg {
+                name: "".into(),
+                ty: TypeDesc::Primitive(Primitive::Hresult),
+                direction: Direction::Return,
+                doc: None,
+            },
+            parameters: vec![Arg {
+                name: "__out".into(),
+                ty: TypeDesc::pointer(TypeDesc::Variant, PointerDirection::Out),
+                direction: Direction::Retval,
+                doc: None,
+            }],
+            doc: None,
+        };
+        let value = PyMethod::try_from(&method, &sample_options().type_systems[0], &ctx).unwrap();
+        assert_eq!(
+            value.body,
+            vec![
+                "raise NotImplementedError(\"value uses VARIANT values which the Python \
+                 bindings do not support\")"
+            ]
+        );
+    }
+
+    #[test]
+    fn accessors_form_properties()
+    {
+        let model = PyLibrary::try_from(sample_lib(), &sample_options()).unwrap();
+        let advanced = &model.interfaces[2];
+        assert_eq!(advanced.name, "IAdvancedCalculator_Automation");
+        assert_eq!(
+            advanced.properties,
+            vec![PyProperty {
+                name: "precision".to_string(),
+                accessors: "get_precision, put_precision".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,720 @@
+//! Enables the generation of Rust bindings for using intercom libraries from
+//! other Rust crates.
+//!
+//! The bindings declare the interfaces as `#[com_interface]` traits with the
+//! IIDs of the library so the virtual tables the attribute builds match the
+//! ones of the library. The Rust signatures are reconstructed from both type
+//! systems as the Automation and the Raw variants of the same method differ
+//! in the string and the array representations.
+
+use std::io::Write;
+
+use super::GeneratorError;
+use super::{doc_lines, snake_case, LibraryContext};
+
+use intercom::type_system::TypeSystemName;
+use intercom::typelib::{
+    Arg, CoClass, Direction, Enum, Interface, Method, MethodKind, PointerDirection, Primitive,
+    StringEncoding, Struct, TypeDesc, TypeInfo, TypeLib,
+};
+use intercom::GUID;
+
+use handlebars::Handlebars;
+use serde_derive::Serialize;
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct RustLibrary
+{
+    pub lib_name: String,
+    pub link_name: String,
+    pub enums: Vec<RustEnum>,
+    pub structs: Vec<RustStruct>,
+    pub interfaces: Vec<RustInterface>,
+    pub classes: Vec<RustClass>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct RustEnum
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub repr: String,
+    pub variants: Vec<RustEnumVariant>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct RustEnumVariant
+{
+    pub name: String,
+    pub value: i64,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct RustStruct
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub fields: Vec<RustField>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct RustField
+{
+    pub name: String,
+    pub ty: String,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct RustInterface
+{
+    pub name: String,
+    pub doc: Vec<String>,
+
+    /// The arguments of the `#[com_interface]` attribute.
+    pub attributes: String,
+    pub base: Option<String>,
+    pub methods: Vec<RustMethod>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct RustMethod
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub property: bool,
+    pub args: Vec<RustArg>,
+    pub returns: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct RustArg
+{
+    pub name: String,
+    pub ty: String,
+}
+
+#[derive(PartialEq, Eq, Serialize, Debug)]
+pub struct RustClass
+{
+    pub name: String,
+    pub doc: Vec<String>,
+    pub clsid: String,
+    pub create_name: String,
+    pub interface: String,
+}
+
+/// The role of a type in the method signature.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Usage
+{
+    /// Parameters borrow the values from the caller.
+    Input,
+
+    /// Return values are owned by the caller.
+    Output,
+
+    /// Elements of arrays and the values behind pointers.
+    Element,
+}
+
+/// The descriptions of the same type in both type systems.
+#[derive(Clone, Copy)]
+struct TypePair<'a>
+{
+    automation: Option<&'a TypeDesc>,
+    raw: Option<&'a TypeDesc>,
+}
+
+impl<'a> TypePair<'a>
+{
+    /// The description used for the parts the type systems agree on.
+    fn primary(&self) -> &'a TypeDesc
+    {
+        self.automation
+            .or(self.raw)
+            .expect("Type pair must have at least one type")
+    }
+
+    fn map(&self, f: impl Fn(&'a TypeDesc) -> Option<&'a TypeDesc>) -> TypePair<'a>
+    {
+        TypePair {
+            automation: self.automation.and_then(&f),
+            raw: self.raw.and_then(&f),
+        }
+    }
+}
+
+impl RustLibrary
+{
+    fn try_from(lib: TypeLib, link_name: &str) -> Result<Self, GeneratorError>
+    {
+        let ctx = LibraryContext::from(&lib);
+
+        let mut enums = vec![];
+        let mut classes = vec![];
+        for t in &lib.types {
+            match t {
+                TypeInfo::Class(cls) => classes.push(RustClass::try_from(cls.as_ref(), &ctx)?),
+                TypeInfo::Enum(e) => enums.push(RustEnum::from(e.as_ref())),
+
+                // The library context lists the structs and the interfaces in
+                // dependency order.
+                TypeInfo::Struct(_) | TypeInfo::Interface(_) => {}
+            }
+        }
+        let interfaces = ctx
+            .interfaces
+            .iter()
+            .map(|itf| RustInterface::try_from(itf, &ctx))
+            .collect::<Result<Vec<_>, _>>()?;
+        let structs = ctx.structs.iter().map(|s| RustStruct::from(s)).collect();
+
+        Ok(Self {
+            lib_name: lib.name.to_string(),
+            link_name: link_name.to_string(),
+            enums,
+            structs,
+            interfaces,
+            classes,
+        })
+    }
+}
+
+impl RustEnum
+{
+    fn from(e: &Enum) -> Self
+    {
+        RustEnum {
+            name: identifier(&e.name),
+            doc: doc_lines(&e.doc),
+            repr: e.ty.to_string(),
+            variants: e
+                .variants
+                .iter()
+                .map(|v| RustEnumVariant {
+                    name: identifier(&v.name),
+                    value: v.value,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl RustStruct
+{
+    fn from(s: &Struct) -> Self
+    {
+        RustStruct {
+            name: identifier(&s.name),
+            doc: doc_lines(&s.doc),
+            fields: s
+                .fields
+                .iter()
+                .map(|f| RustField {
+                    name: identifier(&f.name),
+                    ty: field_type(&f.ty),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl RustInterface
+{
+    fn try_from(itf: &Interface, ctx: &LibraryContext) -> Result<Self, GeneratorError>
+    {
+        let variant = |ts| itf.variants.iter().find(|v| v.ts == ts).map(|v| v.as_ref());
+        let automation = variant(TypeSystemName::Automation);
+        let raw = variant(TypeSystemName::Raw);
+
+        // Dual interfaces derive from IDispatch implicitly and the attribute
+        // doesn't accept an explicit base.
+        let mut attributes = vec![];
+        if itf.options.dual {
+            attributes.push("dual".to_string());
+        }
+        if let Some(v) = automation {
+            attributes.push(format!("com_iid = \"{:-X}\"", v.iid));
+        }
+        if let Some(v) = raw {
+            attributes.push(format!("raw_iid = \"{:-X}\"", v.iid));
+        }
+        let base = match automation.or(raw).and_then(|v| v.base.as_ref()) {
+            _ if itf.options.dual => None,
+            None => None,
+            Some(base) if base == "IUnknown" => None,
+            Some(base) => {
+                let base = ctx
+                    .itfs_by_name
+                    .get(base.as_ref())
+                    .ok_or_else(|| format!("Unknown base interface '{}'", base))?;
+                let base = identifier(&base.name);
+                attributes.push(format!("base = {}", base));
+                Some(base)
+            }
+        };
+
+        let method_count = automation.or(raw).map(|v| v.methods.len()).unwrap_or(0);
+        let methods = (0..method_count)
+            .map(|idx| {
+                RustMethod::try_from(
+                    automation.map(|v| -> &Method { v.methods[idx].as_ref() }),
+                    raw.map(|v| -> &Method { v.methods[idx].as_ref() }),
+                    ctx,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            name: identifier(&itf.name),
+            doc: doc_lines(&itf.doc),
+            attributes: attributes.join(", "),
+            base,
+            methods,
+        })
+    }
+}
+
+impl RustMethod
+{
+    fn try_from(
+        automation: Option<&Method>,
+        raw: Option<&Method>,
+        ctx: &LibraryContext,
+    ) -> Result<Self, GeneratorError>
+    {
+        let primary = automation
+            .or(raw)
+            .expect("Method must exist in a type system");
+
+        // The lengths of the conformant arrays are passed together with the
+        // array data.
+        let lengths = automation
+            .iter()
+            .chain(raw.iter())
+            .flat_map(|m| &m.parameters)
+            .filter_map(|arg| array_length(&arg.ty))
+            .collect::<Vec<_>>();
+        let pair = |arg: &Arg| TypePair {
+            automation: automation.and_then(|m| parameter_type(m, &arg.name)),
+            raw: raw.and_then(|m| parameter_type(m, &arg.name)),
+        };
+        let params = primary
+            .parameters
+            .iter()
+            .filter(|arg| !lengths.contains(&arg.name.as_ref()))
+            .collect::<Vec<_>>();
+
+        let args = params
+            .iter()
+            .filter(|arg| arg.direction == Direction::In)
+            .map(|arg| {
+                Ok(RustArg {
+                    name: identifier(&arg.name),
+                    ty: rust_type(pair(arg), Usage::Input, ctx)?,
+                })
+            })
+            .collect::<Result<Vec<_>, GeneratorError>>()?;
+
+        // Methods returning HRESULT report the errors through the ComResult
+        // and return the out parameters as the Ok value.
+        let returns = match &primary.return_type.ty {
+            TypeDesc::Primitive(Primitive::Hresult) => {
+                let outputs = params
+                    .iter()
+                    .filter(|arg| arg.direction != Direction::In)
+                    .map(|arg| {
+                        let pair = pair(arg).map(|ty| match ty {
+                            TypeDesc::Pointer { pointee, .. } => Some(pointee),
+                            _ => None,
+                        });
+                        rust_type(pair, Usage::Output, ctx)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Some(match outputs.as_slice() {
+                    [] => "ComResult<()>".to_string(),
+                    [output] => format!("ComResult<{}>", output),
+                    outputs => format!("ComResult<({})>", outputs.join(", ")),
+                })
+            }
+            TypeDesc::Void => None,
+            _ => {
+                let pair = TypePair {
+                    automation: automation.map(|m| &m.return_type.ty),
+                    raw: raw.map(|m| &m.return_type.ty),
+                };
+                Some(rust_type(pair, Usage::Output, ctx)?)
+            }
+        };
+
+        let name = match primary.kind {
+            MethodKind::Method => primary.name.to_string(),
+            MethodKind::PropertyGet => format!("get_{}", primary.name),
+            MethodKind::PropertyPut => format!("set_{}", primary.name),
+        };
+
+        // The argument descriptions use the `# Arguments` section the
+        // attributes read the descriptions from.
+        let mut doc = doc_lines(&primary.doc);
+        let arg_docs = params
+            .iter()
+            .filter_map(|arg| arg.doc.as_ref().map(|doc| (&arg.name, doc)))
+            .map(|(name, doc)| format!("- `{}` - {}", name, doc))
+            .collect::<Vec<_>>();
+        if !arg_docs.is_empty() {
+            if !doc.is_empty() {
+                doc.push(String::new());
+            }
+            doc.push("# Arguments".to_string());
+            doc.push(String::new());
+            doc.extend(arg_docs);
+        }
+
+        Ok(Self {
+            name: identifier(&name),
+            doc,
+            property: primary.kind != MethodKind::Method,
+            args,
+            returns,
+        })
+    }
+}
+
+impl RustClass
+{
+    fn try_from(cls: &CoClass, ctx: &LibraryContext) -> Result<Self, GeneratorError>
+    {
+        // The creation helper returns the first interface of the class.
+        let interface = match cls.interfaces.first() {
+            Some(itf) => interface_path(&itf.name, ctx)?,
+            None => "intercom::IUnknown".to_string(),
+        };
+        Ok(RustClass {
+            name: cls.name.to_string(),
+            doc: doc_lines(&cls.doc),
+            clsid: guid_literal(&cls.clsid),
+            create_name: identifier(&format!("create_{}", snake_case(&cls.name))),
+            interface,
+        })
+    }
+}
+
+/// Gets the Rust type of the parameter.
+///
+/// The type systems describe the strings and the arrays differently so both
+/// of the descriptions are needed to choose the Rust type that produces both
+/// of them.
+fn rust_type(pair: TypePair, usage: Usage, ctx: &LibraryContext) -> Result<String, GeneratorError>
+{
+    Ok(match pair.primary() {
+        TypeDesc::Void => "()".to_string(),
+        TypeDesc::Primitive(p) => primitive_type(*p).to_string(),
+
+        // Strings that are BSTRs in both type systems are raw BSTR values.
+        TypeDesc::String(StringEncoding::Utf16)
+            if pair.raw == Some(&TypeDesc::String(StringEncoding::Utf16)) =>
+        {
+            "intercom::raw::BSTR".to_string()
+        }
+        TypeDesc::String(_) => match usage {
+            Usage::Input => "&str",
+            Usage::Output | Usage::Element => "String",
+        }
+        .to_string(),
+        TypeDesc::Variant => "Variant".to_string(),
+        TypeDesc::Interface { name, .. } => {
+            let path = interface_path(name, ctx)?;
+            match usage {
+                Usage::Input => format!("&ComItf<dyn {}>", path),
+                Usage::Output | Usage::Element => format!("ComRc<dyn {}>", path),
+            }
+        }
+        TypeDesc::Struct(name) => match name.as_ref() {
+            "GUID" => "GUID".to_string(),
+            name => identifier(name),
+        },
+        TypeDesc::Enum(name) => match name.as_ref() {
+            "TypeSystemName" => "intercom::type_system::TypeSystemName".to_string(),
+            name => identifier(name),
+        },
+        TypeDesc::Array { .. } | TypeDesc::SafeArray(_) => {
+            let element = rust_type(array_element(pair), Usage::Element, ctx)?;
+            match usage {
+                Usage::Input => format!("&[{}]", element),
+                Usage::Output | Usage::Element => format!("Vec<{}>", element),
+            }
+        }
+
+        // Automation passes the mutable slices as in/out SAFEARRAYs.
+        TypeDesc::Pointer {
+            pointee,
+            direction: PointerDirection::InOut,
+        } if matches!(**pointee, TypeDesc::SafeArray(_)) => {
+            let pointee = pair.map(|ty| match ty {
+                TypeDesc::Pointer { pointee, .. } => Some(pointee),
+                ty => Some(ty),
+            });
+            let element = rust_type(array_element(pointee), Usage::Element, ctx)?;
+            format!("&mut [{}]", element)
+        }
+        TypeDesc::Pointer { pointee, direction } => {
+            let pointee_type = match **pointee {
+                TypeDesc::Void => "std::os::raw::c_void".to_string(),
+                _ => {
+                    let pointee = pair.map(|ty| match ty {
+                        TypeDesc::Pointer { pointee, .. } => Some(pointee),
+                        _ => None,
+                    });
+                    rust_type(pointee, Usage::Element, ctx)?
+                }
+            };
+            match direction {
+                PointerDirection::In => format!("*const {}", pointee_type),
+                PointerDirection::Out | PointerDirection::InOut => {
+                    format!("*mut {}", pointee_type)
+                }
+            }
+        }
+        TypeDesc::Nullable(_) => {
+            let inner = pair.map(|ty| match ty {
+                TypeDesc::Nullable(inner) => Some(inner),
+                ty => Some(ty),
+            });
+            format!("Option<{}>", rust_type(inner, usage, ctx)?)
+        }
+    })
+}
+
+/// Gets the element types of the arrays.
+fn array_element(pair: TypePair) -> TypePair
+{
+    pair.map(|ty| match ty {
+        TypeDesc::Array { element, .. } | TypeDesc::SafeArray(element) => Some(element),
+        _ => None,
+    })
+}
+
+/// Gets the type of the named parameter of the method.
+fn parameter_type<'a>(method: &'a Method, name: &str) -> Option<&'a TypeDesc>
+{
+    method
+        .parameters
+        .iter()
+        .find(|arg| arg.name == name)
+        .map(|arg| &arg.ty)
+}
+
+/// Gets the name of the length parameter if the type is a conformant array.
+fn array_length(ty: &TypeDesc) -> Option<&str>
+{
+    match ty {
+        TypeDesc::Array { length, .. } => Some(length),
+        TypeDesc::Pointer { pointee, .. } => array_length(pointee),
+        _ => None,
+    }
+}
+
+/// Gets the path of the interface trait.
+///
+/// The interfaces of the library are declared in the bindings and the COM
+/// interfaces intercom implements are used from intercom.
+fn interface_path(name: &str, ctx: &LibraryContext) -> Result<String, GeneratorError>
+{
+    if let Some(itf) = ctx.itfs_by_name.get(name) {
+        return Ok(identifier(&itf.name));
+    }
+
+    match name {
+        "IUnknown" => Ok("intercom::IUnknown".to_string()),
+        "IDispatch" => Ok("intercom::IDispatch".to_string()),
+        "IErrorInfo" => Ok("intercom::error::IErrorInfo".to_string()),
+        "ISupportErrorInfo" => Ok("intercom::ISupportErrorInfo".to_string()),
+        other => Err(format!("Unknown interface '{}'", other).into()),
+    }
+}
+
+/// Gets the Rust type of the primitive type.
+fn primitive_type(p: Primitive) -> &'static str
+{
+    match p {
+        Primitive::Hresult => "intercom::raw::HRESULT",
+
+        // The automation container types convert to the Rust types.
+        Primitive::VariantBool => "bool",
+        Primitive::Date => "std::time::SystemTime",
+        p => p.name(),
+    }
+}
+
+/// Gets the Rust type of the struct field.
+///
+/// The structs keep their C layout so the fields use the raw types.
+fn field_type(ty: &TypeDesc) -> String
+{
+    match ty {
+        TypeDesc::Void => "std::os::raw::c_void".to_string(),
+        TypeDesc::Primitive(Primitive::Hresult) => "intercom::raw::HRESULT".to_string(),
+        TypeDesc::Primitive(Primitive::VariantBool) => "intercom::raw::VariantBool".to_string(),
+        TypeDesc::Primitive(Primitive::Date) => "intercom::raw::VariantDate".to_string(),
+        TypeDesc::Primitive(p) => p.name().to_string(),
+        TypeDesc::String(StringEncoding::Utf16) => "intercom::raw::BSTR".to_string(),
+        TypeDesc::String(StringEncoding::Utf8) => "*const std::os::raw::c_char".to_string(),
+        TypeDesc::Variant => {
+            "intercom::raw::Variant<intercom::type_system::AutomationTypeSystem>".to_string()
+        }
+        TypeDesc::Interface { .. } => "intercom::raw::RawComPtr".to_string(),
+        TypeDesc::Struct(name) | TypeDesc::Enum(name) => match name.as_ref() {
+            "GUID" => "GUID".to_string(),
+            "TypeSystemName" => "intercom::type_system::TypeSystemName".to_string(),
+            name => identifier(name),
+        },
+        TypeDesc::SafeArray(_) => "*mut intercom::raw::SAFEARRAY".to_string(),
+        TypeDesc::Array { element, .. } => format!("*mut {}", field_type(element)),
+        TypeDesc::Pointer {
+            pointee,
+            direction: PointerDirection::In,
+        } => format!("*const {}", field_type(pointee)),
+        TypeDesc::Pointer { pointee, .. } => format!("*mut {}", field_type(pointee)),
+        TypeDesc::Nullable(inner) => field_type(inner),
+    }
+}
+
+/// Formats the GUID as a `GUID` struct expression.
+fn guid_literal(g: &GUID) -> String
+{
+    format!(
+        "GUID {{ data1: 0x{:04X}_{:04X}, data2: 0x{:04X}, data3: 0x{:04X}, data4: [{}] }}",
+        g.data1 >> 16,
+        g.data1 & 0xFFFF,
+        g.data2,
+        g.data3,
+        g.data4
+            .iter()
+            .map(|b| format!("0x{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// Gets the name the library is linked with from the library file name.
+///
+/// The Unix linkers add the `lib` prefix to the name.
+fn link_name(file_name: &str) -> &str
+{
+    let (stem, extension) = match file_name.rfind('.') {
+        Some(idx) => (&file_name[..idx], &file_name[idx + 1..]),
+        None => (file_name, ""),
+    };
+    match extension {
+        "dll" => stem,
+        _ => stem.strip_prefix("lib").unwrap_or(stem),
+    }
+}
+
+/// Makes the name a valid Rust identifier.
+fn identifier(name: &str) -> String
+{
+    #[rustfmt::skip]
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "box", "break", "const", "continue", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe",
+        "use", "where", "while", "yield",
+    ];
+    match name {
+        // These can't be raw identifiers.
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        name if KEYWORDS.contains(&name) => format!("r#{}", name),
+        name => name.to_string(),
+    }
+}
+
+/// Generates the Rust bindings.
+///
+/// The bindings cover both type systems as the traits declare the virtual
+/// tables of both.
+///
+/// - `file_name` - The file name of the library the bindings link to.
+/// - `out` - The writer to use for output.
+pub fn write(lib: TypeLib, file_name: &str, out: &mut dyn Write) -> Result<(), GeneratorError>
+{
+    let mut reg = Handlebars::new();
+    reg.register_template_string("rust", include_str!("rust.hbs"))
+        .expect("Error in the built-in Rust template.");
+
+    let rust_model = RustLibrary::try_from(lib, link_name(file_name))?;
+    let rendered = reg
+        .render("rust", &rust_model)
+        .expect("Rendering a valid ComCrate to Rust failed");
+    write!(out, "{}", rendered)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::generators::test::{assert_snapshot, sample_lib};
+
+    #[test]
+    fn rust_snapshot()
+    {
+        let mut output = vec![];
+        write(sample_lib(), "libsample_lib.so", &mut output).unwrap();
+
+        assert_snapshot("sample_lib.rs", &output);
+    }
+
+    #[test]
+    fn string_types_depend_on_both_type_systems()
+    {
+        let lib = sample_lib();
+        let ctx = LibraryContext::from(&lib);
+        let utf16 = TypeDesc::String(StringEncoding::Utf16);
+        let utf8 = TypeDesc::String(StringEncoding::Utf8);
+        let pair = |automation, raw| TypePair {
+            automation: Some(automation),
+            raw: Some(raw),
+        };
+
+        let ty = |pair, usage| rust_type(pair, usage, &ctx).unwrap();
+        assert_eq!(ty(pair(&utf16, &utf8), Usage::Input), "&str");
+        assert_eq!(ty(pair(&utf16, &utf8), Usage::Output), "String");
+        assert_eq!(
+            ty(pair(&utf16, &utf16), Usage::Output),
+            "intercom::raw::BSTR"
+        );
+    }
+
+    #[test]
+    fn array_types_depend_on_both_type_systems()
+    {
+        let lib = sample_lib();
+        let ctx = LibraryContext::from(&lib);
+        let f64_ty = TypeDesc::Primitive(Primitive::F64);
+        let safearray = TypeDesc::SafeArray(Box::new(f64_ty.clone()));
+        let array = TypeDesc::array(f64_ty, "values_len");
+        let pair = |automation, raw| TypePair {
+            automation: Some(automation),
+            raw: Some(raw),
+        };
+
+        let ty = |pair, usage| rust_type(pair, usage, &ctx).unwrap();
+        assert_eq!(ty(pair(&safearray, &array), Usage::Input), "&[f64]");
+        let in_out = TypeDesc::Pointer {
+            pointee: Box::new(safearray.clone()),
+            direction: PointerDirection::InOut,
+        };
+        assert_eq!(ty(pair(&in_out, &array), Usage::Input), "&mut [f64]");
+        assert_eq!(ty(pair(&safearray, &array), Usage::Output), "Vec<f64>");
+    }
+
+    #[test]
+    fn link_names_drop_the_platform_decorations()
+    {
+        assert_eq!(link_name("libtest_lib.so"), "test_lib");
+        assert_eq!(link_name("libtest_lib.dylib"), "test_lib");
+        assert_eq!(link_name("test_lib.dll"), "test_lib");
+    }
+}
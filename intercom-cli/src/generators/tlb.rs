@@ -0,0 +1,1807 @@
+//! Enables the generation of binary type libraries that describe intercom
+//! libraries.
+//!
+//! The type library is written in the MSFT format that MIDL uses so that
+//! the libraries can be described without the Windows SDK. The file consists
+//! of a header, the offsets of the type infos and a directory of segments
+//! followed by the segments themselves. The member records of each type info
+//! are placed after the segments.
+//!
+//! The format is not documented by Microsoft. The layout follows the
+//! description of the format in the Wine project. The output is not compared
+//! against MIDL output. Instead the Windows tests check that `LoadTypeLibEx`
+//! accepts the libraries and reads the same type infos from them.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::GeneratorError;
+use super::{
+    is_in_out_array, member_indices, pascal_case, summary, LibraryContext, ModelOptions,
+    TypeSystemOptions,
+};
+
+use intercom::typelib::{
+    CoClass, Direction, Enum, Interface, InterfaceVariant, Method, MethodKind, Primitive,
+    StringEncoding, Struct, TypeDesc, TypeInfo, TypeLib,
+};
+use intercom::GUID;
+
+#[cfg(test)]
+mod reader;
+
+const MAGIC: i32 = 0x5446_534D;
+const FORMAT_VERSION: i32 = 0x0001_0002;
+
+// SYSKIND
+const SYS_WIN32: i32 = 1;
+const SYS_WIN64: i32 = 3;
+
+const HEADER_SIZE: i32 = 0x54;
+const TYPEINFO_SIZE: i32 = 0x64;
+const SEGMENT_COUNT: usize = 15;
+const SEGMENT_DIRECTORY_SIZE: i32 = SEGMENT_COUNT as i32 * 16;
+
+// Segment indices in the segment directory.
+const SEG_TYPEINFO: usize = 0;
+const SEG_IMPORTINFO: usize = 1;
+const SEG_IMPORTFILES: usize = 2;
+const SEG_REFERENCES: usize = 3;
+const SEG_GUIDHASH: usize = 4;
+const SEG_GUID: usize = 5;
+const SEG_NAMEHASH: usize = 6;
+const SEG_NAME: usize = 7;
+const SEG_STRING: usize = 8;
+const SEG_TYPEDESC: usize = 9;
+const SEG_ARRAYDESC: usize = 10;
+const SEG_CUSTDATA: usize = 11;
+const SEG_CUSTDATAGUID: usize = 12;
+
+/// The order in which the segments are laid out in the file.
+const SEGMENT_ORDER: [usize; 13] = [
+    SEG_TYPEINFO,
+    SEG_GUIDHASH,
+    SEG_GUID,
+    SEG_REFERENCES,
+    SEG_IMPORTINFO,
+    SEG_IMPORTFILES,
+    SEG_NAMEHASH,
+    SEG_NAME,
+    SEG_STRING,
+    SEG_TYPEDESC,
+    SEG_ARRAYDESC,
+    SEG_CUSTDATA,
+    SEG_CUSTDATAGUID,
+];
+
+const GUID_HASH_SIZE: usize = 0x20;
+const NAME_HASH_SIZE: usize = 0x80;
+
+// TYPEKIND
+const TKIND_ENUM: i32 = 0;
+const TKIND_RECORD: i32 = 1;
+const TKIND_INTERFACE: i32 = 3;
+const TKIND_DISPATCH: i32 = 4;
+const TKIND_COCLASS: i32 = 5;
+
+// TYPEFLAGS
+const TYPEFLAG_FCANCREATE: i32 = 0x2;
+const TYPEFLAG_FDUAL: i32 = 0x40;
+const TYPEFLAG_FNONEXTENSIBLE: i32 = 0x80;
+const TYPEFLAG_FOLEAUTOMATION: i32 = 0x100;
+const TYPEFLAG_FDISPATCHABLE: i32 = 0x1000;
+
+// VARENUM
+const VT_I2: i32 = 2;
+const VT_I4: i32 = 3;
+const VT_R4: i32 = 4;
+const VT_R8: i32 = 5;
+const VT_DATE: i32 = 7;
+const VT_BSTR: i32 = 8;
+const VT_DISPATCH: i32 = 9;
+const VT_BOOL: i32 = 11;
+const VT_VARIANT: i32 = 12;
+const VT_UNKNOWN: i32 = 13;
+const VT_I1: i32 = 16;
+const VT_UI1: i32 = 17;
+const VT_UI2: i32 = 18;
+const VT_UI4: i32 = 19;
+const VT_I8: i32 = 20;
+const VT_UI8: i32 = 21;
+const VT_VOID: i32 = 24;
+const VT_HRESULT: i32 = 25;
+const VT_PTR: i32 = 26;
+const VT_SAFEARRAY: i32 = 27;
+const VT_CARRAY: i32 = 28;
+const VT_USERDEFINED: i32 = 29;
+const VT_LPSTR: i32 = 30;
+const VT_INT_PTR: i32 = 37;
+const VT_UINT_PTR: i32 = 38;
+const VT_TYPEMASK: i32 = 0xfff;
+const VT_ARRAY: i32 = 0x2000;
+const VT_BYREF: i32 = 0x4000;
+
+// FUNCDESC and VARDESC values.
+const FUNC_PUREVIRTUAL: i32 = 1;
+const CC_STDCALL: i32 = 4;
+const INVOKE_FUNC: i32 = 1;
+const INVOKE_PROPERTYGET: i32 = 2;
+const INVOKE_PROPERTYPUT: i32 = 4;
+const PARAMFLAG_FIN: i32 = 0x1;
+const PARAMFLAG_FOUT: i32 = 0x2;
+const PARAMFLAG_FRETVAL: i32 = 0x8;
+const VAR_PERINSTANCE: i32 = 0;
+const VAR_CONST: i32 = 2;
+const IMPLTYPEFLAG_FDEFAULT: i32 = 0x1;
+
+// Sizes of the structures the type library API reconstructs from the
+// records. The records declare the memory needed for the reconstruction.
+const FUNCDESC_SIZE: i32 = 52;
+const VARDESC_SIZE: i32 = 36;
+const ELEMDESC_SIZE: i32 = 16;
+const TYPEDESC_SIZE: i32 = 8;
+const VARIANT_SIZE: i32 = 16;
+
+/// The interfaces imported from the OLE Automation type library.
+const STDOLE_LIBID: &str = "00020430-0000-0000-C000-000000000046";
+const STDOLE_FILE: &str = "stdole2.tlb";
+const IUNKNOWN_IID: &str = "00000000-0000-0000-C000-000000000046";
+const IDISPATCH_IID: &str = "00020400-0000-0000-C000-000000000046";
+
+/// The platform the type library describes.
+///
+/// The platform decides the size of the pointers in the virtual tables and
+/// must match the library the type library describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch
+{
+    Win32,
+    Win64,
+}
+
+impl Arch
+{
+    /// Detects the platform of the library from its executable header.
+    ///
+    /// The PE, ELF and Mach-O formats are recognized.
+    pub fn of_library(path: &Path) -> Result<Arch, GeneratorError>
+    {
+        let data = std::fs::read(path)?;
+        Arch::from_binary(&data).ok_or_else(|| {
+            format!(
+                "Could not detect the architecture of '{}', specify it with --arch",
+                path.display()
+            )
+            .into()
+        })
+    }
+
+    fn from_binary(data: &[u8]) -> Option<Arch>
+    {
+        let word = |offset: usize| data.get(offset..offset + 4).map(|_| read_i32(data, offset));
+        match data.get(0..4)? {
+            // The optional header magic of the PE files tells PE32 and PE32+
+            // apart regardless of the machine.
+            [b'M', b'Z', ..] => {
+                let pe = word(0x3c)? as usize;
+                if data.get(pe..pe + 4)? != b"PE\0\0" {
+                    return None;
+                }
+                match data.get(pe + 24..pe + 26)? {
+                    [0x0b, 0x01] => Some(Arch::Win32),
+                    [0x0b, 0x02] => Some(Arch::Win64),
+                    _ => None,
+                }
+            }
+            [0x7f, b'E', b'L', b'F'] => match data.get(4)? {
+                1 => Some(Arch::Win32),
+                2 => Some(Arch::Win64),
+                _ => None,
+            },
+            _ => match word(0)? as u32 {
+                0xfeed_face => Some(Arch::Win32),
+                0xfeed_facf => Some(Arch::Win64),
+                _ => None,
+            },
+        }
+    }
+
+    fn syskind(self) -> i32
+    {
+        match self {
+            Arch::Win32 => SYS_WIN32,
+            Arch::Win64 => SYS_WIN64,
+        }
+    }
+
+    fn pointer_size(self) -> i32
+    {
+        match self {
+            Arch::Win32 => 4,
+            Arch::Win64 => 8,
+        }
+    }
+}
+
+impl FromStr for Arch
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Arch, String>
+    {
+        match s {
+            "win32" => Ok(Arch::Win32),
+            "win64" => Ok(Arch::Win64),
+            other => Err(format!("Unknown architecture '{}'", other)),
+        }
+    }
+}
+
+/// Generates the binary type library.
+///
+/// - `arch` - The platform of the described library.
+/// - `out` - The writer to use for output.
+pub fn write(
+    lib: TypeLib,
+    opts: ModelOptions,
+    arch: Arch,
+    out: &mut dyn Write,
+) -> Result<(), GeneratorError>
+{
+    let ctx = LibraryContext::from(&lib);
+    let mut writer = MsftWriter::new(&ctx, arch);
+
+    // The type infos are allocated before describing them so that the types
+    // may refer to each other regardless of their order.
+    let mut entries = vec![];
+    for t in &lib.types {
+        if let TypeInfo::Enum(e) = t {
+            entries.push(Entry::Enum(e.as_ref()));
+        }
+    }
+    entries.extend(ctx.structs.iter().map(|s| Entry::Struct(s)));
+    for itf in &ctx.interfaces {
+        for ts_opts in &opts.type_systems {
+            if let Some(v) = itf.variants.iter().find(|v| v.as_ref().ts == ts_opts.ts) {
+                entries.push(Entry::Interface(itf, v.as_ref(), ts_opts));
+            }
+        }
+    }
+    for t in &lib.types {
+        if let TypeInfo::Class(cls) = t {
+            entries.push(Entry::Class(cls.as_ref()));
+        }
+    }
+
+    let lib_name = pascal_case(&lib.name);
+    writer.header.name = writer.name(&lib_name)?;
+    writer.header.guid = writer.guid(&lib.libid, -2);
+    writer.header.version = library_version(&lib.version)?;
+    for entry in &entries {
+        let name = entry.name();
+        writer.allocate_typeinfo(&name)?;
+    }
+
+    for (idx, entry) in entries.iter().enumerate() {
+        let typeinfo = match entry {
+            Entry::Enum(e) => writer.enumeration(idx, e)?,
+            Entry::Struct(s) => writer.record(idx, s)?,
+            Entry::Interface(itf, variant, ts_opts) => {
+                writer.interface(idx, itf, variant, ts_opts)?
+            }
+            Entry::Class(cls) => writer.coclass(idx, cls, &opts)?,
+        };
+        writer.typeinfos[idx] = typeinfo;
+    }
+
+    out.write_all(&writer.finish())?;
+    Ok(())
+}
+
+/// A type described by a type info.
+enum Entry<'a>
+{
+    Enum(&'a Enum),
+    Struct(&'a Struct),
+    Interface(&'a Interface, &'a InterfaceVariant, &'a TypeSystemOptions),
+    Class(&'a CoClass),
+}
+
+impl<'a> Entry<'a>
+{
+    fn name(&self) -> String
+    {
+        match self {
+            Entry::Enum(e) => e.name.to_string(),
+            Entry::Struct(s) => s.name.to_string(),
+            Entry::Interface(itf, _, ts_opts) => final_name(itf, ts_opts),
+            Entry::Class(cls) => cls.name.to_string(),
+        }
+    }
+}
+
+/// The name of the interface as it appears in the IDL.
+fn final_name(itf: &Interface, opts: &TypeSystemOptions) -> String
+{
+    let base_name = if itf.options.class_impl_interface {
+        Cow::from(format!("I{}", itf.name))
+    } else {
+        itf.name.clone()
+    };
+
+    match opts.use_full_name {
+        true => format!("{}_{:?}", base_name, opts.ts),
+        false => base_name.to_string(),
+    }
+}
+
+/// Converts the library version into the major version in the low word and
+/// the minor version in the high word.
+fn library_version(version: &str) -> Result<i32, GeneratorError>
+{
+    let mut parts = version.split('.').map(|part| {
+        part.parse::<u16>()
+            .map_err(|_| format!("Invalid library version '{}'", version))
+    });
+    let major = parts.next().unwrap_or(Ok(0))?;
+    let minor = parts.next().unwrap_or(Ok(0))?;
+    Ok(i32::from(major) | i32::from(minor) << 16)
+}
+
+/// The library level values of the file header.
+#[derive(Default)]
+struct Header
+{
+    name: i32,
+    guid: i32,
+    version: i32,
+    dispatch: Option<i32>,
+}
+
+/// The fixed size description of a type.
+#[derive(Default)]
+struct TypeInfoRecord
+{
+    kind: i32,
+    alignment: i32,
+    flags: i32,
+    name: i32,
+    guid: Option<i32>,
+    doc: Option<i32>,
+    impl_types: i16,
+    vtable_size: i16,
+    size: i32,
+    datatype1: Option<i32>,
+    datatype2: i32,
+    functions: Vec<Member>,
+    variables: Vec<Member>,
+}
+
+/// The record of a function or a variable of a type info.
+struct Member
+{
+    id: i32,
+    name: i32,
+    record: Vec<u8>,
+}
+
+impl TypeInfoRecord
+{
+    fn write_member_data(&self, out: &mut Vec<u8>)
+    {
+        let members = self
+            .functions
+            .iter()
+            .chain(&self.variables)
+            .collect::<Vec<_>>();
+        if members.is_empty() {
+            return;
+        }
+
+        // The records are followed by the member IDs, the names and the
+        // offsets of the records.
+        let records: usize = members.iter().map(|m| m.record.len()).sum();
+        push_i32(out, records as i32);
+        for m in &members {
+            out.extend_from_slice(&m.record);
+        }
+        for m in &members {
+            push_i32(out, m.id);
+        }
+        for m in &members {
+            push_i32(out, m.name);
+        }
+        let mut offset = 0;
+        for m in &members {
+            push_i32(out, offset);
+            offset += m.record.len() as i32;
+        }
+    }
+
+    fn write(&self, member_offset: i32, out: &mut Vec<u8>)
+    {
+        // The reserved fields hold the values MIDL writes for them. The
+        // readers don't depend on these.
+        let (mut res2, mut res3) = (0u32, -1);
+        for _ in &self.functions {
+            res2 = match res2 {
+                0 => 0x20,
+                _ => res2,
+            } << 1;
+            res3 = res3.max(0) + 0x38;
+        }
+        for (idx, _) in self.variables.iter().enumerate() {
+            if res2 == 0 {
+                res2 = 0x1a;
+            }
+            if [0, 1, 2, 4, 9].contains(&idx) {
+                res2 <<= 1;
+            }
+            res3 = res3.max(0) + 0x2c;
+        }
+
+        let element_count = (self.variables.len() as i32) << 16 | self.functions.len() as i32;
+        push_i32(out, self.kind | self.alignment << 11 | 0x20);
+        push_i32(out, member_offset);
+        push_i32(out, res2 as i32);
+        push_i32(out, res3);
+        push_i32(out, 3);
+        push_i32(out, 0);
+        push_i32(out, element_count);
+        push_i32(out, 0);
+        push_i32(out, 0);
+        push_i32(out, 0);
+        push_i32(out, 0);
+        push_i32(out, self.guid.unwrap_or(-1));
+        push_i32(out, self.flags);
+        push_i32(out, self.name);
+        push_i32(out, 0); // version
+        push_i32(out, self.doc.unwrap_or(-1));
+        push_i32(out, 0); // help string context
+        push_i32(out, 0); // help context
+        push_i32(out, -1); // custom data
+        push_i16(out, self.impl_types);
+        push_i16(out, self.vtable_size);
+        push_i32(out, self.size);
+        push_i32(out, self.datatype1.unwrap_or(-1));
+        push_i32(out, self.datatype2);
+        push_i32(out, 0);
+        push_i32(out, -1);
+    }
+}
+
+/// Inheritance information of an interface.
+#[derive(Clone, Copy)]
+struct Vtable
+{
+    /// Number of functions in the virtual table, including the inherited ones.
+    functions: i32,
+
+    /// Number of interfaces in the inheritance chain.
+    depth: i32,
+}
+
+/// Builds the segments of the type library.
+struct MsftWriter<'a>
+{
+    ctx: &'a LibraryContext<'a>,
+    arch: Arch,
+    header: Header,
+    segments: Vec<Vec<u8>>,
+    guid_hash: Vec<i32>,
+    name_hash: Vec<i32>,
+    name_count: i32,
+    name_chars: i32,
+    names: HashMap<String, i32>,
+    strings: HashMap<String, i32>,
+    hreftypes: HashMap<String, i32>,
+    imports: HashMap<&'static str, i32>,
+    alignments: HashMap<i32, i32>,
+    vtables: HashMap<i32, Vtable>,
+    typeinfos: Vec<TypeInfoRecord>,
+}
+
+impl<'a> MsftWriter<'a>
+{
+    fn new(ctx: &'a LibraryContext<'a>, arch: Arch) -> Self
+    {
+        MsftWriter {
+            ctx,
+            arch,
+            header: Header::default(),
+            segments: vec![vec![]; SEGMENT_COUNT],
+            guid_hash: vec![-1; GUID_HASH_SIZE],
+            name_hash: vec![-1; NAME_HASH_SIZE],
+            name_count: 0,
+            name_chars: 0,
+            names: HashMap::new(),
+            strings: HashMap::new(),
+            hreftypes: HashMap::new(),
+            imports: HashMap::new(),
+            alignments: HashMap::new(),
+            vtables: HashMap::new(),
+            typeinfos: vec![],
+        }
+    }
+
+    /// Reserves a type info for the type and returns its reference.
+    fn allocate_typeinfo(&mut self, name: &str) -> Result<i32, GeneratorError>
+    {
+        let hreftype = self.typeinfos.len() as i32 * TYPEINFO_SIZE;
+        let name_offset = self.name(name)?;
+
+        // The name table refers back to the type info the name belongs to.
+        let segment = &mut self.segments[SEG_NAME];
+        segment[name_offset as usize..name_offset as usize + 4]
+            .copy_from_slice(&hreftype.to_le_bytes());
+
+        self.hreftypes.insert(name.to_string(), hreftype);
+        self.typeinfos.push(TypeInfoRecord::default());
+        Ok(hreftype)
+    }
+
+    fn enumeration(&mut self, idx: usize, e: &Enum) -> Result<TypeInfoRecord, GeneratorError>
+    {
+        let hreftype = idx as i32 * TYPEINFO_SIZE;
+        self.alignments.insert(hreftype, 4);
+
+        let mut variables = vec![];
+        for (index, v) in e.variants.iter().enumerate() {
+            // The IDL enum values share a single scope so the variant names
+            // are prefixed with the enum name.
+            let value = self.constant(v.value);
+            let record = variable_record(
+                index,
+                builtin(VT_I4),
+                VAR_CONST,
+                VARDESC_SIZE + VARIANT_SIZE,
+                value,
+            );
+            variables.push(Member {
+                id: 0x4000_0000 + index as i32,
+                name: self.name(&format!("{}_{}", e.name, v.name))?,
+                record,
+            });
+        }
+
+        Ok(TypeInfoRecord {
+            kind: TKIND_ENUM,
+            alignment: 4,
+            name: self.name(&e.name)?,
+            doc: self.doc(&e.doc)?,
+            size: 4,
+            variables,
+            ..Default::default()
+        })
+    }
+
+    fn record(&mut self, idx: usize, s: &Struct) -> Result<TypeInfoRecord, GeneratorError>
+    {
+        // The struct layout doesn't depend on the type system. Interface
+        // pointers in the fields refer to the Automation type system.
+        let ts_opts = TypeSystemOptions {
+            ts: intercom::type_system::TypeSystemName::Automation,
+            use_full_name: true,
+        };
+
+        let mut alignment = 1;
+        let mut variables = vec![];
+        for (index, field) in s.fields.iter().enumerate() {
+            let ty = self.type_desc(&field.ty, &ts_opts)?;
+            alignment = alignment.max(self.alignment(ty.0));
+
+            let record = variable_record(
+                index,
+                ty.0,
+                VAR_PERINSTANCE,
+                VARDESC_SIZE + ty.1,
+                field.offset as i32,
+            );
+            variables.push(Member {
+                id: 0x4000_0000 + index as i32,
+                name: self.name(&field.name)?,
+                record,
+            });
+        }
+
+        let hreftype = idx as i32 * TYPEINFO_SIZE;
+        self.alignments.insert(hreftype, alignment);
+        Ok(TypeInfoRecord {
+            kind: TKIND_RECORD,
+            alignment,
+            name: self.name(&s.name)?,
+            doc: self.doc(&s.doc)?,
+            size: s.size as i32,
+            variables,
+            ..Default::default()
+        })
+    }
+
+    fn interface(
+        &mut self,
+        idx: usize,
+        itf: &Interface,
+        variant: &InterfaceVariant,
+        ts_opts: &TypeSystemOptions,
+    ) -> Result<TypeInfoRecord, GeneratorError>
+    {
+        // Dual interfaces derive from IDispatch and are described as dispatch
+        // interfaces, which the type library API presents as both the
+        // dispatch and the virtual table interface.
+        let (kind, flags, base) = match itf.options.dual {
+            true => (
+                TKIND_DISPATCH,
+                TYPEFLAG_FDUAL | TYPEFLAG_FOLEAUTOMATION | TYPEFLAG_FDISPATCHABLE,
+                Some(Cow::from("IDispatch")),
+            ),
+            false => (TKIND_INTERFACE, 0, variant.base.clone()),
+        };
+        let (base, inherited) = match base.as_ref().map(|b| b.as_ref()) {
+            None => (
+                None,
+                Vtable {
+                    functions: 0,
+                    depth: 0,
+                },
+            ),
+            Some("IUnknown") => (
+                Some(self.import("IUnknown")),
+                Vtable {
+                    functions: 3,
+                    depth: 1,
+                },
+            ),
+            Some("IDispatch") => (
+                Some(self.import("IDispatch")),
+                Vtable {
+                    functions: 7,
+                    depth: 2,
+                },
+            ),
+            Some(name) => {
+                let base_itf = self
+                    .ctx
+                    .itfs_by_name
+                    .get(name)
+                    .ok_or_else(|| format!("Unknown base interface '{}'", name))?;
+                let hreftype = self.hreftypes[&final_name(base_itf, ts_opts)];
+                let base_vtable = self.vtables[&hreftype];
+                (
+                    Some(hreftype),
+                    Vtable {
+                        functions: base_vtable.functions,
+                        depth: base_vtable.depth + 1,
+                    },
+                )
+            }
+        };
+
+        // The dispatch IDs of the dual interfaces start from one as zero is
+        // reserved for the default member.
+        let first_id = match itf.options.dual {
+            true => 1,
+            false => 0,
+        };
+        let pointer_size = self.arch.pointer_size();
+        let mut functions = vec![];
+        let members = member_indices(&variant.methods);
+        for (index, (method, member)) in variant.methods.iter().zip(members).enumerate() {
+            let vtable_offset = (inherited.functions + index as i32) * pointer_size;
+            let id = (first_id + member) as i32;
+            functions.push(self.function(index, id, vtable_offset, method.as_ref(), ts_opts)?);
+        }
+
+        let hreftype = idx as i32 * TYPEINFO_SIZE;
+        let vtable = Vtable {
+            functions: inherited.functions + functions.len() as i32,
+            depth: inherited.depth,
+        };
+        self.vtables.insert(hreftype, vtable);
+        self.alignments.insert(hreftype, pointer_size);
+        Ok(TypeInfoRecord {
+            kind,
+            alignment: pointer_size,
+            flags: flags | TYPEFLAG_FNONEXTENSIBLE,
+            name: self.name(&final_name(itf, ts_opts))?,
+            guid: Some(self.guid(&variant.iid, hreftype)),
+            doc: self.doc(&itf.doc)?,
+            impl_types: base.is_some() as i16,
+            vtable_size: (vtable.functions * pointer_size) as i16,
+            size: pointer_size,
+            datatype1: base,
+            datatype2: inherited.functions << 16 | inherited.depth,
+            functions,
+            ..Default::default()
+        })
+    }
+
+    fn function(
+        &mut self,
+        idx: usize,
+        id: i32,
+        vtable_offset: i32,
+        method: &Method,
+        ts_opts: &TypeSystemOptions,
+    ) -> Result<Member, GeneratorError>
+    {
+        let (return_type, return_size) = self.type_desc(&method.return_type.ty, ts_opts)?;
+        let invoke_kind = match method.kind {
+            MethodKind::Method => INVOKE_FUNC,
+            MethodKind::PropertyGet => INVOKE_PROPERTYGET,
+            MethodKind::PropertyPut => INVOKE_PROPERTYPUT,
+        };
+
+        // The optional fields are the help context and the help string.
+        let optional = match self.doc(&method.doc)? {
+            Some(doc) => vec![0, doc],
+            None => vec![],
+        };
+
+        let mut params = vec![];
+        let mut descsize = FUNCDESC_SIZE + return_size;
+        for arg in &method.parameters {
+            let (ty, size) = self.type_desc(&arg.ty, ts_opts)?;
+            let flags = match arg.direction {
+                Direction::In if is_in_out_array(&arg.ty) => PARAMFLAG_FIN | PARAMFLAG_FOUT,
+                Direction::In => PARAMFLAG_FIN,
+                Direction::Out => PARAMFLAG_FOUT,
+                Direction::Retval => PARAMFLAG_FOUT | PARAMFLAG_FRETVAL,
+                Direction::Return => {
+                    return Err("Direction::Return is invalid direction for arguments"
+                        .to_string()
+                        .into());
+                }
+            };
+            descsize += ELEMDESC_SIZE + size;
+            params.push([ty, self.name(&arg.name)?, flags]);
+        }
+
+        let size = 24 + 4 * optional.len() + 12 * params.len();
+        let mut record = vec![];
+        push_i32(&mut record, size as i32 | (idx as i32) << 16);
+        push_i32(&mut record, return_type);
+        push_i32(&mut record, 0);
+        push_i32(&mut record, descsize << 16 | vtable_offset);
+        push_i32(
+            &mut record,
+            CC_STDCALL << 8 | invoke_kind << 3 | FUNC_PUREVIRTUAL,
+        );
+        push_i32(&mut record, params.len() as i32);
+        for value in optional.iter().chain(params.iter().flatten()) {
+            push_i32(&mut record, *value);
+        }
+
+        Ok(Member {
+            id,
+            name: self.name(&method.name)?,
+            record,
+        })
+    }
+
+    fn coclass(
+        &mut self,
+        idx: usize,
+        cls: &CoClass,
+        opts: &ModelOptions,
+    ) -> Result<TypeInfoRecord, GeneratorError>
+    {
+        let pointer_size = self.arch.pointer_size();
+        let mut references = vec![];
+        for itf_ref in &cls.interfaces {
+            let itf = self
+                .ctx
+                .itfs_by_ref
+                .get(itf_ref.name.as_ref())
+                .ok_or_else(|| format!("Unknown interface '{}'", itf_ref.name))?;
+            for ts_opts in &opts.type_systems {
+                references.push(self.hreftypes[&final_name(itf, ts_opts)]);
+            }
+        }
+
+        // The references form a linked list in the reference table.
+        let segment = &mut self.segments[SEG_REFERENCES];
+        let first = segment.len() as i32;
+        for (index, hreftype) in references.iter().enumerate() {
+            let next = match index + 1 == references.len() {
+                true => -1,
+                false => segment.len() as i32 + 16,
+            };
+            let flags = match index {
+                0 => IMPLTYPEFLAG_FDEFAULT,
+                _ => 0,
+            };
+            push_i32(segment, *hreftype);
+            push_i32(segment, flags);
+            push_i32(segment, -1);
+            push_i32(segment, next);
+        }
+
+        let hreftype = idx as i32 * TYPEINFO_SIZE;
+        Ok(TypeInfoRecord {
+            kind: TKIND_COCLASS,
+            alignment: pointer_size,
+            flags: TYPEFLAG_FCANCREATE,
+            name: self.name(&cls.name)?,
+            guid: Some(self.guid(&cls.clsid, hreftype)),
+            doc: self.doc(&cls.doc)?,
+            impl_types: references.len() as i16,
+            size: pointer_size,
+            datatype1: match references.is_empty() {
+                true => None,
+                false => Some(first),
+            },
+            ..Default::default()
+        })
+    }
+
+    /// Encodes the type and gets the size of its reconstructed description.
+    fn type_desc(
+        &mut self,
+        ty: &TypeDesc,
+        ts_opts: &TypeSystemOptions,
+    ) -> Result<(i32, i32), GeneratorError>
+    {
+        Ok(match ty {
+            TypeDesc::Void => (builtin(VT_VOID), 0),
+            TypeDesc::Primitive(p) => (builtin(primitive_vt(*p)), 0),
+            TypeDesc::String(StringEncoding::Utf16) => (builtin(VT_BSTR), 0),
+            TypeDesc::String(StringEncoding::Utf8) => (builtin(VT_LPSTR), 0),
+            TypeDesc::Variant => (builtin(VT_VARIANT), 0),
+            TypeDesc::Interface { name, .. } => match name.as_ref() {
+                "IDispatch" => (builtin(VT_DISPATCH), 0),
+
+                // The interfaces the library doesn't describe, such as the
+                // system interfaces, are passed as IUnknown.
+                name if !self.ctx.itfs_by_name.contains_key(name) => (builtin(VT_UNKNOWN), 0),
+                name => {
+                    let target = self.named_type(name, ts_opts)?;
+                    self.derived_type(VT_PTR, (target, 0))
+                }
+            },
+            TypeDesc::Struct(name) | TypeDesc::Enum(name) => (self.named_type(name, ts_opts)?, 0),
+            TypeDesc::Array {
+                element: pointee, ..
+            }
+            | TypeDesc::Pointer { pointee, .. } => {
+                let target = self.type_desc(pointee, ts_opts)?;
+                self.derived_type(VT_PTR, target)
+            }
+            TypeDesc::SafeArray(element) => {
+                let target = self.type_desc(element, ts_opts)?;
+                self.derived_type(VT_SAFEARRAY, target)
+            }
+            TypeDesc::Nullable(inner) => self.type_desc(inner, ts_opts)?,
+        })
+    }
+
+    /// Gets the user defined type that refers to the named type info.
+    fn named_type(&mut self, name: &str, ts_opts: &TypeSystemOptions)
+        -> Result<i32, GeneratorError>
+    {
+        let final_name = match self.ctx.itfs_by_name.get(name) {
+            Some(itf) => final_name(itf, ts_opts),
+            None => name.to_string(),
+        };
+        let hreftype = match (self.hreftypes.get(&final_name), name) {
+            (Some(hreftype), _) => *hreftype,
+            (None, "GUID") => self.guid_record()?,
+            (None, _) => return Err(format!("Unknown type '{}'", name).into()),
+        };
+
+        let word = 0x7fff << 16 | VT_USERDEFINED;
+        Ok(self.type_entry(word, hreftype))
+    }
+
+    /// Describes the system GUID struct in the library.
+    ///
+    /// MIDL includes the system types the library refers to in the type
+    /// library in the same way.
+    fn guid_record(&mut self) -> Result<i32, GeneratorError>
+    {
+        let hreftype = self.allocate_typeinfo("GUID")?;
+        let (data4, data4_size) = self.array_type(builtin(VT_UI1), 8);
+        let fields = [
+            ("Data1", builtin(VT_UI4), 0, 0),
+            ("Data2", builtin(VT_UI2), 0, 4),
+            ("Data3", builtin(VT_UI2), 0, 6),
+            ("Data4", data4, data4_size, 8),
+        ];
+
+        let mut variables = vec![];
+        for (index, (name, ty, size, offset)) in fields.iter().enumerate() {
+            variables.push(Member {
+                id: 0x4000_0000 + index as i32,
+                name: self.name(name)?,
+                record: variable_record(index, *ty, VAR_PERINSTANCE, VARDESC_SIZE + size, *offset),
+            });
+        }
+
+        self.alignments.insert(hreftype, 4);
+        self.typeinfos[(hreftype / TYPEINFO_SIZE) as usize] = TypeInfoRecord {
+            kind: TKIND_RECORD,
+            alignment: 4,
+            name: self.name("GUID")?,
+            size: 16,
+            variables,
+            ..Default::default()
+        };
+        Ok(hreftype)
+    }
+
+    /// Gets the fixed size array type of the element type.
+    fn array_type(&mut self, element: i32, length: i32) -> (i32, i32)
+    {
+        // The array description holds the element type and the bounds of
+        // each dimension.
+        let segment = &mut self.segments[SEG_ARRAYDESC];
+        let offset = segment.len() as i32;
+        push_i32(segment, element);
+        push_i32(segment, 1);
+        push_i32(segment, length);
+        push_i32(segment, 0);
+
+        let entry = self.type_entry(0x7ffe << 16 | VT_CARRAY, offset);
+        (entry, TYPEDESC_SIZE + 16)
+    }
+
+    /// Gets the pointer or the safe array type of the target type.
+    fn derived_type(&mut self, vt: i32, (target, target_size): (i32, i32)) -> (i32, i32)
+    {
+        // The high word describes the target type for the built-in types.
+        let target_word = match target < 0 {
+            true => target >> 16 & VT_TYPEMASK,
+            false => self.type_word(target),
+        };
+        let mix = match (vt, target < 0) {
+            (VT_PTR, true) => target_word | VT_BYREF,
+            (VT_PTR, false) if target_word & 0xffff == VT_SAFEARRAY => {
+                (target_word >> 16 & VT_TYPEMASK) | VT_ARRAY | VT_BYREF
+            }
+            (VT_SAFEARRAY, true) => target_word | VT_ARRAY,
+            (_, false) if target_word >> 16 == 0x7fff => 0x7fff,
+            _ => 0x7ffe,
+        };
+        let offset = self.type_entry(mix << 16 | vt, target);
+        (offset, TYPEDESC_SIZE + target_size)
+    }
+
+    /// Finds or adds the entry in the type description table.
+    fn type_entry(&mut self, word: i32, target: i32) -> i32
+    {
+        let segment = &mut self.segments[SEG_TYPEDESC];
+        let existing = segment.chunks(8).position(|entry| {
+            read_i32(entry, 0) & 0xffff == word & 0xffff && read_i32(entry, 4) == target
+        });
+        match existing {
+            Some(idx) => idx as i32 * 8,
+            None => {
+                let offset = segment.len() as i32;
+                push_i32(segment, word);
+                push_i32(segment, target);
+                offset
+            }
+        }
+    }
+
+    fn type_word(&self, offset: i32) -> i32
+    {
+        read_i32(&self.segments[SEG_TYPEDESC], offset as usize)
+    }
+
+    /// Gets the alignment of the values of the type.
+    fn alignment(&self, ty: i32) -> i32
+    {
+        if ty >= 0 {
+            return match self.type_word(ty) & VT_TYPEMASK {
+                VT_USERDEFINED => {
+                    let hreftype = read_i32(&self.segments[SEG_TYPEDESC], ty as usize + 4);
+                    self.alignments
+                        .get(&hreftype)
+                        .cloned()
+                        .unwrap_or(self.arch.pointer_size())
+                }
+                _ => self.arch.pointer_size(),
+            };
+        }
+
+        match ty & VT_TYPEMASK {
+            VT_I1 | VT_UI1 => 1,
+            VT_I2 | VT_UI2 | VT_BOOL => 2,
+            VT_I4 | VT_UI4 | VT_R4 | VT_HRESULT => 4,
+            VT_BSTR | VT_LPSTR | VT_DISPATCH | VT_UNKNOWN | VT_INT_PTR | VT_UINT_PTR => {
+                self.arch.pointer_size()
+            }
+            _ => 8,
+        }
+    }
+
+    /// Gets the reference to the type imported from the OLE Automation
+    /// library.
+    fn import(&mut self, name: &'static str) -> i32
+    {
+        if let Some(hreftype) = self.imports.get(name) {
+            return *hreftype;
+        }
+
+        // The import file is shared by all the imported types.
+        if self.segments[SEG_IMPORTFILES].is_empty() {
+            let guid = self.guid(&GUID::parse(STDOLE_LIBID).unwrap(), 2);
+            let segment = &mut self.segments[SEG_IMPORTFILES];
+            push_i32(segment, guid);
+            push_i32(segment, 0); // LCID
+            push_i32(segment, 2); // Version 2.0
+            push_string(segment, STDOLE_FILE, (STDOLE_FILE.len() << 2 | 1) as u16);
+        }
+
+        let (kind, iid) = match name {
+            "IDispatch" => (TKIND_DISPATCH, IDISPATCH_IID),
+            _ => (TKIND_INTERFACE, IUNKNOWN_IID),
+        };
+        let hreftype = self.segments[SEG_IMPORTINFO].len() as i32 | 1;
+        let guid = self.guid(&GUID::parse(iid).unwrap(), hreftype);
+
+        // The flags mark the third field as an offset in the GUID table.
+        let segment = &mut self.segments[SEG_IMPORTINFO];
+        push_i32(segment, kind << 24 | 0x10000);
+        push_i32(segment, 0);
+        push_i32(segment, guid);
+
+        if name == "IDispatch" {
+            self.header.dispatch = Some(hreftype);
+        }
+        self.imports.insert(name, hreftype);
+        hreftype
+    }
+
+    /// Stores the constant value.
+    ///
+    /// Small values are stored in the record while the others are stored in
+    /// the custom data segment.
+    fn constant(&mut self, value: i64) -> i32
+    {
+        if (0..0x400_0000).contains(&value) {
+            return (0x8000_0000u32 | (VT_I4 as u32) << 26 | value as u32) as i32;
+        }
+
+        let segment = &mut self.segments[SEG_CUSTDATA];
+        let offset = segment.len() as i32;
+        match value as i32 as i64 == value {
+            true => {
+                push_i16(segment, VT_I4 as i16);
+                push_i32(segment, value as i32);
+            }
+            false => {
+                push_i16(segment, VT_I8 as i16);
+                segment.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        pad(segment, 0x57);
+        offset
+    }
+
+    /// Adds the name to the name table.
+    fn name(&mut self, name: &str) -> Result<i32, GeneratorError>
+    {
+        if let Some(offset) = self.names.get(name) {
+            return Ok(*offset);
+        }
+
+        // The length of the name is stored in a single byte.
+        let length = u8::try_from(name.len())
+            .map_err(|_| format!("Name '{}' is longer than 255 bytes", name))?;
+        let hash = name_hash(name, self.arch.syskind());
+        let bucket = (hash & 0x7f) as usize;
+        let segment = &mut self.segments[SEG_NAME];
+        let offset = segment.len() as i32;
+        push_i32(segment, -1);
+        push_i32(segment, self.name_hash[bucket]);
+        segment.push(length);
+        segment.push(0);
+        push_i16(segment, hash as i16);
+        segment.extend_from_slice(name.as_bytes());
+        pad(segment, 0x57);
+
+        self.name_hash[bucket] = offset;
+        self.name_count += 1;
+        self.name_chars += name.len() as i32;
+        self.names.insert(name.to_string(), offset);
+        Ok(offset)
+    }
+
+    /// Adds the summary of the documentation to the string table.
+    fn doc(&mut self, doc: &Option<Cow<'static, str>>) -> Result<Option<i32>, GeneratorError>
+    {
+        let summary = match summary(doc) {
+            Some(summary) => summary,
+            None => return Ok(None),
+        };
+        if let Some(offset) = self.strings.get(&summary) {
+            return Ok(Some(*offset));
+        }
+
+        let length = u16::try_from(summary.len())
+            .map_err(|_| format!("Documentation '{}' is longer than 65535 bytes", summary))?;
+        let segment = &mut self.segments[SEG_STRING];
+        let offset = segment.len() as i32;
+        push_string(segment, &summary, length);
+        self.strings.insert(summary, offset);
+        Ok(Some(offset))
+    }
+
+    /// Adds the GUID to the GUID table.
+    fn guid(&mut self, guid: &GUID, hreftype: i32) -> i32
+    {
+        let bytes = guid_bytes(guid);
+        let hash = bytes.chunks(2).fold(0, |hash, word| {
+            hash ^ u16::from_le_bytes([word[0], word[1]])
+        });
+        let bucket = (hash & 0x1f) as usize;
+
+        let segment = &mut self.segments[SEG_GUID];
+        let offset = segment.len() as i32;
+        segment.extend_from_slice(&bytes);
+        push_i32(segment, hreftype);
+        push_i32(segment, self.guid_hash[bucket]);
+        self.guid_hash[bucket] = offset;
+        offset
+    }
+
+    /// Lays out the segments and the member data and writes the file.
+    fn finish(mut self) -> Vec<u8>
+    {
+        let hashes = [
+            (SEG_GUIDHASH, &self.guid_hash),
+            (SEG_NAMEHASH, &self.name_hash),
+        ];
+        for (segment, hash) in hashes.iter() {
+            for value in hash.iter() {
+                push_i32(&mut self.segments[*segment], *value);
+            }
+        }
+
+        // The type infos refer to the member data after the segments so they
+        // are written once the segments have been laid out.
+        let typeinfo_count = self.typeinfos.len() as i32;
+        let mut position = HEADER_SIZE + SEGMENT_DIRECTORY_SIZE + 4 * typeinfo_count;
+        let mut directory = vec![(-1, 0); SEGMENT_COUNT];
+        for segment in SEGMENT_ORDER.iter() {
+            let length = match *segment {
+                SEG_TYPEINFO => typeinfo_count * TYPEINFO_SIZE,
+                other => self.segments[other].len() as i32,
+            };
+            if length > 0 {
+                directory[*segment] = (position, length);
+                position += length;
+            }
+        }
+
+        let mut typeinfo_segment = vec![];
+        let mut member_data = vec![];
+        for typeinfo in &self.typeinfos {
+            typeinfo.write(position + member_data.len() as i32, &mut typeinfo_segment);
+            typeinfo.write_member_data(&mut member_data);
+        }
+        self.segments[SEG_TYPEINFO] = typeinfo_segment;
+
+        let mut out = vec![];
+        push_i32(&mut out, MAGIC);
+        push_i32(&mut out, FORMAT_VERSION);
+        push_i32(&mut out, self.header.guid);
+        push_i32(&mut out, 0); // LCID
+        push_i32(&mut out, 0); // LCID
+        push_i32(&mut out, 0x40 | self.arch.syskind());
+        push_i32(&mut out, self.header.version);
+        push_i32(&mut out, 0); // Flags
+        push_i32(&mut out, self.typeinfos.len() as i32);
+        push_i32(&mut out, -1); // Help string
+        push_i32(&mut out, 0); // Help string context
+        push_i32(&mut out, 0); // Help context
+        push_i32(&mut out, self.name_count);
+        push_i32(&mut out, self.name_chars);
+        push_i32(&mut out, self.header.name);
+        push_i32(&mut out, -1); // Help file
+        push_i32(&mut out, -1); // Custom data
+        push_i32(&mut out, GUID_HASH_SIZE as i32);
+        push_i32(&mut out, NAME_HASH_SIZE as i32);
+        push_i32(&mut out, self.header.dispatch.unwrap_or(-1));
+        push_i32(&mut out, self.imports.len() as i32);
+
+        for idx in 0..self.typeinfos.len() {
+            push_i32(&mut out, idx as i32 * TYPEINFO_SIZE);
+        }
+        for (offset, length) in directory {
+            push_i32(&mut out, offset);
+            push_i32(&mut out, length);
+            push_i32(&mut out, -1);
+            push_i32(&mut out, 0x0f);
+        }
+        for segment in SEGMENT_ORDER.iter() {
+            out.extend_from_slice(&self.segments[*segment]);
+        }
+        out.extend_from_slice(&member_data);
+        out
+    }
+}
+
+fn variable_record(idx: usize, ty: i32, kind: i32, descsize: i32, value: i32) -> Vec<u8>
+{
+    let mut record = vec![];
+    push_i32(&mut record, 0x14 | (idx as i32) << 16);
+    push_i32(&mut record, ty);
+    push_i32(&mut record, 0);
+    push_i32(&mut record, descsize << 16 | kind);
+    push_i32(&mut record, value);
+    record
+}
+
+/// Encodes the built-in type in place.
+fn builtin(vt: i32) -> i32
+{
+    (0x8000_0000u32 | (vt << 16 | vt) as u32) as i32
+}
+
+fn primitive_vt(p: Primitive) -> i32
+{
+    match p {
+        Primitive::Bool => VT_UI1,
+        Primitive::I8 => VT_I1,
+        Primitive::U8 => VT_UI1,
+        Primitive::I16 => VT_I2,
+        Primitive::U16 => VT_UI2,
+        Primitive::I32 => VT_I4,
+        Primitive::U32 => VT_UI4,
+        Primitive::I64 => VT_I8,
+        Primitive::U64 => VT_UI8,
+        Primitive::ISize => VT_INT_PTR,
+        Primitive::USize => VT_UINT_PTR,
+        Primitive::F32 => VT_R4,
+        Primitive::F64 => VT_R8,
+        Primitive::Hresult => VT_HRESULT,
+        Primitive::VariantBool => VT_BOOL,
+        Primitive::Date => VT_DATE,
+    }
+}
+
+/// Hashes the name the way `LHashValOfNameSys` does for the neutral locale.
+///
+/// The type library API uses the hash to look up the names. The names are
+/// compared case-insensitively so the hash ignores the case of the ASCII
+/// letters.
+fn name_hash(name: &str, syskind: i32) -> u32
+{
+    let low = name.bytes().fold(0x0dea_dbeeu32, |hash, c| {
+        hash.wrapping_mul(37)
+            .wrapping_add(u32::from(c.to_ascii_uppercase()))
+    });
+    (syskind as u32) << 16 | (low % 65599) & 0xffff
+}
+
+/// GUID in the little endian memory layout.
+fn guid_bytes(guid: &GUID) -> [u8; 16]
+{
+    let mut bytes = [0; 16];
+    bytes[0..4].copy_from_slice(&guid.data1.to_le_bytes());
+    bytes[4..6].copy_from_slice(&guid.data2.to_le_bytes());
+    bytes[6..8].copy_from_slice(&guid.data3.to_le_bytes());
+    bytes[8..16].copy_from_slice(&guid.data4);
+    bytes
+}
+
+fn push_i32(out: &mut Vec<u8>, value: i32)
+{
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_i16(out: &mut Vec<u8>, value: i16)
+{
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Writes the string prefixed with the length field.
+fn push_string(out: &mut Vec<u8>, value: &str, length: u16)
+{
+    out.extend_from_slice(&length.to_le_bytes());
+    out.extend_from_slice(value.as_bytes());
+    pad(out, 0x57);
+}
+
+/// Pads the segment to four byte alignment.
+fn pad(out: &mut Vec<u8>, value: u8)
+{
+    let padding = (4 - out.len() % 4) % 4;
+    out.resize(out.len() + padding, value);
+}
+
+fn read_i32(data: &[u8], offset: usize) -> i32
+{
+    i32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::generators::test::{assert_binary_snapshot, sample_lib, sample_options};
+    use intercom::type_system::TypeSystemName;
+    use intercom::typelib::{Arg, EnumVariant, Field, InterfaceOptions, PointerDirection};
+    use intercom::ComBox;
+
+    /// The round trip tests describe the libraries for 64-bit Windows.
+    const POINTER_SIZE: i32 = 8;
+
+    fn round_trip(lib: TypeLib) -> reader::Library
+    {
+        let mut output = vec![];
+        write(lib, sample_options(), Arch::Win64, &mut output).unwrap();
+        reader::read(&output).unwrap()
+    }
+
+    #[test]
+    fn tlb_snapshot()
+    {
+        let mut output = vec![];
+        write(sample_lib(), sample_options(), Arch::Win64, &mut output).unwrap();
+
+        assert_binary_snapshot("sample_lib.tlb", &output);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn windows_loads_the_library()
+    {
+        use std::os::windows::ffi::OsStrExt;
+        use winapi::um::oaidl::ITypeLib;
+        use winapi::um::oleauto::{LoadTypeLibEx, REGKIND_NONE};
+
+        let arch = match cfg!(target_pointer_width = "64") {
+            true => Arch::Win64,
+            false => Arch::Win32,
+        };
+        let mut output = vec![];
+        write(sample_lib(), sample_options(), arch, &mut output).unwrap();
+        let expected = reader::read(&output).unwrap();
+
+        let path = std::env::temp_dir().join("intercom-cli-sample_lib.tlb");
+        std::fs::write(&path, &output).unwrap();
+        let wide_path = path
+            .as_os_str()
+            .encode_wide()
+            .chain(Some(0))
+            .collect::<Vec<_>>();
+
+        unsafe {
+            let mut lib: *mut ITypeLib = std::ptr::null_mut();
+            let hr = LoadTypeLibEx(wide_path.as_ptr(), REGKIND_NONE, &mut lib);
+            assert_eq!(hr, 0, "LoadTypeLibEx failed with {:#x}", hr);
+
+            let lib = &*lib;
+            assert_eq!(lib.GetTypeInfoCount() as usize, expected.types.len());
+            for (idx, t) in expected.types.iter().enumerate() {
+                let mut kind = 0;
+                assert_eq!(lib.GetTypeInfoType(idx as u32, &mut kind), 0);
+                assert_eq!(kind as i32, t.kind, "Type info {} differs", t.name);
+            }
+            lib.Release();
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn library_survives_the_round_trip()
+    {
+        let lib = round_trip(sample_lib());
+        assert_eq!(lib.name, "SampleLib");
+        assert_eq!(
+            lib.libid,
+            GUID::parse("00000001-0000-0000-0000-000000000000").unwrap()
+        );
+        assert_eq!(lib.version, (0, 1));
+        assert_eq!(lib.syskind, SYS_WIN64);
+
+        let names = lib
+            .types
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "Rounding",
+                "Point",
+                "ICalculator_Automation",
+                "ICalculator_Raw",
+                "IAdvancedCalculator_Automation",
+                "IAdvancedCalculator_Raw",
+                "Calculator",
+            ]
+        );
+    }
+
+    #[test]
+    fn interfaces_survive_the_round_trip()
+    {
+        let lib = round_trip(sample_lib());
+
+        let itf = &lib.types[2];
+        assert_eq!(itf.kind, TKIND_INTERFACE);
+        assert_eq!(
+            itf.guid,
+            Some(GUID::parse("00000003-0000-0000-0000-000000000000").unwrap())
+        );
+        assert_eq!(itf.help.as_deref(), Some("Basic arithmetic."));
+        assert_eq!(
+            itf.impl_types,
+            vec![(format!("import({})", IUNKNOWN_IID), 0)]
+        );
+        assert_eq!(itf.vtable_size, 6 * POINTER_SIZE);
+
+        let add = &itf.functions[0];
+        assert_eq!(add.name, "add");
+        assert_eq!(add.id, 0);
+        assert_eq!(add.invoke_kind, INVOKE_FUNC);
+        assert_eq!(add.vtable_offset, 3 * POINTER_SIZE);
+        assert_eq!(add.return_type, "long");
+        assert_eq!(
+            add.params,
+            vec![
+                ("a".to_string(), "long".to_string(), PARAMFLAG_FIN),
+                ("b".to_string(), "long".to_string(), PARAMFLAG_FIN),
+            ]
+        );
+        assert_eq!(add.help.as_deref(), Some("Adds the \"a\" and the \"b\"."));
+
+        let sum = &itf.functions[1];
+        assert_eq!(sum.params[0].1, "SAFEARRAY(long)");
+        assert_eq!(sum.help, None);
+
+        let name = &itf.functions[2];
+        assert_eq!(name.return_type, "HRESULT");
+        assert_eq!(
+            name.params,
+            vec![(
+                "__out".to_string(),
+                "BSTR*".to_string(),
+                PARAMFLAG_FOUT | PARAMFLAG_FRETVAL
+            )]
+        );
+
+        // The Raw type system passes the strings as UTF-8 and the slices as
+        // pointers.
+        let raw = &lib.types[3];
+        assert_eq!(raw.functions[1].params[0].1, "long*");
+        assert_eq!(raw.functions[2].params[0].1, "LPSTR*");
+    }
+
+    #[test]
+    fn derived_interfaces_extend_the_vtable()
+    {
+        let lib = round_trip(sample_lib());
+
+        let itf = &lib.types[4];
+        assert_eq!(
+            itf.impl_types,
+            vec![("ICalculator_Automation".to_string(), 0)]
+        );
+        assert_eq!(itf.vtable_size, 9 * POINTER_SIZE);
+
+        let functions = itf
+            .functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.id, f.invoke_kind, f.vtable_offset))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            functions,
+            vec![
+                ("power", 0, INVOKE_FUNC, 6 * POINTER_SIZE),
+                ("precision", 1, INVOKE_PROPERTYGET, 7 * POINTER_SIZE),
+                ("precision", 1, INVOKE_PROPERTYPUT, 8 * POINTER_SIZE),
+            ]
+        );
+    }
+
+    #[test]
+    fn coclasses_survive_the_round_trip()
+    {
+        let lib = round_trip(sample_lib());
+
+        let cls = &lib.types[6];
+        assert_eq!(cls.kind, TKIND_COCLASS);
+        assert_eq!(cls.flags, TYPEFLAG_FCANCREATE);
+        assert_eq!(
+            cls.guid,
+            Some(GUID::parse("00000002-0000-0000-0000-000000000000").unwrap())
+        );
+        assert_eq!(
+            cls.help.as_deref(),
+            Some("Calculator that works on the integers.")
+        );
+        assert_eq!(
+            cls.impl_types,
+            vec![
+                ("ICalculator_Automation".to_string(), IMPLTYPEFLAG_FDEFAULT),
+                ("ICalculator_Raw".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn enums_and_structs_survive_the_round_trip()
+    {
+        let lib = round_trip(sample_lib());
+
+        let e = &lib.types[0];
+        assert_eq!(e.kind, TKIND_ENUM);
+        assert_eq!(e.help.as_deref(), Some("Rounding of the results."));
+        let variants = e
+            .variables
+            .iter()
+            .map(|v| (v.name.as_str(), v.id, v.kind, v.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            variants,
+            vec![
+                ("Rounding_Down", 0x4000_0000, VAR_CONST, 0),
+                ("Rounding_Nearest", 0x4000_0001, VAR_CONST, 5)
+            ]
+        );
+
+        let s = &lib.types[1];
+        assert_eq!(s.kind, TKIND_RECORD);
+        assert_eq!(s.size, 16);
+        assert_eq!(s.alignment, 8);
+        let fields = s
+            .variables
+            .iter()
+            .map(|v| (v.name.as_str(), v.ty.as_str(), v.kind, v.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("x", "double", VAR_PERINSTANCE, 0),
+                ("y", "double", VAR_PERINSTANCE, 8)
+            ]
+        );
+    }
+
+    #[test]
+    fn win32_libraries_use_four_byte_pointers()
+    {
+        let mut output = vec![];
+        write(sample_lib(), sample_options(), Arch::Win32, &mut output).unwrap();
+        let lib = reader::read(&output).unwrap();
+        assert_eq!(lib.syskind, SYS_WIN32);
+
+        let itf = &lib.types[2];
+        assert_eq!(itf.alignment, 4);
+        assert_eq!(itf.size, 4);
+        assert_eq!(itf.vtable_size, 6 * 4);
+        assert_eq!(itf.functions[0].vtable_offset, 3 * 4);
+    }
+
+    #[test]
+    fn arch_is_detected_from_the_binary()
+    {
+        let mut pe = vec![0; 0x100];
+        pe[0..2].copy_from_slice(b"MZ");
+        pe[0x3c] = 0x80;
+        pe[0x80..0x84].copy_from_slice(b"PE\0\0");
+        pe[0x98..0x9a].copy_from_slice(&[0x0b, 0x01]);
+        assert_eq!(Arch::from_binary(&pe), Some(Arch::Win32));
+        pe[0x98..0x9a].copy_from_slice(&[0x0b, 0x02]);
+        assert_eq!(Arch::from_binary(&pe), Some(Arch::Win64));
+
+        assert_eq!(Arch::from_binary(b"\x7fELF\x01"), Some(Arch::Win32));
+        assert_eq!(Arch::from_binary(b"\x7fELF\x02"), Some(Arch::Win64));
+        assert_eq!(Arch::from_binary(b"MZ"), None);
+        assert_eq!(Arch::from_binary(b"text"), None);
+
+        // The test binary itself is a library of the current platform.
+        let expected = match cfg!(target_pointer_width = "64") {
+            true => Arch::Win64,
+            false => Arch::Win32,
+        };
+        let current = std::env::current_exe().unwrap();
+        assert_eq!(Arch::of_library(&current).unwrap(), expected);
+    }
+
+    #[test]
+    fn overlong_names_are_rejected()
+    {
+        let lib = |name: String, doc: String| {
+            TypeLib::__new(
+                "lib".into(),
+                GUID::parse("00000001-0000-0000-0000-000000000000").unwrap(),
+                "1.0".into(),
+                vec![TypeInfo::Enum(ComBox::new(Enum {
+                    name: name.into(),
+                    ty: "i32".into(),
+                    variants: vec![],
+                    doc: Some(doc.into()),
+                }))],
+            )
+        };
+        let write_lib = |lib| write(lib, sample_options(), Arch::Win64, &mut vec![]);
+
+        assert!(write_lib(lib("E".repeat(255), "Doc.".into())).is_ok());
+        assert!(write_lib(lib("E".repeat(256), "Doc.".into())).is_err());
+        assert!(write_lib(lib("E".into(), "D".repeat(0x1_0000))).is_err());
+    }
+
+    #[test]
+    fn large_constants_are_stored_as_custom_data()
+    {
+        let variant = |name: &'static str, value| EnumVariant {
+            name: name.into(),
+            value,
+        };
+        let lib = TypeLib::__new(
+            "lib".into(),
+            GUID::parse("00000001-0000-0000-0000-000000000000").unwrap(),
+            "1.0".into(),
+            vec![TypeInfo::Enum(ComBox::new(Enum {
+                name: "Flags".into(),
+                ty: "i64".into(),
+                variants: vec![
+                    variant("Small", 1),
+                    variant("Negative", -1),
+                    variant("Large", 0x7fff_ffff),
+                    variant("Huge", 0x1_0000_0000),
+                ],
+                doc: None,
+            }))],
+        );
+
+        let lib = round_trip(lib);
+        assert_eq!(lib.version, (1, 0));
+        let values = lib.types[0]
+            .variables
+            .iter()
+            .map(|v| v.value)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![1, -1, 0x7fff_ffff, 0x1_0000_0000]);
+    }
+
+    #[test]
+    fn struct_fields_refer_to_the_library_types()
+    {
+        let field = |name: &'static str, ty, offset| Field {
+            name: name.into(),
+            ty,
+            offset,
+        };
+        let u8_ty = || TypeDesc::Primitive(Primitive::U8);
+        let lib = TypeLib::__new(
+            "lib".into(),
+            GUID::parse("00000001-0000-0000-0000-000000000000").unwrap(),
+            "1.0".into(),
+            vec![
+                TypeInfo::Struct(ComBox::new(Struct {
+                    name: "Node".into(),
+                    size: 16,
+                    fields: vec![
+                        field(
+                            "next",
+                            TypeDesc::pointer(
+                                TypeDesc::Struct("Node".into()),
+                                PointerDirection::InOut,
+                            ),
+                            0,
+                        ),
+                        field("tag", u8_ty(), 8),
+                    ],
+                    doc: None,
+                })),
+                TypeInfo::Struct(ComBox::new(Struct {
+                    name: "Tagged".into(),
+                    size: 2,
+                    fields: vec![field("tag", u8_ty(), 0), field("data", u8_ty(), 1)],
+                    doc: None,
+                })),
+                TypeInfo::Struct(ComBox::new(Struct {
+                    name: "Keyed".into(),
+                    size: 16,
+                    fields: vec![field("key", TypeDesc::Struct("GUID".into()), 0)],
+                    doc: None,
+                })),
+                TypeInfo::Interface(ComBox::new(Interface {
+                    name: "IEmpty".into(),
+                    variants: vec![],
+                    options: InterfaceOptions::default(),
+                    doc: None,
+                })),
+            ],
+        );
+
+        let lib = round_trip(lib);
+        let by_name = |name| lib.types.iter().find(|t| t.name == name).unwrap();
+        let node = by_name("Node");
+        assert_eq!(node.variables[0].ty, "Node*");
+        assert_eq!(node.variables[1].ty, "unsigned char");
+        assert_eq!(node.alignment, 8);
+        assert_eq!(by_name("Tagged").alignment, 1);
+
+        // The system GUID struct is described in the library.
+        let keyed = by_name("Keyed");
+        assert_eq!(keyed.variables[0].ty, "GUID");
+        assert_eq!(keyed.alignment, 4);
+        let fields = by_name("GUID")
+            .variables
+            .iter()
+            .map(|v| (v.name.as_str(), v.ty.as_str(), v.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("Data1", "unsigned long", 0),
+                ("Data2", "unsigned short", 4),
+                ("Data3", "unsigned short", 6),
+                ("Data4", "unsigned char[8]", 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn dual_interfaces_are_dispatch_interfaces()
+    {
+        let method = Method {
+            name: "run".into(),
+            kind: MethodKind::Method,
+            return_type: Arg {
+                name: "".into(),
+                ty: TypeDesc::Primitive(Primitive::Hresult),
+                direction: Direction::Return,
+                doc: None,
+            },
+            parameters: vec![],
+            doc: None,
+        };
+        let lib = TypeLib::__new(
+            "lib".into(),
+            GUID::parse("00000001-0000-0000-0000-000000000000").unwrap(),
+            "1.0".into(),
+            vec![TypeInfo::Interface(ComBox::new(Interface {
+                name: "IRunner".into(),
+                variants: vec![ComBox::new(InterfaceVariant {
+                    ts: TypeSystemName::Automation,
+                    iid: GUID::parse("00000002-0000-0000-0000-000000000000").unwrap(),
+                    methods: vec![ComBox::new(method)],
+                    base: Some("IUnknown".into()),
+                })],
+                options: InterfaceOptions {
+                    dual: true,
+                    ..Default::default()
+                },
+                doc: None,
+            }))],
+        );
+
+        let lib = round_trip(lib);
+        let itf = &lib.types[0];
+        assert_eq!(itf.kind, TKIND_DISPATCH);
+        assert_eq!(
+            itf.flags,
+            TYPEFLAG_FDUAL
+                | TYPEFLAG_FOLEAUTOMATION
+                | TYPEFLAG_FDISPATCHABLE
+                | TYPEFLAG_FNONEXTENSIBLE
+        );
+        assert_eq!(
+            itf.impl_types,
+            vec![(format!("import({})", IDISPATCH_IID), 0)]
+        );
+
+        // The dispatch IDs start from one and the methods follow the
+        // IDispatch methods in the virtual table.
+        assert_eq!(itf.functions[0].id, 1);
+        assert_eq!(itf.functions[0].vtable_offset, 7 * POINTER_SIZE);
+    }
+}
@@ -0,0 +1,378 @@
+//! Reads the binary type libraries back for verifying the writer.
+//!
+//! The reader resolves the references between the segments and describes
+//! the types in a form that is convenient to compare in the tests.
+
+use super::*;
+
+#[derive(Debug)]
+pub struct Library
+{
+    pub name: String,
+    pub libid: GUID,
+    pub version: (u16, u16),
+    pub syskind: i32,
+    pub types: Vec<Type>,
+}
+
+#[derive(Debug)]
+pub struct Type
+{
+    pub kind: i32,
+    pub name: String,
+    pub guid: Option<GUID>,
+    pub flags: i32,
+    pub help: Option<String>,
+    pub size: i32,
+    pub alignment: i32,
+    pub vtable_size: i32,
+    pub impl_types: Vec<(String, i32)>,
+    pub functions: Vec<Function>,
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Debug)]
+pub struct Function
+{
+    pub name: String,
+    pub id: i32,
+    pub invoke_kind: i32,
+    pub vtable_offset: i32,
+    pub return_type: String,
+    pub params: Vec<(String, String, i32)>,
+    pub help: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Variable
+{
+    pub name: String,
+    pub id: i32,
+    pub ty: String,
+    pub kind: i32,
+
+    /// The value of the constants or the offset of the fields.
+    pub value: i64,
+}
+
+/// Reads the type library.
+pub fn read(data: &[u8]) -> Result<Library, String>
+{
+    let reader = Reader::new(data)?;
+    let typeinfo_count = reader.i32(0x20)?;
+    let types = (0..typeinfo_count)
+        .map(|idx| reader.typeinfo(idx * TYPEINFO_SIZE))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let version = reader.i32(0x18)?;
+    Ok(Library {
+        name: reader.name(reader.i32(0x38)?)?,
+        libid: reader.guid(reader.i32(0x08)?)?,
+        version: (version as u16, (version >> 16) as u16),
+        syskind: reader.i32(0x14)? & 0xf,
+        types,
+    })
+}
+
+struct Reader<'a>
+{
+    data: &'a [u8],
+    segments: Vec<(usize, usize)>,
+}
+
+impl<'a> Reader<'a>
+{
+    fn new(data: &'a [u8]) -> Result<Self, String>
+    {
+        let mut reader = Reader {
+            data,
+            segments: vec![],
+        };
+        if reader.i32(0)? != MAGIC {
+            return Err("Not an MSFT type library".to_string());
+        }
+
+        // The help DLL flag adds a field before the segment directory.
+        let mut directory = HEADER_SIZE + 4 * reader.i32(0x20)?;
+        if reader.i32(0x14)? & 0x100 != 0 {
+            directory += 4;
+        }
+        reader.segments = (0..SEGMENT_COUNT as i32)
+            .map(|idx| {
+                let entry = (directory + idx * 16) as usize;
+                let offset = reader.i32(entry)?;
+                let length = reader.i32(entry + 4)?;
+                Ok(match offset {
+                    -1 => (0, 0),
+                    offset => (offset as usize, length as usize),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(reader)
+    }
+
+    fn i32(&self, offset: usize) -> Result<i32, String>
+    {
+        self.data
+            .get(offset..offset + 4)
+            .map(|bytes| read_i32(bytes, 0))
+            .ok_or_else(|| format!("Offset {:#x} is past the end of the file", offset))
+    }
+
+    fn i16(&self, offset: usize) -> Result<i16, String>
+    {
+        self.data
+            .get(offset..offset + 2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+            .ok_or_else(|| format!("Offset {:#x} is past the end of the file", offset))
+    }
+
+    /// Gets the file offset of the segment entry.
+    fn segment(&self, segment: usize, offset: i32) -> Result<usize, String>
+    {
+        let (start, length) = self.segments[segment];
+        match offset >= 0 && (offset as usize) < length {
+            true => Ok(start + offset as usize),
+            false => Err(format!(
+                "Invalid offset {:#x} in segment {}",
+                offset, segment
+            )),
+        }
+    }
+
+    fn name(&self, offset: i32) -> Result<String, String>
+    {
+        let entry = self.segment(SEG_NAME, offset)?;
+        let length = self.data[entry + 8] as usize;
+        let bytes = &self.data[entry + 12..entry + 12 + length];
+        Ok(String::from_utf8_lossy(bytes).to_string())
+    }
+
+    fn string(&self, offset: i32) -> Result<Option<String>, String>
+    {
+        if offset == -1 {
+            return Ok(None);
+        }
+        let entry = self.segment(SEG_STRING, offset)?;
+        let length = self.i16(entry)? as usize;
+        let bytes = &self.data[entry + 2..entry + 2 + length];
+        Ok(Some(String::from_utf8_lossy(bytes).to_string()))
+    }
+
+    fn guid(&self, offset: i32) -> Result<GUID, String>
+    {
+        let entry = self.segment(SEG_GUID, offset)?;
+        let bytes = &self.data[entry..entry + 16];
+        let mut data4 = [0; 8];
+        data4.copy_from_slice(&bytes[8..16]);
+        Ok(GUID {
+            data1: read_i32(bytes, 0) as u32,
+            data2: u16::from_le_bytes([bytes[4], bytes[5]]),
+            data3: u16::from_le_bytes([bytes[6], bytes[7]]),
+            data4,
+        })
+    }
+
+    fn typeinfo(&self, hreftype: i32) -> Result<Type, String>
+    {
+        let base = self.segment(SEG_TYPEINFO, hreftype)?;
+        let field = |offset: usize| self.i32(base + offset);
+        let typekind = field(0x00)?;
+        let element_count = field(0x18)?;
+        let function_count = (element_count & 0xffff) as usize;
+        let variable_count = (element_count >> 16) as usize;
+
+        let kind = typekind & 0xf;
+        let mut impl_types = vec![];
+        let impl_count = self.i16(base + 0x4c)? as usize;
+        let datatype1 = field(0x54)?;
+        if kind == TKIND_COCLASS {
+            let mut reference = datatype1;
+            while reference != -1 {
+                let entry = self.segment(SEG_REFERENCES, reference)?;
+                impl_types.push((self.reference(self.i32(entry)?)?, self.i32(entry + 4)?));
+                reference = self.i32(entry + 12)?;
+            }
+        } else if impl_count > 0 {
+            impl_types.push((self.reference(datatype1)?, 0));
+        }
+
+        let (functions, variables) = match function_count + variable_count {
+            0 => (vec![], vec![]),
+            _ => self.members(field(0x04)? as usize, function_count, variable_count)?,
+        };
+
+        Ok(Type {
+            kind,
+            name: self.name(field(0x34)?)?,
+            guid: match field(0x2c)? {
+                -1 => None,
+                offset => Some(self.guid(offset)?),
+            },
+            flags: field(0x30)?,
+            help: self.string(field(0x3c)?)?,
+            size: field(0x50)?,
+            alignment: typekind >> 11 & 0x1f,
+            vtable_size: i32::from(self.i16(base + 0x4e)?),
+            impl_types,
+            functions,
+            variables,
+        })
+    }
+
+    fn members(
+        &self,
+        offset: usize,
+        function_count: usize,
+        variable_count: usize,
+    ) -> Result<(Vec<Function>, Vec<Variable>), String>
+    {
+        // The records are followed by the IDs, the names and the offsets of
+        // the records.
+        let records = offset + 4;
+        let count = function_count + variable_count;
+        let ids = records + self.i32(offset)? as usize;
+        let names = ids + 4 * count;
+        let offsets = names + 4 * count;
+
+        let mut functions = vec![];
+        let mut variables = vec![];
+        for idx in 0..count {
+            let id = self.i32(ids + 4 * idx)?;
+            let name = self.name(self.i32(names + 4 * idx)?)?;
+            let record = records + self.i32(offsets + 4 * idx)? as usize;
+            let size = (self.i32(record)? & 0xffff) as usize;
+            if idx < function_count {
+                functions.push(self.function(record, size, id, name)?);
+            } else {
+                variables.push(self.variable(record, id, name)?);
+            }
+        }
+        Ok((functions, variables))
+    }
+
+    fn function(
+        &self,
+        record: usize,
+        size: usize,
+        id: i32,
+        name: String,
+    ) -> Result<Function, String>
+    {
+        let param_count = (self.i32(record + 20)? & 0xffff) as usize;
+        let optional = (size - 24 - 12 * param_count) / 4;
+        let params_start = record + 24 + 4 * optional;
+        let params = (0..param_count)
+            .map(|idx| {
+                let param = params_start + 12 * idx;
+                Ok((
+                    self.name(self.i32(param + 4)?)?,
+                    self.type_name(self.i32(param)?)?,
+                    self.i32(param + 8)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Function {
+            name,
+            id,
+            invoke_kind: self.i32(record + 16)? >> 3 & 0xf,
+            vtable_offset: self.i32(record + 12)? & 0xffff,
+            return_type: self.type_name(self.i32(record + 4)?)?,
+            params,
+            help: match optional >= 2 {
+                true => self.string(self.i32(record + 28)?)?,
+                false => None,
+            },
+        })
+    }
+
+    fn variable(&self, record: usize, id: i32, name: String) -> Result<Variable, String>
+    {
+        let kind = self.i32(record + 12)? & 0xffff;
+        let value = self.i32(record + 16)?;
+        let value = match (kind, value) {
+            (VAR_CONST, v) if v < 0 => i64::from(v & 0x3ff_ffff),
+            (VAR_CONST, v) => {
+                let entry = self.segment(SEG_CUSTDATA, v)?;
+                match i32::from(self.i16(entry)?) {
+                    VT_I4 => i64::from(self.i32(entry + 2)?),
+                    VT_I8 => {
+                        let mut bytes = [0; 8];
+                        bytes.copy_from_slice(&self.data[entry + 2..entry + 10]);
+                        i64::from_le_bytes(bytes)
+                    }
+                    vt => return Err(format!("Unsupported constant type {}", vt)),
+                }
+            }
+            (_, v) => i64::from(v),
+        };
+
+        Ok(Variable {
+            name,
+            id,
+            ty: self.type_name(self.i32(record + 4)?)?,
+            kind,
+            value,
+        })
+    }
+
+    /// Gets the name of the referred type.
+    ///
+    /// The imported types are named by their GUIDs.
+    fn reference(&self, hreftype: i32) -> Result<String, String>
+    {
+        if hreftype & 1 == 0 {
+            return self.name(self.i32(self.segment(SEG_TYPEINFO, hreftype)? + 0x34)?);
+        }
+
+        let impinfo = self.segment(SEG_IMPORTINFO, hreftype & !1)?;
+        let guid = self.guid(self.i32(impinfo + 8)?)?;
+        Ok(format!("import({:-X})", guid))
+    }
+
+    fn type_name(&self, ty: i32) -> Result<String, String>
+    {
+        if ty < 0 {
+            return Ok(match ty & VT_TYPEMASK {
+                VT_I2 => "short",
+                VT_I4 => "long",
+                VT_R4 => "float",
+                VT_R8 => "double",
+                VT_DATE => "DATE",
+                VT_BSTR => "BSTR",
+                VT_DISPATCH => "IDispatch*",
+                VT_BOOL => "VARIANT_BOOL",
+                VT_VARIANT => "VARIANT",
+                VT_UNKNOWN => "IUnknown*",
+                VT_I1 => "char",
+                VT_UI1 => "unsigned char",
+                VT_UI2 => "unsigned short",
+                VT_UI4 => "unsigned long",
+                VT_I8 => "int64",
+                VT_UI8 => "uint64",
+                VT_VOID => "void",
+                VT_HRESULT => "HRESULT",
+                VT_LPSTR => "LPSTR",
+                VT_INT_PTR => "INT_PTR",
+                VT_UINT_PTR => "UINT_PTR",
+                vt => return Err(format!("Unsupported type {}", vt)),
+            }
+            .to_string());
+        }
+
+        let entry = self.segment(SEG_TYPEDESC, ty)?;
+        let target = self.i32(entry + 4)?;
+        match self.i32(entry)? & VT_TYPEMASK {
+            VT_PTR => Ok(format!("{}*", self.type_name(target)?)),
+            VT_SAFEARRAY => Ok(format!("SAFEARRAY({})", self.type_name(target)?)),
+            VT_CARRAY => {
+                let array = self.segment(SEG_ARRAYDESC, target)?;
+                let element = self.type_name(self.i32(array)?)?;
+                Ok(format!("{}[{}]", element, self.i32(array + 8)?))
+            }
+            VT_USERDEFINED => self.reference(target),
+            vt => Err(format!("Unsupported type {}", vt)),
+        }
+    }
+}
@@ -63,6 +63,79 @@ fn main()
                         .index(1),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("tlb")
+                .about("Generates a binary type library from the Rust crate")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the crate to process")
+                        .default_value(".")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .value_name("tlb_file")
+                        .required(true)
+                        .help("File path for the generated type library."),
+                )
+                .arg(
+                    Arg::with_name("arch")
+                        .long("arch")
+                        .takes_value(true)
+                        .possible_values(&["win32", "win64"])
+                        .help("Platform of the library. Detected from the library by default."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("cs")
+                .about("Generates C# interop source from the Rust crate")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the crate to process")
+                        .default_value(".")
+                        .index(1),
+                )
+                .arg(Arg::with_name("com-wrappers").long("com-wrappers").help(
+                    "Include the .NET 5 ComWrappers based activation.{n}\
+                     The activation doesn't depend on the built-in COM support of Windows.",
+                ))
+                .arg(Arg::with_name("all").long("all").help(
+                    "Include both Automation and Raw type systems in the C# source.{n}\
+                     Normally the source only includes the Automation type system interfaces.",
+                )),
+        )
+        .subcommand(
+            SubCommand::with_name("python")
+                .about("Generates Python ctypes bindings from the Rust crate")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the crate to process")
+                        .default_value(".")
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rust")
+                .about("Generates Rust bindings from the Rust crate")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the crate to process")
+                        .default_value(".")
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("c")
+                .about("Generates a C header file from the Rust crate")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the crate to process")
+                        .default_value(".")
+                        .index(1),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("cpp")
                 .about("Generates C++ header files from the Rust crate")
@@ -133,6 +206,59 @@ fn run_cmd(matches: &ArgMatches) -> Result<(), failure::Error>
             let lib = typelib::read_typelib(path)?;
             generators::idl::write(lib, opts, &mut io::stdout())?;
         }
+        ("manifest", Some(args)) => {
+            let path = Path::new(args.value_of("path").unwrap());
+            let file_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or_else(|| format_err!("Invalid library path: {}", path.display()))?;
+            let lib = typelib::read_typelib(path)?;
+            generators::manifest::write(lib, file_name, &mut io::stdout())?;
+        }
+        ("tlb", Some(args)) => {
+            let path = Path::new(args.value_of("path").unwrap());
+            let arch = match args.value_of("arch") {
+                Some(arch) => arch.parse().map_err(failure::err_msg)?,
+                None => generators::tlb::Arch::of_library(path)?,
+            };
+            let lib = typelib::read_typelib(path)?;
+            let mut tlb_file = File::create(args.value_of("output").unwrap())?;
+            generators::tlb::write(lib, opts, arch, &mut tlb_file)?;
+        }
+        ("cs", Some(args)) => {
+            let path = Path::new(args.value_of("path").unwrap());
+            let lib = typelib::read_typelib(path)?;
+            let mut opts = opts;
+            if !args.is_present("all") {
+                opts.type_systems
+                    .retain(|ts| ts.ts == intercom::type_system::TypeSystemName::Automation);
+            }
+            generators::cs::write(
+                lib,
+                opts,
+                args.is_present("com-wrappers"),
+                &mut io::stdout(),
+            )?;
+        }
+        ("python", Some(args)) => {
+            let path = Path::new(args.value_of("path").unwrap());
+            let lib = typelib::read_typelib(path)?;
+            generators::python::write(lib, opts, &mut io::stdout())?;
+        }
+        ("rust", Some(args)) => {
+            let path = Path::new(args.value_of("path").unwrap());
+            let file_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or_else(|| format_err!("Invalid library path: {}", path.display()))?;
+            let lib = typelib::read_typelib(path)?;
+            generators::rust::write(lib, file_name, &mut io::stdout())?;
+        }
+        ("c", Some(args)) => {
+            let path = Path::new(args.value_of("path").unwrap());
+            let lib = typelib::read_typelib(path)?;
+            generators::c::write(lib, opts, &mut io::stdout())?;
+        }
         ("cpp", Some(args)) => {
             let path = Path::new(args.value_of("path").unwrap());
             let lib = typelib::read_typelib(path)?;
@@ -0,0 +1,99 @@
+// <auto-generated>
+// Generated by intercom-cli from the sample_lib library.
+// </auto-generated>
+
+using System;
+using System.Runtime.InteropServices;
+
+namespace SampleLib
+{
+    /// <summary>
+    /// Rounding of the results.
+    /// </summary>
+    public enum Rounding : uint
+    {
+        Down = 0,
+        Nearest = 5,
+    }
+
+    [StructLayout(LayoutKind.Explicit, Size = 16)]
+    public struct Point
+    {
+        [FieldOffset(0)]
+        public double x;
+        [FieldOffset(8)]
+        public double y;
+    }
+
+    /// <summary>
+    /// Basic arithmetic.
+    /// </summary>
+    [ComImport]
+    [Guid("00000003-0000-0000-0000-000000000000")]
+    [InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+    public interface ICalculator_Automation
+    {
+        /// <summary>
+        /// Adds the "a" and the "b".
+        /// </summary>
+        /// <remarks>
+        /// Wraps on overflow.
+        /// </remarks>
+        /// <param name="a">The first operand.</param>
+        [PreserveSig]
+        int Add(int a, int b);
+        [PreserveSig]
+        int Sum([MarshalAs(UnmanagedType.SafeArray, SafeArraySubType = VarEnum.VT_I4)] int[] values);
+        [return: MarshalAs(UnmanagedType.BStr)]
+        string Name();
+    }
+
+    [ComImport]
+    [Guid("00000005-0000-0000-0000-000000000000")]
+    [InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+    public interface IAdvancedCalculator_Automation : ICalculator_Automation
+    {
+        /// <summary>
+        /// Adds the "a" and the "b".
+        /// </summary>
+        /// <remarks>
+        /// Wraps on overflow.
+        /// </remarks>
+        /// <param name="a">The first operand.</param>
+        [PreserveSig]
+        new int Add(int a, int b);
+        [PreserveSig]
+        new int Sum([MarshalAs(UnmanagedType.SafeArray, SafeArraySubType = VarEnum.VT_I4)] int[] values);
+        [return: MarshalAs(UnmanagedType.BStr)]
+        new string Name();
+        [PreserveSig]
+        double Power(double value, int exponent);
+        /// <summary>
+        /// Number of significant digits in the results.
+        /// </summary>
+        uint get_Precision();
+        void put_Precision(uint digits);
+    }
+
+    /// <summary>
+    /// Calculator that works on
+    /// the integers.
+    /// </summary>
+    [ComImport]
+    [Guid("00000003-0000-0000-0000-000000000000")]
+    [CoClass(typeof(CalculatorClass))]
+    public interface Calculator : ICalculator_Automation
+    {
+    }
+
+    /// <summary>
+    /// Calculator that works on
+    /// the integers.
+    /// </summary>
+    [ComImport]
+    [Guid("00000002-0000-0000-0000-000000000000")]
+    [ClassInterface(ClassInterfaceType.None)]
+    public class CalculatorClass
+    {
+    }
+}
@@ -0,0 +1,335 @@
+/* Generated by intercom-cli from the sample_lib library. */
+
+#ifndef INTERCOM_LIBRARY_sample_lib_C_H
+#define INTERCOM_LIBRARY_sample_lib_C_H
+
+#include <stddef.h>
+
+/* <stdint.h> is not part of C89. Define INTERCOM_NO_STDINT to use the
+ * fallback typedefs with the toolchains that do not provide it. */
+#if defined( INTERCOM_NO_STDINT ) || ( defined( _MSC_VER ) && _MSC_VER < 1600 )
+
+typedef signed char int8_t;
+typedef unsigned char uint8_t;
+typedef short int16_t;
+typedef unsigned short uint16_t;
+typedef int int32_t;
+typedef unsigned int uint32_t;
+#if defined( _MSC_VER )
+typedef __int64 int64_t;
+typedef unsigned __int64 uint64_t;
+#elif defined( __GNUC__ )
+__extension__ typedef long long int64_t;
+__extension__ typedef unsigned long long uint64_t;
+#else
+typedef long long int64_t;
+typedef unsigned long long uint64_t;
+#endif
+
+#else
+#include <stdint.h>
+#endif
+
+#ifndef INTERCOM_C_TYPES
+#define INTERCOM_C_TYPES
+
+#ifdef _WIN32
+
+#include <windows.h>
+#include <oleauto.h>
+
+#define INTERCOM_CC __stdcall
+
+#else
+
+#define INTERCOM_CC
+
+typedef int32_t HRESULT;
+typedef int16_t VARIANT_BOOL;
+typedef double DATE;
+typedef uint16_t OLECHAR;
+typedef OLECHAR* BSTR;
+
+typedef struct GUID
+{
+    uint32_t Data1;
+    uint16_t Data2;
+    uint16_t Data3;
+    uint8_t Data4[ 8 ];
+} GUID;
+typedef GUID IID;
+typedef GUID CLSID;
+
+typedef struct VARIANT
+{
+    uint16_t vt;
+    uint16_t wReserved1;
+    uint16_t wReserved2;
+    uint16_t wReserved3;
+    union
+    {
+        int64_t llVal;
+        double dblVal;
+        void* byref;
+        struct
+        {
+            void* pvRecord;
+            void* pRecInfo;
+        } brecVal;
+    } value;
+} VARIANT;
+
+typedef struct SAFEARRAY SAFEARRAY;
+
+typedef struct IUnknown IUnknown;
+typedef struct IUnknownVtbl
+{
+    HRESULT ( INTERCOM_CC *QueryInterface )( IUnknown* This, const IID* riid, void** ppvObject );
+    uint32_t ( INTERCOM_CC *AddRef )( IUnknown* This );
+    uint32_t ( INTERCOM_CC *Release )( IUnknown* This );
+} IUnknownVtbl;
+struct IUnknown
+{
+    const IUnknownVtbl* lpVtbl;
+};
+
+typedef struct IDispatch IDispatch;
+typedef struct IErrorInfo IErrorInfo;
+
+#define SUCCEEDED( hr ) ( ( HRESULT )( hr ) >= 0 )
+#define FAILED( hr ) ( ( HRESULT )( hr ) < 0 )
+
+#endif
+
+#endif
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct ICalculator_Automation ICalculator_Automation;
+typedef struct ICalculator_Raw ICalculator_Raw;
+typedef struct IAdvancedCalculator_Automation IAdvancedCalculator_Automation;
+typedef struct IAdvancedCalculator_Raw IAdvancedCalculator_Raw;
+
+/**
+ * Rounding of the results.
+ */
+typedef uint32_t Rounding;
+#define Rounding_Down ( ( Rounding )0 )
+#define Rounding_Nearest ( ( Rounding )5 )
+
+typedef struct Point
+{
+    double x;
+    double y;
+} Point;
+typedef char intercom_check_Point_size[ sizeof( Point ) == 16 ? 1 : -1 ];
+typedef char intercom_check_Point_x[ offsetof( Point, x ) == 0 ? 1 : -1 ];
+typedef char intercom_check_Point_y[ offsetof( Point, y ) == 8 ? 1 : -1 ];
+
+/**
+ * Basic arithmetic.
+ */
+typedef struct ICalculator_AutomationVtbl
+{
+    /* IUnknown */
+    HRESULT ( INTERCOM_CC *QueryInterface )( ICalculator_Automation* This, const IID* riid, void** ppvObject );
+    uint32_t ( INTERCOM_CC *AddRef )( ICalculator_Automation* This );
+    uint32_t ( INTERCOM_CC *Release )( ICalculator_Automation* This );
+
+    /* ICalculator_Automation */
+    /**
+     * Adds the "a" and the "b".
+     *
+     * Wraps on overflow.
+     *
+     * @param a The first operand.
+     */
+    int32_t ( INTERCOM_CC *Add )( ICalculator_Automation* This, int32_t a, int32_t b );
+    int32_t ( INTERCOM_CC *Sum )( ICalculator_Automation* This, SAFEARRAY* values );
+    HRESULT ( INTERCOM_CC *Name )( ICalculator_Automation* This, BSTR* out );
+} ICalculator_AutomationVtbl;
+
+struct ICalculator_Automation
+{
+    const ICalculator_AutomationVtbl* lpVtbl;
+};
+
+static const IID IID_ICalculator_Automation = {0x00000003,0x0000,0x0000,{0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00}};
+
+#define ICalculator_Automation_QueryInterface( This, riid, ppvObject ) \
+    ( ( This )->lpVtbl->QueryInterface( This, riid, ppvObject ) )
+#define ICalculator_Automation_AddRef( This ) \
+    ( ( This )->lpVtbl->AddRef( This ) )
+#define ICalculator_Automation_Release( This ) \
+    ( ( This )->lpVtbl->Release( This ) )
+#define ICalculator_Automation_Add( This, a, b ) \
+    ( ( This )->lpVtbl->Add( This, a, b ) )
+#define ICalculator_Automation_Sum( This, values ) \
+    ( ( This )->lpVtbl->Sum( This, values ) )
+#define ICalculator_Automation_Name( This, out ) \
+    ( ( This )->lpVtbl->Name( This, out ) )
+
+/**
+ * Basic arithmetic.
+ */
+typedef struct ICalculator_RawVtbl
+{
+    /* IUnknown */
+    HRESULT ( INTERCOM_CC *QueryInterface )( ICalculator_Raw* This, const IID* riid, void** ppvObject );
+    uint32_t ( INTERCOM_CC *AddRef )( ICalculator_Raw* This );
+    uint32_t ( INTERCOM_CC *Release )( ICalculator_Raw* This );
+
+    /* ICalculator_Raw */
+    /**
+     * Adds the "a" and the "b".
+     *
+     * Wraps on overflow.
+     *
+     * @param a The first operand.
+     */
+    int32_t ( INTERCOM_CC *Add )( ICalculator_Raw* This, int32_t a, int32_t b );
+    int32_t ( INTERCOM_CC *Sum )( ICalculator_Raw* This, int32_t* values, uint32_t values_len );
+    HRESULT ( INTERCOM_CC *Name )( ICalculator_Raw* This, char** out );
+} ICalculator_RawVtbl;
+
+struct ICalculator_Raw
+{
+    const ICalculator_RawVtbl* lpVtbl;
+};
+
+static const IID IID_ICalculator_Raw = {0x00000004,0x0000,0x0000,{0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00}};
+
+#define ICalculator_Raw_QueryInterface( This, riid, ppvObject ) \
+    ( ( This )->lpVtbl->QueryInterface( This, riid, ppvObject ) )
+#define ICalculator_Raw_AddRef( This ) \
+    ( ( This )->lpVtbl->AddRef( This ) )
+#define ICalculator_Raw_Release( This ) \
+    ( ( This )->lpVtbl->Release( This ) )
+#define ICalculator_Raw_Add( This, a, b ) \
+    ( ( This )->lpVtbl->Add( This, a, b ) )
+#define ICalculator_Raw_Sum( This, values, values_len ) \
+    ( ( This )->lpVtbl->Sum( This, values, values_len ) )
+#define ICalculator_Raw_Name( This, out ) \
+    ( ( This )->lpVtbl->Name( This, out ) )
+
+typedef struct IAdvancedCalculator_AutomationVtbl
+{
+    /* IUnknown */
+    HRESULT ( INTERCOM_CC *QueryInterface )( IAdvancedCalculator_Automation* This, const IID* riid, void** ppvObject );
+    uint32_t ( INTERCOM_CC *AddRef )( IAdvancedCalculator_Automation* This );
+    uint32_t ( INTERCOM_CC *Release )( IAdvancedCalculator_Automation* This );
+
+    /* ICalculator_Automation */
+    /**
+     * Adds the "a" and the "b".
+     *
+     * Wraps on overflow.
+     *
+     * @param a The first operand.
+     */
+    int32_t ( INTERCOM_CC *Add )( IAdvancedCalculator_Automation* This, int32_t a, int32_t b );
+    int32_t ( INTERCOM_CC *Sum )( IAdvancedCalculator_Automation* This, SAFEARRAY* values );
+    HRESULT ( INTERCOM_CC *Name )( IAdvancedCalculator_Automation* This, BSTR* out );
+
+    /* IAdvancedCalculator_Automation */
+    double ( INTERCOM_CC *Power )( IAdvancedCalculator_Automation* This, double value, int32_t exponent );
+    /**
+     * Number of significant digits in the results.
+     */
+    HRESULT ( INTERCOM_CC *get_Precision )( IAdvancedCalculator_Automation* This, uint32_t* out );
+    HRESULT ( INTERCOM_CC *put_Precision )( IAdvancedCalculator_Automation* This, uint32_t digits );
+} IAdvancedCalculator_AutomationVtbl;
+
+struct IAdvancedCalculator_Automation
+{
+    const IAdvancedCalculator_AutomationVtbl* lpVtbl;
+};
+
+static const IID IID_IAdvancedCalculator_Automation = {0x00000005,0x0000,0x0000,{0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00}};
+
+#define IAdvancedCalculator_Automation_QueryInterface( This, riid, ppvObject ) \
+    ( ( This )->lpVtbl->QueryInterface( This, riid, ppvObject ) )
+#define IAdvancedCalculator_Automation_AddRef( This ) \
+    ( ( This )->lpVtbl->AddRef( This ) )
+#define IAdvancedCalculator_Automation_Release( This ) \
+    ( ( This )->lpVtbl->Release( This ) )
+#define IAdvancedCalculator_Automation_Add( This, a, b ) \
+    ( ( This )->lpVtbl->Add( This, a, b ) )
+#define IAdvancedCalculator_Automation_Sum( This, values ) \
+    ( ( This )->lpVtbl->Sum( This, values ) )
+#define IAdvancedCalculator_Automation_Name( This, out ) \
+    ( ( This )->lpVtbl->Name( This, out ) )
+#define IAdvancedCalculator_Automation_Power( This, value, exponent ) \
+    ( ( This )->lpVtbl->Power( This, value, exponent ) )
+#define IAdvancedCalculator_Automation_get_Precision( This, out ) \
+    ( ( This )->lpVtbl->get_Precision( This, out ) )
+#define IAdvancedCalculator_Automation_put_Precision( This, digits ) \
+    ( ( This )->lpVtbl->put_Precision( This, digits ) )
+
+typedef struct IAdvancedCalculator_RawVtbl
+{
+    /* IUnknown */
+    HRESULT ( INTERCOM_CC *QueryInterface )( IAdvancedCalculator_Raw* This, const IID* riid, void** ppvObject );
+    uint32_t ( INTERCOM_CC *AddRef )( IAdvancedCalculator_Raw* This );
+    uint32_t ( INTERCOM_CC *Release )( IAdvancedCalculator_Raw* This );
+
+    /* ICalculator_Raw */
+    /**
+     * Adds the "a" and the "b".
+     *
+     * Wraps on overflow.
+     *
+     * @param a The first operand.
+     */
+    int32_t ( INTERCOM_CC *Add )( IAdvancedCalculator_Raw* This, int32_t a, int32_t b );
+    int32_t ( INTERCOM_CC *Sum )( IAdvancedCalculator_Raw* This, int32_t* values, uint32_t values_len );
+    HRESULT ( INTERCOM_CC *Name )( IAdvancedCalculator_Raw* This, char** out );
+
+    /* IAdvancedCalculator_Raw */
+    double ( INTERCOM_CC *Power )( IAdvancedCalculator_Raw* This, double value, int32_t exponent );
+    /**
+     * Number of significant digits in the results.
+     */
+    HRESULT ( INTERCOM_CC *get_Precision )( IAdvancedCalculator_Raw* This, uint32_t* out );
+    HRESULT ( INTERCOM_CC *put_Precision )( IAdvancedCalculator_Raw* This, uint32_t digits );
+} IAdvancedCalculator_RawVtbl;
+
+struct IAdvancedCalculator_Raw
+{
+    const IAdvancedCalculator_RawVtbl* lpVtbl;
+};
+
+static const IID IID_IAdvancedCalculator_Raw = {0x00000006,0x0000,0x0000,{0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00}};
+
+#define IAdvancedCalculator_Raw_QueryInterface( This, riid, ppvObject ) \
+    ( ( This )->lpVtbl->QueryInterface( This, riid, ppvObject ) )
+#define IAdvancedCalculator_Raw_AddRef( This ) \
+    ( ( This )->lpVtbl->AddRef( This ) )
+#define IAdvancedCalculator_Raw_Release( This ) \
+    ( ( This )->lpVtbl->Release( This ) )
+#define IAdvancedCalculator_Raw_Add( This, a, b ) \
+    ( ( This )->lpVtbl->Add( This, a, b ) )
+#define IAdvancedCalculator_Raw_Sum( This, values, values_len ) \
+    ( ( This )->lpVtbl->Sum( This, values, values_len ) )
+#define IAdvancedCalculator_Raw_Name( This, out ) \
+    ( ( This )->lpVtbl->Name( This, out ) )
+#define IAdvancedCalculator_Raw_Power( This, value, exponent ) \
+    ( ( This )->lpVtbl->Power( This, value, exponent ) )
+#define IAdvancedCalculator_Raw_get_Precision( This, out ) \
+    ( ( This )->lpVtbl->get_Precision( This, out ) )
+#define IAdvancedCalculator_Raw_put_Precision( This, digits ) \
+    ( ( This )->lpVtbl->put_Precision( This, digits ) )
+
+/**
+ * Calculator that works on
+ * the integers.
+ */
+static const CLSID CLSID_Calculator = {0x00000002,0x0000,0x0000,{0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00}};
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
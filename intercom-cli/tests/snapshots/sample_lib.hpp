@@ -0,0 +1,136 @@
+
+#ifndef INTERCOM_LIBRARY_sample_lib_H
+#define INTERCOM_LIBRARY_sample_lib_H
+
+#include <array>
+#include <cstddef>
+#include <intercom.hpp>
+
+namespace sample_lib
+{
+    using i8 = char;
+    using u8 = uint8_t;
+    using i16 = int16_t;
+    using u16 = uint16_t;
+    using i32 = int32_t;
+    using u32 = uint32_t;
+    using i64 = int64_t;
+    using u64 = uint64_t;
+    using f32 = float;
+    using f64 = double;
+    using usize = size_t;
+    using Variant = intercom::VARIANT;
+    using BSTR = intercom::BSTR;
+    using GUID = intercom::IID;
+
+    class Descriptor
+    {
+    public:
+        static const char NAME[];
+        static const char WINDOWS_NAME[];
+        static const char POSIX_NAME[];
+        static const std::array< intercom::CLSID, 1 > CLASSES;
+
+        static bool is_available();
+    };
+
+namespace raw
+{
+    /// Rounding of the results.
+    enum class Rounding : u32
+    {
+        Down = 0,
+        Nearest = 5,
+    };
+
+    struct Point;
+    struct Point
+    {
+        f64 x;
+        f64 y;
+    };
+    static_assert( sizeof( Point ) == 16, "Size of Point differs from the library." );
+    static_assert( offsetof( Point, x ) == 0,
+            "Offset of Point::x differs from the library." );
+    static_assert( offsetof( Point, y ) == 8,
+            "Offset of Point::y differs from the library." );
+
+    struct ICalculator_Automation;
+    struct ICalculator_Raw;
+    struct IAdvancedCalculator_Automation;
+    struct IAdvancedCalculator_Raw;
+    /// Basic arithmetic.
+    struct ICalculator_Automation : IUnknown
+    {
+        static const intercom::IID ID;
+        /// Adds the "a" and the "b".
+        ///
+        /// Wraps on overflow.
+        ///
+        /// @param a The first operand.
+        virtual i32 INTERCOM_CC Add(i32 a, i32 b) = 0;
+        virtual i32 INTERCOM_CC Sum(intercom::SAFEARRAY* values) = 0;
+        virtual intercom::HRESULT INTERCOM_CC Name(BSTR* __out) = 0;
+    };
+    /// Basic arithmetic.
+    struct ICalculator_Raw : IUnknown
+    {
+        static const intercom::IID ID;
+        /// Adds the "a" and the "b".
+        ///
+        /// Wraps on overflow.
+        ///
+        /// @param a The first operand.
+        virtual i32 INTERCOM_CC Add(i32 a, i32 b) = 0;
+        virtual i32 INTERCOM_CC Sum(i32* values, u32 values_len) = 0;
+        virtual intercom::HRESULT INTERCOM_CC Name(char** __out) = 0;
+    };
+    struct IAdvancedCalculator_Automation : ICalculator_Automation
+    {
+        static const intercom::IID ID;
+        virtual f64 INTERCOM_CC Power(f64 value, i32 exponent) = 0;
+        /// Number of significant digits in the results.
+        virtual intercom::HRESULT INTERCOM_CC get_Precision(u32* __out) = 0;
+        virtual intercom::HRESULT INTERCOM_CC put_Precision(u32 digits) = 0;
+    };
+    struct IAdvancedCalculator_Raw : ICalculator_Raw
+    {
+        static const intercom::IID ID;
+        virtual f64 INTERCOM_CC Power(f64 value, i32 exponent) = 0;
+        /// Number of significant digits in the results.
+        virtual intercom::HRESULT INTERCOM_CC get_Precision(u32* __out) = 0;
+        virtual intercom::HRESULT INTERCOM_CC put_Precision(u32 digits) = 0;
+    };
+    /// Calculator that works on
+    /// the integers.
+    class CalculatorDescriptor
+    {
+    public:
+        static const intercom::CLSID ID;
+
+        static const std::array<intercom::IID, 2> INTERFACES;
+
+        using Library = sample_lib::Descriptor;
+
+        CalculatorDescriptor() = delete;
+        ~CalculatorDescriptor() = delete;
+    };
+
+}
+}
+
+#ifdef INTERCOM_FLATTEN_DECLARATIONS
+    using Rounding = sample_lib::raw::Rounding;
+    using Point = sample_lib::raw::Point;
+    static constexpr intercom::IID IID_ICalculator_Automation = {0x00000003,0x0000,0x0000,{0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00}};
+    using ICalculator_Automation = sample_lib::raw::ICalculator_Automation;
+    static constexpr intercom::IID IID_ICalculator_Raw = {0x00000004,0x0000,0x0000,{0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00}};
+    using ICalculator_Raw = sample_lib::raw::ICalculator_Raw;
+    static constexpr intercom::IID IID_IAdvancedCalculator_Automation = {0x00000005,0x0000,0x0000,{0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00}};
+    using IAdvancedCalculator_Automation = sample_lib::raw::IAdvancedCalculator_Automation;
+    static constexpr intercom::IID IID_IAdvancedCalculator_Raw = {0x00000006,0x0000,0x0000,{0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00}};
+    using IAdvancedCalculator_Raw = sample_lib::raw::IAdvancedCalculator_Raw;
+    static constexpr intercom::CLSID CLSID_Calculator = {0x00000002,0x0000,0x0000,{0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00}};
+#endif
+
+#endif
@@ -0,0 +1,447 @@
+# Generated by intercom-cli from the sample_lib library.
+"""ctypes bindings for the sample_lib library."""
+
+import ctypes
+import enum
+import sys
+import uuid
+
+# The COM methods use stdcall on 32-bit Windows.
+FUNCTYPE = getattr(ctypes, "WINFUNCTYPE", ctypes.CFUNCTYPE)
+HRESULT = ctypes.c_int32
+
+
+class GUID(ctypes.Structure):
+    """Globally unique identifier."""
+
+    _fields_ = [
+        ("Data1", ctypes.c_uint32),
+        ("Data2", ctypes.c_uint16),
+        ("Data3", ctypes.c_uint16),
+        ("Data4", ctypes.c_uint8 * 8),
+    ]
+
+    def __init__(self, text=None):
+        super().__init__()
+        if text is not None:
+            ctypes.memmove(ctypes.addressof(self), uuid.UUID(text).bytes_le, 16)
+
+    def __str__(self):
+        return str(uuid.UUID(bytes_le=bytes(self)))
+
+
+class VARIANT(ctypes.Structure):
+    """Automation VARIANT value."""
+
+    _fields_ = [
+        ("vt", ctypes.c_uint16),
+        ("reserved", ctypes.c_uint16 * 3),
+        ("data", ctypes.c_void_p * 2),
+    ]
+
+
+class ComError(Exception):
+    """Failed COM call."""
+
+    def __init__(self, hresult, description=None):
+        super().__init__(description or "HRESULT 0x%08X" % (hresult & 0xFFFFFFFF))
+        self.hresult = hresult
+        self.description = description
+
+
+class _BStr:
+    """BSTR the callee may read for the duration of the call."""
+
+    def __init__(self, text):
+        if text is None:
+            self.value = None
+            return
+        data = text.encode("utf-16-le")
+        self._buffer = ctypes.create_string_buffer(
+            len(data).to_bytes(4, "little") + data + b"\0\0")
+        self.value = ctypes.addressof(self._buffer) + 4
+
+
+def _utf8(text):
+    return None if text is None else text.encode("utf-8")
+
+
+def _array(ctype, values):
+    return None if values is None else (ctype * len(values))(*values)
+
+
+def _len(values):
+    return 0 if values is None else len(values)
+
+
+def _as_interface(value, cls):
+    if value is None or isinstance(value, cls):
+        return value
+    return value.query(cls)
+
+
+def _pointer(value):
+    return None if value is None else value._ptr
+
+
+class IUnknownVtbl(ctypes.Structure):
+    _fields_ = [
+        ("QueryInterface", FUNCTYPE(
+            HRESULT, ctypes.c_void_p, ctypes.POINTER(GUID), ctypes.POINTER(ctypes.c_void_p))),
+        ("AddRef", FUNCTYPE(ctypes.c_uint32, ctypes.c_void_p)),
+        ("Release", FUNCTYPE(ctypes.c_uint32, ctypes.c_void_p)),
+    ]
+
+
+class IDispatchVtbl(ctypes.Structure):
+    _fields_ = IUnknownVtbl._fields_ + [
+        ("GetTypeInfoCount", FUNCTYPE(
+            HRESULT, ctypes.c_void_p, ctypes.POINTER(ctypes.c_uint32))),
+        ("GetTypeInfo", FUNCTYPE(
+            HRESULT, ctypes.c_void_p, ctypes.c_uint32, ctypes.c_uint32,
+            ctypes.POINTER(ctypes.c_void_p))),
+        ("GetIDsOfNames", FUNCTYPE(
+            HRESULT, ctypes.c_void_p, ctypes.POINTER(GUID), ctypes.POINTER(ctypes.c_void_p),
+            ctypes.c_uint32, ctypes.c_uint32, ctypes.POINTER(ctypes.c_int32))),
+        ("Invoke", FUNCTYPE(
+            HRESULT, ctypes.c_void_p, ctypes.c_int32, ctypes.POINTER(GUID), ctypes.c_uint32,
+            ctypes.c_uint16, ctypes.c_void_p, ctypes.POINTER(VARIANT), ctypes.c_void_p,
+            ctypes.POINTER(ctypes.c_uint32))),
+    ]
+
+
+class Unknown:
+    """Reference to a COM interface.
+
+    The reference is released when the object is collected.
+    """
+
+    IID = GUID("00000000-0000-0000-C000-000000000046")
+    VTBL = IUnknownVtbl
+
+    def __init__(self, lib, ptr):
+        self._lib = lib
+        self._ptr = ctypes.c_void_p(ptr)
+        self._vtbl = ctypes.cast(ptr, ctypes.POINTER(ctypes.POINTER(self.VTBL))).contents.contents
+
+    def __del__(self):
+        if self._ptr.value:
+            self._vtbl.Release(self._ptr)
+            self._ptr = ctypes.c_void_p()
+
+    def query(self, cls):
+        """Queries the interface from the object."""
+        ptr = ctypes.c_void_p()
+        self._lib.check(
+            self._vtbl.QueryInterface(self._ptr, ctypes.byref(cls.IID), ctypes.byref(ptr)))
+        return cls(self._lib, ptr.value)
+
+
+class _IClassFactoryVtbl(ctypes.Structure):
+    _fields_ = IUnknownVtbl._fields_ + [
+        ("CreateInstance", FUNCTYPE(
+            HRESULT, ctypes.c_void_p, ctypes.c_void_p, ctypes.POINTER(GUID),
+            ctypes.POINTER(ctypes.c_void_p))),
+        ("LockServer", FUNCTYPE(HRESULT, ctypes.c_void_p, ctypes.c_int32)),
+    ]
+
+
+class _IClassFactory(Unknown):
+    IID = GUID("00000001-0000-0000-C000-000000000046")
+    VTBL = _IClassFactoryVtbl
+
+
+class _IAllocatorVtbl(ctypes.Structure):
+    _fields_ = IUnknownVtbl._fields_ + [
+        ("AllocBstr", FUNCTYPE(
+            ctypes.c_void_p, ctypes.c_void_p, ctypes.c_void_p, ctypes.c_uint32)),
+        ("FreeBstr", FUNCTYPE(None, ctypes.c_void_p, ctypes.c_void_p)),
+        ("Alloc", FUNCTYPE(ctypes.c_void_p, ctypes.c_void_p, ctypes.c_size_t)),
+        ("Free", FUNCTYPE(None, ctypes.c_void_p, ctypes.c_void_p)),
+    ]
+
+
+class _IAllocator(Unknown):
+    IID = GUID("18EE22B3-B0C6-44A5-A94A-7A417676FB66")
+    VTBL = _IAllocatorVtbl
+
+
+class _IErrorStoreVtbl(ctypes.Structure):
+    _fields_ = IUnknownVtbl._fields_ + [
+        ("GetErrorInfo", FUNCTYPE(
+            HRESULT, ctypes.c_void_p, ctypes.POINTER(ctypes.c_void_p))),
+    ]
+
+
+class _IErrorStore(Unknown):
+    IID = GUID("D7F996C5-0B51-4053-82F8-19A7261793A9")
+    VTBL = _IErrorStoreVtbl
+
+
+class _IErrorInfoVtbl(ctypes.Structure):
+    _fields_ = IUnknownVtbl._fields_ + [
+        ("GetGUID", FUNCTYPE(HRESULT, ctypes.c_void_p, ctypes.POINTER(GUID))),
+        ("GetSource", FUNCTYPE(HRESULT, ctypes.c_void_p, ctypes.POINTER(ctypes.c_void_p))),
+        ("GetDescription", FUNCTYPE(
+            HRESULT, ctypes.c_void_p, ctypes.POINTER(ctypes.c_void_p))),
+        ("GetHelpFile", FUNCTYPE(HRESULT, ctypes.c_void_p, ctypes.POINTER(ctypes.c_void_p))),
+        ("GetHelpContext", FUNCTYPE(
+            HRESULT, ctypes.c_void_p, ctypes.POINTER(ctypes.c_uint32))),
+    ]
+
+
+class _IErrorInfo(Unknown):
+    IID = GUID("1CF2B120-547D-101B-8E65-08002B2BF1FA")
+    VTBL = _IErrorInfoVtbl
+
+
+class Library:
+    """Loaded intercom library.
+
+    The objects are created through the DllGetClassObject export and the
+    memory the library returns is released through its allocator.
+    """
+
+    NAME = None
+    ALLOCATOR = None
+    ERROR_STORE = None
+
+    def __init__(self, path=None):
+        if path is None:
+            pattern = {"win32": "%s.dll", "darwin": "lib%s.dylib"}.get(sys.platform, "lib%s.so")
+            path = pattern % self.NAME
+        self.dll = ctypes.CDLL(path)
+        self.dll.DllGetClassObject.restype = HRESULT
+        self.dll.DllGetClassObject.argtypes = [
+            ctypes.POINTER(GUID), ctypes.POINTER(GUID), ctypes.POINTER(ctypes.c_void_p)]
+        self._allocator = None
+
+    def create(self, clsid, cls):
+        """Creates an instance of the class and queries the interface from it."""
+        hr, instance = self._create(clsid, cls)
+        self.check(hr)
+        return instance
+
+    def wrap(self, cls, ptr):
+        """Takes over the reference to the interface pointer."""
+        return cls(self, ptr) if ptr else None
+
+    def check(self, hr):
+        """Raises the failed HRESULT with the error description of the call."""
+        if hr < 0:
+            raise ComError(hr, self._error_description())
+
+    def take_bstr(self, ptr):
+        """Converts the BSTR the library returned and frees it."""
+        if not ptr:
+            return None
+        length = ctypes.c_uint32.from_address(ptr - 4).value
+        text = ctypes.string_at(ptr, length).decode("utf-16-le")
+        allocator = self._get_allocator()
+        allocator._vtbl.FreeBstr(allocator._ptr, ptr)
+        return text
+
+    def take_string(self, ptr):
+        """Converts the UTF-8 string the library returned and frees it."""
+        if not ptr:
+            return None
+        text = ctypes.string_at(ptr).decode("utf-8")
+        allocator = self._get_allocator()
+        allocator._vtbl.Free(allocator._ptr, ptr)
+        return text
+
+    def _create(self, clsid, cls):
+        factory = ctypes.c_void_p()
+        hr = self.dll.DllGetClassObject(
+            ctypes.byref(clsid), ctypes.byref(_IClassFactory.IID), ctypes.byref(factory))
+        if hr < 0:
+            return hr, None
+        factory = _IClassFactory(self, factory.value)
+        instance = ctypes.c_void_p()
+        hr = factory._vtbl.CreateInstance(
+            factory._ptr, None, ctypes.byref(cls.IID), ctypes.byref(instance))
+        if hr < 0:
+            return hr, None
+        return hr, cls(self, instance.value)
+
+    def _get_allocator(self):
+        if self._allocator is None:
+            if self.ALLOCATOR is None:
+                raise ComError(-2147467263, "The library doesn't expose the intercom allocator.")
+            self._allocator = self.create(self.ALLOCATOR, _IAllocator)
+        return self._allocator
+
+    def _error_description(self):
+        # The description is best effort. Failing to get it must not hide
+        # the original error.
+        if self.ERROR_STORE is None:
+            return None
+        _, store = self._create(self.ERROR_STORE, _IErrorStore)
+        if store is None:
+            return None
+        info = ctypes.c_void_p()
+        if store._vtbl.GetErrorInfo(store._ptr, ctypes.byref(info)) < 0 or not info.value:
+            return None
+        info = _IErrorInfo(self, info.value)
+        description = ctypes.c_void_p()
+        if info._vtbl.GetDescription(info._ptr, ctypes.byref(description)) < 0:
+            return None
+        return self.take_bstr(description.value)
+
+
+
+class Rounding(enum.IntEnum):
+    """Rounding of the results."""
+    Down = 0
+    Nearest = 5
+
+
+class Point(ctypes.Structure):
+    _fields_ = [
+        ("x", ctypes.c_double),
+        ("y", ctypes.c_double),
+    ]
+
+
+assert ctypes.sizeof(Point) == 16
+
+
+class ICalculator_AutomationVtbl(ctypes.Structure):
+    _fields_ = IUnknownVtbl._fields_ + [
+        ("Add", FUNCTYPE(ctypes.c_int32, ctypes.c_void_p, ctypes.c_int32, ctypes.c_int32)),
+        ("Sum", FUNCTYPE(ctypes.c_int32, ctypes.c_void_p, ctypes.c_void_p)),
+        ("Name", FUNCTYPE(HRESULT, ctypes.c_void_p, ctypes.POINTER(ctypes.c_void_p))),
+    ]
+
+
+class ICalculator_Automation(Unknown):
+    """Basic arithmetic."""
+
+    IID = GUID("00000003-0000-0000-0000-000000000000")
+    VTBL = ICalculator_AutomationVtbl
+
+    def add(self, a, b):
+        """Adds the "a" and the "b".
+
+        Wraps on overflow.
+
+        :param a: The first operand.
+        """
+        _result = self._vtbl.Add(self._ptr, a, b)
+        return _result
+
+    def sum(self, values):
+        raise NotImplementedError("sum uses SAFEARRAY values which the Python bindings do not support")
+
+    def name(self):
+        _out = ctypes.c_void_p()
+        self._lib.check(self._vtbl.Name(self._ptr, ctypes.byref(_out)))
+        return self._lib.take_bstr(_out.value)
+
+
+class ICalculator_RawVtbl(ctypes.Structure):
+    _fields_ = IUnknownVtbl._fields_ + [
+        ("Add", FUNCTYPE(ctypes.c_int32, ctypes.c_void_p, ctypes.c_int32, ctypes.c_int32)),
+        ("Sum", FUNCTYPE(ctypes.c_int32, ctypes.c_void_p, ctypes.POINTER(ctypes.c_int32), ctypes.c_uint32)),
+        ("Name", FUNCTYPE(HRESULT, ctypes.c_void_p, ctypes.POINTER(ctypes.c_void_p))),
+    ]
+
+
+class ICalculator_Raw(Unknown):
+    """Basic arithmetic."""
+
+    IID = GUID("00000004-0000-0000-0000-000000000000")
+    VTBL = ICalculator_RawVtbl
+
+    def add(self, a, b):
+        """Adds the "a" and the "b".
+
+        Wraps on overflow.
+
+        :param a: The first operand.
+        """
+        _result = self._vtbl.Add(self._ptr, a, b)
+        return _result
+
+    def sum(self, values):
+        _values = _array(ctypes.c_int32, values)
+        _result = self._vtbl.Sum(self._ptr, _values, _len(values))
+        return _result
+
+    def name(self):
+        _out = ctypes.c_void_p()
+        self._lib.check(self._vtbl.Name(self._ptr, ctypes.byref(_out)))
+        return self._lib.take_string(_out.value)
+
+
+class IAdvancedCalculator_AutomationVtbl(ctypes.Structure):
+    _fields_ = ICalculator_AutomationVtbl._fields_ + [
+        ("Power", FUNCTYPE(ctypes.c_double, ctypes.c_void_p, ctypes.c_double, ctypes.c_int32)),
+        ("get_Precision", FUNCTYPE(HRESULT, ctypes.c_void_p, ctypes.POINTER(ctypes.c_uint32))),
+        ("put_Precision", FUNCTYPE(HRESULT, ctypes.c_void_p, ctypes.c_uint32)),
+    ]
+
+
+class IAdvancedCalculator_Automation(ICalculator_Automation):
+    IID = GUID("00000005-0000-0000-0000-000000000000")
+    VTBL = IAdvancedCalculator_AutomationVtbl
+
+    def power(self, value, exponent):
+        _result = self._vtbl.Power(self._ptr, value, exponent)
+        return _result
+
+    def get_precision(self):
+        """Number of significant digits in the results."""
+        _out = ctypes.c_uint32()
+        self._lib.check(self._vtbl.get_Precision(self._ptr, ctypes.byref(_out)))
+        return _out.value
+
+    def put_precision(self, digits):
+        self._lib.check(self._vtbl.put_Precision(self._ptr, digits))
+
+    precision = property(get_precision, put_precision)
+
+
+class IAdvancedCalculator_RawVtbl(ctypes.Structure):
+    _fields_ = ICalculator_RawVtbl._fields_ + [
+        ("Power", FUNCTYPE(ctypes.c_double, ctypes.c_void_p, ctypes.c_double, ctypes.c_int32)),
+        ("get_Precision", FUNCTYPE(HRESULT, ctypes.c_void_p, ctypes.POINTER(ctypes.c_uint32))),
+        ("put_Precision", FUNCTYPE(HRESULT, ctypes.c_void_p, ctypes.c_uint32)),
+    ]
+
+
+class IAdvancedCalculator_Raw(ICalculator_Raw):
+    IID = GUID("00000006-0000-0000-0000-000000000000")
+    VTBL = IAdvancedCalculator_RawVtbl
+
+    def power(self, value, exponent):
+        _result = self._vtbl.Power(self._ptr, value, exponent)
+        return _result
+
+    def get_precision(self):
+        """Number of significant digits in the results."""
+        _out = ctypes.c_uint32()
+        self._lib.check(self._vtbl.get_Precision(self._ptr, ctypes.byref(_out)))
+        return _out.value
+
+    def put_precision(self, digits):
+        self._lib.check(self._vtbl.put_Precision(self._ptr, digits))
+
+    precision = property(get_precision, put_precision)
+
+
+CLSID_Calculator = GUID("00000002-0000-0000-0000-000000000000")
+
+
+class SampleLib(Library):
+    """The sample_lib library."""
+
+    NAME = "sample_lib"
+    ALLOCATOR = None
+    ERROR_STORE = None
+
+    def create_calculator(self, cls=ICalculator_Automation):
+        """Calculator that works on
+        the integers.
+        """
+        return self.create(CLSID_Calculator, cls)
@@ -0,0 +1,125 @@
+// Generated by intercom-cli from the sample_lib library.
+//
+// The bindings link to the sample_lib library and are meant to be included
+// in a module of a crate that depends on intercom.
+
+#[allow(unused_imports)]
+use intercom::{ComItf, ComRc, ComResult, Variant, GUID};
+
+/// Rounding of the results.
+#[derive(
+    intercom::ExternType,
+    intercom::ExternInput,
+    intercom::ExternOutput,
+    intercom::ForeignType,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq
+)]
+#[repr(u32)]
+pub enum Rounding
+{
+    Down = 0,
+    Nearest = 5,
+}
+
+#[derive(
+    intercom::ExternType,
+    intercom::ExternInput,
+    intercom::ExternOutput,
+    intercom::ForeignType,
+    Clone,
+    Copy,
+    Debug
+)]
+#[repr(C)]
+pub struct Point
+{
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Basic arithmetic.
+#[intercom::com_interface(com_iid = "00000003-0000-0000-0000-000000000000", raw_iid = "00000004-0000-0000-0000-000000000000")]
+pub trait ICalculator
+{
+    /// Adds the "a" and the "b".
+    ///
+    /// Wraps on overflow.
+    ///
+    /// # Arguments
+    ///
+    /// - `a` - The first operand.
+    fn add(&self, a: i32, b: i32) -> i32;
+    fn sum(&self, values: &[i32]) -> i32;
+    fn name(&self) -> ComResult<String>;
+}
+
+#[intercom::com_interface(com_iid = "00000005-0000-0000-0000-000000000000", raw_iid = "00000006-0000-0000-0000-000000000000", base = ICalculator)]
+pub trait IAdvancedCalculator: ICalculator
+{
+    fn power(&self, value: f64, exponent: i32) -> f64;
+
+    /// Number of significant digits in the results.
+    #[intercom::com_property]
+    fn get_precision(&self) -> ComResult<u32>;
+    #[intercom::com_property]
+    fn set_precision(&self, digits: u32) -> ComResult<()>;
+}
+
+/// Calculator that works on
+/// the integers.
+#[allow(non_upper_case_globals)]
+pub const CLSID_Calculator: GUID = GUID { data1: 0x0000_0002, data2: 0x0000, data3: 0x0000, data4: [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00] };
+
+/// Creates a new `Calculator` instance.
+pub fn create_calculator() -> ComResult<ComRc<dyn ICalculator>>
+{
+    create_instance(&CLSID_Calculator)
+}
+
+#[link(name = "sample_lib")]
+extern "system"
+{
+    #[link_name = "DllGetClassObject"]
+    fn dll_get_class_object(
+        rclsid: intercom::REFCLSID,
+        riid: intercom::REFIID,
+        pout: *mut intercom::raw::RawComPtr,
+    ) -> intercom::raw::HRESULT;
+}
+
+/// Creates an instance of the class and queries it for the interface.
+///
+/// The class factory is acquired from the library directly so the classes
+/// don't need to be registered.
+pub fn create_instance<I: intercom::attributes::ComInterface + ?Sized>(
+    clsid: &GUID,
+) -> ComResult<ComRc<I>>
+{
+    use intercom::raw::InterfacePtr;
+    use intercom::type_system::AutomationTypeSystem;
+
+    unsafe {
+        let mut factory = std::ptr::null_mut();
+        match dll_get_class_object(clsid, &intercom::IID_IClassFactory, &mut factory) {
+            intercom::raw::S_OK => {}
+            hr => return Err(hr.into()),
+        }
+        let factory =
+            InterfacePtr::<AutomationTypeSystem, dyn intercom::IClassFactory>::new(factory)
+                .ok_or(intercom::ComError::E_POINTER)?;
+        let factory = ComRc::wrap(factory);
+
+        let iid = <dyn intercom::IUnknown as intercom::attributes::ComInterface>::iid_ts::<
+            AutomationTypeSystem,
+        >();
+        let unknown = factory.create_instance(std::ptr::null_mut(), iid)?;
+        let unknown = InterfacePtr::<AutomationTypeSystem, dyn intercom::IUnknown>::new(unknown)
+            .ok_or(intercom::ComError::E_POINTER)?;
+        let unknown = ComRc::wrap(unknown);
+        ComItf::query_interface(&unknown)
+    }
+}
@@ -0,0 +1,349 @@
+// <auto-generated>
+// Generated by intercom-cli from the sample_lib library.
+// </auto-generated>
+
+using System;
+using System.Collections;
+using System.Collections.Generic;
+using System.Runtime.InteropServices;
+
+namespace SampleLib
+{
+    /// <summary>
+    /// Rounding of the results.
+    /// </summary>
+    public enum Rounding : uint
+    {
+        Down = 0,
+        Nearest = 5,
+    }
+
+    [StructLayout(LayoutKind.Explicit, Size = 16)]
+    public struct Point
+    {
+        [FieldOffset(0)]
+        public double x;
+        [FieldOffset(8)]
+        public double y;
+    }
+
+    /// <summary>
+    /// Basic arithmetic.
+    /// </summary>
+    [ComImport]
+    [Guid("00000003-0000-0000-0000-000000000000")]
+    [InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+    public interface ICalculator_Automation
+    {
+        /// <summary>
+        /// Adds the "a" and the "b".
+        /// </summary>
+        /// <remarks>
+        /// Wraps on overflow.
+        /// </remarks>
+        /// <param name="a">The first operand.</param>
+        [PreserveSig]
+        int Add(int a, int b);
+        [PreserveSig]
+        int Sum([MarshalAs(UnmanagedType.SafeArray, SafeArraySubType = VarEnum.VT_I4)] int[] values);
+        [return: MarshalAs(UnmanagedType.BStr)]
+        string Name();
+    }
+
+    [ComImport]
+    [Guid("00000005-0000-0000-0000-000000000000")]
+    [InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+    public interface IAdvancedCalculator_Automation : ICalculator_Automation
+    {
+        /// <summary>
+        /// Adds the "a" and the "b".
+        /// </summary>
+        /// <remarks>
+        /// Wraps on overflow.
+        /// </remarks>
+        /// <param name="a">The first operand.</param>
+        [PreserveSig]
+        new int Add(int a, int b);
+        [PreserveSig]
+        new int Sum([MarshalAs(UnmanagedType.SafeArray, SafeArraySubType = VarEnum.VT_I4)] int[] values);
+        [return: MarshalAs(UnmanagedType.BStr)]
+        new string Name();
+        [PreserveSig]
+        double Power(double value, int exponent);
+        /// <summary>
+        /// Number of significant digits in the results.
+        /// </summary>
+        uint get_Precision();
+        void put_Precision(uint digits);
+    }
+
+    /// <summary>
+    /// Calculator that works on
+    /// the integers.
+    /// </summary>
+    [ComImport]
+    [Guid("00000003-0000-0000-0000-000000000000")]
+    [CoClass(typeof(CalculatorClass))]
+    public interface Calculator : ICalculator_Automation
+    {
+    }
+
+    /// <summary>
+    /// Calculator that works on
+    /// the integers.
+    /// </summary>
+    [ComImport]
+    [Guid("00000002-0000-0000-0000-000000000000")]
+    [ClassInterface(ClassInterfaceType.None)]
+    public class CalculatorClass
+    {
+    }
+
+    /// <summary>
+    /// Creates the library objects without the built-in COM support.
+    /// </summary>
+    /// <remarks>
+    /// The objects are created through the DllGetClassObject export of the
+    /// library and wrapped with ComWrappers. Requires .NET 5 or later and
+    /// unsafe code.
+    /// </remarks>
+    public static class Activation
+    {
+        private const string Library = "sample_lib";
+
+        private static readonly Guid IID_IClassFactory =
+            new Guid("00000001-0000-0000-C000-000000000046");
+
+        private static readonly ComWrappers Wrappers = new LibraryComWrappers();
+
+        [DllImport(Library)]
+        private static extern int DllGetClassObject(
+            ref Guid clsid, ref Guid iid, out IntPtr classFactory);
+
+        /// <summary>
+        /// Creates a new Calculator object.
+        /// </summary>
+        public static Calculator CreateCalculator()
+        {
+            return CreateInstance<Calculator>(
+                new Guid("00000002-0000-0000-0000-000000000000"));
+        }
+
+        /// <summary>
+        /// Creates an instance of the class and queries the interface from it.
+        /// </summary>
+        public static unsafe T CreateInstance<T>(Guid clsid) where T : class
+        {
+            var factoryIid = IID_IClassFactory;
+            Marshal.ThrowExceptionForHR(
+                DllGetClassObject(ref clsid, ref factoryIid, out var factory));
+            try
+            {
+                var iid = typeof(T) == typeof(object)
+                    ? new Guid("00000000-0000-0000-C000-000000000046")
+                    : typeof(T).GUID;
+                IntPtr instance;
+                var createInstance = (delegate* unmanaged[Stdcall]<IntPtr, IntPtr, Guid*, IntPtr*, int>)
+                    (*(IntPtr**)factory)[3];
+                Marshal.ThrowExceptionForHR(createInstance(factory, IntPtr.Zero, &iid, &instance));
+                return (T)GetObject(instance);
+            }
+            finally
+            {
+                Marshal.Release(factory);
+            }
+        }
+
+        /// <summary>
+        /// Wraps the interface pointer and releases the reference to it.
+        /// </summary>
+        internal static object GetObject(IntPtr pointer)
+        {
+            if (pointer == IntPtr.Zero)
+                return null;
+
+            try
+            {
+                return Wrappers.GetOrCreateObjectForComInstance(pointer, CreateObjectFlags.None);
+            }
+            finally
+            {
+                Marshal.Release(pointer);
+            }
+        }
+
+        /// <summary>
+        /// Gets a new reference to the interface of the wrapped object.
+        /// </summary>
+        internal static IntPtr GetInterface(object obj, Guid iid)
+        {
+            if (obj == null)
+                return IntPtr.Zero;
+            if (!(obj is ComObject com))
+                throw new NotSupportedException(
+                    "Only the objects created by the library can be passed to it.");
+
+            var pointer = com.GetInterface(iid);
+            Marshal.AddRef(pointer);
+            return pointer;
+        }
+
+        internal static void Release(IntPtr pointer)
+        {
+            if (pointer != IntPtr.Zero)
+                Marshal.Release(pointer);
+        }
+
+        internal static string TakeBstr(IntPtr bstr)
+        {
+            if (bstr == IntPtr.Zero)
+                return null;
+
+            var value = Marshal.PtrToStringBSTR(bstr);
+            Marshal.FreeBSTR(bstr);
+            return value;
+        }
+
+        internal static string TakeUtf8(IntPtr str)
+        {
+            if (str == IntPtr.Zero)
+                return null;
+
+            var value = Marshal.PtrToStringUTF8(str);
+            Marshal.FreeCoTaskMem(str);
+            return value;
+        }
+
+        private sealed class LibraryComWrappers : ComWrappers
+        {
+            protected override unsafe ComInterfaceEntry* ComputeVtables(
+                object obj, CreateComInterfaceFlags flags, out int count)
+            {
+                // The managed objects aren't exposed to the library.
+                count = 0;
+                return null;
+            }
+
+            protected override object CreateObject(
+                IntPtr externalComObject, CreateObjectFlags flags)
+            {
+                return new ComObject(externalComObject);
+            }
+
+            protected override void ReleaseObjects(IEnumerable objects)
+            {
+                throw new NotSupportedException();
+            }
+        }
+    }
+
+    /// <summary>
+    /// Calls the library objects through their virtual tables.
+    /// </summary>
+    /// <remarks>
+    /// The object implements all the library interfaces. Calling the methods
+    /// of an interface the object doesn't support throws an exception.
+    /// </remarks>
+    internal sealed unsafe class ComObject :
+        ICalculator_Automation,
+        IAdvancedCalculator_Automation,
+        Calculator
+    {
+        private readonly IntPtr unknown;
+        private readonly Dictionary<Guid, IntPtr> interfaces = new Dictionary<Guid, IntPtr>();
+
+        internal ComObject(IntPtr unknown)
+        {
+            Marshal.AddRef(unknown);
+            this.unknown = unknown;
+        }
+
+        ~ComObject()
+        {
+            foreach (var pointer in interfaces.Values)
+                Marshal.Release(pointer);
+            Marshal.Release(unknown);
+        }
+
+        /// <summary>
+        /// Gets the cached interface pointer.
+        /// </summary>
+        internal IntPtr GetInterface(Guid iid)
+        {
+            lock (interfaces)
+            {
+                if (!interfaces.TryGetValue(iid, out var pointer))
+                {
+                    Marshal.ThrowExceptionForHR(Marshal.QueryInterface(unknown, ref iid, out pointer));
+                    interfaces.Add(iid, pointer);
+                }
+                return pointer;
+            }
+        }
+
+        int ICalculator_Automation.Add(int a, int b)
+        {
+            var __this = GetInterface(typeof(ICalculator_Automation).GUID);
+            var __result = ((delegate* unmanaged[Stdcall]<IntPtr, int, int, int>)(*(IntPtr**)__this)[3])(__this, a, b);
+            return __result;
+        }
+
+        int ICalculator_Automation.Sum(int[] values)
+        {
+            throw new NotSupportedException("Passing int[] is not supported.");
+        }
+
+        string ICalculator_Automation.Name()
+        {
+            var __this = GetInterface(typeof(ICalculator_Automation).GUID);
+            IntPtr __retval = default;
+            var __hr = ((delegate* unmanaged[Stdcall]<IntPtr, IntPtr*, int>)(*(IntPtr**)__this)[5])(__this, &__retval);
+            Marshal.ThrowExceptionForHR(__hr);
+            return Activation.TakeBstr(__retval);
+        }
+
+        int IAdvancedCalculator_Automation.Add(int a, int b)
+        {
+            var __this = GetInterface(typeof(IAdvancedCalculator_Automation).GUID);
+            var __result = ((delegate* unmanaged[Stdcall]<IntPtr, int, int, int>)(*(IntPtr**)__this)[3])(__this, a, b);
+            return __result;
+        }
+
+        int IAdvancedCalculator_Automation.Sum(int[] values)
+        {
+            throw new NotSupportedException("Passing int[] is not supported.");
+        }
+
+        string IAdvancedCalculator_Automation.Name()
+        {
+            var __this = GetInterface(typeof(IAdvancedCalculator_Automation).GUID);
+            IntPtr __retval = default;
+            var __hr = ((delegate* unmanaged[Stdcall]<IntPtr, IntPtr*, int>)(*(IntPtr**)__this)[5])(__this, &__retval);
+            Marshal.ThrowExceptionForHR(__hr);
+            return Activation.TakeBstr(__retval);
+        }
+
+        double IAdvancedCalculator_Automation.Power(double value, int exponent)
+        {
+            var __this = GetInterface(typeof(IAdvancedCalculator_Automation).GUID);
+            var __result = ((delegate* unmanaged[Stdcall]<IntPtr, double, int, double>)(*(IntPtr**)__this)[6])(__this, value, exponent);
+            return __result;
+        }
+
+        uint IAdvancedCalculator_Automation.get_Precision()
+        {
+            var __this = GetInterface(typeof(IAdvancedCalculator_Automation).GUID);
+            uint __retval = default;
+            var __hr = ((delegate* unmanaged[Stdcall]<IntPtr, uint*, int>)(*(IntPtr**)__this)[7])(__this, &__retval);
+            Marshal.ThrowExceptionForHR(__hr);
+            return __retval;
+        }
+
+        void IAdvancedCalculator_Automation.put_Precision(uint digits)
+        {
+            var __this = GetInterface(typeof(IAdvancedCalculator_Automation).GUID);
+            var __hr = ((delegate* unmanaged[Stdcall]<IntPtr, uint, int>)(*(IntPtr**)__this)[8])(__this, digits);
+            Marshal.ThrowExceptionForHR(__hr);
+        }
+    }
+}
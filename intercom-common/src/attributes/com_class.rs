@@ -63,8 +63,32 @@ pub fn expand_com_class(
             } else
         ),
     ];
+    // Every class implements IWeakReferenceSource automatically.
+    let weak_reference_source_vtbl = quote!(
+        <dyn intercom::IWeakReferenceSource as intercom::attributes::ComInterfaceVariant<
+            intercom::type_system::AutomationTypeSystem,
+        >>::VTable
+    );
+    query_interface_match_arms.push(quote!(
+        if riid == <dyn intercom::IWeakReferenceSource as intercom::attributes::ComInterfaceVariant<intercom::type_system::AutomationTypeSystem>>::iid() {
+            let ptr = ( &vtables._IWeakReferenceSource )
+                as *const &#weak_reference_source_vtbl
+                as *mut &#weak_reference_source_vtbl
+                as intercom::raw::RawComPtr;
+            intercom::logging::trace(|l| l(module_path!(), format_args!(
+                "[{:p}] {}::query_interface({:-X}) -> IWeakReferenceSource [{:p}]",
+                vtables, #cls_name, riid, ptr)));
+            ptr
+        } else
+    ));
     let mut support_error_info_match_arms = vec![];
 
+    // Name of the struct listing the virtual tables of the class.
+    let vtable_list_ident = Ident::new(
+        &format!("__intercom_vtable_for_{}", cls_ident),
+        Span::call_site(),
+    );
+
     output.push(quote!(
         impl #impl_generics intercom::IUnknown for #cls_ident #ty_generics #where_clause {}
     ));
@@ -79,20 +103,32 @@ pub fn expand_com_class(
     // We ensure this by defining the primary IUnknown methods on the
     // ISupportErrorInfo virtual table and having that at the beginning.
     let mut vtable_list_field_defs = vec![];
-    let mut vtable_list_field_decls = vec![quote!(
-        _ISupportErrorInfo:
-            &'static <dyn intercom::ISupportErrorInfo as intercom::attributes::ComInterfaceVariant<
-                intercom::type_system::AutomationTypeSystem,
-            >>::VTable
-    )];
+    let mut vtable_list_field_decls = vec![
+        quote!(
+            _ISupportErrorInfo:
+                &'static <dyn intercom::ISupportErrorInfo as intercom::attributes::ComInterfaceVariant<
+                    intercom::type_system::AutomationTypeSystem,
+                >>::VTable
+        ),
+        quote!(_IWeakReferenceSource: &'static #weak_reference_source_vtbl),
+    ];
     let mut vtable_list_field_values = vec![];
-    let mut vtable_list_field_ptrs = vec![quote!(
+    let mut vtable_list_field_ptrs = vec![
+        quote!(
                  _ISupportErrorInfo :
                 &<dyn intercom::ISupportErrorInfo as intercom::attributes::ComInterfaceVTableFor<
                     dyn intercom::ISupportErrorInfo,
                     #cls_ident #ty_generics,
                     intercom::type_system::AutomationTypeSystem>>::VTABLE
-    )];
+        ),
+        quote!(
+                 _IWeakReferenceSource :
+                &<dyn intercom::IWeakReferenceSource as intercom::attributes::ComInterfaceVTableFor<
+                    dyn intercom::IWeakReferenceSource,
+                    #cls_ident #ty_generics,
+                    intercom::type_system::AutomationTypeSystem>>::VTABLE
+        ),
+    ];
 
     // Create the vtable data for the additional interfaces.
     // The data should include the match-arms for the primary query_interface
@@ -113,6 +149,7 @@ pub fn expand_com_class(
             let ts_type = ts.as_typesystem_type(itf.span());
 
             // Implement ComClassInterface.
+            let offset = vtable_offset(vtable_list_field_decls.len());
             output.push(quote!(
                 #[allow(non_snake_case)]
                 impl #impl_generics intercom::attributes::ComClassInterface<
@@ -120,10 +157,8 @@ pub fn expand_com_class(
 
                     #[inline(always)]
                     fn offset() -> usize {
-                        unsafe {
-                            &intercom::ComBoxData::< #cls_ident #ty_generics >::null_vtable().#itf_variant
-                                    as *const _ as usize
-                        }
+                        // The vtable list is the first field of the ComBoxData.
+                        #offset
                     }
                 }
             ));
@@ -161,6 +196,22 @@ pub fn expand_com_class(
                 } else
             ));
 
+            // Dual interfaces begin with the IDispatch virtual table so the
+            // first one of them also serves the IDispatch queries.
+            query_interface_match_arms.push(quote!(
+                if <#maybe_dyn #itf as intercom::attributes::ComInterface>::DUAL &&
+                    riid == <dyn intercom::IDispatch as intercom::attributes::ComInterfaceVariant<#ts_type>>::iid() {
+                    let ptr = &vtables.#itf_variant
+                        as *const &#itf_attrib_data::VTable
+                        as *mut &#itf_attrib_data::VTable
+                        as intercom::raw::RawComPtr;
+                    intercom::logging::trace(|l| l(module_path!(), format_args!(
+                        "[{:p}] {}::query_interface({:-X}) -> IDispatch through {} ({}) [{:p}]",
+                        vtables, #cls_name, riid, #itf_name, #ts_name, ptr)));
+                    ptr
+                } else
+            ));
+
             // Define the support error info match arms.
             support_error_info_match_arms.push(quote!(
                 if riid == #itf_attrib_data::iid() {
@@ -170,6 +221,80 @@ pub fn expand_com_class(
         }
     }
 
+    // Aggregatable classes need a separate non-delegating IUnknown that the
+    // outer object uses to control the lifetime of the aggregated object.
+    let mut aggregation_impl = quote!();
+    if cls.aggregatable {
+        let iunknown_vtable = quote!(
+            <dyn intercom::IUnknown as intercom::attributes::ComInterfaceVariant<
+                intercom::type_system::AutomationTypeSystem,
+            >>::VTable
+        );
+        let offset = vtable_offset(vtable_list_field_decls.len());
+        vtable_list_field_decls.push(quote!(
+            _NonDelegatingIUnknown: &'static #iunknown_vtable
+        ));
+        vtable_list_field_ptrs.push(quote!(
+            _NonDelegatingIUnknown:
+                &intercom::interfaces::NonDelegatingVTable::<#cls_ident #ty_generics>::VTABLE
+        ));
+        output.push(quote!(
+            #[allow(non_snake_case)]
+            impl #impl_generics intercom::attributes::ComClassInterface<
+                intercom::interfaces::NonDelegatingIUnknown,
+                intercom::type_system::AutomationTypeSystem>
+            for #cls_ident #ty_generics #where_clause {
+
+                #[inline(always)]
+                fn offset() -> usize {
+                    #offset
+                }
+            }
+        ));
+        aggregation_impl = quote!(
+            const AGGREGATABLE: bool = true;
+
+            fn non_delegating_unknown(
+                vtables : &Self::VTableList
+            ) -> Option<intercom::raw::RawComPtr>
+            {
+                Some(&vtables._NonDelegatingIUnknown
+                    as *const &#iunknown_vtable
+                    as *mut &#iunknown_vtable
+                    as intercom::raw::RawComPtr)
+            }
+        );
+    }
+
+    // Classes with a custom allocator allocate their instances through it.
+    let mut allocator_impl = quote!();
+    if let Some(ref allocator) = cls.allocator {
+        allocator_impl = quote_spanned!(allocator.span() =>
+            fn allocator() -> &'static dyn intercom::allocator::ComAllocator
+            {
+                &#allocator
+            }
+        );
+    }
+
+    // Classes with a threading model other than the default guard their
+    // method calls accordingly.
+    let mut threading_impl = quote!();
+    if let Some(threading) = cls.threading {
+        let model = Ident::new(
+            match threading {
+                model::ThreadingModel::Single => "Single",
+                model::ThreadingModel::Mutex => "Mutex",
+                model::ThreadingModel::Free => "Free",
+            },
+            Span::call_site(),
+        );
+        threading_impl = quote!(
+            const THREADING: intercom::threading::ThreadingModel =
+                intercom::threading::ThreadingModel::#model;
+        );
+    }
+
     /////////////////////
     // ISupportErrorInfo virtual table instance.
     //
@@ -186,9 +311,26 @@ pub fn expand_com_class(
         }
     ));
 
-    // Mark the struct as having IUnknown.
+    // IWeakReferenceSource virtual table instance.
+    let weak_reference_source_offset = vtable_offset(1);
+    output.push(quote!(
+        #[allow(non_snake_case)]
+        impl #impl_generics intercom::attributes::ComClassInterface<
+            dyn intercom::IWeakReferenceSource,
+            intercom::type_system::AutomationTypeSystem>
+        for #cls_ident #ty_generics #where_clause {
+
+            #[inline(always)]
+            fn offset() -> usize {
+                #weak_reference_source_offset
+            }
+        }
+    ));
+
+    // Mark the struct as having IUnknown and IWeakReferenceSource.
     output.push(quote!(
         impl #impl_generics intercom::attributes::HasInterface< dyn intercom::IUnknown > for #cls_ident #ty_generics #where_clause {}
+        impl #impl_generics intercom::attributes::HasInterface< dyn intercom::IWeakReferenceSource > for #cls_ident #ty_generics #where_clause {}
     ));
 
     // The ComClass implementation.
@@ -197,15 +339,12 @@ pub fn expand_com_class(
     // interfaces that the coclass implements.
 
     // VTableList struct definition.
-    let vtable_list_ident = Ident::new(
-        &format!("__intercom_vtable_for_{}", cls_ident),
-        Span::call_site(),
-    );
     let visibility = &cls.visibility;
     output.push(quote!(
         #[allow(non_snake_case)]
         #[doc(hidden)]
         #[derive(Clone, Copy)]
+        #[repr(C)]
         #visibility struct #vtable_list_ident {
             #( #vtable_list_field_decls ),*
         }
@@ -263,9 +402,44 @@ pub fn expand_com_class(
                     { false }
                 }
             }
+
+            #aggregation_impl
+            #allocator_impl
+            #threading_impl
         }
     ));
 
+    // Classes with a custom factory function are constructed through it.
+    // Other classes are constructed with `Default` if they implement it. The
+    // bound is higher-ranked so that it isn't rejected as a trivial bound for
+    // the classes that don't.
+    if let Some(ref factory) = cls.factory {
+        output.push(quote_spanned!(factory.span() =>
+            impl #impl_generics intercom::attributes::ComClassFactory for #cls_ident #ty_generics #where_clause {
+                fn create(args: intercom::Variant) -> intercom::ComResult<Self> {
+                    #factory(args)
+                }
+            }
+        ));
+    } else {
+        let mut generics = cls.generics.clone();
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(for<'__intercom> #cls_ident #ty_generics: Default));
+        let where_clause = &generics.where_clause;
+        output.push(quote!(
+            impl #impl_generics intercom::attributes::ComClassFactory for #cls_ident #ty_generics #where_clause {
+                fn create(args: intercom::Variant) -> intercom::ComResult<Self> {
+                    match args {
+                        intercom::Variant::None => Ok(<Self as Default>::default()),
+                        _ => Err(intercom::ComError::E_INVALIDARG),
+                    }
+                }
+            }
+        ));
+    }
+
     // CLSID constant for the class.
     let clsid_ident = idents::clsid(cls_ident);
     if let Some(ref guid) = cls.clsid {
@@ -302,6 +476,7 @@ fn create_get_typeinfo_function(cls: &model::ComClass) -> TokenStream
         }
     };
     let clsid_tokens = utils::get_guid_tokens(clsid, Span::call_site());
+    let doc_tokens = utils::get_doc_tokens(&cls.doc, Span::call_site());
     let (impl_generics, ty_generics, where_clause) = cls.generics.split_for_impl();
     let (interfaces, interface_info): (Vec<_>, Vec<_>) = cls
         .interfaces
@@ -341,7 +516,9 @@ fn create_get_typeinfo_function(cls: &model::ComClass) -> TokenStream
                     intercom::ComBox::new( intercom::typelib::CoClass::__new(
                         #cls_name.into(),
                         #clsid_tokens,
-                        vec![ #( #interfaces ),* ]
+                        vec![ #( #interfaces ),* ],
+                        <#cls_ident #ty_generics as intercom::attributes::ComClass>::THREADING,
+                        #doc_tokens,
                     ) ) )
                 ];
                 #( #interface_info )*
@@ -350,3 +527,14 @@ fn create_get_typeinfo_function(cls: &model::ComClass) -> TokenStream
         }
     )
 }
+
+/// Gets the offset of the virtual table reference in the virtual table list.
+///
+/// The list is a `repr(C)` struct of references so the offset depends only on
+/// the position of the field. Computing it without the field types leaves the
+/// missing `ComInterface` implementations to be reported once by the
+/// `HasInterface` bound.
+fn vtable_offset(index: usize) -> TokenStream
+{
+    quote!(#index * std::mem::size_of::<&'static ()>())
+}
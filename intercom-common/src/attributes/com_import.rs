@@ -0,0 +1,38 @@
+use crate::idl;
+use crate::prelude::*;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use syn::LitStr;
+
+/// Expands the `com_import!` macro.
+///
+/// The macro reads the IDL file relative to the crate root and declares the
+/// interfaces of the file as `#[com_interface]` traits. The errors point to
+/// the path and name the line and the column within the IDL file.
+pub fn expand_com_import(args: TokenStreamNightly) -> Result<TokenStreamNightly, syn::Error>
+{
+    let path: LitStr = syn::parse(args)?;
+    let root = env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| syn::Error::new(path.span(), "CARGO_MANIFEST_DIR is not defined"))?;
+    let full_path = Path::new(&root).join(path.value());
+    let source = fs::read_to_string(&full_path).map_err(|e| {
+        syn::Error::new(
+            path.span(),
+            format!("Could not read {}: {}", full_path.display(), e),
+        )
+    })?;
+
+    let interfaces = idl::parse(&source)
+        .and_then(|file| idl::expand(&file))
+        .map_err(|e| syn::Error::new(path.span(), format!("{}:{}", path.value(), e)))?;
+
+    // Including the file makes Cargo rebuild the crate when the file changes.
+    let full_path = full_path.to_string_lossy();
+    Ok(quote!(
+        const _: &[u8] = include_bytes!(#full_path);
+        #interfaces
+    )
+    .into())
+}
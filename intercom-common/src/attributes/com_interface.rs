@@ -5,7 +5,7 @@ use std::collections::BTreeMap;
 use std::iter;
 
 use crate::idents;
-use crate::methodinfo::ComMethodInfo;
+use crate::methodinfo::{ComMethodInfo, PropertyKind};
 use crate::model;
 use crate::tyhandlers::{Direction, ModelTypeSystem};
 use crate::utils;
@@ -146,6 +146,12 @@ pub fn expand_com_interface(
 
     // Implement the ComInterface for the trait.
     let iid_arms = itf_output.iid_arms;
+    let dual_const = match itf.dual {
+        true => quote!(
+            const DUAL: bool = true;
+        ),
+        false => quote!(),
+    };
     let (deref_impl, deref_ret) = if itf.item_type == utils::InterfaceType::Trait {
         (
             quote_spanned!(itf.span => com_itf),
@@ -214,6 +220,8 @@ pub fn expand_com_interface(
             ) -> #deref_ret {
                 #deref_impl
             }
+
+            #dual_const
         }
     ));
 
@@ -224,6 +232,23 @@ pub fn expand_com_interface(
 
             /// The name of the type.
             fn type_name() -> &'static str { stringify!( #itf_path )  }
+
+            /// The interface pointers refer to the type system specific IID.
+            /// The bare interface uses the first IID it has.
+            fn type_desc() -> intercom::typelib::TypeDesc {
+                let iid = [
+                    intercom::type_system::TypeSystemName::Automation,
+                    intercom::type_system::TypeSystemName::Raw,
+                ].iter()
+                    .filter_map(|ts| <#itf_ref as intercom::attributes::ComInterface>::iid(*ts))
+                    .next()
+                    .cloned()
+                    .unwrap_or_else(intercom::GUID::zero_guid);
+                intercom::typelib::TypeDesc::Interface {
+                    name: Self::type_name().into(),
+                    iid,
+                }
+            }
         }
 
     ));
@@ -273,8 +298,15 @@ fn process_itf_variant(
     let mut vtbl_fields = vec![];
     let mut vtbl_values = vec![];
     if let Some(ref base) = itf.base_interface {
-        vtbl_values.push(quote_spanned!(itf.span =>
-                __base : <dyn #base as #attr_cominterfacevtablefor>::VTABLE));
+        // Dual interfaces get the IDispatch implementation from Intercom.
+        // The implementation invokes the methods of the current interface.
+        let base_vtable = match itf.dual {
+            true => quote_spanned!(itf.span =>
+                intercom::dispatch::DispatchVTable::<I, S, #ts_type_tokens, #itf_ref>::VTABLE),
+            false => quote_spanned!(itf.span =>
+                <dyn #base as #attr_cominterfacevtablefor>::VTABLE),
+        };
+        vtbl_values.push(quote_spanned!(itf.span => __base : #base_vtable));
         vtbl_fields.push(quote_spanned!(itf.span =>
                 pub __base : <dyn #base as #attr_cominterfacevariant>::VTable));
     }
@@ -337,6 +369,10 @@ fn process_itf_variant(
         ));
     }
 
+    if itf.dual {
+        output.push(create_dispatch_impl(itf, ts, itf_variant));
+    }
+
     let iid_tokens = utils::get_guid_tokens(&itf_variant.iid, itf.span);
     output.push(quote_spanned!(itf_path.span() =>
         #[allow(non_camel_case_types)]
@@ -382,6 +418,14 @@ fn rust_to_com_delegate(
         })
         .collect::<Vec<_>>();
 
+    // Conformant arrays are converted before the call. The conversions are
+    // kept alive until the call returns.
+    let in_arg_declarations = method_info
+        .args
+        .iter()
+        .filter_map(|ra| ra.handler.array_input_declaration(&ra.name, ra.span))
+        .collect::<Vec<_>>();
+
     // Format the in and out parameters for the COM call.
     let params: Vec<_> = method_info
         .raw_com_args()
@@ -426,6 +470,7 @@ fn rust_to_com_delegate(
 
             #[allow(unused_unsafe)]  // The fn itself _might_ be unsafe.
             unsafe {
+                #( #in_arg_declarations )*
                 #( #out_arg_declarations )*
                 let #return_ident = ((**vtbl).#method_ident)( #( #params ),* );
 
@@ -445,6 +490,7 @@ fn rust_to_com_delegate(
             // us to handle the results here immediately.
             #[allow(unused_unsafe)]  // The fn itself _might_ be unsafe.
             let __intercom_result : Result< #return_ty, intercom::ComError > = ( || unsafe {
+                #( #in_arg_declarations )*
                 #( #out_arg_declarations )*
                 let #return_ident = ((**vtbl).#method_ident)( #( #params ),* );
 
@@ -525,6 +571,28 @@ fn create_virtual_method(
         quote!(&mut **self_combox)
     };
 
+    // Calls into the user implementations are guarded by the threading model
+    // of the class. The rejected calls are returned as errors. Methods without
+    // an error channel panic instead, which aborts the process.
+    let exclusive = !method_info.is_const;
+    let enter_call = match (&itf.implemented_by, infallible) {
+        (Some(_), _) => quote!(),
+        (None, false) => quote!(
+            let _call = intercom::ComBoxData::enter_call(&*self_combox, #exclusive)?;
+        ),
+        (None, true) if method_info.has_error_channel() => quote!(
+            let _call = match intercom::ComBoxData::enter_call(&*self_combox, #exclusive) {
+                Ok(call) => call,
+                Err(err) => return <#ret_ty as intercom::ErrorValue>::from_error(
+                    intercom::store_error(err)),
+            };
+        ),
+        (None, true) => quote!(
+            let _call = intercom::ComBoxData::enter_call(&*self_combox, #exclusive)
+                .expect("Call rejected by the threading model of the class");
+        ),
+    };
+
     // The implemented_by option affects the actual method implementation
     // as well as the interface bounds. Interfaces implemented manually
     // do not require the interface as a bound.
@@ -555,9 +623,20 @@ fn create_virtual_method(
         ),
     };
 
+    // Panics must not unwind into the foreign caller. Fallible methods and
+    // methods returning a raw HRESULT can report the panic as an error. Other
+    // infallible methods have no error channel so the process is aborted
+    // instead.
+    let catch_panic = if method_info.has_error_channel() {
+        quote!(intercom::panic::catch_panic)
+    } else {
+        quote!(intercom::panic::catch_panic_or_abort)
+    };
+
     // Format the payload depending on whether the method is infallible or not.
     let payload = if infallible {
         quote!(
+            #enter_call
             let self_struct = #self_struct_expr;
             let #return_ident = #call;
 
@@ -571,6 +650,7 @@ fn create_virtual_method(
         // Fallible methods require an error-catching closure and error handling.
         quote!(
             let result : Result< #ret_ty, intercom::ComError > = ( || {
+                #enter_call
                 let self_struct = #self_struct_expr;
                 let #return_ident = #call;
                 Ok( { #return_statement } )
@@ -616,7 +696,160 @@ fn create_virtual_method(
                 self_combox, self_vtable,
                 std::any::type_name::<#s_ref>(), #method_name)));
 
-            #payload
+            #catch_panic(std::any::type_name::<#s_ref>(), #method_name, || {
+                #payload
+            })
+        }
+    )
+}
+
+/// Creates the `ComDispatch` implementation that invokes the interface
+/// methods for `IDispatch`.
+fn create_dispatch_impl(
+    itf: &model::ComInterface,
+    ts: ModelTypeSystem,
+    itf_variant: &model::ComInterfaceVariant,
+) -> TokenStream
+{
+    let itf_ident = &itf.ident;
+    let itf_path = &itf.path;
+    let itf_ref = &itf.itf_ref;
+    let ts_type_tokens = ts.as_typesystem_type(itf.span);
+    let args_ident = Ident::new("__intercom_args", Span::call_site());
+
+    // The getter and the setter of a property share the member.
+    let mut members = vec![];
+    for m in &itf_variant.methods {
+        let name = m.member_name();
+        if !members.contains(&name) {
+            members.push(name);
+        }
+    }
+
+    // The dispatch IDs follow the member order starting from one.
+    let arms = itf_variant.methods.iter().map(|m| {
+        let member_name = m.member_name();
+        let dispid = members
+            .iter()
+            .position(|name| *name == member_name)
+            .unwrap() as i32
+            + 1;
+        let flags = match m.property {
+            Some(PropertyKind::Put) => quote!(intercom::raw::DISPATCH_PROPERTYPUT),
+            _ => quote!(intercom::raw::DISPATCH_METHOD | intercom::raw::DISPATCH_PROPERTYGET),
+        };
+        let arg_count = m.args.len() as u32;
+        let method_ident = &m.name;
+
+        // Borrowed parameters are converted into the owned type first.
+        let (conversions, call_args): (Vec<_>, Vec<_>) = m
+            .args
+            .iter()
+            .map(|ca| {
+                let name = &ca.name;
+                match &ca.ty {
+                    syn::Type::Reference(r) if r.mutability.is_some() => {
+                        let inner = &r.elem;
+                        (
+                            quote_spanned!(ca.span =>
+                                let mut #name : <#inner as std::borrow::ToOwned>::Owned =
+                                    #args_ident.next_arg()?;),
+                            quote_spanned!(ca.span =>
+                                <_ as std::borrow::BorrowMut<#inner>>::borrow_mut(&mut #name)),
+                        )
+                    }
+                    syn::Type::Reference(r) => {
+                        let inner = &r.elem;
+                        (
+                            quote_spanned!(ca.span =>
+                                let #name : <#inner as std::borrow::ToOwned>::Owned =
+                                    #args_ident.next_arg()?;),
+                            quote_spanned!(ca.span =>
+                                <_ as std::borrow::Borrow<#inner>>::borrow(&#name)),
+                        )
+                    }
+                    ty => (
+                        quote_spanned!(ca.span => let #name : #ty = #args_ident.next_arg()?;),
+                        quote_spanned!(ca.span => #name),
+                    ),
+                }
+            })
+            .unzip();
+
+        let exclusive = !m.is_const;
+        let self_struct_expr = match m.is_const {
+            true => quote!(&**self_combox),
+            false => quote!(&mut **self_combox),
+        };
+        let call = quote_spanned!(m.signature_span =>
+            self_struct.#method_ident( #( #call_args ),* ));
+        let value = match m.returnhandler.is_infallible() {
+            true => call,
+            false => quote_spanned!(m.signature_span =>
+                #call.map_err(intercom::ComError::from)?),
+        };
+
+        quote_spanned!(m.signature_span =>
+            #dispid if __intercom_flags & (#flags) != 0 => {
+                #args_ident.expect_count(#arg_count)?;
+                #( #conversions )*
+                let _call = intercom::ComBoxData::enter_call(&*self_combox, #exclusive)?;
+                let self_struct = #self_struct_expr;
+                Ok(intercom::Variant::from(#value))
+            }
+        )
+    });
+
+    let (generics, bounds, s_ref, i_ref) = match itf.item_type {
+        utils::InterfaceType::Struct => (
+            quote!(<I, S>),
+            quote!(
+                where I: ?Sized,
+                      S: intercom::attributes::ComClassInterface<I, #ts_type_tokens>
+                        + intercom::attributes::ComClass
+            ),
+            quote!(#itf_path),
+            quote!(#itf_path),
+        ),
+        utils::InterfaceType::Trait => (
+            quote!(<I, S>),
+            quote!(
+                where I: ?Sized,
+                      S: intercom::attributes::ComClassInterface<I, #ts_type_tokens>
+                        + intercom::attributes::ComClass + #itf_ident
+            ),
+            quote!(S),
+            quote!(I),
+        ),
+    };
+
+    quote_spanned!(itf.span =>
+        #[allow(non_snake_case)]
+        #[allow(clippy::all)]
+        #[doc(hidden)]
+        impl #generics intercom::attributes::ComDispatch<I, S, #ts_type_tokens> for #itf_ref
+        #bounds
+        {
+            const MEMBERS: &'static [&'static str] = &[ #( #members ),* ];
+
+            unsafe fn invoke(
+                self_vtable: intercom::raw::RawComPtr,
+                dispid: intercom::raw::DISPID,
+                __intercom_flags: u16,
+                mut #args_ident: intercom::dispatch::DispatchArgs,
+            ) -> Result<intercom::Variant, intercom::dispatch::DispatchError>
+            {
+                let offset = <#s_ref as intercom::attributes::ComClassInterface<
+                    #i_ref, #ts_type_tokens>>::offset();
+                let self_combox = ( self_vtable as usize - offset )
+                        as *mut intercom::ComBoxData<#s_ref>;
+
+                match dispid {
+                    #( #arms )*
+                    _ => Err(intercom::dispatch::DispatchError::Invoke(
+                        intercom::raw::DISP_E_MEMBERNOTFOUND)),
+                }
+            }
         }
     )
 }
@@ -625,11 +858,41 @@ fn create_get_typeinfo_function(itf: &model::ComInterface) -> TokenStream
 {
     let itf_name = itf.ident.to_string();
     let itf_ref = &itf.itf_ref;
+
+    // The base interface is recorded by name. The intrinsic COM interfaces are
+    // known to the consumers of the type library, but any other base
+    // interface is gathered along with the current one so that the library
+    // describes the whole inheritance chain, even across crates.
+    let base_name = itf
+        .base_interface
+        .as_ref()
+        .and_then(|base| base.segments.last())
+        .map(|segment| segment.ident.to_string());
+    let base_tokens = match &base_name {
+        Some(name) => quote_spanned!(itf.span => Some(#name.into())),
+        None => quote_spanned!(itf.span => None),
+    };
+    let gathered_types = match (&itf.base_interface, &base_name) {
+        (Some(base), Some(name)) if name != "IUnknown" && name != "IDispatch" => {
+            quote_spanned!(itf.span =>
+                types.into_iter().chain(
+                    <dyn #base as intercom::attributes::ComInterfaceTypeInfo>::gather_type_info()
+                ).collect()
+            )
+        }
+        _ => quote!(types),
+    };
+
     let mut variant_tokens = vec![];
     for (ts, variant) in &itf.variants {
-        variant_tokens.push(create_typeinfo_for_variant(itf, *ts, variant));
+        variant_tokens.push(create_typeinfo_for_variant(itf, *ts, variant, &base_tokens));
     }
     let is_impl_interface = itf.item_type == utils::InterfaceType::Struct;
+    let doc_tokens = utils::get_doc_tokens(&itf.doc, itf.span);
+    let dual_option = match itf.dual {
+        true => quote!(dual: true,),
+        false => quote!(),
+    };
 
     quote_spanned!(itf.span =>
         #[allow(non_snake_case)]
@@ -640,16 +903,19 @@ fn create_get_typeinfo_function(itf: &model::ComInterface) -> TokenStream
             {
                 let variants = vec![ #( #variant_tokens ),* ];
 
-                vec![ intercom::typelib::TypeInfo::Interface(
+                let types = vec![ intercom::typelib::TypeInfo::Interface(
                     intercom::ComBox::new( intercom::typelib::Interface {
                         name: #itf_name.into(),
                         variants,
                         options: intercom::typelib::InterfaceOptions {
                             class_impl_interface: #is_impl_interface,
+                            #dual_option
                             ..Default::default()
-                        }
+                        },
+                        doc: #doc_tokens,
                     })
-                ) ]
+                ) ];
+                #gathered_types
             }
         }
     )
@@ -659,64 +925,96 @@ fn create_typeinfo_for_variant(
     itf: &model::ComInterface,
     ts: ModelTypeSystem,
     itf_variant: &model::ComInterfaceVariant,
+    base_tokens: &TokenStream,
 ) -> TokenStream
 {
     let ts_tokens = ts.as_typesystem_tokens(itf.span);
     let ts_type = ts.as_typesystem_type(itf.span);
     let iid_tokens = utils::get_guid_tokens(&itf_variant.iid, itf.span);
-    let methods = itf_variant.methods.iter().map( |m| {
-        let method_name = m.name.to_string();
-        let return_type = match &m.return_type {
-            Some(rt) => quote_spanned!(m.signature_span =>
+    let methods = itf_variant
+        .methods
+        .iter()
+        .map(|m| {
+            let method_name = m.member_name();
+            let doc_tokens = utils::get_doc_tokens(&m.doc, m.signature_span);
+            let kind = Ident::new(
+                match m.property {
+                    None => "Method",
+                    Some(PropertyKind::Get) => "PropertyGet",
+                    Some(PropertyKind::Put) => "PropertyPut",
+                },
+                m.signature_span,
+            );
+            let return_type = match &m.return_type {
+                Some(rt) => quote_spanned!(m.signature_span =>
                 intercom::typelib::Arg {
                     name: "".into(),
-                    ty: <
-                        <#rt as intercom::type_system::ExternType<#ts_type>>::ForeignType
-                        as intercom::type_system::ForeignType>::type_name().into(),
-                    indirection_level: <
-                        <#rt as intercom::type_system::ExternType<#ts_type>>::ForeignType
-                        as intercom::type_system::ForeignType>::indirection_level(),
+                    ty: <#rt as intercom::type_system::ExternType<#ts_type>>::type_desc(),
                     direction: intercom::typelib::Direction::Return,
+                    doc: None,
                 }),
-            None => quote_spanned!(m.signature_span => intercom::typelib::Arg {
+                None => quote_spanned!(m.signature_span => intercom::typelib::Arg {
                 name: "".into(),
-                ty: "void".into(),
-                indirection_level: 0,
+                ty: intercom::typelib::TypeDesc::Void,
                 direction: intercom::typelib::Direction::Return,
+                doc: None,
             } ),
-        };
-
-        let params = m.raw_com_args().into_iter().map(|arg| {
-            let com_ty = arg.handler.com_ty(arg.span);
-            let arg_name = arg.name.to_string();
-            let dir_ident = Ident::new(match arg.dir {
-                Direction::In => "In",
-                Direction::Out => "Out",
-                Direction::Retval => "Retval"
-            }, arg.span);
-
-            quote_spanned!(arg.span => intercom::typelib::Arg {
-                name: #arg_name.into(),
-                ty: <#com_ty as intercom::type_system::ForeignType>::type_name().into(),
-                indirection_level: <#com_ty as intercom::type_system::ForeignType>::indirection_level(),
-                direction: intercom::typelib::Direction::#dir_ident,
-            })
-        }).collect::<Vec<_>>();
+            };
 
-        quote_spanned!(m.signature_span =>
-            intercom::ComBox::new(intercom::typelib::Method {
-                name: #method_name.into(),
-                return_type: #return_type,
-                parameters: vec![ #( #params ),* ],
-            })
-        )
-    }).collect::<Vec<_>>();
+            let params = m
+                .raw_com_args()
+                .into_iter()
+                .map(|arg| {
+                    let arg_name = arg.name.to_string();
+                    let ty = arg.handler.type_desc(&arg.name, arg.span);
+
+                    // The out parameters are pointers to the values the callee
+                    // writes.
+                    let ty = match arg.dir {
+                        Direction::In => ty,
+                        Direction::Out | Direction::Retval => quote_spanned!(arg.span =>
+                    intercom::typelib::TypeDesc::pointer(
+                        #ty, intercom::typelib::PointerDirection::Out)),
+                    };
+                    let dir_ident = Ident::new(
+                        match arg.dir {
+                            Direction::In => "In",
+                            Direction::Out => "Out",
+                            Direction::Retval => "Retval",
+                        },
+                        arg.span,
+                    );
+
+                    let doc =
+                        utils::get_doc_tokens(&m.arg_doc(&arg.name).map(str::to_string), arg.span);
+
+                    quote_spanned!(arg.span => intercom::typelib::Arg {
+                        name: #arg_name.into(),
+                        ty: #ty,
+                        direction: intercom::typelib::Direction::#dir_ident,
+                        doc: #doc,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            quote_spanned!(m.signature_span =>
+                intercom::ComBox::new(intercom::typelib::Method {
+                    name: #method_name.into(),
+                    kind: intercom::typelib::MethodKind::#kind,
+                    return_type: #return_type,
+                    parameters: vec![ #( #params ),* ],
+                    doc: #doc_tokens,
+                })
+            )
+        })
+        .collect::<Vec<_>>();
 
     quote_spanned!(itf.span =>
         intercom::ComBox::new( intercom::typelib::InterfaceVariant {
             ts: #ts_tokens,
             iid: #iid_tokens,
             methods: vec![ #( #methods ),* ],
+            base: #base_tokens,
         })
     )
 }
@@ -13,7 +13,9 @@ use syn::spanned::Spanned;
 /// The macro expansion results in the following items:
 ///
 /// - `DllGetClassObject` extern function implementation.
+/// - `DllCanUnloadNow` extern function implementation.
 /// - `IntercomListClassObjects` extern function implementation.
+/// - `IntercomDumpLiveObjects` extern function implementation.
 pub fn expand_com_module(
     arg_tokens: TokenStreamNightly,
     com_library: bool,
@@ -73,6 +75,15 @@ pub fn expand_com_module(
         quote!()
     };
 
+    // Register the on_panic() hook before any calls are served. Objects are
+    // created through DllGetClassObject so setting the hook there covers
+    // platforms that do not call DllMain.
+    let on_panic = if let Some(ref on_panic) = &lib.on_panic {
+        quote!(intercom::panic::set_panic_handler(#on_panic);)
+    } else {
+        quote!()
+    };
+
     // Implement DllGetClassObject and DllMain.
     //
     // This is more or less the only symbolic entry point that the COM
@@ -80,8 +91,9 @@ pub fn expand_com_module(
     // the IClassFactory interfaces that are then used to construct the
     // actual coclasses.
     if com_library {
-        let dll_get_class_object = get_dll_get_class_object_function();
+        let dll_get_class_object = get_dll_get_class_object_function(&on_panic);
         output.push(dll_get_class_object);
+        output.push(get_dll_can_unload_now_function(&lib));
         output.push(quote!(
             #[doc(hidden)]
             static mut __INTERCOM_DLL_INSTANCE: *mut std::os::raw::c_void = 0 as _;
@@ -100,6 +112,7 @@ pub fn expand_com_module(
                     // DLL_PROCESS_ATTACH
                     1 => unsafe {
                         __INTERCOM_DLL_INSTANCE = dll_instance;
+                        #on_panic
                         #on_load
                     },
                     _ => {}
@@ -121,6 +134,9 @@ pub fn expand_com_module(
         let list_class_objects = get_intercom_list_class_objects_function();
         output.push(list_class_objects);
 
+        // IntercomDumpLiveObjects reports the objects the clients have leaked.
+        output.push(get_intercom_dump_live_objects_function());
+
         // DllListClassObjects returns all CLSIDs implemented in the crate.
         let dll_register_server = get_register_server_function(&lib);
         output.push(dll_register_server);
@@ -129,7 +145,7 @@ pub fn expand_com_module(
     Ok(TokenStream::from_iter(output.into_iter()).into())
 }
 
-fn get_dll_get_class_object_function() -> TokenStream
+fn get_dll_get_class_object_function(on_panic: &TokenStream) -> TokenStream
 {
     quote!(
         #[no_mangle]
@@ -142,6 +158,8 @@ fn get_dll_get_class_object_function() -> TokenStream
             pout: *mut intercom::raw::RawComPtr,
         ) -> intercom::raw::HRESULT
         {
+            #on_panic
+
             // Delegate to the module implementation.
             if let Some(hr) = __get_module_class_factory(rclsid, riid, pout) {
                 return hr;
@@ -157,6 +175,38 @@ fn get_dll_get_class_object_function() -> TokenStream
     )
 }
 
+fn get_dll_can_unload_now_function(lib: &model::ComLibrary) -> TokenStream
+{
+    // intercom::module invokes the on_unload() function once each time the
+    // library becomes unused. COM polls DllCanUnloadNow periodically and may
+    // keep the library loaded after the first S_OK so the hook must not run
+    // on every poll.
+    let lib_name = lib_name();
+    let on_unload = if let Some(ref on_unload) = &lib.on_unload {
+        quote!(
+            intercom::panic::catch_panic(#lib_name, "on_unload", || {
+                #on_unload();
+                intercom::raw::S_OK
+            })
+        )
+    } else {
+        quote!(intercom::raw::S_OK)
+    };
+
+    quote!(
+        #[no_mangle]
+        #[allow(non_snake_case)]
+        #[allow(dead_code)]
+        #[doc(hidden)]
+        pub unsafe extern "system" fn DllCanUnloadNow() -> intercom::raw::HRESULT
+        {
+            // The library must stay loaded while there are live objects or
+            // server locks.
+            intercom::module::can_unload_now(|| #on_unload)
+        }
+    )
+}
+
 fn create_gather_module_types(lib: &model::ComLibrary) -> TokenStream
 {
     let create_class_typeinfo = lib.coclasses.iter().map(|path| {
@@ -169,6 +219,16 @@ fn create_gather_module_types(lib: &model::ComLibrary) -> TokenStream
             <dyn #path as intercom::attributes::ComInterfaceTypeInfo>::gather_type_info()
         )
     });
+    let create_struct_typeinfo = lib.structs.iter().map(|path| {
+        quote!(
+            <#path as intercom::attributes::ComStructTypeInfo>::gather_type_info()
+        )
+    });
+    let create_enum_typeinfo = lib.enums.iter().map(|path| {
+        quote!(
+            <#path as intercom::attributes::ComEnumTypeInfo>::gather_type_info()
+        )
+    });
     let gather_submodule_types = lib
         .submodules
         .iter()
@@ -180,6 +240,8 @@ fn create_gather_module_types(lib: &model::ComLibrary) -> TokenStream
                 #( #create_class_typeinfo, )*
                 #( #gather_submodule_types, )*
                 #( #create_interface_typeinfo, )*
+                #( #create_struct_typeinfo, )*
+                #( #create_enum_typeinfo, )*
             ]
             .into_iter()
             .flatten()
@@ -267,6 +329,20 @@ fn get_intercom_list_class_objects_function() -> TokenStream
     )
 }
 
+fn get_intercom_dump_live_objects_function() -> TokenStream
+{
+    quote!(
+        #[no_mangle]
+        #[allow(non_snake_case)]
+        #[allow(dead_code)]
+        #[doc(hidden)]
+        pub unsafe extern "system" fn IntercomDumpLiveObjects() -> intercom::raw::HRESULT
+        {
+            intercom::leaks::dump_live_objects()
+        }
+    )
+}
+
 fn get_register_server_function(lib: &model::ComLibrary) -> TokenStream
 {
     // We'll need token streams to for the hook functions to use in the quote macros. If the user
@@ -6,6 +6,9 @@ pub use self::com_class::expand_com_class;
 mod com_interface;
 pub use self::com_interface::expand_com_interface;
 
+mod com_import;
+pub use self::com_import::expand_com_import;
+
 mod com_library;
 pub use self::com_library::expand_com_module;
 
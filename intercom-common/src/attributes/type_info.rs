@@ -1,4 +1,7 @@
 use crate::prelude::*;
+use crate::utils;
+
+use syn::spanned::Spanned;
 
 /// Expands the `ForeignType` derive attribute.
 ///
@@ -13,12 +16,26 @@ pub fn expand_bidirectional_type_info(
     let input: syn::DeriveInput = syn::parse(item_tokens)?;
     let name = &input.ident;
 
+    // Enums are passed as their discriminants. Other types use the default
+    // struct description.
+    let type_desc = match input.data {
+        syn::Data::Enum(_) => quote!(
+            fn type_desc() -> intercom::typelib::TypeDesc
+            {
+                intercom::typelib::TypeDesc::Enum(stringify!( #name ).into())
+            }
+        ),
+        _ => quote!(),
+    };
+
     // Immpl requires the the generics in particular way.
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let result = quote! { impl #impl_generics intercom::type_system::ForeignType for #name #ty_generics #where_clause {
 
         /// The default name is the name of the type.
         fn type_name() -> &'static str { stringify!( #name ) }
+
+        #type_desc
     } };
 
     Ok(result.into())
@@ -29,6 +46,10 @@ pub fn expand_bidirectional_type_info(
 /// The attribute expansion results in the following items:
 ///
 /// - Implementation of the ExternType trait.
+/// - Implementation of the ComStructTypeInfo trait for `#[repr(C)]` structs.
+/// - Implementation of the ComEnumTypeInfo trait for C-like enums.
+///
+/// C-like enums are passed as their discriminants.
 pub fn expand_derive_extern_type(
     item_tokens: TokenStreamNightly,
 ) -> Result<TokenStreamNightly, syn::Error>
@@ -39,15 +60,194 @@ pub fn expand_derive_extern_type(
 
     // Immpl requires the the generics in particular way.
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    if let Some(c_enum) = CEnum::parse(&input) {
+        let repr = &c_enum.repr;
+        let type_info = c_enum.type_info(name, &input.attrs);
+        return Ok(quote! {
+            impl<TS: intercom::type_system::TypeSystem> intercom::type_system::ExternType<TS> for #name {
+                type ForeignType = intercom::type_system::EnumRepr<#name, #repr>;
+            }
+
+            #type_info
+        }
+        .into());
+    }
+
+    let type_info = struct_type_info(&input);
     let result = quote! {
         impl<TS: intercom::type_system::TypeSystem> #impl_generics intercom::type_system::ExternType<TS> for #name #ty_generics #where_clause {
             type ForeignType = #name;
         }
+
+        #type_info
     };
 
     Ok(result.into())
 }
 
+/// Creates the type library description of a struct.
+///
+/// Only non-generic `#[repr(C)]` structs with named fields have a layout that
+/// can be described to the foreign code. The field types must implement
+/// `ForeignType`.
+fn struct_type_info(input: &syn::DeriveInput) -> Option<TokenStream>
+{
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => return None,
+    };
+    if !input.generics.params.is_empty() || !is_repr_c(&input.attrs) {
+        return None;
+    }
+
+    let name = &input.ident;
+    let doc = utils::get_doc_tokens(&utils::get_doc(&input.attrs), name.span());
+    let field_info = fields.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        quote_spanned!(ty.span() => (
+            std::mem::size_of::<#ty>(),
+            intercom::typelib::Field {
+                name: stringify!(#ident).into(),
+                ty: <#ty as intercom::type_system::ForeignType>::type_desc(),
+                offset: std::mem::offset_of!(#name, #ident) as u32,
+            }
+        ))
+    });
+
+    Some(quote!(
+        impl intercom::attributes::ComStructTypeInfo for #name
+        {
+            fn gather_type_info() -> Vec<intercom::typelib::TypeInfo>
+            {
+                // Zero sized fields have no representation in C.
+                let fields = vec![ #( #field_info ),* ]
+                    .into_iter()
+                    .filter(|(size, _)| *size > 0)
+                    .map(|(_, field)| field)
+                    .collect();
+                vec![ intercom::typelib::TypeInfo::Struct(
+                    intercom::ComBox::new( intercom::typelib::Struct {
+                        name: stringify!(#name).into(),
+                        size: std::mem::size_of::<#name>() as u32,
+                        fields,
+                        doc: #doc,
+                    })
+                ) ]
+            }
+        }
+    ))
+}
+
+/// A fieldless enum that is passed as its discriminant.
+struct CEnum<'a>
+{
+    /// The integer type of the discriminants.
+    repr: syn::Ident,
+    variants: Vec<&'a syn::Ident>,
+}
+
+impl<'a> CEnum<'a>
+{
+    /// Parses the enum details. Returns `None` for other types.
+    fn parse(input: &'a syn::DeriveInput) -> Option<Self>
+    {
+        let data = match &input.data {
+            syn::Data::Enum(data) => data,
+            _ => return None,
+        };
+        if !input.generics.params.is_empty()
+            || data.variants.iter().any(|v| v.fields != syn::Fields::Unit)
+        {
+            return None;
+        }
+
+        // The `#[repr(C)]` enums use the C `int`.
+        let repr = repr_args(&input.attrs)
+            .into_iter()
+            .find(|ident| INTEGER_TYPES.contains(&ident.to_string().as_str()))
+            .unwrap_or_else(|| syn::Ident::new("i32", proc_macro2::Span::call_site()));
+
+        Some(CEnum {
+            repr,
+            variants: data.variants.iter().map(|v| &v.ident).collect(),
+        })
+    }
+
+    /// Creates the conversion from the discriminant value.
+    fn parse_discriminant(&self, name: &syn::Ident, value: TokenStream) -> TokenStream
+    {
+        let repr = &self.repr;
+        let variants = &self.variants;
+        quote!(
+            match #value {
+                #( v if v == #name::#variants as #repr => Ok(#name::#variants), )*
+                _ => Err(intercom::ComError::E_INVALIDARG),
+            }
+        )
+    }
+
+    /// Creates the type library description of the enum.
+    fn type_info(&self, name: &syn::Ident, attrs: &[syn::Attribute]) -> TokenStream
+    {
+        let repr = &self.repr;
+        let variants = &self.variants;
+        let doc = utils::get_doc_tokens(&utils::get_doc(attrs), name.span());
+        quote!(
+            impl intercom::attributes::ComEnumTypeInfo for #name
+            {
+                fn gather_type_info() -> Vec<intercom::typelib::TypeInfo>
+                {
+                    vec![ intercom::typelib::TypeInfo::Enum(
+                        intercom::ComBox::new( intercom::typelib::Enum {
+                            name: stringify!(#name).into(),
+                            ty: stringify!(#repr).into(),
+                            variants: vec![ #(
+                                intercom::typelib::EnumVariant {
+                                    name: stringify!(#variants).into(),
+                                    value: #name::#variants as #repr as i64,
+                                },
+                            )* ],
+                            doc: #doc,
+                        })
+                    ) ]
+                }
+            }
+        )
+    }
+}
+
+const INTEGER_TYPES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "isize", "u8", "u16", "u32", "u64", "usize",
+];
+
+/// Gets the arguments of the `#[repr(...)]` attributes.
+fn repr_args(attrs: &[syn::Attribute]) -> Vec<syn::Ident>
+{
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("repr"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|meta| match meta {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) => path.get_ident().cloned(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Checks whether the attributes include `#[repr(C)]`.
+fn is_repr_c(attrs: &[syn::Attribute]) -> bool
+{
+    repr_args(attrs).iter().any(|ident| ident == "C")
+}
+
 /// Expands the `ExternInput` derive attribute.
 ///
 /// The attribute expansion results in the following items:
@@ -61,6 +261,31 @@ pub fn expand_derive_extern_input(
     let input: syn::DeriveInput = syn::parse(item_tokens)?;
     let name = &input.ident;
 
+    // The enum discriminants are validated so there are no infallible
+    // conversions for the enums.
+    if let Some(c_enum) = CEnum::parse(&input) {
+        let parse_discriminant = c_enum.parse_discriminant(name, quote!(source.value));
+        return Ok(quote! {
+            unsafe impl<TS: intercom::type_system::TypeSystem> intercom::type_system::ExternInput<TS> for #name {
+
+                type Lease = ();
+
+                #[inline(always)]
+                unsafe fn into_foreign_parameter(self) -> intercom::ComResult<(Self::ForeignType, Self::Lease)> {
+                    Ok((intercom::type_system::EnumRepr::new(self as _), ()))
+                }
+
+                type Owned = #name;
+
+                #[inline(always)]
+                unsafe fn from_foreign_parameter(source: Self::ForeignType) -> intercom::ComResult<Self::Owned> {
+                    #parse_discriminant
+                }
+            }
+        }
+        .into());
+    }
+
     // Immpl requires the the generics in particular way.
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let result = quote! {
@@ -115,6 +340,25 @@ pub fn expand_derive_extern_output(
     let input: syn::DeriveInput = syn::parse(item_tokens)?;
     let name = &input.ident;
 
+    if let Some(c_enum) = CEnum::parse(&input) {
+        let parse_discriminant = c_enum.parse_discriminant(name, quote!(source.value));
+        return Ok(quote! {
+            unsafe impl<TS: intercom::type_system::TypeSystem> intercom::type_system::ExternOutput<TS> for #name {
+
+                #[inline(always)]
+                fn into_foreign_output(self) -> intercom::ComResult<Self::ForeignType> {
+                    Ok(intercom::type_system::EnumRepr::new(self as _))
+                }
+
+                #[inline(always)]
+                unsafe fn from_foreign_output(source: Self::ForeignType) -> intercom::ComResult<Self> {
+                    #parse_discriminant
+                }
+            }
+        }
+        .into());
+    }
+
     // Impl requires the the generics in particular way.
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let result = quote! {
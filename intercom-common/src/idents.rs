@@ -33,6 +33,12 @@ pub fn with_ts(ident: &Ident, ts: ModelTypeSystem) -> Ident
     Ident::new(&format!("{}_{:?}", ident, ts), Span::call_site())
 }
 
+/// Name of the length parameter for a conformant array.
+pub fn array_length(ident: &Ident) -> Ident
+{
+    Ident::new(&format!("{}_len", ident), ident.span())
+}
+
 pub fn clsid_path(struct_path: &Path) -> Path
 {
     let mut clsid_path = struct_path.clone();
@@ -0,0 +1,1280 @@
+//! Parser for the COM interfaces declared in IDL files.
+//!
+//! The parser understands the object interface declarations of the MIDL
+//! syntax and turns them into `#[com_interface]` traits. The IID of the
+//! interface becomes the `com_iid` of the trait so the Automation type system
+//! vtable matches the declaration. The methods take `&self` as the IDL
+//! doesn't tell which of them modify the object.
+//!
+//! The `import` statements are skipped. The interfaces may refer to
+//! `IUnknown`, `IDispatch` and the interfaces declared in the same file.
+//! Other declarations, such as `typedef`s, `coclass`es and `library` blocks,
+//! are rejected with an error that points to the declaration.
+
+use crate::guid::GUID;
+use crate::prelude::*;
+use std::fmt;
+
+/// Error in the IDL source.
+#[derive(Debug, PartialEq)]
+pub struct IdlError
+{
+    pub msg: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for IdlError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{}:{}: {}", self.line, self.column, self.msg)
+    }
+}
+
+pub type IdlResult<T> = Result<T, IdlError>;
+
+/// Line and column of an item in the IDL source, both starting from 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position
+{
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position
+{
+    fn error<T>(self, msg: impl Into<String>) -> IdlResult<T>
+    {
+        Err(IdlError {
+            msg: msg.into(),
+            line: self.line,
+            column: self.column,
+        })
+    }
+}
+
+/// The interfaces declared in an IDL file.
+#[derive(Debug)]
+pub struct IdlFile
+{
+    pub interfaces: Vec<Interface>,
+}
+
+#[derive(Debug)]
+pub struct Interface
+{
+    pub name: String,
+    pub iid: GUID,
+    pub base: String,
+    pub doc: Option<String>,
+    pub methods: Vec<Method>,
+    pub position: Position,
+}
+
+impl Interface
+{
+    /// Interfaces deriving from `IDispatch` are imported as dual interfaces.
+    pub fn is_dual(&self) -> bool
+    {
+        self.base == "IDispatch"
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MethodKind
+{
+    Method,
+    PropertyGet,
+    PropertyPut,
+}
+
+#[derive(Debug)]
+pub struct Method
+{
+    pub name: String,
+    pub kind: MethodKind,
+
+    /// The `id` attribute of the method.
+    pub dispid: Option<i32>,
+    pub return_type: Type,
+    pub params: Vec<Param>,
+    pub doc: Option<String>,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction
+{
+    In,
+    Out,
+    Retval,
+}
+
+#[derive(Debug)]
+pub struct Param
+{
+    pub name: String,
+    pub ty: Type,
+    pub direction: Direction,
+
+    /// Whether the pointer may be null.
+    pub unique: bool,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type
+{
+    /// A named type. The built-in types with several words, such as
+    /// `unsigned long`, are separated by single spaces.
+    Named(String),
+    Pointer(Box<Type>),
+    SafeArray(Box<Type>),
+}
+
+impl Type
+{
+    fn is(&self, name: &str) -> bool
+    {
+        matches!(self, Type::Named(n) if n == name)
+    }
+}
+
+impl fmt::Display for Type
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            Type::Named(name) => write!(f, "{}", name),
+            Type::Pointer(pointee) => write!(f, "{}*", pointee),
+            Type::SafeArray(element) => write!(f, "SAFEARRAY({})", element),
+        }
+    }
+}
+
+/// Attribute in the square brackets preceding a declaration.
+struct Attribute
+{
+    name: String,
+
+    /// Source text between the parentheses.
+    args: Option<String>,
+    position: Position,
+}
+
+impl Attribute
+{
+    fn string_arg(&self) -> IdlResult<String>
+    {
+        let args = self.args.as_deref().unwrap_or("").trim();
+        match args.strip_prefix('"').and_then(|a| a.strip_suffix('"')) {
+            Some(s) => Ok(s.replace("\\\"", "\"")),
+            None => self
+                .position
+                .error(format!("`{}` requires a string argument", self.name)),
+        }
+    }
+}
+
+/// Parses the interfaces from the IDL source.
+pub fn parse(source: &str) -> IdlResult<IdlFile>
+{
+    let mut parser = Parser {
+        chars: source.chars().collect(),
+        idx: 0,
+        line: 1,
+        column: 1,
+    };
+
+    let mut interfaces = vec![];
+    loop {
+        parser.skip_trivia()?;
+        let position = parser.position();
+        match parser.peek() {
+            None => break,
+            Some('#') => return position.error("Preprocessor directives are not supported"),
+            Some(';') => {
+                parser.bump();
+            }
+            Some('[') => {
+                let attrs = parser.attributes()?;
+                let (keyword, position) = parser.ident("a declaration")?;
+                if keyword != "interface" {
+                    return unsupported_declaration(&keyword, position);
+                }
+                match parser.interface(attrs, position)? {
+                    Some(itf) => interfaces.push(itf),
+                    None => return position.error("Interface declaration has no body"),
+                }
+            }
+            Some(_) => {
+                let (keyword, position) = parser.ident("a declaration")?;
+                match keyword.as_str() {
+                    // The standard imports declare the base interfaces, which
+                    // are known without parsing the imported files.
+                    "import" => parser.skip_past(';')?,
+                    "cpp_quote" => {
+                        parser.expect('(')?;
+                        parser.parenthesized()?;
+                    }
+                    "interface" => {
+                        // Forward declarations are fine, but the interfaces
+                        // with bodies need the IID.
+                        if let Some(itf) = parser.interface(vec![], position)? {
+                            return itf.position.error(format!(
+                                "Interface `{}` has no `uuid` attribute",
+                                itf.name
+                            ));
+                        }
+                    }
+                    _ => return unsupported_declaration(&keyword, position),
+                }
+            }
+        }
+    }
+
+    Ok(IdlFile { interfaces })
+}
+
+fn unsupported_declaration<T>(keyword: &str, position: Position) -> IdlResult<T>
+{
+    position.error(format!(
+        "`{}` declarations are not supported, only object interfaces can be imported",
+        keyword
+    ))
+}
+
+struct Parser
+{
+    chars: Vec<char>,
+    idx: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Parser
+{
+    fn position(&self) -> Position
+    {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn peek(&self) -> Option<char>
+    {
+        self.chars.get(self.idx).cloned()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char>
+    {
+        self.chars.get(self.idx + offset).cloned()
+    }
+
+    fn bump(&mut self) -> Option<char>
+    {
+        let c = self.peek()?;
+        self.idx += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    /// Skips the whitespace and the comments.
+    fn skip_trivia(&mut self) -> IdlResult<()>
+    {
+        loop {
+            match (self.peek(), self.peek_at(1)) {
+                (Some(c), _) if c.is_whitespace() => {
+                    self.bump();
+                }
+                (Some('/'), Some('/')) => {
+                    while self.peek().map(|c| c != '\n').unwrap_or(false) {
+                        self.bump();
+                    }
+                }
+                (Some('/'), Some('*')) => {
+                    let position = self.position();
+                    self.bump();
+                    self.bump();
+                    loop {
+                        match (self.peek(), self.peek_at(1)) {
+                            (Some('*'), Some('/')) => break,
+                            (None, _) => return position.error("Unterminated comment"),
+                            _ => {
+                                self.bump();
+                            }
+                        }
+                    }
+                    self.bump();
+                    self.bump();
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Consumes the character if it is next in the source.
+    fn eat(&mut self, c: char) -> IdlResult<bool>
+    {
+        self.skip_trivia()?;
+        if self.peek() == Some(c) {
+            self.bump();
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn expect(&mut self, c: char) -> IdlResult<()>
+    {
+        match self.eat(c)? {
+            true => Ok(()),
+            false => self.position().error(format!("Expected `{}`", c)),
+        }
+    }
+
+    fn ident(&mut self, expected: &str) -> IdlResult<(String, Position)>
+    {
+        self.skip_trivia()?;
+        let position = self.position();
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+            _ => return position.error(format!("Expected {}", expected)),
+        }
+
+        let mut ident = String::new();
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_alphanumeric() && c != '_' {
+                break;
+            }
+            ident.push(c);
+            self.bump();
+        }
+        Ok((ident, position))
+    }
+
+    /// Consumes the identifier if it is one of the candidates.
+    fn eat_ident(&mut self, candidates: &[&str]) -> IdlResult<Option<String>>
+    {
+        self.skip_trivia()?;
+        let (idx, line, column) = (self.idx, self.line, self.column);
+        if let Ok((ident, _)) = self.ident("") {
+            if candidates.contains(&ident.as_str()) {
+                return Ok(Some(ident));
+            }
+        }
+
+        self.idx = idx;
+        self.line = line;
+        self.column = column;
+        Ok(None)
+    }
+
+    fn skip_past(&mut self, c: char) -> IdlResult<()>
+    {
+        let position = self.position();
+        loop {
+            self.skip_trivia()?;
+            match self.bump() {
+                Some('"') => {
+                    self.string_tail()?;
+                }
+                Some(next) if next == c => return Ok(()),
+                Some(_) => {}
+                None => return position.error(format!("Expected `{}`", c)),
+            }
+        }
+    }
+
+    /// Consumes the rest of a string literal.
+    fn string_tail(&mut self) -> IdlResult<String>
+    {
+        let position = self.position();
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    s.push('\\');
+                    if let Some(c) = self.bump() {
+                        s.push(c);
+                    }
+                }
+                Some(c) => s.push(c),
+                None => return position.error("Unterminated string"),
+            }
+        }
+    }
+
+    /// Consumes the source up to the closing parenthesis and returns it.
+    fn parenthesized(&mut self) -> IdlResult<String>
+    {
+        let position = self.position();
+        let mut text = String::new();
+        let mut depth = 0;
+        loop {
+            match self.bump() {
+                Some(')') if depth == 0 => return Ok(text),
+                Some(c) => {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        '"' => {
+                            text.push('"');
+                            text.push_str(&self.string_tail()?);
+                        }
+                        _ => {}
+                    }
+                    text.push(c);
+                }
+                None => return position.error("Expected `)`"),
+            }
+        }
+    }
+
+    /// Parses the attributes in square brackets if there are any.
+    fn attributes(&mut self) -> IdlResult<Vec<Attribute>>
+    {
+        let mut attrs = vec![];
+        if !self.eat('[')? {
+            return Ok(attrs);
+        }
+
+        loop {
+            let (name, position) = self.ident("an attribute")?;
+            let args = match self.eat('(')? {
+                true => Some(self.parenthesized()?),
+                false => None,
+            };
+            attrs.push(Attribute {
+                name,
+                args,
+                position,
+            });
+
+            if self.eat(']')? {
+                return Ok(attrs);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    /// Parses the interface following the `interface` keyword.
+    ///
+    /// Returns `None` for forward declarations.
+    fn interface(
+        &mut self,
+        attrs: Vec<Attribute>,
+        position: Position,
+    ) -> IdlResult<Option<Interface>>
+    {
+        let (name, _) = self.ident("the interface name")?;
+        if self.eat(';')? {
+            return Ok(None);
+        }
+
+        if !self.eat(':')? {
+            return position.error(format!(
+                "Interface `{}` must derive from IUnknown or another interface",
+                name
+            ));
+        }
+        let (base, _) = self.ident("the base interface")?;
+
+        let mut iid = None;
+        let mut doc = None;
+        for attr in &attrs {
+            match attr.name.as_str() {
+                "uuid" => {
+                    let uuid = attr.args.as_deref().unwrap_or("").trim();
+                    iid =
+                        Some(GUID::parse(uuid).or_else(|_| {
+                            attr.position.error(format!("Invalid uuid `{}`", uuid))
+                        })?);
+                }
+                "helpstring" => doc = Some(attr.string_arg()?),
+                "object" | "dual" | "oleautomation" | "local" | "pointer_default" | "version"
+                | "nonextensible" | "hidden" | "restricted" | "helpcontext" => {}
+                other => {
+                    return attr
+                        .position
+                        .error(format!("Unsupported interface attribute `{}`", other))
+                }
+            }
+        }
+
+        self.expect('{')?;
+        let mut methods = vec![];
+        while !self.eat('}')? {
+            let attrs = self.attributes()?;
+            methods.push(self.method(attrs)?);
+        }
+        self.eat(';')?;
+
+        let iid = match iid {
+            Some(iid) => iid,
+            None => return position.error(format!("Interface `{}` has no `uuid` attribute", name)),
+        };
+
+        Ok(Some(Interface {
+            name,
+            iid,
+            base,
+            doc,
+            methods,
+            position,
+        }))
+    }
+
+    fn method(&mut self, attrs: Vec<Attribute>) -> IdlResult<Method>
+    {
+        self.skip_trivia()?;
+        let position = self.position();
+        let return_type = self.ty()?;
+        let (name, _) = self.ident("the method name")?;
+
+        let mut kind = MethodKind::Method;
+        let mut dispid = None;
+        let mut doc = None;
+        for attr in &attrs {
+            match attr.name.as_str() {
+                "propget" => kind = MethodKind::PropertyGet,
+                "propput" => kind = MethodKind::PropertyPut,
+                "id" => {
+                    let id = attr.args.as_deref().unwrap_or("").trim();
+                    let value = match id.strip_prefix("0x").or_else(|| id.strip_prefix("0X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).map(|v| v as i32).ok(),
+                        None => id.parse::<i32>().ok(),
+                    };
+                    dispid = match value {
+                        Some(value) => Some(value),
+                        None => return attr.position.error(format!("Invalid id `{}`", id)),
+                    };
+                }
+                "helpstring" => doc = Some(attr.string_arg()?),
+                "hidden" | "restricted" | "helpcontext" => {}
+                "propputref" => {
+                    return attr
+                        .position
+                        .error("`propputref` accessors are not supported")
+                }
+                other => {
+                    return attr
+                        .position
+                        .error(format!("Unsupported method attribute `{}`", other))
+                }
+            }
+        }
+
+        self.expect('(')?;
+        let mut params = vec![];
+        if !self.eat(')')? {
+            loop {
+                let param = self.param()?;
+
+                // `void` alone declares an empty parameter list.
+                if params.is_empty() && param.is_none() && self.eat(')')? {
+                    break;
+                }
+                match param {
+                    Some(param) => params.push(param),
+                    None => return self.position().error("Expected a parameter name"),
+                }
+
+                if self.eat(')')? {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+        self.expect(';')?;
+
+        Ok(Method {
+            name,
+            kind,
+            dispid,
+            return_type,
+            params,
+            doc,
+            position,
+        })
+    }
+
+    /// Parses a parameter.
+    ///
+    /// Returns `None` for a lone `void`.
+    fn param(&mut self) -> IdlResult<Option<Param>>
+    {
+        self.skip_trivia()?;
+        let position = self.position();
+        let attrs = self.attributes()?;
+        let ty = self.ty()?;
+        if attrs.is_empty() && ty.is("void") {
+            self.skip_trivia()?;
+            if self.peek() == Some(')') {
+                return Ok(None);
+            }
+        }
+        let (name, _) = self.ident("a parameter name")?;
+        if self.eat('[')? {
+            return position.error("Array parameters are not supported");
+        }
+
+        let (mut is_in, mut is_out, mut is_retval, mut unique) = (false, false, false, false);
+        for attr in &attrs {
+            match attr.name.as_str() {
+                "in" => is_in = true,
+                "out" => is_out = true,
+                "retval" => is_retval = true,
+                "unique" => unique = true,
+                "iid_is" | "defaultvalue" | "optional" | "ref" => {}
+                "size_is" | "length_is" | "max_is" | "first_is" | "last_is" => {
+                    return attr.position.error("Conformant arrays are not supported")
+                }
+                other => {
+                    return attr
+                        .position
+                        .error(format!("Unsupported parameter attribute `{}`", other))
+                }
+            }
+        }
+
+        let direction = match (is_in, is_out, is_retval) {
+            (true, true, _) => return position.error("`[in, out]` parameters are not supported"),
+            (_, false, true) => return position.error("`[retval]` requires `[out]`"),
+            (_, true, true) => Direction::Retval,
+            (_, true, false) => Direction::Out,
+            _ => Direction::In,
+        };
+
+        Ok(Some(Param {
+            name,
+            ty,
+            direction,
+            unique,
+            position,
+        }))
+    }
+
+    fn ty(&mut self) -> IdlResult<Type>
+    {
+        self.eat_ident(&["const"])?;
+        let (name, position) = self.ident("a type")?;
+        let name = match name.as_str() {
+            "signed" | "unsigned" => {
+                let int = self.int_type()?.unwrap_or_else(|| "int".to_string());
+                format!("{} {}", name, int)
+            }
+            "long" | "short" | "small" | "hyper" | "int" | "char" | "__int64" => {
+                self.eat_ident(&["int"])?;
+                match name == "long" && self.eat_ident(&["long"])?.is_some() {
+                    true => "long long".to_string(),
+                    false => name,
+                }
+            }
+            "struct" | "union" | "enum" => {
+                return position.error(format!("`{}` types are not supported", name))
+            }
+            "SAFEARRAY" => {
+                self.expect('(')?;
+                let element = self.ty()?;
+                self.expect(')')?;
+                return self.pointers(Type::SafeArray(Box::new(element)));
+            }
+            _ => name,
+        };
+        self.pointers(Type::Named(name))
+    }
+
+    /// Parses the integer type following `signed` or `unsigned`.
+    fn int_type(&mut self) -> IdlResult<Option<String>>
+    {
+        let int = self.eat_ident(&["long", "short", "small", "hyper", "int", "char", "__int64"])?;
+        if let Some(int) = &int {
+            self.eat_ident(&["int"])?;
+            if int == "long" && self.eat_ident(&["long"])?.is_some() {
+                return Ok(Some("long long".to_string()));
+            }
+        }
+        Ok(int)
+    }
+
+    fn pointers(&mut self, mut ty: Type) -> IdlResult<Type>
+    {
+        loop {
+            self.eat_ident(&["const"])?;
+            if !self.eat('*')? {
+                return Ok(ty);
+            }
+            ty = Type::Pointer(Box::new(ty));
+        }
+    }
+}
+
+/// Declares the interfaces of the file as `#[com_interface]` traits.
+pub fn expand(file: &IdlFile) -> IdlResult<TokenStream>
+{
+    let ctx = Context { file };
+    let mut output = vec![];
+    for itf in &file.interfaces {
+        output.push(ctx.interface(itf)?);
+    }
+    Ok(quote!( #( #output )* ))
+}
+
+struct Context<'a>
+{
+    file: &'a IdlFile,
+}
+
+impl Context<'_>
+{
+    fn interface(&self, itf: &Interface) -> IdlResult<TokenStream>
+    {
+        let ident = Ident::new(&itf.name, Span::call_site());
+        let iid = format!("{:-X}", itf.iid);
+
+        let (attr, supertrait) = match itf.base.as_str() {
+            "IUnknown" => (quote!(com_iid = #iid), quote!()),
+            "IDispatch" => (quote!(com_iid = #iid, dual), quote!()),
+            base if self.is_declared(base) => {
+                let base = Ident::new(base, Span::call_site());
+                (quote!(com_iid = #iid, base = #base), quote!(: #base))
+            }
+            base => {
+                return itf
+                    .position
+                    .error(format!("Unknown base interface `{}`", base))
+            }
+        };
+
+        let mut members: Vec<&str> = vec![];
+        let mut methods = vec![];
+        for method in &itf.methods {
+            if !members.contains(&method.name.as_str()) {
+                members.push(&method.name);
+            }
+
+            // Intercom assigns the dispatch IDs in the declaration order so
+            // the declared IDs must match that.
+            let expected = members.len() as i32;
+            match method.dispid {
+                Some(dispid) if itf.is_dual() && dispid != expected => {
+                    return method.position.error(format!(
+                        "`{}` has the id {} but the dispatch IDs of the imported interfaces \
+                         follow the member order and the member is number {}",
+                        method.name, dispid, expected
+                    ))
+                }
+                _ => {}
+            }
+
+            methods.push(self.method(method)?);
+        }
+
+        let doc = doc_attr(&itf.doc);
+        Ok(quote!(
+            #doc
+            #[intercom::com_interface( #attr )]
+            pub trait #ident #supertrait
+            {
+                #( #methods )*
+            }
+        ))
+    }
+
+    fn method(&self, method: &Method) -> IdlResult<TokenStream>
+    {
+        let position = method.position;
+        let returns_hresult = method.return_type.is("HRESULT");
+
+        let mut args = vec![];
+        let mut outputs = vec![];
+        for (idx, param) in method.params.iter().enumerate() {
+            let name = Ident::new(&identifier(&snake_case(&param.name)), Span::call_site());
+            match param.direction {
+                Direction::In if !outputs.is_empty() => {
+                    return param
+                        .position
+                        .error("Input parameters must precede the output parameters")
+                }
+                Direction::In => {
+                    let ty = self.input_type(&param.ty, param.unique, param.position)?;
+                    args.push(quote!( #name: #ty ));
+                }
+                _ if !returns_hresult => {
+                    return param
+                        .position
+                        .error("Output parameters require an HRESULT return value")
+                }
+                Direction::Retval if idx + 1 != method.params.len() => {
+                    return param
+                        .position
+                        .error("The `[retval]` parameter must be the last parameter")
+                }
+                Direction::Out | Direction::Retval => {
+                    let ty = match &param.ty {
+                        Type::Pointer(value) => {
+                            self.output_type(value, param.unique, param.position)?
+                        }
+                        _ => {
+                            return param.position.error(format!(
+                                "Output parameter `{}` must be a pointer",
+                                param.name
+                            ))
+                        }
+                    };
+                    outputs.push(ty);
+                }
+            }
+        }
+
+        match method.kind {
+            MethodKind::Method => {}
+            MethodKind::PropertyGet if !args.is_empty() || outputs.len() != 1 => {
+                return position
+                    .error("Property getters must have a single output parameter and no inputs")
+            }
+            MethodKind::PropertyPut if args.len() != 1 || !outputs.is_empty() => {
+                return position.error("Property setters must have a single input parameter")
+            }
+            _ if !returns_hresult => {
+                return position.error("Property accessors must return HRESULT")
+            }
+            _ => {}
+        }
+
+        let returns = if returns_hresult {
+            match outputs.len() {
+                0 => quote!( -> intercom::ComResult<()> ),
+                1 => quote!( -> intercom::ComResult<#( #outputs )*> ),
+                _ => quote!( -> intercom::ComResult<( #( #outputs ),* )> ),
+            }
+        } else if method.return_type.is("void") {
+            quote!()
+        } else {
+            let ty = self.output_type(&method.return_type, false, position)?;
+            quote!( -> #ty )
+        };
+
+        let name = match method.kind {
+            MethodKind::Method => snake_case(&method.name),
+            MethodKind::PropertyGet => format!("get_{}", snake_case(&method.name)),
+            MethodKind::PropertyPut => format!("set_{}", snake_case(&method.name)),
+        };
+        let name = Ident::new(&identifier(&name), Span::call_site());
+        let property = match method.kind {
+            MethodKind::Method => quote!(),
+            _ => quote!(#[intercom::com_property]),
+        };
+        let doc = doc_attr(&method.doc);
+        Ok(quote!(
+            #doc
+            #property
+            fn #name(&self #( , #args )*) #returns;
+        ))
+    }
+
+    /// Maps the type of an input parameter.
+    fn input_type(&self, ty: &Type, unique: bool, position: Position) -> IdlResult<TokenStream>
+    {
+        let tokens = match ty {
+            Type::Pointer(pointee) => match &**pointee {
+                Type::Named(name) if self.is_interface(name) => {
+                    let itf = self.interface_path(name);
+                    match unique {
+                        true => return Ok(quote!(Option<&intercom::ComItf<dyn #itf>>)),
+                        false => return Ok(quote!(&intercom::ComItf<dyn #itf>)),
+                    }
+                }
+                Type::Named(name) if name == "void" => quote!(*const std::ffi::c_void),
+                Type::Named(name) => {
+                    let pointee = self.value_type(name, position)?;
+                    quote!(*const #pointee)
+                }
+                _ => return unsupported_type(ty, position),
+            },
+            Type::Named(name) => match name.as_str() {
+                "BSTR" => quote!(&str),
+                "REFIID" | "REFGUID" | "REFCLSID" => quote!(*const intercom::GUID),
+                _ => self.value_type(name, position)?,
+            },
+            Type::SafeArray(element) => {
+                let element = self.output_type(element, false, position)?;
+                quote!(&[#element])
+            }
+        };
+
+        match unique {
+            true => position.error("`[unique]` is supported only for interface pointers"),
+            false => Ok(tokens),
+        }
+    }
+
+    /// Maps the type of a value the callee passes to the caller.
+    ///
+    /// The types are owned by the caller.
+    fn output_type(&self, ty: &Type, unique: bool, position: Position) -> IdlResult<TokenStream>
+    {
+        let tokens = match ty {
+            Type::Pointer(pointee) => match &**pointee {
+                Type::Named(name) if self.is_interface(name) => {
+                    let itf = self.interface_path(name);
+                    match unique {
+                        true => return Ok(quote!(Option<intercom::ComRc<dyn #itf>>)),
+                        false => return Ok(quote!(intercom::ComRc<dyn #itf>)),
+                    }
+                }
+                Type::Named(name) if name == "void" => quote!(*mut std::ffi::c_void),
+                Type::Named(name) => {
+                    let pointee = self.value_type(name, position)?;
+                    quote!(*mut #pointee)
+                }
+                _ => return unsupported_type(ty, position),
+            },
+            Type::Named(name) => match name.as_str() {
+                "BSTR" => quote!(String),
+                _ => self.value_type(name, position)?,
+            },
+            Type::SafeArray(element) => {
+                let element = self.output_type(element, false, position)?;
+                quote!(Vec<#element>)
+            }
+        };
+
+        match unique {
+            true => position.error("`[unique]` is supported only for interface pointers"),
+            false => Ok(tokens),
+        }
+    }
+
+    /// Maps the types that are passed by value in both directions.
+    fn value_type(&self, name: &str, position: Position) -> IdlResult<TokenStream>
+    {
+        Ok(match name {
+            "char" | "signed char" | "small" | "signed small" | "CHAR" | "INT8" => quote!(i8),
+            "unsigned char" | "unsigned small" | "byte" | "BYTE" | "UCHAR" | "UINT8"
+            | "boolean" => quote!(u8),
+            "short" | "signed short" | "SHORT" | "INT16" => quote!(i16),
+            "unsigned short" | "USHORT" | "WORD" | "UINT16" => quote!(u16),
+            "long" | "signed long" | "int" | "signed int" | "LONG" | "INT" | "INT32" | "BOOL" => {
+                quote!(i32)
+            }
+            "unsigned long" | "unsigned int" | "ULONG" | "UINT" | "DWORD" | "UINT32" => {
+                quote!(u32)
+            }
+            "hyper" | "signed hyper" | "__int64" | "signed __int64" | "long long"
+            | "signed long long" | "LONGLONG" | "INT64" => quote!(i64),
+            "unsigned hyper" | "unsigned __int64" | "unsigned long long" | "ULONGLONG"
+            | "UINT64" => quote!(u64),
+            "float" | "FLOAT" => quote!(f32),
+            "double" | "DOUBLE" => quote!(f64),
+
+            // Rust `bool` is a single byte so the two byte `VARIANT_BOOL` is
+            // passed as its raw value.
+            "VARIANT_BOOL" => quote!(i16),
+            "HRESULT" => quote!(intercom::raw::HRESULT),
+            "GUID" | "IID" | "CLSID" => quote!(intercom::GUID),
+            "VARIANT" => quote!(intercom::Variant),
+            name if self.is_interface(name) => {
+                return position.error(format!("Interface `{}` must be passed as a pointer", name))
+            }
+            name => return position.error(format!("Unsupported type `{}`", name)),
+        })
+    }
+
+    fn is_declared(&self, name: &str) -> bool
+    {
+        self.file.interfaces.iter().any(|itf| itf.name == name)
+    }
+
+    fn is_interface(&self, name: &str) -> bool
+    {
+        name == "IUnknown" || name == "IDispatch" || self.is_declared(name)
+    }
+
+    fn interface_path(&self, name: &str) -> TokenStream
+    {
+        match name {
+            "IUnknown" => quote!(intercom::IUnknown),
+            "IDispatch" => quote!(intercom::IDispatch),
+            name => {
+                let ident = Ident::new(name, Span::call_site());
+                quote!(#ident)
+            }
+        }
+    }
+}
+
+fn unsupported_type<T>(ty: &Type, position: Position) -> IdlResult<T>
+{
+    position.error(format!("Unsupported type `{}`", ty))
+}
+
+fn doc_attr(doc: &Option<String>) -> TokenStream
+{
+    match doc {
+        Some(doc) => quote!(#[doc = #doc]),
+        None => quote!(),
+    }
+}
+
+/// Converts the `PascalCase` or `camelCase` IDL name to `snake_case`.
+fn snake_case(name: &str) -> String
+{
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut output = String::with_capacity(name.len() + 4);
+    for (idx, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && idx > 0 {
+            // Acronyms stay together until the start of the next word.
+            let prev = chars[idx - 1];
+            let next_lower = chars
+                .get(idx + 1)
+                .map(|c| c.is_lowercase())
+                .unwrap_or(false);
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                output.push('_');
+            }
+        }
+        output.extend(c.to_lowercase());
+    }
+    output
+}
+
+/// Avoids the Rust keywords in the identifiers.
+fn identifier(name: &str) -> String
+{
+    #[rustfmt::skip]
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true",
+        "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final",
+        "macro", "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+    ];
+    match KEYWORDS.contains(&name) {
+        true => format!("{}_", name),
+        false => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    fn import(source: &str) -> IdlResult<syn::File>
+    {
+        Ok(syn::parse2(expand(&parse(source)?)?).unwrap())
+    }
+
+    fn error(source: &str) -> (usize, usize, String)
+    {
+        let e = import(source).unwrap_err();
+        (e.line, e.column, e.msg)
+    }
+
+    #[test]
+    fn import_interfaces()
+    {
+        let source = r#"
+            // Comments are skipped.
+            import "oaidl.idl";
+
+            interface ICounter;
+
+            [
+                object,
+                uuid(12345678-1234-1234-1234-567890ABCDEF),
+                helpstring("Counts things.")
+            ]
+            interface ICounter : IUnknown
+            {
+                [helpstring("Adds to the counter.")]
+                HRESULT Add([in] long amount, [out, retval] long* total);
+                HRESULT GetHTMLName([out] BSTR* name, [out] unsigned short* length);
+                ULONG Total(void);
+                [propget] HRESULT Name([out, retval] BSTR* value);
+                [propput] HRESULT Name([in] BSTR value);
+            };
+
+            [object, uuid(12345678-1234-1234-1234-567890FEDCBA)]
+            interface INamed : ICounter
+            {
+                HRESULT Compare(
+                    [in, unique] ICounter* other,
+                    [in] REFIID riid,
+                    [out] IUnknown** result);
+                HRESULT Values([in] SAFEARRAY(double) values, [out] SAFEARRAY(BSTR)* names);
+            }
+        "#;
+
+        // Skipped so rustfmt doesn't add a trailing comma to `compare`.
+        #[rustfmt::skip]
+        let expected = quote!(
+            #[doc = "Counts things."]
+            #[intercom::com_interface(com_iid = "12345678-1234-1234-1234-567890ABCDEF")]
+            pub trait ICounter
+            {
+                #[doc = "Adds to the counter."]
+                fn add(&self, amount: i32) -> intercom::ComResult<i32>;
+                fn get_html_name(&self) -> intercom::ComResult<(String, u16)>;
+                fn total(&self) -> u32;
+                #[intercom::com_property]
+                fn get_name(&self) -> intercom::ComResult<String>;
+                #[intercom::com_property]
+                fn set_name(&self, value: &str) -> intercom::ComResult<()>;
+            }
+
+            #[intercom::com_interface(
+                com_iid = "12345678-1234-1234-1234-567890FEDCBA",
+                base = ICounter
+            )]
+            pub trait INamed: ICounter
+            {
+                fn compare(
+                    &self,
+                    other: Option<&intercom::ComItf<dyn ICounter>>,
+                    riid: *const intercom::GUID
+                ) -> intercom::ComResult<intercom::ComRc<dyn intercom::IUnknown>>;
+                fn values(&self, values: &[f64]) -> intercom::ComResult<Vec<String>>;
+            }
+        );
+        assert_eq!(import(source).unwrap(), syn::parse2(expected).unwrap());
+    }
+
+    #[test]
+    fn import_dual_interface()
+    {
+        let source = r#"
+            [object, dual, uuid(12345678-1234-1234-1234-567890ABCDEF)]
+            interface IAutomated : IDispatch
+            {
+                [id(1), propget] HRESULT Value([out, retval] VARIANT* value);
+                [id(1), propput] HRESULT Value([in] VARIANT value);
+                [id(2)] HRESULT Enabled([out, retval] VARIANT_BOOL* enabled);
+            };
+        "#;
+
+        let expected = quote!(
+            #[intercom::com_interface(com_iid = "12345678-1234-1234-1234-567890ABCDEF", dual)]
+            pub trait IAutomated
+            {
+                #[intercom::com_property]
+                fn get_value(&self) -> intercom::ComResult<intercom::Variant>;
+                #[intercom::com_property]
+                fn set_value(&self, value: intercom::Variant) -> intercom::ComResult<()>;
+                fn enabled(&self) -> intercom::ComResult<i16>;
+            }
+        );
+        assert_eq!(import(source).unwrap(), syn::parse2(expected).unwrap());
+
+        let reordered = source.replace("[id(2)]", "[id(5)]");
+        let (line, column, msg) = error(&reordered);
+        assert_eq!((line, column), (7, 25));
+        assert!(msg.contains("has the id 5"), "{}", msg);
+    }
+
+    #[test]
+    fn unsupported_declarations()
+    {
+        assert_eq!(
+            error("\n  typedef long FOO;"),
+            (
+                2,
+                3,
+                "`typedef` declarations are not supported, \
+                 only object interfaces can be imported"
+                    .into()
+            )
+        );
+        assert_eq!(
+            error("#include \"foo.h\""),
+            (1, 1, "Preprocessor directives are not supported".into())
+        );
+        assert_eq!(
+            error("[uuid(12345678-1234-1234-1234-567890ABCDEF)] coclass Foo {}"),
+            (
+                1,
+                46,
+                "`coclass` declarations are not supported, \
+                 only object interfaces can be imported"
+                    .into()
+            )
+        );
+        assert_eq!(
+            error("interface IFoo : IUnknown {}"),
+            (1, 1, "Interface `IFoo` has no `uuid` attribute".into())
+        );
+    }
+
+    #[test]
+    fn unsupported_parameters()
+    {
+        let method = |m: &str| {
+            error(&format!(
+                "[object, uuid(12345678-1234-1234-1234-567890ABCDEF)]\n\
+                 interface IFoo : IUnknown\n{{\n    {}\n}}",
+                m
+            ))
+        };
+
+        assert_eq!(
+            method("HRESULT Foo([in, out] long* value);"),
+            (4, 17, "`[in, out]` parameters are not supported".into())
+        );
+        assert_eq!(
+            method("HRESULT Foo([in] long count, [in, size_is(count)] long* values);"),
+            (4, 39, "Conformant arrays are not supported".into())
+        );
+        assert_eq!(
+            method("HRESULT Foo([in] LPCWSTR name);"),
+            (4, 17, "Unsupported type `LPCWSTR`".into())
+        );
+        assert_eq!(
+            method("HRESULT Foo([out] long* value, [in] long input);"),
+            (
+                4,
+                36,
+                "Input parameters must precede the output parameters".into()
+            )
+        );
+        assert_eq!(
+            method("long Foo([out] long* value);"),
+            (
+                4,
+                14,
+                "Output parameters require an HRESULT return value".into()
+            )
+        );
+        assert_eq!(
+            method("HRESULT Foo([in] IBar* bar);"),
+            (4, 17, "Unsupported type `IBar`".into())
+        );
+    }
+
+    #[test]
+    fn unknown_base_interface()
+    {
+        let (line, column, msg) =
+            error("[object, uuid(12345678-1234-1234-1234-567890ABCDEF)]\ninterface IFoo : IBar {}");
+        assert_eq!((line, column), (2, 1));
+        assert_eq!(msg, "Unknown base interface `IBar`");
+    }
+
+    #[test]
+    fn snake_case_names()
+    {
+        assert_eq!(snake_case("GetValue"), "get_value");
+        assert_eq!(snake_case("pbstrName"), "pbstr_name");
+        assert_eq!(snake_case("GetHTMLName"), "get_html_name");
+        assert_eq!(snake_case("Utf8Value"), "utf8_value");
+        assert_eq!(identifier("type"), "type_");
+    }
+}
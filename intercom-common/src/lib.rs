@@ -13,6 +13,7 @@ pub mod attributes;
 pub mod error;
 pub mod guid;
 pub mod idents;
+pub mod idl;
 pub mod methodinfo;
 pub mod model;
 pub mod prelude;
@@ -4,6 +4,7 @@ use std::rc::Rc;
 use syn::{spanned::Spanned, FnArg, PathArguments, Receiver, ReturnType, Signature, Type};
 
 use crate::ast_converters::*;
+use crate::idents;
 use crate::returnhandlers::{get_return_handler, ReturnHandler};
 use crate::tyhandlers::{get_ty_handler, Direction, ModelTypeSystem, TypeContext, TypeHandler};
 use crate::utils;
@@ -17,6 +18,17 @@ pub enum ComMethodInfoError
     BadReturnType,
 }
 
+/// Property accessor implemented by a `#[com_property]` method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyKind
+{
+    /// Returns the property value.
+    Get,
+
+    /// Sets the property value.
+    Put,
+}
+
 #[derive(Clone)]
 pub struct RustArg
 {
@@ -79,6 +91,20 @@ pub struct ComArg
 
     /// Argument direction. COM uses OUT params while Rust uses return values.
     pub dir: Direction,
+
+    /// The part of the conformant array the argument passes.
+    pub array_part: Option<ArrayPart>,
+}
+
+/// Conformant arrays are passed as two COM arguments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArrayPart
+{
+    /// Pointer to the array elements.
+    Data,
+
+    /// Number of elements in the array.
+    Length,
 }
 
 impl ComArg
@@ -98,6 +124,7 @@ impl ComArg
             dir,
             span,
             handler: tyhandler,
+            array_part: None,
         }
     }
 
@@ -110,8 +137,50 @@ impl ComArg
             dir,
             span: rustarg.span,
             handler: tyhandler,
+            array_part: None,
         }
     }
+
+    /// Expands a conformant array into the data and the length arguments.
+    ///
+    /// Other arguments are passed as they are.
+    pub fn expand_array(self) -> Vec<ComArg>
+    {
+        if !self.handler.is_array() {
+            return vec![self];
+        }
+
+        // The retval must be the last parameter so output arrays use plain
+        // out parameters.
+        let dir = match self.dir {
+            Direction::Retval => Direction::Out,
+            dir => dir,
+        };
+        let length_ty: Type = parse_quote!(u32);
+        let length = ComArg {
+            name: idents::array_length(&self.name),
+            handler: get_ty_handler(&length_ty, TypeContext::new(self.handler.type_system())),
+            ty: length_ty,
+            dir,
+            span: self.span,
+            array_part: Some(ArrayPart::Length),
+        };
+        let data = ComArg {
+            dir,
+            array_part: Some(ArrayPart::Data),
+            ..self
+        };
+
+        vec![data, length]
+    }
+
+    /// True if the argument is the length of a conformant array.
+    ///
+    /// The length is converted together with the data argument.
+    pub fn is_array_length(&self) -> bool
+    {
+        self.array_part == Some(ArrayPart::Length)
+    }
 }
 
 impl PartialEq for ComArg
@@ -168,6 +237,15 @@ pub struct ComMethodInfo
 
     /// Is the method infallible.
     pub infallible: bool,
+
+    /// The property accessor the method implements.
+    pub property: Option<PropertyKind>,
+
+    /// Documentation of the method.
+    pub doc: Option<String>,
+
+    /// Documentation of the arguments by the argument name.
+    pub arg_docs: Vec<(String, String)>,
 }
 
 impl PartialEq for ComMethodInfo
@@ -181,6 +259,7 @@ impl PartialEq for ComMethodInfo
             && self.retval_type == other.retval_type
             && self.return_type == other.return_type
             && self.args == other.args
+            && self.property == other.property
     }
 }
 
@@ -251,15 +330,92 @@ impl ComMethodInfo
             args,
             is_unsafe: unsafety,
             type_system,
+            property: None,
+            doc: None,
+            arg_docs: vec![],
         })
     }
 
-    pub fn raw_com_args(&self) -> Vec<ComArg>
+    /// Marks the method as a property accessor.
+    ///
+    /// Getters take no arguments and return the value in a `Result`. Setters
+    /// take the value as the only argument and return a `Result` with no
+    /// value.
+    pub fn into_property(mut self) -> Result<ComMethodInfo, String>
+    {
+        let com_args = self.raw_com_args();
+        let dirs = com_args.iter().map(|arg| arg.dir).collect::<Vec<_>>();
+        let kind = match dirs.as_slice() {
+            [Direction::Retval] => PropertyKind::Get,
+            [Direction::In] if !self.infallible => PropertyKind::Put,
+            _ => {
+                return Err(format!(
+                    "Property `{}` must either take no arguments and return a Result with \
+                     a value or take one argument and return a Result with no value",
+                    self.name
+                ))
+            }
+        };
+
+        self.property = Some(kind);
+        Ok(self)
+    }
+
+    /// Sets the documentation from the rustdoc comments.
+    ///
+    /// The `# Arguments` section describes the arguments.
+    pub fn set_doc(&mut self, doc: Option<String>)
+    {
+        let (doc, arg_docs) = match doc {
+            Some(doc) => utils::split_argument_docs(&doc),
+            None => (None, vec![]),
+        };
+        self.doc = doc;
+        self.arg_docs = arg_docs;
+    }
+
+    /// Gets the documentation of the argument.
+    pub fn arg_doc(&self, name: &Ident) -> Option<&str>
     {
-        let in_args = self
-            .args
+        self.arg_docs
+            .iter()
+            .find(|(arg, _)| name == arg)
+            .map(|(_, doc)| doc.as_str())
+    }
+
+    /// Checks whether the method can report an error to the COM caller.
+    ///
+    /// Infallible methods that return a raw `HRESULT` can still return the
+    /// error code.
+    pub fn has_error_channel(&self) -> bool
+    {
+        !self.returnhandler.is_infallible() || self.return_type.iter().any(utils::is_hresult)
+    }
+
+    /// The name the COM clients use for the method.
+    ///
+    /// Property accessors are named after the property, which is the method
+    /// name without the `get_`, `set_` or `put_` prefix.
+    pub fn member_name(&self) -> String
+    {
+        let name = self.name.to_string();
+        if self.property.is_none() {
+            return name;
+        }
+
+        ["get_", "set_", "put_"]
             .iter()
-            .map(|ca| ComArg::from_rustarg(ca.clone(), Direction::In, self.type_system));
+            .find_map(|prefix| name.strip_prefix(prefix))
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .unwrap_or(name)
+    }
+
+    pub fn raw_com_args(&self) -> Vec<ComArg>
+    {
+        let in_args = self.args.iter().flat_map(|ca| {
+            ComArg::from_rustarg(ca.clone(), Direction::In, self.type_system).expand_array()
+        });
         let out_args = self.returnhandler.com_out_args();
 
         in_args.chain(out_args).collect()
@@ -393,6 +549,42 @@ mod tests
         assert_eq!(info.args[1].ty, parse_quote!(f32));
     }
 
+    #[test]
+    fn property_accessors()
+    {
+        let getter = test_info("fn get_value( &self ) -> ComResult<u32> {}", Automation)
+            .into_property()
+            .unwrap();
+        assert_eq!(getter.property, Some(PropertyKind::Get));
+        assert_eq!(getter.member_name(), "value");
+
+        let setter = test_info(
+            "fn set_value( &mut self, v : u32 ) -> ComResult<()> {}",
+            Raw,
+        )
+        .into_property()
+        .unwrap();
+        assert_eq!(setter.property, Some(PropertyKind::Put));
+        assert_eq!(setter.member_name(), "value");
+
+        let plain = test_info("fn get_value( &self ) -> ComResult<u32> {}", Automation);
+        assert_eq!(plain.property, None);
+        assert_eq!(plain.member_name(), "get_value");
+    }
+
+    #[test]
+    fn invalid_property_accessors()
+    {
+        let infallible_setter = test_info("fn set_value( &mut self, v : u32 ) {}", Raw);
+        assert!(infallible_setter.into_property().is_err());
+
+        let getter_with_args = test_info("fn value( &self, a : u32 ) -> ComResult<u32> {}", Raw);
+        assert!(getter_with_args.into_property().is_err());
+
+        let void_getter = test_info("fn value( &self ) -> ComResult<()> {}", Automation);
+        assert!(void_getter.into_property().is_err());
+    }
+
     fn test_info(code: &str, ts: ModelTypeSystem) -> ComMethodInfo
     {
         let item = syn::parse_str(code).unwrap();
@@ -3,14 +3,27 @@ use super::*;
 use crate::prelude::*;
 
 use crate::guid::GUID;
-use syn::{Generics, Path, Visibility};
+use syn::{Generics, LitBool, LitStr, Path, Visibility};
 
 intercom_attribute!(
     ComClassAttr<ComClassAttrParam, Path> {
         clsid : StrOption,
+        factory : Path,
+        allocator : Path,
+        threading : LitStr,
+        aggregatable : LitBool,
     }
 );
 
+/// Concurrency policy specified with the `threading` option.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ThreadingModel
+{
+    Single,
+    Mutex,
+    Free,
+}
+
 /// Details of a struct marked with `#[com_class]` attribute.
 #[derive(Debug, PartialEq, Eq)]
 pub struct ComClass
@@ -20,6 +33,13 @@ pub struct ComClass
     pub visibility: Visibility,
     pub interfaces: Vec<Path>,
     pub generics: Generics,
+    pub aggregatable: bool,
+    pub factory: Option<Path>,
+    pub allocator: Option<Path>,
+    pub threading: Option<ThreadingModel>,
+
+    /// Documentation of the class.
+    pub doc: Option<String>,
 }
 
 impl ComClass
@@ -57,7 +77,49 @@ impl ComClass
             Some(StrOption::None) => None,
         };
 
-        // Remaining parameters are coclasses.
+        // Custom factory function used to construct the class instances.
+        let factory = attr
+            .factory()
+            .map_err(|msg| ParseError::ComClass(item.ident.to_string(), msg))?
+            .cloned();
+
+        // Static allocator used for the class instances.
+        let allocator = attr
+            .allocator()
+            .map_err(|msg| ParseError::ComClass(item.ident.to_string(), msg))?
+            .cloned();
+
+        // Concurrency policy of the class instances.
+        let threading = match attr
+            .threading()
+            .map_err(|msg| ParseError::ComClass(item.ident.to_string(), msg))?
+        {
+            None => None,
+            Some(model) => Some(match model.value().as_str() {
+                "single" => ThreadingModel::Single,
+                "mutex" => ThreadingModel::Mutex,
+                "free" => ThreadingModel::Free,
+                other => {
+                    return Err(ParseError::ComClass(
+                        item.ident.to_string(),
+                        format!(
+                            "Unknown threading model \"{}\"; \
+                             expected \"single\", \"mutex\" or \"free\"",
+                            other
+                        ),
+                    ))
+                }
+            }),
+        };
+
+        // Aggregation is refused unless the class opts in.
+        let aggregatable = attr
+            .aggregatable()
+            .map_err(|msg| ParseError::ComClass(item.ident.to_string(), msg))?
+            .map(|flag| flag.value)
+            .unwrap_or(false);
+
+        // Remaining parameters are the interfaces.
         let name = item.ident.clone();
         let interfaces = attr
             .args()
@@ -69,11 +131,16 @@ impl ComClass
             .collect();
 
         Ok(ComClass {
+            doc: crate::utils::get_doc(&item.attrs),
             visibility: item.vis.clone(),
             generics: item.generics,
             name,
             clsid,
             interfaces,
+            aggregatable,
+            factory,
+            allocator,
+            threading,
         })
     }
 
@@ -186,4 +253,86 @@ mod test
         assert_eq!(cls.clsid, None);
         assert_eq!(cls.interfaces.len(), 1);
     }
+
+    #[test]
+    fn parse_com_class_aggregatable()
+    {
+        let cls = ComClass::parse(
+            "not used",
+            quote!(aggregatable = true, ITestInterface),
+            quote!(
+                struct Inner;
+            ),
+        )
+        .expect("com_class attribute parsing failed");
+
+        assert!(cls.aggregatable);
+        assert_eq!(cls.interfaces.len(), 1);
+        assert_eq!(cls.interfaces[0], parse_quote!(ITestInterface));
+    }
+
+    #[test]
+    fn parse_com_class_with_factory()
+    {
+        let cls = ComClass::parse(
+            "not used",
+            quote!(factory = factories::create_configured, ITestInterface),
+            quote!(
+                struct Configured
+                {
+                    value: u32,
+                }
+            ),
+        )
+        .expect("com_class attribute parsing failed");
+
+        assert_eq!(
+            cls.factory,
+            Some(parse_quote!(factories::create_configured))
+        );
+        assert_eq!(cls.interfaces.len(), 1);
+        assert_eq!(cls.interfaces[0], parse_quote!(ITestInterface));
+    }
+
+    #[test]
+    fn parse_com_class_with_allocator()
+    {
+        let cls = ComClass::parse(
+            "not used",
+            quote!(allocator = pools::VALUE_POOL, ITestInterface),
+            quote!(
+                struct Value;
+            ),
+        )
+        .expect("com_class attribute parsing failed");
+
+        assert_eq!(cls.allocator, Some(parse_quote!(pools::VALUE_POOL)));
+        assert_eq!(cls.factory, None);
+        assert_eq!(cls.interfaces.len(), 1);
+    }
+
+    #[test]
+    fn parse_com_class_with_threading()
+    {
+        let cls = ComClass::parse(
+            "not used",
+            quote!(threading = "single", ITestInterface),
+            quote!(
+                struct Apartment;
+            ),
+        )
+        .expect("com_class attribute parsing failed");
+
+        assert_eq!(cls.threading, Some(ThreadingModel::Single));
+        assert_eq!(cls.interfaces.len(), 1);
+
+        let result = ComClass::parse(
+            "not used",
+            quote!(threading = "apartment", ITestInterface),
+            quote!(
+                struct Apartment;
+            ),
+        );
+        assert!(result.is_err());
+    }
 }
@@ -1,11 +1,10 @@
-use super::macros::*;
 use super::*;
 use crate::prelude::*;
 
 use crate::ast_converters::*;
 use crate::guid::GUID;
 use crate::idents::{self, SomeIdent};
-use crate::methodinfo::ComMethodInfo;
+use crate::methodinfo::{ComMethodInfo, PropertyKind};
 use crate::quote::ToTokens;
 use crate::tyhandlers::ModelTypeSystem;
 use indexmap::IndexMap;
@@ -14,7 +13,7 @@ use std::iter::FromIterator;
 use syn::{Ident, LitStr, Path, TypePath, Visibility};
 
 intercom_attribute!(
-    ComInterfaceAttr< ComInterfaceAttrParam, NoParams > {
+    ComInterfaceAttr< ComInterfaceAttrParam, Ident > {
         com_iid : LitStr,
         raw_iid : LitStr,
         base : Path,
@@ -48,6 +47,10 @@ pub struct ComInterface
     pub itf_ref: TokenStream,
     pub vtable_of: Option<Path>,
     pub implemented_by: Option<Path>,
+    pub dual: bool,
+
+    /// Documentation of the interface.
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -95,6 +98,20 @@ impl ComInterface
             )
         })?;
 
+        // The only positional argument is the `dual` flag.
+        let mut dual = false;
+        for flag in attr.args() {
+            match flag.to_string().as_ref() {
+                "dual" => dual = true,
+                other => {
+                    return Err(ParseError::ComInterface(
+                        ident.to_string(),
+                        format!("Unknown interface flag `{}`", other),
+                    ))
+                }
+            }
+        }
+
         // The second argument is the optional base class. If there's no base
         // class defined, use IUnknown as the default. The value of NO_BASE will
         // construct an interface that has no base class.
@@ -111,9 +128,23 @@ impl ComInterface
                     Some(b.to_owned())
                 }
             }
+            None if dual => Some(syn::parse2(quote!(intercom::IDispatch)).unwrap()),
             None => Some(syn::parse2(quote!(intercom::IUnknown)).unwrap()),
         };
 
+        // Dual interfaces derive from IDispatch, which Intercom implements on
+        // top of the interface methods.
+        let implemented_by = attr
+            .implemented_by()
+            .map_err(|e| ParseError::ComInterface(ident.to_string(), e))?
+            .cloned();
+        if dual && (attr.base().ok().flatten().is_some() || implemented_by.is_some()) {
+            return Err(ParseError::ComInterface(
+                ident.to_string(),
+                "Dual interfaces cannot specify `base` or `implemented_by`".into(),
+            ));
+        }
+
         // Visibility for trait interfaces is the visibility of the trait.
         //
         // For implicit interfaces (impl Struct) the visibility is always public.
@@ -122,6 +153,12 @@ impl ComInterface
         //
         // Note this may conflict with visibility of the actual [com_class], but
         // nothing we can do for this really.
+        let doc = match &item {
+            ::syn::Item::Trait(t) => crate::utils::get_doc(&t.attrs),
+            ::syn::Item::Impl(i) => crate::utils::get_doc(&i.attrs),
+            _ => None,
+        };
+
         let visibility = if let ::syn::Item::Trait(ref t) = item {
             t.vis.clone()
         } else {
@@ -151,9 +188,20 @@ impl ComInterface
                     //       something smarter.
                     let methods = fns
                         .iter()
-                        .map(|sig| ComMethodInfo::new(sig, ts))
-                        .filter_map(Result::ok)
-                        .collect::<Vec<_>>();
+                        .filter_map(|(sig, attrs)| {
+                            ComMethodInfo::new(sig, ts).ok().map(|m| (m, attrs))
+                        })
+                        .map(|(mut method, attrs)| {
+                            method.set_doc(crate::utils::get_doc(attrs));
+                            match is_property(attrs) {
+                                true => method.into_property().map_err(|msg| {
+                                    ParseError::ComInterface(ident.to_string(), msg)
+                                }),
+                                false => Ok(method),
+                            }
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    check_members(&ident, &methods)?;
 
                     Ok((
                         ts,
@@ -181,14 +229,13 @@ impl ComInterface
                 .vtable_of()
                 .map_err(|e| ParseError::ComInterface(ident.to_string(), e))?
                 .cloned(),
-            implemented_by: attr
-                .implemented_by()
-                .map_err(|e| ParseError::ComInterface(ident.to_string(), e))?
-                .cloned(),
+            implemented_by,
+            dual,
             path,
             ident,
             visibility,
             variants,
+            doc,
             itf_ref,
         })
     }
@@ -209,6 +256,46 @@ impl ComInterface
     }
 }
 
+/// Checks whether the method attributes include `#[com_property]`.
+fn is_property(attrs: &[syn::Attribute]) -> bool
+{
+    attrs.iter().any(|attr| {
+        attr.path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "com_property")
+            .unwrap_or(false)
+    })
+}
+
+/// Ensures the member names the clients see are unique.
+///
+/// A property may have one getter and one setter that share the name.
+fn check_members(ident: &Ident, methods: &[ComMethodInfo]) -> ParseResult<()>
+{
+    let mut members: Vec<(String, Option<PropertyKind>)> = vec![];
+    for method in methods {
+        let name = method.member_name();
+        let conflict = members.iter().any(|(other, kind)| {
+            *other == name
+                && !matches!(
+                    (kind, method.property),
+                    (Some(PropertyKind::Get), Some(PropertyKind::Put))
+                        | (Some(PropertyKind::Put), Some(PropertyKind::Get))
+                )
+        });
+        if conflict {
+            return Err(ParseError::ComInterface(
+                ident.to_string(),
+                format!("Member `{}` is defined more than once", name),
+            ));
+        }
+        members.push((name, method.property));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod test
 {
@@ -345,6 +432,49 @@ mod test
         assert_eq!(variant.methods[1].name, "two");
     }
 
+    #[test]
+    fn parse_dual_com_interface()
+    {
+        let itf = ComInterface::from_ast(
+            "not used",
+            quote!(dual),
+            quote!(
+                pub trait IDual
+                {
+                    fn one(&self);
+                }
+            ),
+        )
+        .expect("com_interface attribute parsing failed");
+
+        assert!(itf.dual);
+        assert_eq!(
+            itf.base_interface.as_ref().unwrap(),
+            &parse_quote!(intercom::IDispatch)
+        );
+
+        ComInterface::from_ast(
+            "not used",
+            quote!(dual, base = IBase),
+            quote!(
+                pub trait IDual
+                {
+                    fn one(&self);
+                }
+            ),
+        )
+        .expect_err("dual interface accepted a base interface");
+
+        ComInterface::from_ast(
+            "not used",
+            quote!(duel),
+            quote!(
+                pub trait IDual {}
+            ),
+        )
+        .expect_err("unknown flag accepted");
+    }
+
     #[test]
     fn parse_com_interface_with_no_base_interface()
     {
@@ -385,4 +515,70 @@ mod test
         assert_eq!(variant.methods[0].name, "one");
         assert_eq!(variant.methods[1].name, "two");
     }
+
+    #[test]
+    fn parse_com_interface_with_properties()
+    {
+        let itf = ComInterface::from_ast(
+            "not used",
+            quote!(),
+            quote!(
+                trait IProperties
+                {
+                    #[com_property]
+                    fn get_value(&self) -> ComResult<u32>;
+                    #[com_property]
+                    fn set_value(&mut self, value: u32) -> ComResult<()>;
+                    fn get_other(&self) -> ComResult<u32>;
+                }
+            ),
+        )
+        .expect("com_interface attribute parsing failed");
+
+        let variant = &itf.variants[&Automation];
+        assert_eq!(variant.methods[0].property, Some(PropertyKind::Get));
+        assert_eq!(variant.methods[0].member_name(), "value");
+        assert_eq!(variant.methods[1].property, Some(PropertyKind::Put));
+        assert_eq!(variant.methods[1].member_name(), "value");
+        assert_eq!(variant.methods[2].property, None);
+        assert_eq!(variant.methods[2].member_name(), "get_other");
+    }
+
+    #[test]
+    fn parse_com_interface_with_duplicate_members()
+    {
+        let result = ComInterface::from_ast(
+            "not used",
+            quote!(),
+            quote!(
+                trait IDuplicates
+                {
+                    #[com_property]
+                    fn get_value(&self) -> ComResult<u32>;
+                    #[com_property]
+                    fn value(&self) -> ComResult<u32>;
+                }
+            ),
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_com_interface_with_infallible_mut_method()
+    {
+        let result = ComInterface::from_ast(
+            "not used",
+            quote!(),
+            quote!(
+                trait IMutable
+                {
+                    fn get_value(&self) -> u32;
+                    fn set_value(&mut self, value: u32);
+                    fn reset(&mut self) -> HRESULT;
+                }
+            ),
+        );
+        assert!(result.is_ok());
+    }
 }
@@ -10,19 +10,31 @@ pub enum LibraryItemType
     Module(Path),
     Class(Path),
     Interface(Path),
+    Struct(Path),
+    Enum(Path),
 }
 
 impl syn::parse::Parse for LibraryItemType
 {
     fn parse(input: syn::parse::ParseStream) -> syn::parse::Result<Self>
     {
+        // 'struct' and 'enum' are keywords so they won't parse as identifiers.
+        if input.peek(Token![struct]) {
+            input.parse::<Token![struct]>()?;
+            return Ok(LibraryItemType::Struct(input.parse()?));
+        }
+        if input.peek(Token![enum]) {
+            input.parse::<Token![enum]>()?;
+            return Ok(LibraryItemType::Enum(input.parse()?));
+        }
+
         let ident: syn::Ident = input.parse()?;
         match ident.to_string().as_str() {
             "module" => Ok(LibraryItemType::Module(input.parse()?)),
             "class" => Ok(LibraryItemType::Class(input.parse()?)),
             "interface" => Ok(LibraryItemType::Interface(input.parse()?)),
             _ => Err(input.error(&format!(
-                "Expected 'class', 'interface' or 'module', found {}",
+                "Expected 'class', 'interface', 'struct', 'enum' or 'module', found {}",
                 ident
             ))),
         }
@@ -33,6 +45,8 @@ intercom_attribute!(
     ComLibraryAttr< ComLibraryAttrParam, LibraryItemType > {
         libid : LitStr,
         on_load : Path,
+        on_unload : Path,
+        on_panic : Path,
         on_register : Path,
         on_unregister : Path,
     }
@@ -45,10 +59,14 @@ pub struct ComLibrary
     pub name: String,
     pub libid: GUID,
     pub on_load: Option<Path>,
+    pub on_unload: Option<Path>,
+    pub on_panic: Option<Path>,
     pub on_register: Option<Path>,
     pub on_unregister: Option<Path>,
     pub coclasses: Vec<Path>,
     pub interfaces: Vec<Path>,
+    pub structs: Vec<Path>,
+    pub enums: Vec<Path>,
     pub submodules: Vec<Path>,
 }
 
@@ -67,6 +85,8 @@ impl ComLibrary
         };
 
         let on_load = attr.on_load().map_err(ParseError::ComLibrary)?.cloned();
+        let on_unload = attr.on_unload().map_err(ParseError::ComLibrary)?.cloned();
+        let on_panic = attr.on_panic().map_err(ParseError::ComLibrary)?.cloned();
         let on_register = attr.on_register().map_err(ParseError::ComLibrary)?.cloned();
         let on_unregister = attr
             .on_unregister()
@@ -75,11 +95,15 @@ impl ComLibrary
 
         let mut coclasses = vec![];
         let mut interfaces = vec![];
+        let mut structs = vec![];
+        let mut enums = vec![];
         let mut submodules = vec![];
         for arg in attr.args().into_iter().cloned() {
             match arg {
                 LibraryItemType::Class(cls) => coclasses.push(cls),
                 LibraryItemType::Interface(cls) => interfaces.push(cls),
+                LibraryItemType::Struct(cls) => structs.push(cls),
+                LibraryItemType::Enum(cls) => enums.push(cls),
                 LibraryItemType::Module(cls) => submodules.push(cls),
             }
         }
@@ -87,10 +111,14 @@ impl ComLibrary
         Ok(ComLibrary {
             name: crate_name.to_owned(),
             on_load,
+            on_unload,
+            on_panic,
             on_register,
             on_unregister,
             coclasses,
             interfaces,
+            structs,
+            enums,
             submodules,
             libid,
         })
@@ -150,6 +178,44 @@ mod test
         assert_eq!(lib.coclasses[1], parse_quote!(Two));
     }
 
+    #[test]
+    fn parse_com_library_with_hooks()
+    {
+        let lib = ComLibrary::parse(
+            "lib".into(),
+            quote!(
+                on_load = load,
+                on_unload = unload,
+                on_panic = hooks::panic,
+                class Foo
+            ),
+        )
+        .expect("com_library attribute parsing failed");
+
+        assert_eq!(lib.on_load, Some(parse_quote!(load)));
+        assert_eq!(lib.on_unload, Some(parse_quote!(unload)));
+        assert_eq!(lib.on_panic, Some(parse_quote!(hooks::panic)));
+        assert_eq!(lib.on_register, None);
+        assert_eq!(lib.coclasses.len(), 1);
+    }
+
+    #[test]
+    fn parse_com_library_with_types()
+    {
+        let lib = ComLibrary::parse(
+            "lib",
+            quote!(class Foo, struct Point, enum Color, struct shapes::Rectangle),
+        )
+        .expect("com_library attribute parsing failed");
+
+        assert_eq!(lib.coclasses.len(), 1);
+        assert_eq!(lib.structs.len(), 2);
+        assert_eq!(lib.structs[0], parse_quote!(Point));
+        assert_eq!(lib.structs[1], parse_quote!(shapes::Rectangle));
+        assert_eq!(lib.enums.len(), 1);
+        assert_eq!(lib.enums[0], parse_quote!(Color));
+    }
+
     #[test]
     fn parse_com_library_with_empty_parameters()
     {
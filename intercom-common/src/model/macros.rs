@@ -3,6 +3,7 @@ use syn::parse::{Parse, ParseStream, Result};
 /// An empty type that cannot be parsed.
 ///
 /// Used especially with attributes that don't take positional arguments.
+#[allow(dead_code)]
 #[derive(Debug)]
 pub enum NoParams {}
 impl Parse for NoParams
@@ -1,3 +1,4 @@
+use crate::idents;
 use crate::methodinfo::ComArg;
 use crate::prelude::*;
 use crate::tyhandlers::{self, Direction, ModelTypeSystem, TypeContext};
@@ -203,6 +204,7 @@ impl ReturnHandler for ErrorResultHandler
         let ok_idents = self
             .com_out_args()
             .iter()
+            .filter(|arg| !arg.is_array_length())
             .enumerate()
             .map(|(idx, _)| Ident::new(&format!("v{}", idx + 1), Span::call_site()))
             .collect::<Vec<_>>();
@@ -264,7 +266,7 @@ fn get_out_args_for_result(
     type_system: ModelTypeSystem,
 ) -> Vec<ComArg>
 {
-    match *retval_ty {
+    let args = match *retval_ty {
         // Tuples map to multiple out args, no [retval].
         Type::Tuple(ref t) => t
             .elems
@@ -287,7 +289,9 @@ fn get_out_args_for_result(
             Direction::Retval,
             type_system,
         )],
-    }
+    };
+
+    args.into_iter().flat_map(ComArg::expand_array).collect()
 }
 
 fn write_out_values(
@@ -302,6 +306,7 @@ fn write_out_values(
     let mut temp_tokens = vec![];
     let mut ok_tokens = vec![];
     let mut err_tokens = vec![];
+    let out_args = out_args.into_iter().filter(|arg| !arg.is_array_length());
     for (ident, out_arg) in idents.iter().zip(out_args) {
         let arg_name = out_arg.name;
         let temp_name = Ident::new(&format!("__{}_guard", arg_name), span);
@@ -311,6 +316,19 @@ fn write_out_values(
             .rust_to_com(ident, span, Direction::Out, infallible);
         let err_value = out_arg.handler.default_value();
 
+        // Conformant arrays write both the data and the length.
+        if out_arg.handler.is_array() {
+            let len_name = idents::array_length(&arg_name);
+            temp_tokens.push(quote!( let #temp_name = #ok_value ));
+            ok_tokens.push(quote!( {
+                let (ptr, len) = #temp_name.consume();
+                *#arg_name = ptr;
+                *#len_name = len;
+            } ));
+            err_tokens.push(quote!( *#arg_name = #err_value; *#len_name = 0 ));
+            continue;
+        }
+
         temp_tokens.push(quote!( let #temp_name = intercom::type_system::OutputGuard::<#ts, #ty>::wrap( #ok_value ) ));
         ok_tokens.push(quote!( *#arg_name = #temp_name.consume() ));
         err_tokens.push(quote!( *#arg_name = #err_value ));
@@ -327,7 +345,7 @@ fn get_rust_ok_values(
 {
     let mut temp_tokens = vec![];
     let mut ok_tokens = vec![];
-    for out_arg in out_args {
+    for out_arg in out_args.into_iter().filter(|arg| !arg.is_array_length()) {
         let value =
             out_arg
                 .handler
@@ -98,12 +98,29 @@ impl TypeContext
     }
 }
 
+/// Kinds of arrays passed as a pointer and a length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArrayKind
+{
+    /// `&[T]` parameter.
+    Slice,
+
+    /// `&mut [T]` parameter modified in place.
+    SliceMut,
+
+    /// `Vec<T>` parameter or return value.
+    Vec,
+}
+
 /// Defines Type-specific logic for handling the various parameter types in the
 /// Rust/COM interface.
 pub struct TypeHandler
 {
     ty: Type,
     context: TypeContext,
+
+    /// Array kind and element type for conformant arrays.
+    array: Option<(ArrayKind, Type)>,
 }
 
 impl TypeHandler
@@ -114,18 +131,56 @@ impl TypeHandler
         self.ty.clone()
     }
 
+    /// The type system of the type.
+    pub fn type_system(&self) -> ModelTypeSystem
+    {
+        self.context.type_system
+    }
+
+    /// True if the type is passed as a pointer and a length.
+    pub fn is_array(&self) -> bool
+    {
+        self.array.is_some()
+    }
+
     /// The COM type.
+    ///
+    /// Conformant arrays are represented by the pointer to the elements.
     pub fn com_ty(&self, span: Span) -> Type
     {
         // Construct bits for the quote.
-        let ty = &self.ty;
         let ts = self.context.type_system.as_typesystem_type(span);
-        syn::parse2(
-            quote_spanned!(span => <#ty as intercom::type_system::ExternType<#ts>>::ForeignType),
-        )
+        let (ptr, ty) = match &self.array {
+            Some((ArrayKind::SliceMut, elem)) => (quote!(*mut), elem),
+            Some((_, elem)) => (quote!(*const), elem),
+            None => (quote!(), &self.ty),
+        };
+        syn::parse2(quote_spanned!(span =>
+                #ptr <#ty as intercom::type_system::ExternType<#ts>>::ForeignType))
         .unwrap()
     }
 
+    /// Describes the COM type of the parameter for the type library.
+    ///
+    /// Conformant arrays refer to the length parameter of the named parameter.
+    pub fn type_desc(&self, name: &Ident, span: Span) -> TokenStream
+    {
+        let ts = self.context.type_system.as_typesystem_type(span);
+        match &self.array {
+            Some((_, elem)) => {
+                let length = crate::idents::array_length(name).to_string();
+                quote_spanned!(span => intercom::typelib::TypeDesc::array(
+                    <#elem as intercom::type_system::ExternType<#ts>>::type_desc(),
+                    #length))
+            }
+            None => {
+                let ty = &self.ty;
+                quote_spanned!(span =>
+                    <#ty as intercom::type_system::ExternType<#ts>>::type_desc())
+            }
+        }
+    }
+
     /// Converts a COM parameter named by the ident into a Rust type.
     pub fn com_to_rust(
         &self,
@@ -135,6 +190,10 @@ impl TypeHandler
         infallible: bool,
     ) -> TokenStream
     {
+        if let Some((kind, elem)) = &self.array {
+            return self.array_com_to_rust(*kind, elem, ident, span, dir);
+        }
+
         // Construct bits for the quote.
         let ty = &self.ty;
         let ts = self.context.type_system.as_typesystem_type(span);
@@ -162,6 +221,16 @@ impl TypeHandler
         infallible: bool,
     ) -> TokenStream
     {
+        if let Some((_, elem)) = &self.array {
+            // Input arrays are prepared by the `array_input_declaration`.
+            let ts = self.context.type_system.as_typesystem_type(span);
+            return match dir {
+                Direction::In => quote_spanned!(span=> #ident),
+                Direction::Out | Direction::Retval => quote_spanned!(span=>
+                        intercom::conformant::OutputArrayGuard::<#ts, #elem>::wrap(#ident)?),
+            };
+        }
+
         // Construct bits for the quote.
         let ty = &self.ty;
         let ts = self.context.type_system.as_typesystem_type(span);
@@ -181,12 +250,104 @@ impl TypeHandler
     {
         quote!(intercom::type_system::ExternDefault::extern_default())
     }
+
+    /// Declares the pointer and the length for a Rust-to-COM call when the
+    /// input parameter is a conformant array.
+    ///
+    /// The declarations shadow the parameter with the pointer and keep the
+    /// converted elements alive until the call returns.
+    pub fn array_input_declaration(&self, ident: &Ident, span: Span) -> Option<TokenStream>
+    {
+        let (kind, elem) = self.array.as_ref()?;
+        let ts = self.context.type_system.as_typesystem_type(span);
+        let len = crate::idents::array_length(ident);
+        let lease = Ident::new(&format!("__{}_lease", ident), span);
+        let values = match kind {
+            ArrayKind::Slice => quote_spanned!(span=> #ident.iter().cloned()),
+            ArrayKind::Vec => quote_spanned!(span=> #ident),
+            ArrayKind::SliceMut => {
+                return Some(quote_spanned!(span=>
+                    let #len = #ident.len() as u32;
+                    let #ident = #ident.as_mut_ptr();
+                ))
+            }
+        };
+
+        Some(quote_spanned!(span=>
+            let #lease = intercom::conformant::ArrayLease::<#ts, #elem>::new(#values)?;
+            let #ident = #lease.as_ptr();
+            let #len = #lease.len();
+        ))
+    }
+
+    fn array_com_to_rust(
+        &self,
+        kind: ArrayKind,
+        elem: &Type,
+        ident: &Ident,
+        span: Span,
+        dir: Direction,
+    ) -> TokenStream
+    {
+        let ts = self.context.type_system.as_typesystem_type(span);
+        let len = crate::idents::array_length(ident);
+        match (dir, kind) {
+            (Direction::In, ArrayKind::Slice) => quote_spanned!(span=>
+                    &intercom::conformant::from_foreign_parameter::<#ts, #elem>(#ident, #len)?),
+            (Direction::In, ArrayKind::Vec) => quote_spanned!(span=>
+                    intercom::conformant::from_foreign_parameter::<#ts, #elem>(#ident, #len)?),
+            (Direction::In, ArrayKind::SliceMut) => quote_spanned!(span=>
+                    intercom::conformant::from_foreign_slice_mut::<#ts, #elem>(#ident, #len)?),
+            (Direction::Out, _) | (Direction::Retval, _) => quote_spanned!(span=>
+                    intercom::conformant::from_foreign_output::<#ts, #elem>(#ident, #len)),
+        }
+    }
+}
+
+/// Resolves the array kind and the element type for the types passed as
+/// conformant arrays.
+///
+/// Only the Raw type system passes slices and vectors as conformant arrays.
+/// The Automation type system passes them as `SAFEARRAY`s through their
+/// `ExternType` implementations.
+fn get_array_type(ty: &Type, type_system: ModelTypeSystem) -> Option<(ArrayKind, Type)>
+{
+    if type_system != ModelTypeSystem::Raw {
+        return None;
+    }
+
+    match ty {
+        syn::Type::Reference(r) => match &*r.elem {
+            syn::Type::Slice(s) if r.mutability.is_some() => {
+                Some((ArrayKind::SliceMut, (*s.elem).clone()))
+            }
+            syn::Type::Slice(s) => Some((ArrayKind::Slice, (*s.elem).clone())),
+            _ => None,
+        },
+        syn::Type::Path(p) => {
+            let last_segment = p.path.segments.last()?;
+            if last_segment.ident != "Vec" {
+                return None;
+            }
+            match &last_segment.arguments {
+                syn::PathArguments::AngleBracketed(generics) => match generics.args.first()? {
+                    syn::GenericArgument::Type(t) => Some((ArrayKind::Vec, t.clone())),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
 }
 
 fn resolve_ref(ty: &Type) -> (TokenStream, TokenStream)
 {
-    if let syn::Type::Reference(..) = ty {
-        return (quote!(&), quote!());
+    if let syn::Type::Reference(r) = ty {
+        return match r.mutability {
+            Some(_) => (quote!(&mut), quote!()),
+            None => (quote!(&), quote!()),
+        };
     }
 
     if has_ref(ty) {
@@ -240,6 +401,7 @@ pub fn get_ty_handler(arg_ty: &Type, context: TypeContext) -> Rc<TypeHandler>
 {
     Rc::new(TypeHandler {
         ty: arg_ty.clone(),
+        array: get_array_type(arg_ty, context.type_system),
         context,
     })
 }
@@ -12,9 +12,12 @@ pub enum InterfaceType
     Struct,
 }
 
+/// Method signature together with the method attributes.
+pub type MethodData<'a> = (&'a Signature, &'a [Attribute]);
+
 pub type InterfaceData<'a> = (
     Path,
-    Vec<&'a Signature>,
+    Vec<MethodData<'a>>,
     InterfaceType,
     Option<Token!(unsafe)>,
 );
@@ -38,7 +41,7 @@ pub fn get_ident_and_fns(item: &Item) -> Option<InterfaceData>
             ref items,
             ..
         }) => {
-            let methods: Option<Vec<&Signature>> = items.iter().map(get_trait_method).collect();
+            let methods: Option<Vec<MethodData>> = items.iter().map(get_trait_method).collect();
             let path = syn::Path::from(ident.clone());
 
             methods.map(|m| (path, m, InterfaceType::Trait, unsafety))
@@ -47,7 +50,7 @@ pub fn get_ident_and_fns(item: &Item) -> Option<InterfaceData>
     }
 }
 
-pub type ImplData<'a> = (Option<Path>, Path, Vec<&'a Signature>);
+pub type ImplData<'a> = (Option<Path>, Path, Vec<MethodData<'a>>);
 
 fn get_impl_data_raw<'a>(
     trait_ref: &'a Option<(Option<Token!(!)>, Path, Token!(for))>,
@@ -67,24 +70,24 @@ fn get_impl_data_raw<'a>(
 
     let trait_path = trait_ref.as_ref().map(|(_, path, _)| path.clone());
 
-    let methods_opt: Option<Vec<&Signature>> = items.iter().map(get_impl_method).collect();
+    let methods_opt: Option<Vec<MethodData>> = items.iter().map(get_impl_method).collect();
     let methods = methods_opt.unwrap_or_default();
 
     (trait_path, struct_path, methods)
 }
 
-pub fn get_impl_method(i: &ImplItem) -> Option<&Signature>
+pub fn get_impl_method(i: &ImplItem) -> Option<MethodData<'_>>
 {
     match *i {
-        ImplItem::Method(ref itm) => Some(&itm.sig),
+        ImplItem::Method(ref itm) => Some((&itm.sig, &itm.attrs)),
         _ => None,
     }
 }
 
-pub fn get_trait_method(i: &TraitItem) -> Option<&Signature>
+pub fn get_trait_method(i: &TraitItem) -> Option<MethodData<'_>>
 {
     match *i {
-        TraitItem::Method(ref tim) => Some(&tim.sig),
+        TraitItem::Method(ref tim) => Some((&tim.sig, &tim.attrs)),
         _ => None,
     }
 }
@@ -178,6 +181,23 @@ pub fn is_unit(tk: &Type) -> bool
     }
 }
 
+/// Checks whether the type refers to `HRESULT`.
+///
+/// The check is purely syntactic. Both `HRESULT` and paths ending in it, such
+/// as `intercom::raw::HRESULT`, are accepted.
+pub fn is_hresult(tk: &Type) -> bool
+{
+    match *tk {
+        Type::Path(ref p) => p
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "HRESULT")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
 pub fn unit_ty(span: Span) -> Type
 {
     syn::parse2(quote_spanned!(span => ())).unwrap()
@@ -204,6 +224,96 @@ pub fn get_guid_tokens(g: &guid::GUID, span: Span) -> TokenStream
     )
 }
 
+/// Gets the rustdoc documentation from the `///` comments.
+///
+/// The leading space of each comment line is removed. `None` if the item has
+/// no documentation.
+pub fn get_doc(attrs: &[Attribute]) -> Option<String>
+{
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(s), ..
+            })) => Some(s.value()),
+            _ => None,
+        })
+        .map(|line| match line.strip_prefix(' ') {
+            Some(line) => line.to_string(),
+            None => line,
+        })
+        .collect::<Vec<_>>();
+
+    let doc = lines.join("\n");
+    match doc.trim() {
+        "" => None,
+        doc => Some(doc.to_string()),
+    }
+}
+
+/// Splits the `# Arguments` section off the method documentation.
+///
+/// The section lists the arguments as ``- `name` - description`` items.
+/// Returns the rest of the documentation and the argument descriptions.
+pub fn split_argument_docs(doc: &str) -> (Option<String>, Vec<(String, String)>)
+{
+    let mut rest = vec![];
+    let mut args: Vec<(String, String)> = vec![];
+    let mut in_arguments = false;
+    for line in doc.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            in_arguments = trimmed.trim_start_matches('#').trim() == "Arguments";
+            if in_arguments {
+                continue;
+            }
+        }
+
+        if !in_arguments {
+            rest.push(line);
+            continue;
+        }
+
+        // The item continues on the following lines until the next item.
+        let item = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "));
+        match item {
+            Some(item) => {
+                let parts = item.splitn(3, '`').collect::<Vec<_>>();
+                if let ["", name, desc] = parts.as_slice() {
+                    let desc = desc.trim_start_matches([' ', '-', ':']);
+                    args.push((name.to_string(), desc.to_string()));
+                }
+            }
+            None if !trimmed.is_empty() => {
+                if let Some((_, desc)) = args.last_mut() {
+                    desc.push(' ');
+                    desc.push_str(trimmed);
+                }
+            }
+            None => {}
+        }
+    }
+
+    let rest = rest.join("\n");
+    let rest = match rest.trim() {
+        "" => None,
+        rest => Some(rest.to_string()),
+    };
+    (rest, args)
+}
+
+/// Creates the `Option<Cow<str>>` tokens for the documentation.
+pub fn get_doc_tokens(doc: &Option<String>, span: Span) -> TokenStream
+{
+    match doc {
+        Some(doc) => quote_spanned!(span => Some(#doc.into())),
+        None => quote_spanned!(span => None),
+    }
+}
+
 /// Convert the Rust identifier from `snake_case` to `PascalCase`
 pub fn pascal_case<T: AsRef<str>>(input: T) -> String
 {
@@ -266,4 +376,57 @@ mod test
     {
         test_ty("()")
     }
+
+    #[test]
+    fn doc_comments_are_joined()
+    {
+        let item: ItemFn = parse_quote!(
+            /// Adds the values.
+            ///
+            ///   Indented line.
+            #[inline]
+            fn add() {}
+        );
+        assert_eq!(
+            get_doc(&item.attrs).unwrap(),
+            "Adds the values.\n\n  Indented line."
+        );
+
+        let item: ItemFn = parse_quote!(
+            fn undocumented() {}
+        );
+        assert_eq!(get_doc(&item.attrs), None);
+    }
+
+    #[test]
+    fn argument_docs_are_split_off()
+    {
+        let (doc, args) = split_argument_docs(
+            "Allocates memory.\n\
+             \n\
+             # Arguments\n\
+             \n\
+             - `len` - Size of the memory block\n\
+             \x20 in bytes.\n\
+             - `align`: Alignment.\n\
+             \n\
+             # Safety\n\
+             \n\
+             Free the memory.",
+        );
+        assert_eq!(
+            doc.unwrap(),
+            "Allocates memory.\n\n# Safety\n\nFree the memory."
+        );
+        assert_eq!(
+            args,
+            vec![
+                (
+                    "len".to_string(),
+                    "Size of the memory block in bytes.".to_string()
+                ),
+                ("align".to_string(), "Alignment.".to_string()),
+            ]
+        );
+    }
 }
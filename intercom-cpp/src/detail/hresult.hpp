@@ -31,6 +31,8 @@ namespace hresult
 
     // Source: https://msdn.microsoft.com/en-us/library/windows/desktop/ms690088(v=vs.85).aspx
     static const uint8_t ERROR_FACILITY_NULL = 0;
+    static const uint8_t ERROR_FACILITY_RPC = 1;
+    static const uint8_t ERROR_FACILITY_DISPATCH = 2;
     static const uint8_t ERROR_FACILITY_ITF = 4;
     static const uint8_t ERROR_FACILITY_WIN32 = 7;
 
@@ -64,6 +66,24 @@ namespace hresult
         using Error::Error;
     };
 
+    /**
+     * @brief Error type for FACILITY_RPC errors.
+     *
+     */
+    class RpcError : public Error
+    {
+        using Error::Error;
+    };
+
+    /**
+     * @brief Error type for FACILITY_DISPATCH errors.
+     *
+     */
+    class DispatchError : public Error
+    {
+        using Error::Error;
+    };
+
     /**
      * @brief Error type for FACILITY_ITF errors.
      *
@@ -114,6 +134,32 @@ namespace hresult
          return error( intercom::detail::hresult::ERROR_FACILITY_NULL, NullError( error_code ).error_code() );
      }
 
+     /**
+     * @brief Creates HRESULT error code from the specified values.
+     *
+     * @param rpc_error The actual error code.
+     * @return constexpr HRESULT
+     */
+     constexpr ::intercom::HRESULT rpc_error(
+         uint16_t error_code
+     ) noexcept
+     {
+         return error( intercom::detail::hresult::ERROR_FACILITY_RPC, RpcError( error_code ).error_code() );
+     }
+
+     /**
+     * @brief Creates HRESULT error code from the specified values.
+     *
+     * @param dispatch_error The actual error code.
+     * @return constexpr HRESULT
+     */
+     constexpr ::intercom::HRESULT dispatch_error(
+         uint16_t error_code
+     ) noexcept
+     {
+         return error( intercom::detail::hresult::ERROR_FACILITY_DISPATCH, DispatchError( error_code ).error_code() );
+     }
+
      /**
      * @brief Creates HRESULT error code from the specified values.
      *
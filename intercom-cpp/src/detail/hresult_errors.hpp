@@ -21,7 +21,18 @@ namespace hresult
     static const HRESULT EC_POINTER = null_error( 0x4003 );
     static const HRESULT EC_ABORT = null_error( 0x4003 );
     static const HRESULT EC_FAIL = null_error( 0x4005 );
+    static const HRESULT EC_UNEXPECTED = null_error( 0xFFFF );
+    static const HRESULT EC_ILLEGAL_METHOD_CALL = null_error( 0x000E );
+    static const HRESULT EC_WRONG_THREAD = rpc_error( 0x010E );
+    static const HRESULT EC_MEMBERNOTFOUND = dispatch_error( 0x0003 );
+    static const HRESULT EC_TYPEMISMATCH = dispatch_error( 0x0005 );
+    static const HRESULT EC_UNKNOWNNAME = dispatch_error( 0x0006 );
+    static const HRESULT EC_NONAMEDARGS = dispatch_error( 0x0007 );
+    static const HRESULT EC_EXCEPTION = dispatch_error( 0x0009 );
+    static const HRESULT EC_ARRAYISLOCKED = dispatch_error( 0x000D );
+    static const HRESULT EC_BADPARAMCOUNT = dispatch_error( 0x000E );
     static const HRESULT EC_CLASSNOTREG = itf_error( 0x0154 );
+    static const HRESULT EC_NOAGGREGATION = itf_error( 0x0110 );
     static_assert( NullError( 0x4005 ).error_code() == 0x4005, "Internal check failed: Invalid error code storage." );
 
     static const HRESULT EC_OUTOFMEMORY = win32_error( 0x000E );
@@ -20,6 +20,17 @@ namespace intercom
     static const intercom::HRESULT EC_INVALIDARG = intercom::detail::hresult::EC_INVALIDARG;
     static const intercom::HRESULT EC_POINTER = intercom::detail::hresult::EC_POINTER;
     static const intercom::HRESULT EC_CLASSNOTREG = intercom::detail::hresult::EC_CLASSNOTREG;
+    static const intercom::HRESULT EC_NOAGGREGATION = intercom::detail::hresult::EC_NOAGGREGATION;
+    static const intercom::HRESULT EC_UNEXPECTED = intercom::detail::hresult::EC_UNEXPECTED;
+    static const intercom::HRESULT EC_ILLEGAL_METHOD_CALL = intercom::detail::hresult::EC_ILLEGAL_METHOD_CALL;
+    static const intercom::HRESULT EC_WRONG_THREAD = intercom::detail::hresult::EC_WRONG_THREAD;
+    static const intercom::HRESULT EC_MEMBERNOTFOUND = intercom::detail::hresult::EC_MEMBERNOTFOUND;
+    static const intercom::HRESULT EC_TYPEMISMATCH = intercom::detail::hresult::EC_TYPEMISMATCH;
+    static const intercom::HRESULT EC_UNKNOWNNAME = intercom::detail::hresult::EC_UNKNOWNNAME;
+    static const intercom::HRESULT EC_NONAMEDARGS = intercom::detail::hresult::EC_NONAMEDARGS;
+    static const intercom::HRESULT EC_EXCEPTION = intercom::detail::hresult::EC_EXCEPTION;
+    static const intercom::HRESULT EC_ARRAYISLOCKED = intercom::detail::hresult::EC_ARRAYISLOCKED;
+    static const intercom::HRESULT EC_BADPARAMCOUNT = intercom::detail::hresult::EC_BADPARAMCOUNT;
     static_assert( EC_FAIL == 0x80004005, "Internal check failed: Invalid error code structure." );
 
     /**
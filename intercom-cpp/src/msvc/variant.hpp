@@ -78,6 +78,20 @@ namespace intercom
     using ::VT_ILLEGAL;
     using ::VT_ILLEGALMASKED;
     using ::VT_TYPEMASK;
+
+    using ::VariantInit;
+    using ::VariantClear;
+
+    using ::SafeArrayCreate;
+    using ::SafeArrayCreateVector;
+    using ::SafeArrayDestroy;
+    using ::SafeArrayGetDim;
+    using ::SafeArrayGetElemsize;
+    using ::SafeArrayGetVartype;
+    using ::SafeArrayGetLBound;
+    using ::SafeArrayGetUBound;
+    using ::SafeArrayAccessData;
+    using ::SafeArrayUnaccessData;
 }
 
 #endif
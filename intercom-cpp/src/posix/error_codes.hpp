@@ -10,6 +10,17 @@ static const intercom::HRESULT E_NOTIMPL = intercom::EC_NOTIMPL;
 static const intercom::HRESULT E_NOINTERFACE = intercom::EC_NOINTERFACE;
 static const intercom::HRESULT E_OUTOFMEMORY = intercom::EC_OUTOFMEMORY;
 static const intercom::HRESULT E_INVALIDARG = intercom::EC_INVALIDARG;
+static const intercom::HRESULT E_UNEXPECTED = intercom::EC_UNEXPECTED;
+static const intercom::HRESULT CLASS_E_NOAGGREGATION = intercom::EC_NOAGGREGATION;
+static const intercom::HRESULT E_ILLEGAL_METHOD_CALL = intercom::EC_ILLEGAL_METHOD_CALL;
+static const intercom::HRESULT RPC_E_WRONG_THREAD = intercom::EC_WRONG_THREAD;
+static const intercom::HRESULT DISP_E_MEMBERNOTFOUND = intercom::EC_MEMBERNOTFOUND;
+static const intercom::HRESULT DISP_E_TYPEMISMATCH = intercom::EC_TYPEMISMATCH;
+static const intercom::HRESULT DISP_E_UNKNOWNNAME = intercom::EC_UNKNOWNNAME;
+static const intercom::HRESULT DISP_E_NONAMEDARGS = intercom::EC_NONAMEDARGS;
+static const intercom::HRESULT DISP_E_EXCEPTION = intercom::EC_EXCEPTION;
+static const intercom::HRESULT DISP_E_ARRAYISLOCKED = intercom::EC_ARRAYISLOCKED;
+static const intercom::HRESULT DISP_E_BADPARAMCOUNT = intercom::EC_BADPARAMCOUNT;
 
 #endif
 
@@ -7,6 +7,81 @@
 #include "../error_codes.hpp"
 #include "../guiddef.hpp"
 
-class IDispatch;
+#include "iunknown.hpp"
+
+namespace intercom
+{
+    struct VARIANT;
+}
+
+typedef intercom::INT32 DISPID;
+typedef intercom::DWORD LCID;
+
+static const DISPID DISPID_UNKNOWN = -1;
+static const DISPID DISPID_VALUE = 0;
+static const DISPID DISPID_PROPERTYPUT = -3;
+
+static const intercom::WORD DISPATCH_METHOD = 0x1;
+static const intercom::WORD DISPATCH_PROPERTYGET = 0x2;
+static const intercom::WORD DISPATCH_PROPERTYPUT = 0x4;
+static const intercom::WORD DISPATCH_PROPERTYPUTREF = 0x8;
+
+struct DISPPARAMS
+{
+    intercom::VARIANT* rgvarg;
+    DISPID* rgdispidNamedArgs;
+    intercom::UINT cArgs;
+    intercom::UINT cNamedArgs;
+};
+
+struct EXCEPINFO
+{
+    intercom::WORD wCode;
+    intercom::WORD wReserved;
+    intercom::BSTR bstrSource;
+    intercom::BSTR bstrDescription;
+    intercom::BSTR bstrHelpFile;
+    intercom::DWORD dwHelpContext;
+    intercom::PVOID pvReserved;
+    intercom::HRESULT ( INTERCOM_CC *pfnDeferredFillIn )( EXCEPINFO* );
+    intercom::SCODE scode;
+};
+
+// MIDL_INTERFACE("00020400-0000-0000-C000-000000000046")
+struct IDispatch : public IUnknown
+{
+public:
+
+    virtual intercom::HRESULT INTERCOM_CC GetTypeInfoCount(
+        intercom::UINT *pctinfo
+    ) = 0;
+
+    virtual intercom::HRESULT INTERCOM_CC GetTypeInfo(
+        intercom::UINT iTInfo,
+        LCID lcid,
+        void **ppTInfo
+    ) = 0;
+
+    virtual intercom::HRESULT INTERCOM_CC GetIDsOfNames(
+        intercom::REFIID riid,
+        intercom::OLECHAR **rgszNames,
+        intercom::UINT cNames,
+        LCID lcid,
+        DISPID *rgDispId
+    ) = 0;
+
+    virtual intercom::HRESULT INTERCOM_CC Invoke(
+        DISPID dispIdMember,
+        intercom::REFIID riid,
+        LCID lcid,
+        intercom::WORD wFlags,
+        DISPPARAMS *pDispParams,
+        intercom::VARIANT *pVarResult,
+        EXCEPINFO *pExcepInfo,
+        intercom::UINT *puArgErr
+    ) = 0;
+};
+
+static const intercom::IID IID_IDispatch = { 0x00020400, 0x0000, 0x0000, { 0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,  0x46 } };
 
 #endif
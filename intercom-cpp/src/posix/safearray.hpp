@@ -0,0 +1,318 @@
+#ifndef INTERCOM_CPP_POSIX_SAFEARRAY_H
+#define INTERCOM_CPP_POSIX_SAFEARRAY_H
+
+#include <cstdlib>
+#include <cstring>
+
+#include "../error_codes.hpp"
+#include "datatypes.hpp"
+#include "memory.hpp"
+#include "variant.hpp"
+
+// Implements the SAFEARRAY API of oleaut32 on platforms other than Windows.
+//
+// The memory layout matches the implementation in the intercom crate so the
+// arrays can be passed between C++ and Rust in both directions.
+namespace intercom
+{
+    static const USHORT FADF_HAVEVARTYPE = 0x80;
+    static const USHORT FADF_BSTR = 0x100;
+    static const USHORT FADF_UNKNOWN = 0x200;
+    static const USHORT FADF_DISPATCH = 0x400;
+    static const USHORT FADF_VARIANT = 0x800;
+
+namespace posix
+{
+namespace detail
+{
+    // The descriptor is preceded by a header that holds the VARTYPE of the
+    // elements in its last four bytes.
+    static const size_t SAFEARRAY_HEADER_SIZE = 16;
+
+    inline ULONG safearray_element_size( VARTYPE vt )
+    {
+        switch( vt )
+        {
+        case VT_I1: case VT_UI1:
+            return 1;
+        case VT_I2: case VT_UI2: case VT_BOOL:
+            return 2;
+        case VT_I4: case VT_UI4: case VT_R4: case VT_ERROR: case VT_INT: case VT_UINT:
+            return 4;
+        case VT_I8: case VT_UI8: case VT_R8: case VT_CY: case VT_DATE:
+            return 8;
+        case VT_BSTR: case VT_UNKNOWN: case VT_DISPATCH:
+            return sizeof( void* );
+        case VT_VARIANT:
+            return sizeof( VARIANT );
+        default:
+            return 0;
+        }
+    }
+
+    inline USHORT safearray_features( VARTYPE vt )
+    {
+        switch( vt )
+        {
+        case VT_BSTR: return FADF_HAVEVARTYPE | FADF_BSTR;
+        case VT_UNKNOWN: return FADF_HAVEVARTYPE | FADF_UNKNOWN;
+        case VT_DISPATCH: return FADF_HAVEVARTYPE | FADF_DISPATCH;
+        case VT_VARIANT: return FADF_HAVEVARTYPE | FADF_VARIANT;
+        default: return FADF_HAVEVARTYPE;
+        }
+    }
+
+    inline size_t safearray_element_count( const SAFEARRAY* psa )
+    {
+        size_t count = 1;
+        for( USHORT dim = 0; dim < psa->cDims; ++dim )
+            count *= psa->rgsabound[ dim ].cElements;
+        return count;
+    }
+}
+}
+
+    inline HRESULT SafeArrayDestroy( SAFEARRAY* psa );
+
+    /**
+     * @brief Initializes a VARIANT as VT_EMPTY.
+     */
+    inline void VariantInit(
+        VARIANT* pvarg
+    ) noexcept
+    {
+        std::memset( pvarg, 0, sizeof( VARIANT ) );
+        pvarg->vt = VT_EMPTY;
+    }
+
+    /**
+     * @brief Releases the value held by the VARIANT and sets it to VT_EMPTY.
+     */
+    inline HRESULT VariantClear(
+        VARIANT* pvarg
+    ) noexcept
+    {
+        if( pvarg == nullptr )
+            return EC_INVALIDARG;
+
+        if( ( pvarg->vt & VT_BYREF ) == 0 )
+        {
+            if( pvarg->vt & VT_ARRAY )
+            {
+                HRESULT hr = SafeArrayDestroy( pvarg->parray );
+                if( hr != SC_OK )
+                    return hr;
+            }
+            else if( pvarg->vt == VT_BSTR )
+            {
+                free_bstr( pvarg->bstrVal );
+            }
+            else if( pvarg->vt == VT_UNKNOWN && pvarg->punkVal != nullptr )
+            {
+                pvarg->punkVal->Release();
+            }
+            else if( pvarg->vt == VT_DISPATCH && pvarg->pdispVal != nullptr )
+            {
+                pvarg->pdispVal->Release();
+            }
+        }
+
+        VariantInit( pvarg );
+        return SC_OK;
+    }
+
+    /**
+     * @brief Creates an array with the given element type and bounds.
+     *
+     * @return The array or nullptr if the element type is not supported.
+     */
+    inline SAFEARRAY* SafeArrayCreate(
+        VARTYPE vt,
+        UINT cDims,
+        SAFEARRAYBOUND* rgsabound
+    ) noexcept
+    {
+        ULONG size = posix::detail::safearray_element_size( vt );
+        if( size == 0 || cDims == 0 || rgsabound == nullptr )
+            return nullptr;
+
+        size_t descriptor_size = sizeof( SAFEARRAY ) + ( cDims - 1 ) * sizeof( SAFEARRAYBOUND );
+        char* buffer = static_cast< char* >(
+                std::calloc( 1, posix::detail::SAFEARRAY_HEADER_SIZE + descriptor_size ) );
+        if( buffer == nullptr )
+            return nullptr;
+        uint32_t vartype = vt;
+        std::memcpy( buffer + posix::detail::SAFEARRAY_HEADER_SIZE - 4, &vartype, 4 );
+
+        SAFEARRAY* psa = reinterpret_cast< SAFEARRAY* >(
+                buffer + posix::detail::SAFEARRAY_HEADER_SIZE );
+        psa->cDims = static_cast< USHORT >( cDims );
+        psa->fFeatures = posix::detail::safearray_features( vt );
+        psa->cbElements = size;
+        std::memcpy( psa->rgsabound, rgsabound, cDims * sizeof( SAFEARRAYBOUND ) );
+
+        // The elements start zeroed, which is the empty value of every
+        // supported element type.
+        size_t count = posix::detail::safearray_element_count( psa );
+        psa->pvData = std::calloc( count > 0 ? count : 1, size );
+        if( psa->pvData == nullptr )
+        {
+            std::free( buffer );
+            return nullptr;
+        }
+        return psa;
+    }
+
+    /**
+     * @brief Creates a one dimensional array.
+     */
+    inline SAFEARRAY* SafeArrayCreateVector(
+        VARTYPE vt,
+        LONG lLbound,
+        ULONG cElements
+    ) noexcept
+    {
+        SAFEARRAYBOUND bound = { cElements, lLbound };
+        return SafeArrayCreate( vt, 1, &bound );
+    }
+
+    /**
+     * @brief Destroys the array and releases its elements.
+     */
+    inline HRESULT SafeArrayDestroy(
+        SAFEARRAY* psa
+    )
+    {
+        if( psa == nullptr )
+            return SC_OK;
+        if( psa->cLocks > 0 )
+            return EC_ARRAYISLOCKED;
+
+        char* data = static_cast< char* >( psa->pvData );
+        size_t count = posix::detail::safearray_element_count( psa );
+        for( size_t idx = 0; idx < count; ++idx )
+        {
+            void* element = data + idx * psa->cbElements;
+            if( psa->fFeatures & FADF_BSTR )
+            {
+                free_bstr( *static_cast< BSTR* >( element ) );
+            }
+            else if( psa->fFeatures & ( FADF_UNKNOWN | FADF_DISPATCH ) )
+            {
+                IUnknown* punk = *static_cast< IUnknown** >( element );
+                if( punk != nullptr )
+                    punk->Release();
+            }
+            else if( psa->fFeatures & FADF_VARIANT )
+            {
+                VariantClear( static_cast< VARIANT* >( element ) );
+            }
+        }
+
+        std::free( psa->pvData );
+        std::free( reinterpret_cast< char* >( psa ) - posix::detail::SAFEARRAY_HEADER_SIZE );
+        return SC_OK;
+    }
+
+    /**
+     * @brief Gets the number of dimensions in the array.
+     */
+    inline UINT SafeArrayGetDim(
+        SAFEARRAY* psa
+    ) noexcept
+    {
+        return psa == nullptr ? 0 : psa->cDims;
+    }
+
+    /**
+     * @brief Gets the size of an array element in bytes.
+     */
+    inline UINT SafeArrayGetElemsize(
+        SAFEARRAY* psa
+    ) noexcept
+    {
+        return psa == nullptr ? 0 : psa->cbElements;
+    }
+
+    /**
+     * @brief Gets the VARTYPE of the array elements.
+     */
+    inline HRESULT SafeArrayGetVartype(
+        SAFEARRAY* psa,
+        VARTYPE* pvt
+    ) noexcept
+    {
+        if( psa == nullptr || pvt == nullptr || ( psa->fFeatures & FADF_HAVEVARTYPE ) == 0 )
+            return EC_INVALIDARG;
+
+        uint32_t vartype;
+        std::memcpy( &vartype, reinterpret_cast< char* >( psa ) - 4, 4 );
+        *pvt = static_cast< VARTYPE >( vartype );
+        return SC_OK;
+    }
+
+    /**
+     * @brief Gets the lower bound of a dimension. The dimensions start from 1.
+     */
+    inline HRESULT SafeArrayGetLBound(
+        SAFEARRAY* psa,
+        UINT nDim,
+        LONG* plLbound
+    ) noexcept
+    {
+        if( psa == nullptr || plLbound == nullptr || nDim == 0 || nDim > psa->cDims )
+            return EC_INVALIDARG;
+
+        *plLbound = psa->rgsabound[ nDim - 1 ].lLbound;
+        return SC_OK;
+    }
+
+    /**
+     * @brief Gets the upper bound of a dimension. The dimensions start from 1.
+     */
+    inline HRESULT SafeArrayGetUBound(
+        SAFEARRAY* psa,
+        UINT nDim,
+        LONG* plUbound
+    ) noexcept
+    {
+        if( psa == nullptr || plUbound == nullptr || nDim == 0 || nDim > psa->cDims )
+            return EC_INVALIDARG;
+
+        const SAFEARRAYBOUND& bound = psa->rgsabound[ nDim - 1 ];
+        *plUbound = bound.lLbound + static_cast< LONG >( bound.cElements ) - 1;
+        return SC_OK;
+    }
+
+    /**
+     * @brief Locks the array and gets a pointer to its data.
+     */
+    inline HRESULT SafeArrayAccessData(
+        SAFEARRAY* psa,
+        void** ppvData
+    ) noexcept
+    {
+        if( psa == nullptr || ppvData == nullptr )
+            return EC_INVALIDARG;
+
+        ++psa->cLocks;
+        *ppvData = psa->pvData;
+        return SC_OK;
+    }
+
+    /**
+     * @brief Unlocks the array locked with SafeArrayAccessData.
+     */
+    inline HRESULT SafeArrayUnaccessData(
+        SAFEARRAY* psa
+    ) noexcept
+    {
+        if( psa == nullptr || psa->cLocks == 0 )
+            return EC_UNEXPECTED;
+
+        --psa->cLocks;
+        return SC_OK;
+    }
+}
+
+#endif
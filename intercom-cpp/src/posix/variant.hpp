@@ -14,7 +14,7 @@ namespace intercom
 {
 
     class IRecordInfo;
-    class SAFEARRAY;
+    struct SAFEARRAY;
 
     typedef unsigned short VARTYPE;
 
@@ -160,13 +160,14 @@ namespace intercom
         LONG lLbound;
     };
 
+    // The bounds of the dimensions beyond the first one follow the descriptor.
     struct SAFEARRAY {
         USHORT cDims;
         USHORT fFeatures;
         ULONG cbElements;
         ULONG cLocks;
-        SAFEARRAYUNION uArrayStructs;
-        SAFEARRAYBOUND rgsabound[];
+        PVOID pvData;
+        SAFEARRAYBOUND rgsabound[ 1 ];
     };
 }
 
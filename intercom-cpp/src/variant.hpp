@@ -6,6 +6,7 @@
 #include "msvc/variant.hpp"
 #else
 #include "posix/variant.hpp"
+#include "posix/safearray.hpp"
 #endif
 
 #endif
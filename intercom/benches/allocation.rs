@@ -0,0 +1,88 @@
+//! Compares the heap and pool allocators for `ComBoxData`.
+//!
+//! The benchmarks require a nightly toolchain:
+//!
+//! ```text
+//! cargo +nightly bench -p intercom --bench allocation
+//! ```
+
+#![feature(test)]
+extern crate test;
+
+extern crate intercom;
+use intercom::allocator::PoolAllocator;
+
+// Number of objects alive at the same time during a single iteration.
+const OBJECT_COUNT: usize = 1000;
+
+static POOL: PoolAllocator = PoolAllocator::new(OBJECT_COUNT);
+
+#[intercom::com_class(IValue)]
+struct HeapValue(u32);
+
+#[intercom::com_class(allocator = POOL, IValue)]
+struct PoolValue(u32);
+
+#[intercom::com_interface]
+trait IValue
+{
+    fn get(&self) -> u32;
+}
+
+impl IValue for HeapValue
+{
+    fn get(&self) -> u32
+    {
+        self.0
+    }
+}
+
+impl IValue for PoolValue
+{
+    fn get(&self) -> u32
+    {
+        self.0
+    }
+}
+
+#[bench]
+fn create_single_heap_object(bencher: &mut test::Bencher)
+{
+    bencher.iter(|| {
+        let rc: intercom::ComRc<dyn IValue> =
+            intercom::ComRc::from(intercom::ComBox::new(HeapValue(1)));
+        test::black_box(&rc).get()
+    });
+}
+
+#[bench]
+fn create_single_pool_object(bencher: &mut test::Bencher)
+{
+    bencher.iter(|| {
+        let rc: intercom::ComRc<dyn IValue> =
+            intercom::ComRc::from(intercom::ComBox::new(PoolValue(1)));
+        test::black_box(&rc).get()
+    });
+}
+
+#[bench]
+fn create_many_heap_objects(bencher: &mut test::Bencher)
+{
+    bencher.iter(|| {
+        let objects: Vec<intercom::ComRc<dyn IValue>> = (0..OBJECT_COUNT as u32)
+            .map(|i| intercom::ComRc::from(intercom::ComBox::new(HeapValue(i))))
+            .collect();
+        test::black_box(objects)
+    });
+}
+
+#[bench]
+fn create_many_pool_objects(bencher: &mut test::Bencher)
+{
+    bencher.iter(|| {
+        let objects: Vec<intercom::ComRc<dyn IValue>> = (0..OBJECT_COUNT as u32)
+            .map(|i| intercom::ComRc::from(intercom::ComBox::new(PoolValue(i))))
+            .collect();
+        test::black_box(objects)
+    });
+}
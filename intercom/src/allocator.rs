@@ -0,0 +1,261 @@
+//! Memory allocation strategies for the COM objects.
+//!
+//! Each `ComBoxData` is allocated through the allocator of its class. The
+//! classes use the `HeapAllocator` by default. A different allocator can be
+//! specified with `#[com_class(allocator = STATIC_ALLOCATOR)]`.
+//!
+//! The `PoolAllocator` reuses the memory of the released objects, which helps
+//! with classes that are created and released in large numbers.
+//!
+//! Not to be confused with the [`alloc`](../alloc/index.html) module, which
+//! provides the allocator for the memory shared with the COM clients.
+
+use std::alloc::Layout;
+use std::cell::RefCell;
+
+/// Allocator for the `ComBoxData` memory.
+///
+/// # Safety
+///
+/// The `allocate` implementation must return either null or a pointer to
+/// memory that is valid for the given layout until it is passed to
+/// `deallocate`.
+pub unsafe trait ComAllocator: Sync
+{
+    /// Allocates memory for the layout.
+    ///
+    /// Returns null if the allocation failed.
+    ///
+    /// # Safety
+    ///
+    /// The layout must have a non-zero size.
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8;
+
+    /// Deallocates memory allocated with `allocate`.
+    ///
+    /// # Safety
+    ///
+    /// The `ptr` must have been allocated by this allocator with the same
+    /// `layout`.
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout);
+}
+
+/// Allocates the objects from the global Rust heap.
+///
+/// The default allocator for the COM classes.
+pub struct HeapAllocator;
+
+unsafe impl ComAllocator for HeapAllocator
+{
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8
+    {
+        std::alloc::alloc(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout)
+    {
+        std::alloc::dealloc(ptr, layout)
+    }
+}
+
+/// Allocator that keeps the released memory blocks for reuse.
+///
+/// The released blocks are kept in per-thread free lists, so allocating and
+/// releasing the objects does not require synchronization between threads.
+/// Blocks released on a different thread than they were allocated on are
+/// reused by the releasing thread.
+///
+/// ```rust
+/// use intercom::{com_class, com_interface, allocator::PoolAllocator};
+///
+/// static VALUE_POOL: PoolAllocator = PoolAllocator::new(1024);
+///
+/// #[com_class(allocator = VALUE_POOL, Value)]
+/// pub struct Value(u32);
+///
+/// #[com_interface]
+/// impl Value
+/// {
+///     fn get(&self) -> u32 { self.0 }
+/// }
+/// # fn main() {}
+/// ```
+pub struct PoolAllocator
+{
+    max_free: usize,
+}
+
+/// Released blocks of a single pool and layout on the current thread.
+struct FreeList
+{
+    pool: usize,
+    layout: Layout,
+
+    // The addresses are stored as usize to avoid dealing with raw pointers.
+    blocks: Vec<usize>,
+}
+
+impl Drop for FreeList
+{
+    fn drop(&mut self)
+    {
+        for ptr in self.blocks.drain(..) {
+            unsafe { std::alloc::dealloc(ptr as *mut u8, self.layout) };
+        }
+    }
+}
+
+thread_local! {
+    static FREE_LISTS: RefCell<Vec<FreeList>> = const { RefCell::new(Vec::new()) };
+}
+
+impl PoolAllocator
+{
+    /// Creates a new pool.
+    ///
+    /// Each thread keeps at most `max_free` released blocks of each layout
+    /// for reuse. Further blocks are returned to the heap.
+    pub const fn new(max_free: usize) -> PoolAllocator
+    {
+        PoolAllocator { max_free }
+    }
+
+    /// Gets the number of the released blocks available for reuse on the
+    /// current thread.
+    pub fn free_count(&self) -> usize
+    {
+        FREE_LISTS
+            .try_with(|lists| {
+                lists
+                    .borrow()
+                    .iter()
+                    .filter(|list| list.pool == self.id())
+                    .map(|list| list.blocks.len())
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+
+    /// Returns the released blocks of the current thread to the heap.
+    pub fn shrink(&self)
+    {
+        let _ = FREE_LISTS.try_with(|lists| {
+            lists.borrow_mut().retain(|list| list.pool != self.id());
+        });
+    }
+
+    fn id(&self) -> usize
+    {
+        self as *const PoolAllocator as usize
+    }
+
+    /// Invokes the callback with the free list of the layout.
+    ///
+    /// Returns `None` if the thread local free lists are not available.
+    fn with_free_list<R>(&self, layout: Layout, f: impl FnOnce(&mut Vec<usize>) -> R) -> Option<R>
+    {
+        let pool = self.id();
+        FREE_LISTS
+            .try_with(|lists| {
+                let mut lists = lists.try_borrow_mut().ok()?;
+                let idx = match lists
+                    .iter()
+                    .position(|list| list.pool == pool && list.layout == layout)
+                {
+                    Some(idx) => idx,
+                    None => {
+                        lists.push(FreeList {
+                            pool,
+                            layout,
+                            blocks: Vec::new(),
+                        });
+                        lists.len() - 1
+                    }
+                };
+                Some(f(&mut lists[idx].blocks))
+            })
+            .ok()
+            .flatten()
+    }
+}
+
+unsafe impl ComAllocator for PoolAllocator
+{
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8
+    {
+        match self.with_free_list(layout, |blocks| blocks.pop()) {
+            Some(Some(ptr)) => ptr as *mut u8,
+            _ => std::alloc::alloc(layout),
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout)
+    {
+        let max_free = self.max_free;
+        let stored = self.with_free_list(layout, |blocks| {
+            if blocks.len() < max_free {
+                blocks.push(ptr as usize);
+                true
+            } else {
+                false
+            }
+        });
+
+        if stored != Some(true) {
+            std::alloc::dealloc(ptr, layout)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn pool_reuses_blocks()
+    {
+        let pool = PoolAllocator::new(1);
+        let layout = Layout::new::<[u64; 4]>();
+
+        unsafe {
+            let first = pool.allocate(layout);
+            let second = pool.allocate(layout);
+            assert!(!first.is_null());
+            assert!(!second.is_null());
+
+            pool.deallocate(first, layout);
+            pool.deallocate(second, layout);
+            assert_eq!(pool.free_count(), 1);
+
+            let reused = pool.allocate(layout);
+            assert_eq!(reused, first);
+            assert_eq!(pool.free_count(), 0);
+            pool.deallocate(reused, layout);
+        }
+
+        pool.shrink();
+        assert_eq!(pool.free_count(), 0);
+    }
+
+    #[test]
+    fn pool_keeps_layouts_separate()
+    {
+        let pool = PoolAllocator::new(16);
+        let layout = Layout::new::<[u64; 4]>();
+        let other = Layout::new::<[u64; 8]>();
+
+        unsafe {
+            let block = pool.allocate(layout);
+            pool.deallocate(block, layout);
+
+            let other_block = pool.allocate(other);
+            assert_ne!(other_block, block);
+            pool.deallocate(other_block, other);
+            assert_eq!(pool.free_count(), 2);
+        }
+
+        pool.shrink();
+        assert_eq!(pool.free_count(), 0);
+    }
+}
@@ -14,6 +14,41 @@ pub trait ComClass
     const VTABLE: Self::VTableList;
     fn query_interface(vtables: &Self::VTableList, riid: REFIID) -> RawComResult<RawComPtr>;
     fn interface_supports_error_info(riid: REFIID) -> bool;
+
+    /// Specifies whether the class may be aggregated by other COM objects.
+    const AGGREGATABLE: bool = false;
+
+    /// Gets the non-delegating `IUnknown` of an aggregatable class.
+    ///
+    /// Returns `None` if the class does not support aggregation.
+    fn non_delegating_unknown(_vtables: &Self::VTableList) -> Option<RawComPtr>
+    {
+        None
+    }
+
+    /// Specifies how the method calls into the class instances are guarded.
+    const THREADING: crate::threading::ThreadingModel = crate::threading::ThreadingModel::Free;
+
+    /// Gets the allocator used for the `ComBoxData` instances of the class.
+    fn allocator() -> &'static dyn crate::allocator::ComAllocator
+    {
+        &crate::allocator::HeapAllocator
+    }
+}
+
+/// Constructs the instances of a COM class for the class factory.
+///
+/// Implemented by `#[com_class]`. Classes that specify a factory function
+/// with `#[com_class(factory = ...)]` use the function. Other classes are
+/// constructed with `Default::default()` if they implement `Default`.
+pub trait ComClassFactory: Sized
+{
+    /// Creates a new instance.
+    ///
+    /// The `args` are the initialization arguments given to
+    /// `IIntercomClassFactoryEx::create_instance_with`. Instances created
+    /// through the standard `IClassFactory` receive `Variant::None`.
+    fn create(args: crate::Variant) -> crate::ComResult<Self>;
 }
 
 pub trait HasInterface<T: ComInterface + ?Sized>: ComClass {}
@@ -65,6 +100,10 @@ pub trait ComInterface
     /// contexts. For generic `ComItf<T>` use, Intercom ipmls `Deref<Target=T>`
     /// for `ComItf<T>` which requires this method.
     fn deref(com_itf: &crate::ComItf<Self>) -> &Self;
+
+    /// Specifies whether the interface is a dual interface that derives from
+    /// `IDispatch`.
+    const DUAL: bool = false;
 }
 
 pub trait ComInterfaceVariant<TS: TypeSystem>
@@ -73,6 +112,35 @@ pub trait ComInterfaceVariant<TS: TypeSystem>
     fn iid() -> &'static IID;
 }
 
+/// Late bound access to the methods of a dual interface.
+///
+/// Implemented automatically for the `#[com_interface(dual)]` interfaces. The
+/// `IDispatch` implementation of the interface uses the trait to resolve and
+/// invoke the members.
+pub trait ComDispatch<I: ?Sized, S, TS: TypeSystem>
+{
+    /// Names of the interface members in the declaration order.
+    ///
+    /// The dispatch ID of a member is its index in the list plus one. The
+    /// accessors of a property share the member.
+    const MEMBERS: &'static [&'static str];
+
+    /// Invokes the member identified by the `dispid`.
+    ///
+    /// The `flags` are the `DISPATCH_*` flags of the call, which select
+    /// between the accessors of a property.
+    ///
+    /// # Safety
+    ///
+    /// The `self_vtable` must point to the `I` virtual table of a `S` class.
+    unsafe fn invoke(
+        self_vtable: RawComPtr,
+        dispid: crate::raw::DISPID,
+        flags: u16,
+        args: crate::dispatch::DispatchArgs,
+    ) -> Result<crate::Variant, crate::dispatch::DispatchError>;
+}
+
 pub trait ComClassTypeInfo
 {
     fn gather_type_info() -> Vec<crate::typelib::TypeInfo>;
@@ -82,3 +150,19 @@ pub trait ComInterfaceTypeInfo
 {
     fn gather_type_info() -> Vec<crate::typelib::TypeInfo>;
 }
+
+/// Type info for the `#[repr(C)]` structs.
+///
+/// Implemented by `#[derive(ExternType)]` for structs with named fields.
+pub trait ComStructTypeInfo
+{
+    fn gather_type_info() -> Vec<crate::typelib::TypeInfo>;
+}
+
+/// Type info for the C-like enums.
+///
+/// Implemented by `#[derive(ExternType)]` for enums without fields.
+pub trait ComEnumTypeInfo
+{
+    fn gather_type_info() -> Vec<crate::typelib::TypeInfo>;
+}
@@ -23,43 +23,70 @@ pub trait IClassFactory
     fn lock_server(&self, lock: bool) -> ComResult<()>;
 }
 
+/// Class factory extension for passing initialization arguments to the
+/// created instances.
+///
+/// Implemented by the class factories of all intercom classes. The arguments
+/// are passed to the factory function of the class, see
+/// `#[com_class(factory = ...)]`.
+#[com_interface(
+    com_iid = "A1CC4723-C1F7-4153-9D15-A6168359FEBE",
+    raw_iid = "2FF62C40-CFCD-40E9-9000-C960CF146E3C"
+)]
+pub trait IIntercomClassFactoryEx
+{
+    /// # Safety
+    ///
+    /// The REFIID must be a valid IID pointer.
+    unsafe fn create_instance_with(&self, args: Variant, riid: REFIID) -> ComResult<RawComPtr>;
+}
+
 #[doc(hidden)]
-#[com_class(IClassFactory)]
-pub struct ClassFactory<T: Default + intercom::attributes::ComClass>
+#[com_class(IClassFactory, IIntercomClassFactoryEx)]
+pub struct ClassFactory<T: attributes::ComClassFactory + attributes::ComClass>
 {
     phantom: std::marker::PhantomData<T>,
 }
 
-impl<T: Default + attributes::ComClass> IClassFactory for ClassFactory<T>
+impl<T: attributes::ComClassFactory + attributes::ComClass> IClassFactory for ClassFactory<T>
 {
-    unsafe fn create_instance(&self, _outer: RawComPtr, riid: REFIID) -> ComResult<RawComPtr>
+    unsafe fn create_instance(&self, outer: RawComPtr, riid: REFIID) -> ComResult<RawComPtr>
     {
-        let instance = ComBox::new(T::default());
-        let mut out = std::ptr::null_mut();
-        let hr = ComBoxData::query_interface(instance.as_ref(), riid, &mut out);
-        if hr == raw::S_OK {
-            Ok(out)
-        } else {
-            Err(ComError::from(hr))
+        if !outer.is_null() {
+            return Self::create_aggregated(outer, riid);
         }
+
+        Self::create_instance_with(self, Variant::None, riid)
     }
 
     fn lock_server(&self, lock: bool) -> ComResult<()>
     {
-        unsafe {
-            if lock {
-                ComBoxData::add_ref(ComBoxData::of(self));
-            } else {
-                ComBoxData::release(
-                    ComBoxData::of(self) as *const ComBoxData<Self> as *mut ComBoxData<Self>
-                );
-            }
+        // The locks keep the whole library loaded instead of the factory.
+        match lock {
+            true => crate::module::lock(),
+            false => crate::module::unlock(),
         }
         Ok(())
     }
 }
 
-impl<T: Default + attributes::ComClass> ClassFactory<T>
+impl<T: attributes::ComClassFactory + attributes::ComClass> IIntercomClassFactoryEx
+    for ClassFactory<T>
+{
+    unsafe fn create_instance_with(&self, args: Variant, riid: REFIID) -> ComResult<RawComPtr>
+    {
+        let instance = ComBox::new(T::create(args)?);
+        let mut out = std::ptr::null_mut();
+        let hr = ComBoxData::query_interface(instance.as_ref(), riid, &mut out);
+        if hr == raw::S_OK {
+            Ok(out)
+        } else {
+            Err(ComError::from(hr))
+        }
+    }
+}
+
+impl<T: attributes::ComClassFactory + attributes::ComClass> ClassFactory<T>
 {
     /// # Safety
     ///
@@ -73,4 +100,133 @@ impl<T: Default + attributes::ComClass> ClassFactory<T>
 
         intercom::ComBoxData::query_interface(intercom::ComBox::new(factory).as_mut(), riid, out)
     }
+
+    /// Creates an instance aggregated by the `outer` object.
+    ///
+    /// The outer object must request the non-delegating `IUnknown`, which is
+    /// the only interface that is not delegated back to the outer object.
+    unsafe fn create_aggregated(outer: RawComPtr, riid: REFIID) -> ComResult<RawComPtr>
+    {
+        let iunknown_iid = <dyn IUnknown as attributes::ComInterfaceVariant<
+            type_system::AutomationTypeSystem,
+        >>::iid();
+        if !T::AGGREGATABLE || riid.is_null() || *riid != *iunknown_iid {
+            return Err(ComError::CLASS_E_NOAGGREGATION);
+        }
+
+        let instance = ComBox::new_aggregated(T::create(Variant::None)?, outer);
+        let inner = T::non_delegating_unknown(ComBoxData::vtable(&instance))
+            .ok_or(ComError::CLASS_E_NOAGGREGATION)?;
+
+        // The reference held by the `instance` is released at the end of the
+        // scope so we need a new one for the returned pointer.
+        ComBoxData::add_ref(instance.as_ref());
+        Ok(inner)
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[com_class(clsid = None)]
+    #[derive(Default)]
+    struct NotAggregatable;
+
+    #[com_interface]
+    trait IConfigured
+    {
+        fn get_value(&self) -> i32;
+    }
+
+    // The factory function takes precedence over `Default`.
+    #[com_class(clsid = None, factory = create_configured, IConfigured)]
+    #[derive(Default)]
+    struct Configured
+    {
+        value: i32,
+    }
+
+    impl IConfigured for Configured
+    {
+        fn get_value(&self) -> i32
+        {
+            self.value
+        }
+    }
+
+    fn create_configured(args: Variant) -> ComResult<Configured>
+    {
+        match args {
+            Variant::None => Ok(Configured { value: 1 }),
+            Variant::I32(value) => Ok(Configured { value }),
+            _ => Err(ComError::E_INVALIDARG),
+        }
+    }
+
+    fn create_with<T: attributes::ComClassFactory + attributes::ComClass>(
+        args: Variant,
+    ) -> ComResult<Option<ComRc<dyn IConfigured>>>
+    {
+        let factory = ClassFactory::<T> {
+            phantom: std::marker::PhantomData,
+        };
+        let iid = <dyn IConfigured as attributes::ComInterfaceVariant<
+            type_system::AutomationTypeSystem,
+        >>::iid();
+
+        unsafe {
+            let ptr = factory.create_instance_with(args, iid)?;
+            Ok(
+                raw::InterfacePtr::<type_system::AutomationTypeSystem, dyn IConfigured>::new(ptr)
+                    .map(|ptr| ComRc::wrap(ptr)),
+            )
+        }
+    }
+
+    #[test]
+    fn aggregation_is_rejected_by_default()
+    {
+        let factory = ClassFactory::<NotAggregatable> {
+            phantom: std::marker::PhantomData,
+        };
+
+        // The outer pointer is never dereferenced as the request is rejected
+        // before the instance is created.
+        let mut dummy_outer = 0usize;
+        let outer = &mut dummy_outer as *mut usize as RawComPtr;
+        let iunknown_iid = <dyn IUnknown as attributes::ComInterfaceVariant<
+            type_system::AutomationTypeSystem,
+        >>::iid();
+
+        let err = unsafe { factory.create_instance(outer, iunknown_iid) }.unwrap_err();
+        assert_eq!(err.hresult, raw::CLASS_E_NOAGGREGATION);
+    }
+
+    #[test]
+    fn factory_function_receives_arguments()
+    {
+        let configured = create_with::<Configured>(Variant::I32(10))
+            .unwrap()
+            .unwrap();
+        assert_eq!(configured.get_value(), 10);
+
+        let configured = create_with::<Configured>(Variant::None).unwrap().unwrap();
+        assert_eq!(configured.get_value(), 1);
+    }
+
+    #[test]
+    fn factory_function_errors_are_returned()
+    {
+        let err = create_with::<Configured>(Variant::Bool(true)).unwrap_err();
+        assert_eq!(err.hresult, raw::E_INVALIDARG);
+    }
+
+    #[test]
+    fn default_classes_reject_arguments()
+    {
+        let err = create_with::<NotAggregatable>(Variant::I32(10)).unwrap_err();
+        assert_eq!(err.hresult, raw::E_INVALIDARG);
+    }
 }
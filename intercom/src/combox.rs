@@ -2,6 +2,8 @@ use super::*;
 use crate::attributes::{ComClass, ComInterface, HasInterface};
 use crate::raw::RawComPtr;
 use crate::type_system::TypeSystemName;
+use std::alloc::Layout;
+use std::mem::ManuallyDrop;
 use std::sync::atomic::{AtomicU32, Ordering};
 
 /// Pointer to a COM-enabled Rust struct.
@@ -37,6 +39,26 @@ impl<T: ComClass> ComBox<T>
         ComBox { data: cb }
     }
 
+    /// Constructs a new `ComBox` aggregated by the `outer` object.
+    ///
+    /// The `IUnknown` methods of the interfaces of the new object delegate to
+    /// the controlling `outer` object. The outer object must hold the object
+    /// through the non-delegating `IUnknown` acquired through
+    /// [`ComClass::non_delegating_unknown`](attributes/trait.ComClass.html#method.non_delegating_unknown).
+    ///
+    /// # Safety
+    ///
+    /// The `outer` must be a valid `IUnknown` pointer that outlives the
+    /// constructed object.
+    pub unsafe fn new_aggregated(value: T, outer: RawComPtr) -> ComBox<T>
+    {
+        let cb = ComBoxData::new(value);
+        (*cb).outer = outer;
+        ComBoxData::add_ref(&*cb);
+
+        ComBox { data: cb }
+    }
+
     /// Acquires a ComItf for this struct.
     ///
     /// # Safety
@@ -161,7 +183,10 @@ pub struct ComBoxData<T: ComClass>
 {
     vtable_list: T::VTableList,
     ref_count: AtomicU32,
-    value: T,
+    weak_count: AtomicU32,
+    outer: RawComPtr,
+    calls: crate::threading::CallState,
+    value: ManuallyDrop<T>,
 }
 
 impl<T: ComClass> ComBoxData<T>
@@ -175,13 +200,42 @@ impl<T: ComClass> ComBoxData<T>
     /// The value should be cleaned by calling 'release'.
     pub fn new(value: T) -> *mut ComBoxData<T>
     {
-        // TODO: Fix this to use raw heap allocation at some point. There's
-        // no need to construct and immediately detach a Box.
-        Box::into_raw(Box::new(ComBoxData {
-            vtable_list: T::VTABLE,
-            ref_count: AtomicU32::new(0),
-            value,
-        }))
+        // The object keeps the library loaded until it is deallocated.
+        crate::module::object_created();
+
+        // Allocate the memory through the allocator of the class. The same
+        // allocator releases the memory in `release_weak`.
+        let layout = Layout::new::<ComBoxData<T>>();
+        let data = unsafe { T::allocator().allocate(layout) } as *mut ComBoxData<T>;
+        if data.is_null() {
+            std::alloc::handle_alloc_error(layout);
+        }
+
+        unsafe {
+            data.write(ComBoxData {
+                vtable_list: T::VTABLE,
+                ref_count: AtomicU32::new(0),
+
+                // The strong references hold a single weak reference between
+                // them. This keeps the memory alive until the value has been
+                // dropped.
+                weak_count: AtomicU32::new(1),
+                outer: std::ptr::null_mut(),
+                calls: crate::threading::CallState::new(T::THREADING),
+                value: ManuallyDrop::new(value),
+            });
+        }
+
+        #[cfg(feature = "leak-tracking")]
+        unsafe {
+            crate::leaks::register(
+                data as usize,
+                std::any::type_name::<T>(),
+                &(*data).ref_count,
+            );
+        }
+
+        data
     }
 
     /// Acquires a specific interface pointer.
@@ -231,6 +285,83 @@ impl<T: ComClass> ComBoxData<T>
         self.ref_count.load(Ordering::Relaxed)
     }
 
+    /// Increments the reference count if the object is still alive.
+    ///
+    /// Returns `false` if the value has already been dropped.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold a weak reference to the object.
+    pub unsafe fn try_add_ref(this: &Self) -> bool
+    {
+        let mut current = this.ref_count.load(Ordering::Relaxed);
+        loop {
+            if current == 0 {
+                return false;
+            }
+
+            match this.ref_count.compare_exchange_weak(
+                current,
+                current + 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Increments the weak reference count.
+    ///
+    /// Weak references keep the `ComBoxData` memory alive but allow the value
+    /// to be dropped once the last strong reference is released.
+    ///
+    /// # Safety
+    ///
+    /// The weak reference must be released with `release_weak`.
+    pub unsafe fn add_weak(this: &Self) -> u32
+    {
+        this.weak_count.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Decrements the weak reference count. Deallocates the memory if the
+    /// count reaches zero.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be valid and hold a weak reference acquired with
+    /// `add_weak`.
+    pub unsafe fn release_weak(this: *mut Self) -> u32
+    {
+        let rc = (*this).weak_count.fetch_sub(1, Ordering::Relaxed) - 1;
+        if rc == 0 {
+            std::ptr::drop_in_place(this);
+            T::allocator().deallocate(this as *mut u8, Layout::new::<ComBoxData<T>>());
+            crate::module::object_destroyed();
+        }
+        rc
+    }
+
+    /// Enters a method call on the object.
+    ///
+    /// Verifies the call is allowed by the threading model of the class. The
+    /// `exclusive` flag specifies whether the method takes `&mut self`. The
+    /// call is in progress until the returned guard is dropped.
+    pub fn enter_call(this: &Self, exclusive: bool) -> ComResult<crate::threading::CallGuard<'_>>
+    {
+        this.calls.enter(exclusive)
+    }
+
+    /// Gets the controlling `IUnknown` if the object is aggregated.
+    pub fn outer(&self) -> Option<RawComPtr>
+    {
+        match self.outer.is_null() {
+            true => None,
+            false => Some(self.outer),
+        }
+    }
+
     /// Decrements the reference count. Destroys the object if the count reaches
     /// zero.
     ///
@@ -265,11 +396,14 @@ impl<T: ComClass> ComBoxData<T>
         let previous_value = (*this).ref_count.fetch_sub(1, Ordering::Relaxed);
         let rc = previous_value - 1;
 
-        // If that was the last reference we can drop self. Do this by giving
-        // it back to a box and then dropping the box. This should reverse the
-        // allocation we did by boxing the value in the first place.
+        // If that was the last reference we can drop the value. The memory
+        // itself is released once the possible weak references are gone.
         if rc == 0 {
-            drop(Box::from_raw(this));
+            #[cfg(feature = "leak-tracking")]
+            crate::leaks::unregister(this as usize);
+
+            ManuallyDrop::drop(&mut (*this).value);
+            Self::release_weak(this);
         }
         rc
     }
@@ -321,8 +455,7 @@ impl<T: ComClass> ComBoxData<T>
     pub unsafe fn of(value: &T) -> &ComBoxData<T>
     {
         // Resolve the offset of the 'value' field.
-        let null_combox = std::ptr::null() as *const ComBoxData<T>;
-        let value_offset = &((*null_combox).value) as *const _ as usize;
+        let value_offset = std::mem::offset_of!(ComBoxData<T>, value);
 
         let combox_loc = value as *const T as usize - value_offset;
         &mut *(combox_loc as *mut ComBoxData<T>)
@@ -348,8 +481,7 @@ impl<T: ComClass> ComBoxData<T>
     pub unsafe fn of_mut(value: &mut T) -> &mut ComBoxData<T>
     {
         // Resolve the offset of the 'value' field.
-        let null_combox = std::ptr::null() as *const ComBoxData<T>;
-        let value_offset = &((*null_combox).value) as *const _ as usize;
+        let value_offset = std::mem::offset_of!(ComBoxData<T>, value);
 
         let combox_loc = value as *mut T as usize - value_offset;
         &mut *(combox_loc as *mut ComBoxData<T>)
@@ -99,7 +99,7 @@ impl ComItf<dyn IUnknown>
 {
     /// Tries to convert the ComRc into a different interface within a single
     /// type system. Used to implement the generic conversion method.
-    fn query_interface_ts<TS: TypeSystem, TTarget: ComInterface + ?Sized>(
+    pub(crate) fn query_interface_ts<TS: TypeSystem, TTarget: ComInterface + ?Sized>(
         &self,
     ) -> ComResult<ComRc<TTarget>>
     {
@@ -210,6 +210,11 @@ where
     I: ForeignType,
 {
     type ForeignType = Option<crate::raw::InterfacePtr<TS, I>>;
+
+    fn type_desc() -> crate::typelib::TypeDesc
+    {
+        crate::typelib::TypeDesc::nullable(<Self::ForeignType as ForeignType>::type_desc())
+    }
 }
 
 unsafe impl<'a, TS: TypeSystem, I: ComInterface + ?Sized> ExternInput<TS> for &'a crate::ComItf<I>
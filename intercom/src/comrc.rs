@@ -174,6 +174,11 @@ where
     I: ForeignType,
 {
     type ForeignType = Option<crate::raw::InterfacePtr<TS, I>>;
+
+    fn type_desc() -> crate::typelib::TypeDesc
+    {
+        crate::typelib::TypeDesc::nullable(<Self::ForeignType as ForeignType>::type_desc())
+    }
 }
 
 unsafe impl<TS: TypeSystem, I: ComInterface + ?Sized> ExternInput<TS> for crate::ComRc<I>
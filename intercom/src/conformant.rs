@@ -0,0 +1,373 @@
+//! Conformant arrays passed as a pointer and a length.
+//!
+//! The Raw type system passes `&[T]`, `&mut [T]` and `Vec<T>` parameters as a
+//! pointer to the first element followed by a `u32` length parameter, which
+//! corresponds to the `[size_is]` arrays in IDL. `&mut [T]` parameters are
+//! modified in place, which requires the element type to have an identical
+//! foreign representation.
+//!
+//! The conformant arrays are not automation compatible. The Automation type
+//! system passes the same parameters as `SAFEARRAY`s, see
+//! `intercom::safearray`.
+//!
+//! The elements are converted one by one with the `ExternInput` and
+//! `ExternOutput` implementations of the element type. Output arrays are
+//! allocated with `intercom::alloc::allocate`. The caller takes over the
+//! elements and must release the array memory with `alloc::free` or
+//! `IAllocator::free`.
+//!
+//! The functions in this module are used by the code generated for the
+//! interfaces.
+
+use crate::type_system::{ExternInput, ExternOutput, ExternType, TypeSystem};
+use crate::{ComError, ComResult};
+use std::marker::PhantomData;
+
+/// Holds the foreign elements of an input array for the duration of a call.
+pub struct ArrayLease<TS: TypeSystem, T: ExternInput<TS>>
+{
+    values: Vec<T::ForeignType>,
+    _leases: Vec<T::Lease>,
+}
+
+impl<TS: TypeSystem, T: ExternInput<TS>> ArrayLease<TS, T>
+{
+    /// Converts the values into foreign elements.
+    ///
+    /// # Safety
+    ///
+    /// The elements are valid only as long as the lease is held.
+    pub unsafe fn new<I: IntoIterator<Item = T>>(values: I) -> ComResult<Self>
+    {
+        let values = values.into_iter();
+        let mut foreign = Vec::with_capacity(values.size_hint().0);
+        let mut leases = Vec::with_capacity(values.size_hint().0);
+        for value in values {
+            let (value, lease) = value.into_foreign_parameter()?;
+            foreign.push(value);
+            leases.push(lease);
+        }
+
+        Ok(ArrayLease {
+            values: foreign,
+            _leases: leases,
+        })
+    }
+
+    /// Pointer to the first element.
+    pub fn as_ptr(&self) -> *const T::ForeignType
+    {
+        self.values.as_ptr()
+    }
+
+    /// Number of elements in the array.
+    pub fn len(&self) -> u32
+    {
+        self.values.len() as u32
+    }
+
+    /// Checks whether the array is empty.
+    pub fn is_empty(&self) -> bool
+    {
+        self.values.is_empty()
+    }
+}
+
+/// Converts the elements of a foreign input array.
+///
+/// # Safety
+///
+/// The `ptr` must point to `len` valid elements unless `len` is zero.
+pub unsafe fn from_foreign_parameter<TS, T>(
+    ptr: *const T::ForeignType,
+    len: u32,
+) -> ComResult<Vec<T>>
+where
+    TS: TypeSystem,
+    T: ExternInput<TS, Owned = T>,
+{
+    if len == 0 {
+        return Ok(vec![]);
+    }
+    if ptr.is_null() {
+        return Err(ComError::E_POINTER);
+    }
+
+    // The caller retains the ownership of the elements. The conversions
+    // only borrow the bitwise copies.
+    (0..len as usize)
+        .map(|idx| T::from_foreign_parameter(std::ptr::read(ptr.add(idx))))
+        .collect()
+}
+
+/// Borrows a foreign array that is modified in place.
+///
+/// # Safety
+///
+/// The `ptr` must point to `len` valid elements that remain valid for the
+/// lifetime `'a` unless `len` is zero.
+pub unsafe fn from_foreign_slice_mut<'a, TS, T>(ptr: *mut T, len: u32) -> ComResult<&'a mut [T]>
+where
+    TS: TypeSystem,
+    T: ExternType<TS, ForeignType = T>,
+{
+    if len == 0 {
+        return Ok(&mut []);
+    }
+    if ptr.is_null() {
+        return Err(ComError::E_POINTER);
+    }
+
+    Ok(std::slice::from_raw_parts_mut(ptr, len as usize))
+}
+
+/// Holds a converted output array and releases it unless consumed.
+pub struct OutputArrayGuard<TS: TypeSystem, T: ExternOutput<TS>>
+{
+    ptr: *mut T::ForeignType,
+    len: u32,
+    phantom: PhantomData<TS>,
+}
+
+impl<TS: TypeSystem, T: ExternOutput<TS>> OutputArrayGuard<TS, T>
+{
+    /// Converts the values into a foreign array.
+    ///
+    /// The array is allocated with `intercom::alloc::allocate`.
+    pub fn wrap(values: Vec<T>) -> ComResult<Self>
+    {
+        let len = values.len();
+        let size = std::mem::size_of::<T::ForeignType>() * len;
+        let ptr = match size {
+            0 => std::ptr::null_mut(),
+            _ => {
+                let ptr = unsafe { crate::alloc::allocate(size) } as *mut T::ForeignType;
+                if ptr.is_null() {
+                    return Err(ComError::new_hr(crate::raw::E_OUTOFMEMORY));
+                }
+                ptr
+            }
+        };
+
+        // The guard releases the elements converted so far if one of the
+        // conversions fails.
+        let mut guard = OutputArrayGuard {
+            ptr,
+            len: 0,
+            phantom: PhantomData,
+        };
+        for (idx, value) in values.into_iter().enumerate() {
+            let value = value.into_foreign_output()?;
+            unsafe { std::ptr::write(ptr.add(idx), value) };
+            guard.len += 1;
+        }
+
+        Ok(guard)
+    }
+
+    /// Consumes the guard, giving the array to the caller.
+    pub fn consume(self) -> (*const T::ForeignType, u32)
+    {
+        let result = (self.ptr as *const _, self.len);
+        std::mem::forget(self);
+        result
+    }
+}
+
+impl<TS: TypeSystem, T: ExternOutput<TS>> Drop for OutputArrayGuard<TS, T>
+{
+    fn drop(&mut self)
+    {
+        unsafe { drop_foreign_output::<TS, T>(self.ptr, self.len) }
+    }
+}
+
+/// Takes the ownership of a foreign output array.
+///
+/// All elements are converted and the array memory is released even if some
+/// of the conversions fail.
+///
+/// # Safety
+///
+/// The array must be allocated with `intercom::alloc::allocate` and the
+/// caller must own it and its elements.
+pub unsafe fn from_foreign_output<TS, T>(ptr: *const T::ForeignType, len: u32) -> ComResult<Vec<T>>
+where
+    TS: TypeSystem,
+    T: ExternOutput<TS>,
+{
+    if ptr.is_null() {
+        return match len {
+            0 => Ok(vec![]),
+            _ => Err(ComError::E_POINTER),
+        };
+    }
+
+    let mut values = Vec::with_capacity(len as usize);
+    let mut error = None;
+    for idx in 0..len as usize {
+        match T::from_foreign_output(std::ptr::read(ptr.add(idx))) {
+            Ok(value) => values.push(value),
+            Err(e) => {
+                error.get_or_insert(e);
+            }
+        }
+    }
+
+    crate::alloc::free(ptr as *mut _);
+    match error {
+        Some(e) => Err(e),
+        None => Ok(values),
+    }
+}
+
+/// Releases a foreign output array and its elements.
+///
+/// # Safety
+///
+/// The array must be allocated with `intercom::alloc::allocate` and the
+/// caller must own it and its elements.
+pub unsafe fn drop_foreign_output<TS, T>(ptr: *const T::ForeignType, len: u32)
+where
+    TS: TypeSystem,
+    T: ExternOutput<TS>,
+{
+    if ptr.is_null() {
+        return;
+    }
+
+    for idx in 0..len as usize {
+        T::drop_foreign_output(std::ptr::read(ptr.add(idx)));
+    }
+    crate::alloc::free(ptr as *mut _);
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::type_system::RawTypeSystem;
+    use crate::*;
+
+    #[com_interface]
+    trait IArrays
+    {
+        fn sum(&self, values: &[i32]) -> ComResult<i32>;
+        fn join(&self, values: Vec<String>) -> ComResult<String>;
+        fn double(&self, values: &mut [f64]) -> ComResult<()>;
+        fn split(&self, value: &str) -> ComResult<Vec<String>>;
+        fn partition(&self, values: &[i32]) -> ComResult<(Vec<i32>, Vec<i32>)>;
+    }
+
+    #[com_class(clsid = None, IArrays)]
+    struct Arrays;
+
+    impl IArrays for Arrays
+    {
+        fn sum(&self, values: &[i32]) -> ComResult<i32>
+        {
+            Ok(values.iter().sum())
+        }
+
+        fn join(&self, values: Vec<String>) -> ComResult<String>
+        {
+            Ok(values.join(","))
+        }
+
+        fn double(&self, values: &mut [f64]) -> ComResult<()>
+        {
+            values.iter_mut().for_each(|v| *v *= 2.0);
+            Ok(())
+        }
+
+        fn split(&self, value: &str) -> ComResult<Vec<String>>
+        {
+            Ok(value.split(',').map(String::from).collect())
+        }
+
+        fn partition(&self, values: &[i32]) -> ComResult<(Vec<i32>, Vec<i32>)>
+        {
+            Ok(values.iter().partition(|v| *v % 2 == 0))
+        }
+    }
+
+    #[test]
+    fn interfaces_pass_conformant_arrays()
+    {
+        let arrays: ComRc<dyn IArrays> = ComRc::from(ComBox::new(Arrays));
+
+        // The Raw type system is preferred for calls within Rust.
+        assert!(ComItf::ptr::<RawTypeSystem>(&arrays).is_some());
+
+        assert_eq!(arrays.sum(&[1, 2, 3]).unwrap(), 6);
+        assert_eq!(arrays.sum(&[]).unwrap(), 0);
+        assert_eq!(arrays.join(vec!["a".into(), "b".into()]).unwrap(), "a,b");
+        assert_eq!(arrays.split("x,y,z").unwrap(), vec!["x", "y", "z"]);
+
+        let mut values = [1.0, 2.5];
+        arrays.double(&mut values).unwrap();
+        assert_eq!(values, [2.0, 5.0]);
+
+        let (even, odd) = arrays.partition(&[1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(even, vec![2, 4]);
+        assert_eq!(odd, vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn input_arrays_round_trip()
+    {
+        unsafe {
+            let lease =
+                ArrayLease::<RawTypeSystem, String>::new(vec!["foo".into(), "bar".into()]).unwrap();
+            assert_eq!(lease.len(), 2);
+
+            let values =
+                from_foreign_parameter::<RawTypeSystem, String>(lease.as_ptr(), lease.len())
+                    .unwrap();
+            assert_eq!(values, vec!["foo".to_string(), "bar".to_string()]);
+        }
+    }
+
+    #[test]
+    fn empty_input_arrays_accept_null()
+    {
+        unsafe {
+            let values = from_foreign_parameter::<RawTypeSystem, i32>(std::ptr::null(), 0).unwrap();
+            assert!(values.is_empty());
+
+            let error = from_foreign_parameter::<RawTypeSystem, i32>(std::ptr::null(), 1);
+            assert_eq!(error.unwrap_err().hresult, crate::raw::E_POINTER);
+        }
+    }
+
+    #[test]
+    fn output_arrays_round_trip()
+    {
+        let (ptr, len) = OutputArrayGuard::<RawTypeSystem, String>::wrap(vec![
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+        ])
+        .unwrap()
+        .consume();
+        assert_eq!(len, 3);
+
+        let values = unsafe { from_foreign_output::<RawTypeSystem, String>(ptr, len) }.unwrap();
+        assert_eq!(values, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn mutable_slices_share_memory()
+    {
+        let mut values = vec![1, 2, 3];
+        unsafe {
+            let slice = from_foreign_slice_mut::<RawTypeSystem, i32>(
+                values.as_mut_ptr(),
+                values.len() as u32,
+            )
+            .unwrap();
+            slice[1] = 20;
+        }
+        assert_eq!(values, vec![1, 20, 3]);
+    }
+}
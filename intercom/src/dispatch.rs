@@ -0,0 +1,956 @@
+//! Late bound automation through `IDispatch`.
+//!
+//! Interfaces declared with `#[com_interface(dual)]` derive from `IDispatch`
+//! in addition to exposing their methods through the virtual table. The
+//! `IDispatch` implementation resolves the members by name and invokes them
+//! with the `Variant` arguments of the caller:
+//!
+//! - The dispatch ID of a member is its position in the interface plus one.
+//!   This matches the method order in the type library. The dispatch ID zero
+//!   is reserved for the default member.
+//! - The getter and the setter of a `#[com_property]` form a single member
+//!   named after the property. `DISPATCH_PROPERTYGET` invokes the getter and
+//!   `DISPATCH_PROPERTYPUT` the setter. The plain methods are invoked with
+//!   either `DISPATCH_METHOD` or `DISPATCH_PROPERTYGET`.
+//! - `GetIDsOfNames` ignores the case and the underscores of the names, so
+//!   both `GetValue` and `get_value` resolve to the `get_value` method.
+//! - `Invoke` converts the arguments to the parameter types through the
+//!   `TryFrom<Variant>` implementations and the return value into a `Variant`
+//!   through `From`. Named arguments are not supported.
+//! - Errors returned by the member are reported as `DISP_E_EXCEPTION` with
+//!   the details in the `EXCEPINFO` structure. If the caller did not provide
+//!   the structure, the `HRESULT` of the error is returned directly.
+//!
+//! The objects do not provide type information through `IDispatch`.
+//!
+//! Automation objects without a compile time interface can be called through
+//! `ComItf<dyn IDispatch>`. The members are looked up by name and the
+//! `EXCEPINFO` of a failed call is turned into a `ComError`:
+//!
+//! ```rust,ignore
+//! let sum = ComItf::invoke(&dispatch, "Add", &[Variant::I32(1), Variant::I32(2)])?;
+//! ComItf::set(&dispatch, "Name", Variant::from("Intercom".to_string()))?;
+//! let name = ComItf::get(&dispatch, "Name")?;
+//! ```
+
+use crate::attributes::ComInterfaceVariant;
+use crate::attributes::{ComClass, ComClassInterface, ComDispatch, ComInterfaceVTableFor};
+use crate::error::ErrorInfo;
+use crate::type_system::{AutomationTypeSystem, RawTypeSystem, TypeSystem};
+use crate::{BString, ComError, ComItf, ComResult, IDispatch, IUnknown, Variant, GUID};
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+
+/// The arguments of a dispatch call in the parameter order.
+pub struct DispatchArgs
+{
+    args: std::vec::IntoIter<Variant>,
+    count: u32,
+    position: u32,
+}
+
+impl DispatchArgs
+{
+    /// Creates the arguments from the values in the parameter order.
+    pub fn new(args: Vec<Variant>) -> DispatchArgs
+    {
+        DispatchArgs {
+            count: args.len() as u32,
+            args: args.into_iter(),
+            position: 0,
+        }
+    }
+
+    /// Gets the number of arguments.
+    pub fn count(&self) -> u32
+    {
+        self.count
+    }
+
+    /// Ensures the call has the expected number of arguments.
+    pub fn expect_count(&self, count: u32) -> Result<(), DispatchError>
+    {
+        match self.count == count {
+            true => Ok(()),
+            false => Err(DispatchError::Invoke(crate::raw::DISP_E_BADPARAMCOUNT)),
+        }
+    }
+
+    /// Converts the next argument into the parameter type.
+    pub fn next_arg<T: TryFrom<Variant>>(&mut self) -> Result<T, DispatchError>
+    {
+        let position = self.position;
+        let value = self
+            .args
+            .next()
+            .ok_or(DispatchError::Invoke(crate::raw::DISP_E_BADPARAMCOUNT))?;
+        self.position += 1;
+        T::try_from(value).map_err(|_| DispatchError::Argument {
+            position,
+            hresult: crate::raw::DISP_E_TYPEMISMATCH,
+        })
+    }
+}
+
+/// Failure of a dispatch call.
+#[derive(Debug)]
+pub enum DispatchError
+{
+    /// The call was rejected before the member was invoked.
+    Invoke(crate::raw::HRESULT),
+
+    /// An argument could not be converted into the parameter type.
+    Argument
+    {
+        /// Position of the argument in the parameter order.
+        position: u32,
+
+        /// The `HRESULT` describing the failure.
+        hresult: crate::raw::HRESULT,
+    },
+
+    /// The member failed with an error.
+    Exception(ComError),
+}
+
+impl From<ComError> for DispatchError
+{
+    fn from(error: ComError) -> DispatchError
+    {
+        DispatchError::Exception(error)
+    }
+}
+
+/// Checks whether a dispatch name refers to the member.
+///
+/// The names are compared ignoring the case and the underscores.
+pub fn member_matches(member: &str, name: &str) -> bool
+{
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| *c != '_')
+            .flat_map(char::to_lowercase)
+            .collect::<Vec<_>>()
+    };
+    normalize(member) == normalize(name)
+}
+
+/// The `IDispatch` virtual table of the dual interface `D`.
+#[doc(hidden)]
+pub struct DispatchVTable<I: ?Sized, S, TS, D: ?Sized>(
+    PhantomData<(*const I, *const S, TS, *const D)>,
+);
+
+type AutomationVTable = <dyn IDispatch as ComInterfaceVariant<AutomationTypeSystem>>::VTable;
+type RawVTable = <dyn IDispatch as ComInterfaceVariant<RawTypeSystem>>::VTable;
+
+impl<I, S, D> DispatchVTable<I, S, AutomationTypeSystem, D>
+where
+    I: ?Sized,
+    S: ComClassInterface<I, AutomationTypeSystem> + ComClass,
+    D: ComDispatch<I, S, AutomationTypeSystem> + ?Sized,
+{
+    pub const VTABLE: AutomationVTable = AutomationVTable {
+        __base: <dyn IUnknown as ComInterfaceVTableFor<I, S, AutomationTypeSystem>>::VTABLE,
+        get_type_info_count,
+        get_type_info,
+        get_ids_of_names: get_ids_of_names::<I, S, AutomationTypeSystem, D>,
+        invoke: invoke::<I, S, AutomationTypeSystem, D>,
+    };
+}
+
+impl<I, S, D> DispatchVTable<I, S, RawTypeSystem, D>
+where
+    I: ?Sized,
+    S: ComClassInterface<I, RawTypeSystem> + ComClass,
+    D: ComDispatch<I, S, RawTypeSystem> + ?Sized,
+{
+    pub const VTABLE: RawVTable = RawVTable {
+        __base: <dyn IUnknown as ComInterfaceVTableFor<I, S, RawTypeSystem>>::VTABLE,
+        get_type_info_count,
+        get_type_info,
+        get_ids_of_names: get_ids_of_names::<I, S, RawTypeSystem, D>,
+        invoke: invoke::<I, S, RawTypeSystem, D>,
+    };
+}
+
+unsafe extern "system" fn get_type_info_count(
+    _self_vtable: crate::raw::RawComPtr,
+    count: *mut u32,
+) -> crate::raw::HRESULT
+{
+    if count.is_null() {
+        return crate::raw::E_POINTER;
+    }
+    *count = 0;
+    crate::raw::S_OK
+}
+
+unsafe extern "system" fn get_type_info(
+    _self_vtable: crate::raw::RawComPtr,
+    _index: u32,
+    _lcid: u32,
+    type_info: *mut crate::raw::RawComPtr,
+) -> crate::raw::HRESULT
+{
+    if !type_info.is_null() {
+        *type_info = std::ptr::null_mut();
+    }
+    crate::raw::DISP_E_BADINDEX
+}
+
+unsafe extern "system" fn get_ids_of_names<I, S, TS, D>(
+    _self_vtable: crate::raw::RawComPtr,
+    _riid: crate::REFIID,
+    names: *mut *mut u16,
+    count: u32,
+    _lcid: u32,
+    dispids: *mut crate::raw::DISPID,
+) -> crate::raw::HRESULT
+where
+    I: ?Sized,
+    S: ComClassInterface<I, TS> + ComClass,
+    TS: TypeSystem,
+    D: ComDispatch<I, S, TS> + ?Sized,
+{
+    if count == 0 {
+        return crate::raw::S_OK;
+    }
+    if names.is_null() || dispids.is_null() {
+        return crate::raw::E_POINTER;
+    }
+
+    crate::panic::catch_panic(std::any::type_name::<S>(), "get_ids_of_names", || {
+        let names = std::slice::from_raw_parts(names, count as usize);
+        let dispids = std::slice::from_raw_parts_mut(dispids, count as usize);
+        for dispid in dispids.iter_mut() {
+            *dispid = crate::raw::DISPID_UNKNOWN;
+        }
+
+        // The first name is the member name. The rest would name the
+        // parameters for named arguments, which are not supported.
+        let name = wide_str(names[0]);
+        match D::MEMBERS.iter().position(|m| member_matches(m, &name)) {
+            Some(idx) if count == 1 => {
+                dispids[0] = idx as crate::raw::DISPID + 1;
+                crate::raw::S_OK
+            }
+            Some(idx) => {
+                dispids[0] = idx as crate::raw::DISPID + 1;
+                crate::raw::DISP_E_UNKNOWNNAME
+            }
+            None => crate::raw::DISP_E_UNKNOWNNAME,
+        }
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "system" fn invoke<I, S, TS, D>(
+    self_vtable: crate::raw::RawComPtr,
+    dispid: crate::raw::DISPID,
+    _riid: crate::REFIID,
+    _lcid: u32,
+    flags: u16,
+    params: *mut crate::raw::DISPPARAMS,
+    result: *mut crate::raw::Variant<AutomationTypeSystem>,
+    exception: *mut crate::raw::EXCEPINFO,
+    arg_err: *mut u32,
+) -> crate::raw::HRESULT
+where
+    I: ?Sized,
+    S: ComClassInterface<I, TS> + ComClass,
+    TS: TypeSystem,
+    D: ComDispatch<I, S, TS> + ?Sized,
+{
+    let supported = crate::raw::DISPATCH_METHOD
+        | crate::raw::DISPATCH_PROPERTYGET
+        | crate::raw::DISPATCH_PROPERTYPUT;
+    if flags & supported == 0 {
+        return crate::raw::DISP_E_MEMBERNOTFOUND;
+    }
+    if params.is_null() {
+        return crate::raw::E_POINTER;
+    }
+    let params = &*params;
+    if (params.cArgs != 0 && params.rgvarg.is_null())
+        || (params.cNamedArgs != 0 && params.rgdispidNamedArgs.is_null())
+    {
+        return crate::raw::E_POINTER;
+    }
+
+    // The value of a property put is passed as the named DISPID_PROPERTYPUT
+    // argument. It is the last argument in the parameter order.
+    let property_put = flags & crate::raw::DISPATCH_PROPERTYPUT != 0
+        && params.cNamedArgs == 1
+        && *params.rgdispidNamedArgs == crate::raw::DISPID_PROPERTYPUT;
+    if params.cNamedArgs != 0 && !property_put {
+        return crate::raw::DISP_E_NONAMEDARGS;
+    }
+
+    crate::panic::catch_panic(std::any::type_name::<S>(), "invoke", || {
+        // The arguments are stored in the reverse order.
+        let mut args = Vec::with_capacity(params.cArgs as usize);
+        for idx in (0..params.cArgs).rev() {
+            match Variant::from_raw_ref(&*params.rgvarg.add(idx as usize)) {
+                Ok(arg) => args.push(arg),
+                Err(_) => {
+                    if !arg_err.is_null() {
+                        *arg_err = idx;
+                    }
+                    return crate::raw::DISP_E_TYPEMISMATCH;
+                }
+            }
+        }
+
+        match D::invoke(self_vtable, dispid, flags, DispatchArgs::new(args)) {
+            Ok(value) => {
+                if !result.is_null() {
+                    match crate::raw::Variant::try_from(value) {
+                        Ok(value) => *result = value,
+                        Err(e) => return e.hresult,
+                    }
+                }
+                crate::raw::S_OK
+            }
+            Err(DispatchError::Invoke(hresult)) => hresult,
+            Err(DispatchError::Argument { position, hresult }) => {
+                if !arg_err.is_null() {
+                    *arg_err = params.cArgs - 1 - position;
+                }
+                hresult
+            }
+            Err(DispatchError::Exception(error)) => {
+                if exception.is_null() {
+                    return crate::store_error(error).hresult;
+                }
+                let source = BString::from(std::any::type_name::<S>());
+                let description = BString::from(error.description().unwrap_or(""));
+                *exception = crate::raw::EXCEPINFO {
+                    bstrSource: source.into_ptr(),
+                    bstrDescription: description.into_ptr(),
+                    scode: error.hresult,
+                    ..Default::default()
+                };
+                crate::raw::DISP_E_EXCEPTION
+            }
+        }
+    })
+}
+
+impl ComItf<dyn IDispatch>
+{
+    /// Gets the dispatch ID of a member.
+    pub fn get_dispid(this: &Self, name: &str) -> ComResult<crate::raw::DISPID>
+    {
+        let mut name = name.encode_utf16().chain(Some(0)).collect::<Vec<_>>();
+        let mut names = [name.as_mut_ptr()];
+        let mut dispid = crate::raw::DISPID_UNKNOWN;
+        let hr = unsafe {
+            IDispatch::get_ids_of_names(
+                &**this,
+                &GUID::zero_guid(),
+                names.as_mut_ptr(),
+                1,
+                0,
+                &mut dispid,
+            )
+        };
+        match hr {
+            crate::raw::S_OK => Ok(dispid),
+            hr => Err(ComError::new_hr(hr)),
+        }
+    }
+
+    /// Calls a method by name with the arguments in the parameter order.
+    pub fn invoke(this: &Self, name: &str, args: &[Variant]) -> ComResult<Variant>
+    {
+        let dispid = ComItf::get_dispid(this, name)?;
+        invoke_dispid(this, dispid, crate::raw::DISPATCH_METHOD, args, false)
+    }
+
+    /// Gets the value of a property by name.
+    pub fn get(this: &Self, name: &str) -> ComResult<Variant>
+    {
+        let dispid = ComItf::get_dispid(this, name)?;
+        invoke_dispid(this, dispid, crate::raw::DISPATCH_PROPERTYGET, &[], false)
+    }
+
+    /// Sets the value of a property by name.
+    pub fn set(this: &Self, name: &str, value: Variant) -> ComResult<()>
+    {
+        let dispid = ComItf::get_dispid(this, name)?;
+        invoke_dispid(
+            this,
+            dispid,
+            crate::raw::DISPATCH_PROPERTYPUT,
+            &[value],
+            true,
+        )?;
+        Ok(())
+    }
+}
+
+/// Invokes a member through `IDispatch::Invoke`.
+///
+/// The arguments are given in the parameter order. A property put passes
+/// the last argument as the named `DISPID_PROPERTYPUT` argument.
+fn invoke_dispid(
+    this: &ComItf<dyn IDispatch>,
+    dispid: crate::raw::DISPID,
+    flags: u16,
+    args: &[Variant],
+    property_put: bool,
+) -> ComResult<Variant>
+{
+    // The arguments are passed in the reverse order.
+    let mut raw_args = Vec::with_capacity(args.len());
+    for arg in args.iter().rev() {
+        match crate::raw::Variant::<AutomationTypeSystem>::try_from(arg.clone()) {
+            Ok(arg) => raw_args.push(arg),
+            Err(e) => {
+                free_args(raw_args);
+                return Err(e);
+            }
+        }
+    }
+
+    let mut named_arg = crate::raw::DISPID_PROPERTYPUT;
+    let mut params = crate::raw::DISPPARAMS {
+        rgvarg: raw_args.as_mut_ptr(),
+        rgdispidNamedArgs: match property_put {
+            true => &mut named_arg,
+            false => std::ptr::null_mut(),
+        },
+        cArgs: raw_args.len() as u32,
+        cNamedArgs: property_put as u32,
+    };
+    let mut result = crate::raw::Variant::default();
+    let mut exception = crate::raw::EXCEPINFO::default();
+    let mut arg_err = 0;
+
+    let hr = unsafe {
+        IDispatch::invoke(
+            &**this,
+            dispid,
+            &GUID::zero_guid(),
+            0,
+            flags,
+            &mut params,
+            &mut result,
+            &mut exception,
+            &mut arg_err,
+        )
+    };
+    free_args(raw_args);
+
+    match hr {
+        crate::raw::S_OK => unsafe { Variant::from_raw(result) },
+        crate::raw::DISP_E_EXCEPTION => Err(unsafe { exception_error(exception) }),
+        crate::raw::DISP_E_TYPEMISMATCH | crate::raw::DISP_E_PARAMNOTFOUND => {
+            // The argument index is given in the reverse order. Other
+            // implementations may report these errors without arguments.
+            match (args.len() as u32).checked_sub(1) {
+                Some(last) => {
+                    let position = last - arg_err.min(last);
+                    Err(ComError::new_message(
+                        hr,
+                        format!("Invalid argument at position {}", position),
+                    ))
+                }
+                None => Err(ComError::new_hr(hr)),
+            }
+        }
+        hr => Err(ComError::new_hr(hr)),
+    }
+}
+
+/// Releases the arguments of a dispatch call.
+fn free_args(args: Vec<crate::raw::Variant<AutomationTypeSystem>>)
+{
+    for arg in args {
+        drop(unsafe { Variant::from_raw(arg) });
+    }
+}
+
+/// Converts the `EXCEPINFO` of a failed call into an error.
+///
+/// The strings of the structure are released.
+unsafe fn exception_error(mut exception: crate::raw::EXCEPINFO) -> ComError
+{
+    if let Some(fill_in) = exception.pfnDeferredFillIn {
+        fill_in(&mut exception);
+    }
+
+    drop(BString::from_ptr(exception.bstrSource));
+    drop(BString::from_ptr(exception.bstrHelpFile));
+    let description = BString::from_ptr(exception.bstrDescription);
+    let description = match description.is_empty() {
+        true => None,
+        false => description.to_string().ok(),
+    };
+
+    // Automation objects may report the error with an application specific
+    // wCode instead of an HRESULT.
+    let hresult = match exception.scode {
+        crate::raw::S_OK => crate::raw::DISP_E_EXCEPTION,
+        scode => scode,
+    };
+    ComError {
+        hresult,
+        error_info: description.map(ErrorInfo::new),
+    }
+}
+
+/// Reads a null terminated wide string.
+unsafe fn wide_str(ptr: *const u16) -> String
+{
+    if ptr.is_null() {
+        return String::new();
+    }
+    let mut len = 0;
+    while *ptr.add(len) != 0 {
+        len += 1;
+    }
+    String::from_utf16_lossy(std::slice::from_raw_parts(ptr, len))
+}
+
+pub mod raw
+{
+    use crate::raw::{Variant, HRESULT};
+    use crate::type_system::AutomationTypeSystem;
+    use intercom_attributes::ForeignType;
+
+    /// Dispatch ID of an `IDispatch` member.
+    pub type DISPID = i32;
+
+    /// Dispatch ID of an unknown name.
+    pub const DISPID_UNKNOWN: DISPID = -1;
+
+    /// Dispatch ID of the default member.
+    pub const DISPID_VALUE: DISPID = 0;
+
+    /// Dispatch ID of the value argument of a property put.
+    pub const DISPID_PROPERTYPUT: DISPID = -3;
+
+    /// `Invoke` flag for calling a method.
+    pub const DISPATCH_METHOD: u16 = 1;
+
+    /// `Invoke` flag for getting a property.
+    pub const DISPATCH_PROPERTYGET: u16 = 2;
+
+    /// `Invoke` flag for setting a property.
+    pub const DISPATCH_PROPERTYPUT: u16 = 4;
+
+    /// `Invoke` flag for setting a property by reference.
+    pub const DISPATCH_PROPERTYPUTREF: u16 = 8;
+
+    /// Arguments of an `IDispatch::Invoke` call.
+    #[repr(C)]
+    #[derive(ForeignType)]
+    #[allow(non_snake_case)]
+    #[allow(clippy::upper_case_acronyms)]
+    pub struct DISPPARAMS
+    {
+        /// The arguments in the reverse order.
+        pub rgvarg: *mut Variant<AutomationTypeSystem>,
+        pub rgdispidNamedArgs: *mut DISPID,
+        pub cArgs: u32,
+        pub cNamedArgs: u32,
+    }
+
+    /// Error details of an `IDispatch::Invoke` call.
+    #[repr(C)]
+    #[derive(ForeignType)]
+    #[allow(non_snake_case)]
+    #[allow(clippy::upper_case_acronyms)]
+    pub struct EXCEPINFO
+    {
+        pub wCode: u16,
+        pub wReserved: u16,
+        pub bstrSource: *mut u16,
+        pub bstrDescription: *mut u16,
+        pub bstrHelpFile: *mut u16,
+        pub dwHelpContext: u32,
+        pub pvReserved: *mut std::os::raw::c_void,
+        pub pfnDeferredFillIn: Option<unsafe extern "system" fn(*mut EXCEPINFO) -> HRESULT>,
+        pub scode: HRESULT,
+    }
+
+    impl Default for EXCEPINFO
+    {
+        fn default() -> EXCEPINFO
+        {
+            EXCEPINFO {
+                wCode: 0,
+                wReserved: 0,
+                bstrSource: std::ptr::null_mut(),
+                bstrDescription: std::ptr::null_mut(),
+                bstrHelpFile: std::ptr::null_mut(),
+                dwHelpContext: 0,
+                pvReserved: std::ptr::null_mut(),
+                pfnDeferredFillIn: None,
+                scode: crate::raw::S_OK,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::raw;
+    use crate::*;
+
+    #[com_interface(dual)]
+    trait ICalculator
+    {
+        fn add(&self, a: i32, b: i32) -> i32;
+        fn greet(&self, name: &str) -> ComResult<String>;
+        fn fail(&self) -> ComResult<()>;
+    }
+
+    #[com_class(clsid = None, ICalculator)]
+    struct Calculator;
+
+    impl ICalculator for Calculator
+    {
+        fn add(&self, a: i32, b: i32) -> i32
+        {
+            a + b
+        }
+
+        fn greet(&self, name: &str) -> ComResult<String>
+        {
+            Ok(format!("Hello, {}!", name))
+        }
+
+        fn fail(&self) -> ComResult<()>
+        {
+            Err(ComError::E_ACCESSDENIED.with_message("Access denied"))
+        }
+    }
+
+    fn dispatch() -> ComRc<dyn IDispatch>
+    {
+        let calculator: ComRc<dyn ICalculator> = ComRc::from(ComBox::new(Calculator));
+        ComItf::query_interface(&calculator).unwrap()
+    }
+
+    fn get_id(disp: &ComItf<dyn IDispatch>, name: &str) -> (raw::HRESULT, raw::DISPID)
+    {
+        let mut name = name.encode_utf16().chain(Some(0)).collect::<Vec<_>>();
+        let mut names = [name.as_mut_ptr()];
+        let mut dispid = 0;
+        let hr = unsafe {
+            disp.get_ids_of_names(std::ptr::null(), names.as_mut_ptr(), 1, 0, &mut dispid)
+        };
+        (hr, dispid)
+    }
+
+    fn call(
+        disp: &ComItf<dyn IDispatch>,
+        dispid: raw::DISPID,
+        args: Vec<Variant>,
+    ) -> (raw::HRESULT, Variant, raw::EXCEPINFO, u32)
+    {
+        let mut raw_args = args
+            .into_iter()
+            .rev()
+            .map(|arg| raw::Variant::try_from(arg).unwrap())
+            .collect::<Vec<_>>();
+        let mut params = raw::DISPPARAMS {
+            rgvarg: raw_args.as_mut_ptr(),
+            rgdispidNamedArgs: std::ptr::null_mut(),
+            cArgs: raw_args.len() as u32,
+            cNamedArgs: 0,
+        };
+        let mut result = raw::Variant::default();
+        let mut exception = raw::EXCEPINFO::default();
+        let mut arg_err = u32::MAX;
+        unsafe {
+            let hr = disp.invoke(
+                dispid,
+                std::ptr::null(),
+                0,
+                raw::DISPATCH_METHOD,
+                &mut params,
+                &mut result,
+                &mut exception,
+                &mut arg_err,
+            );
+            for arg in raw_args {
+                drop(Variant::from_raw(arg));
+            }
+            (hr, Variant::from_raw(result).unwrap(), exception, arg_err)
+        }
+    }
+
+    #[test]
+    fn names_resolve_to_method_order()
+    {
+        let disp = dispatch();
+        assert_eq!(get_id(&disp, "Add"), (raw::S_OK, 1));
+        assert_eq!(get_id(&disp, "greet"), (raw::S_OK, 2));
+        assert_eq!(get_id(&disp, "FAIL"), (raw::S_OK, 3));
+        assert_eq!(
+            get_id(&disp, "Missing"),
+            (raw::DISP_E_UNKNOWNNAME, raw::DISPID_UNKNOWN)
+        );
+    }
+
+    #[test]
+    fn invoke_converts_arguments()
+    {
+        let disp = dispatch();
+        let (hr, result, _, _) = call(&disp, 1, vec![Variant::I32(1), Variant::I16(2)]);
+        assert_eq!(hr, raw::S_OK);
+        assert_eq!(i32::try_from(result).unwrap(), 3);
+
+        let (hr, result, _, _) = call(&disp, 2, vec![Variant::from("World".to_string())]);
+        assert_eq!(hr, raw::S_OK);
+        assert_eq!(String::try_from(result).unwrap(), "Hello, World!");
+    }
+
+    #[test]
+    fn invoke_rejects_bad_arguments()
+    {
+        let disp = dispatch();
+        let (hr, _, _, _) = call(&disp, 1, vec![Variant::I32(1)]);
+        assert_eq!(hr, raw::DISP_E_BADPARAMCOUNT);
+
+        // The argument error is the index in the reversed argument list.
+        let (hr, _, _, arg_err) = call(
+            &disp,
+            1,
+            vec![Variant::from("1".to_string()), Variant::I32(2)],
+        );
+        assert_eq!(hr, raw::DISP_E_TYPEMISMATCH);
+        assert_eq!(arg_err, 1);
+
+        let (hr, _, _, _) = call(&disp, 4, vec![]);
+        assert_eq!(hr, raw::DISP_E_MEMBERNOTFOUND);
+    }
+
+    #[test]
+    fn invoke_reports_exceptions()
+    {
+        let disp = dispatch();
+        let (hr, _, exception, _) = call(&disp, 3, vec![]);
+        assert_eq!(hr, raw::DISP_E_EXCEPTION);
+        assert_eq!(exception.scode, raw::E_ACCESSDENIED);
+        unsafe {
+            let description = BString::from_ptr(exception.bstrDescription);
+            assert_eq!(description.to_string().unwrap(), "Access denied");
+            drop(BString::from_ptr(exception.bstrSource));
+        }
+    }
+
+    #[test]
+    fn dispatch_variant_round_trip()
+    {
+        let variant = Variant::IDispatch(dispatch());
+        let variant = unsafe {
+            Variant::from_raw(raw::Variant::<AutomationTypeSystem>::try_from(variant).unwrap())
+                .unwrap()
+        };
+        let calculator = ComRc::<dyn ICalculator>::try_from(variant).unwrap();
+        assert_eq!(calculator.add(2, 3), 5);
+    }
+
+    #[test]
+    fn client_invokes_by_name()
+    {
+        let disp = dispatch();
+        assert_eq!(ComItf::get_dispid(&disp, "greet").unwrap(), 2);
+
+        let sum = ComItf::invoke(&disp, "Add", &[Variant::I32(1), Variant::I32(2)]).unwrap();
+        assert_eq!(i32::try_from(sum).unwrap(), 3);
+
+        let greeting = ComItf::invoke(&disp, "greet", &[Variant::from("World".to_string())]);
+        assert_eq!(
+            String::try_from(greeting.unwrap()).unwrap(),
+            "Hello, World!"
+        );
+
+        let missing = ComItf::get(&disp, "Missing").unwrap_err();
+        assert_eq!(missing.hresult, raw::DISP_E_UNKNOWNNAME);
+    }
+
+    #[test]
+    fn client_reports_errors()
+    {
+        let disp = dispatch();
+        let error = ComItf::invoke(&disp, "fail", &[]).unwrap_err();
+        assert_eq!(error.hresult, raw::E_ACCESSDENIED);
+        assert_eq!(error.description(), Some("Access denied"));
+
+        let error = ComItf::invoke(&disp, "add", &[Variant::I32(1), Variant::None]).unwrap_err();
+        assert_eq!(error.hresult, raw::DISP_E_TYPEMISMATCH);
+        assert_eq!(error.description(), Some("Invalid argument at position 1"));
+
+        let error = ComItf::invoke(&disp, "add", &[Variant::I32(1)]).unwrap_err();
+        assert_eq!(error.hresult, raw::DISP_E_BADPARAMCOUNT);
+
+        // Methods cannot be set as properties.
+        let error = ComItf::set(&disp, "add", Variant::I32(1)).unwrap_err();
+        assert_eq!(error.hresult, raw::DISP_E_MEMBERNOTFOUND);
+    }
+
+    /// An `IDispatch` implementation that rejects every call.
+    #[com_class(clsid = None, IDispatch)]
+    struct Mismatch;
+
+    impl IDispatch for Mismatch
+    {
+        unsafe fn get_type_info_count(&self, _count: *mut u32) -> raw::HRESULT
+        {
+            raw::E_NOTIMPL
+        }
+
+        unsafe fn get_type_info(
+            &self,
+            _index: u32,
+            _lcid: u32,
+            _type_info: *mut raw::RawComPtr,
+        ) -> raw::HRESULT
+        {
+            raw::E_NOTIMPL
+        }
+
+        unsafe fn get_ids_of_names(
+            &self,
+            _riid: REFIID,
+            _names: *mut *mut u16,
+            count: u32,
+            _lcid: u32,
+            dispids: *mut raw::DISPID,
+        ) -> raw::HRESULT
+        {
+            std::slice::from_raw_parts_mut(dispids, count as usize).fill(1);
+            raw::S_OK
+        }
+
+        unsafe fn invoke(
+            &self,
+            _dispid: raw::DISPID,
+            _riid: REFIID,
+            _lcid: u32,
+            _flags: u16,
+            _params: *mut raw::DISPPARAMS,
+            _result: *mut raw::Variant<AutomationTypeSystem>,
+            _exception: *mut raw::EXCEPINFO,
+            arg_err: *mut u32,
+        ) -> raw::HRESULT
+        {
+            *arg_err = 0;
+            raw::DISP_E_TYPEMISMATCH
+        }
+    }
+
+    #[test]
+    fn client_reports_argument_errors_without_arguments()
+    {
+        let disp: ComRc<dyn IDispatch> = ComRc::from(ComBox::new(Mismatch));
+        let error = ComItf::invoke(&disp, "Anything", &[]).unwrap_err();
+        assert_eq!(error.hresult, raw::DISP_E_TYPEMISMATCH);
+
+        let error = ComItf::get(&disp, "Anything").unwrap_err();
+        assert_eq!(error.hresult, raw::DISP_E_TYPEMISMATCH);
+
+        let error = ComItf::invoke(&disp, "Anything", &[Variant::I32(1)]).unwrap_err();
+        assert_eq!(error.description(), Some("Invalid argument at position 0"));
+    }
+
+    #[com_interface(dual)]
+    trait ICounter
+    {
+        #[com_property]
+        fn get_count(&self) -> ComResult<i32>;
+
+        #[com_property]
+        fn set_count(&mut self, count: i32) -> ComResult<()>;
+
+        fn increment(&mut self) -> i32;
+    }
+
+    #[com_class(clsid = None, ICounter)]
+    #[derive(Default)]
+    struct Counter
+    {
+        count: i32,
+    }
+
+    impl ICounter for Counter
+    {
+        fn get_count(&self) -> ComResult<i32>
+        {
+            Ok(self.count)
+        }
+
+        fn set_count(&mut self, count: i32) -> ComResult<()>
+        {
+            self.count = count;
+            Ok(())
+        }
+
+        fn increment(&mut self) -> i32
+        {
+            self.count += 1;
+            self.count
+        }
+    }
+
+    #[test]
+    fn properties_share_the_dispid()
+    {
+        let counter: ComRc<dyn ICounter> = ComRc::from(ComBox::new(Counter::default()));
+        let disp: ComRc<dyn IDispatch> = ComItf::query_interface(&counter).unwrap();
+        assert_eq!(get_id(&disp, "Count"), (raw::S_OK, 1));
+        assert_eq!(get_id(&disp, "increment"), (raw::S_OK, 2));
+
+        ComItf::set(&disp, "Count", Variant::I32(41)).unwrap();
+        let count = ComItf::invoke(&disp, "Increment", &[]).unwrap();
+        assert_eq!(i32::try_from(count).unwrap(), 42);
+        let count = ComItf::get(&disp, "Count").unwrap();
+        assert_eq!(i32::try_from(count).unwrap(), 42);
+
+        // The getter does not take arguments and methods cannot be set.
+        let (hr, _, _, _) = call(&disp, 1, vec![Variant::I32(1)]);
+        assert_eq!(hr, raw::DISP_E_BADPARAMCOUNT);
+        let error = ComItf::set(&disp, "Increment", Variant::I32(1)).unwrap_err();
+        assert_eq!(error.hresult, raw::DISP_E_MEMBERNOTFOUND);
+    }
+
+    #[test]
+    fn named_arguments_require_the_dispids()
+    {
+        let counter: ComRc<dyn ICounter> = ComRc::from(ComBox::new(Counter::default()));
+        let disp: ComRc<dyn IDispatch> = ComItf::query_interface(&counter).unwrap();
+
+        let mut value = raw::Variant::try_from(Variant::I32(1)).unwrap();
+        let mut params = raw::DISPPARAMS {
+            rgvarg: &mut value,
+            rgdispidNamedArgs: std::ptr::null_mut(),
+            cArgs: 1,
+            cNamedArgs: 1,
+        };
+        let hr = unsafe {
+            disp.invoke(
+                1,
+                std::ptr::null(),
+                0,
+                raw::DISPATCH_PROPERTYPUT,
+                &mut params,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        assert_eq!(hr, raw::E_POINTER);
+        assert_eq!(
+            i32::try_from(ComItf::get(&disp, "Count").unwrap()).unwrap(),
+            0
+        );
+    }
+}
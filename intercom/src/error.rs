@@ -142,6 +142,14 @@ impl ComError
         hresult: raw::E_INVALIDARG,
         error_info: None,
     };
+    pub const E_UNEXPECTED: ComError = ComError {
+        hresult: raw::E_UNEXPECTED,
+        error_info: None,
+    };
+    pub const CLASS_E_NOAGGREGATION: ComError = ComError {
+        hresult: raw::CLASS_E_NOAGGREGATION,
+        error_info: None,
+    };
     pub const E_ACCESSDENIED: ComError = ComError {
         hresult: raw::E_ACCESSDENIED,
         error_info: None,
@@ -166,6 +174,14 @@ impl ComError
         hresult: raw::RPC_E_TIMEOUT,
         error_info: None,
     };
+    pub const RPC_E_WRONG_THREAD: ComError = ComError {
+        hresult: raw::RPC_E_WRONG_THREAD,
+        error_info: None,
+    };
+    pub const E_ILLEGAL_METHOD_CALL: ComError = ComError {
+        hresult: raw::E_ILLEGAL_METHOD_CALL,
+        error_info: None,
+    };
 }
 
 impl From<ComError> for std::io::Error
@@ -669,11 +685,26 @@ pub mod raw
         E_INVALIDARG = 0x8007_0057
     );
 
+    make_hr!(
+        /// `HRESULT` indicating an allocation failed.
+        E_OUTOFMEMORY = 0x8007_000E
+    );
+
     make_hr!(
         /// `HRESULT` for unavailable CLSID.
         E_CLASSNOTAVAILABLE = 0x8004_0111
     );
 
+    make_hr!(
+        /// `HRESULT` for catastrophic failure, such as a panic in the callee.
+        E_UNEXPECTED = 0x8000_FFFF
+    );
+
+    make_hr!(
+        /// `HRESULT` indicating the class does not support aggregation.
+        CLASS_E_NOAGGREGATION = 0x8004_0110
+    );
+
     // These might be deprecated. They are a bit too specific for cross-platform
     // support. We'll just need to ensure the winapi HRESULTs are compatible.
     make_hr!(E_ACCESSDENIED = 0x8007_0005);
@@ -682,4 +713,63 @@ pub mod raw
     make_hr!(RPC_E_CALL_REJECTED = 0x8001_0001);
     make_hr!(RPC_E_CALL_CANCELED = 0x8001_0002);
     make_hr!(RPC_E_TIMEOUT = 0x8001_011F);
+
+    make_hr!(
+        /// `HRESULT` indicating the object was called on a thread it is not
+        /// bound to.
+        RPC_E_WRONG_THREAD = 0x8001_010E
+    );
+
+    make_hr!(
+        /// `HRESULT` indicating the call conflicts with another call in
+        /// progress on the object.
+        E_ILLEGAL_METHOD_CALL = 0x8000_000E
+    );
+
+    make_hr!(
+        /// `HRESULT` indicating the dispatch member does not exist.
+        DISP_E_MEMBERNOTFOUND = 0x8002_0003
+    );
+
+    make_hr!(
+        /// `HRESULT` indicating a required dispatch argument was not given.
+        DISP_E_PARAMNOTFOUND = 0x8002_0004
+    );
+
+    make_hr!(
+        /// `HRESULT` indicating a dispatch argument has an unsupported type.
+        DISP_E_TYPEMISMATCH = 0x8002_0005
+    );
+
+    make_hr!(
+        /// `HRESULT` indicating a dispatch name was not recognized.
+        DISP_E_UNKNOWNNAME = 0x8002_0006
+    );
+
+    make_hr!(
+        /// `HRESULT` indicating the dispatch member does not support named
+        /// arguments.
+        DISP_E_NONAMEDARGS = 0x8002_0007
+    );
+
+    make_hr!(
+        /// `HRESULT` indicating the dispatch member failed. The details are
+        /// returned in the `EXCEPINFO` structure.
+        DISP_E_EXCEPTION = 0x8002_0009
+    );
+
+    make_hr!(
+        /// `HRESULT` indicating an invalid type info index.
+        DISP_E_BADINDEX = 0x8002_000B
+    );
+
+    make_hr!(
+        /// `HRESULT` indicating the array is locked and cannot be destroyed.
+        DISP_E_ARRAYISLOCKED = 0x8002_000D
+    );
+
+    make_hr!(
+        /// `HRESULT` indicating the wrong number of dispatch arguments.
+        DISP_E_BADPARAMCOUNT = 0x8002_000E
+    );
 }
@@ -1,4 +1,5 @@
 use super::*;
+use crate::attributes::ComInterfaceVariant;
 use crate::raw::HRESULT;
 use crate::type_system::{AutomationTypeSystem, RawTypeSystem};
 
@@ -91,7 +92,12 @@ where
         self_vtable,
         std::any::type_name::<S>()
     );
-    intercom::ComBoxData::<S>::query_interface(combox, riid, out)
+    if let Some((outer, vtable)) = controlling_unknown(combox) {
+        return (vtable.query_interface)(outer, riid, out);
+    }
+    intercom::panic::catch_panic(std::any::type_name::<S>(), "query_interface", || {
+        intercom::ComBoxData::<S>::query_interface(combox, riid, out)
+    })
 }
 
 #[doc(hidden)]
@@ -108,7 +114,12 @@ where
         self_vtable,
         std::any::type_name::<S>()
     );
-    intercom::ComBoxData::<S>::add_ref(combox)
+    if let Some((outer, vtable)) = controlling_unknown(combox) {
+        return (vtable.add_ref)(outer);
+    }
+    intercom::panic::catch_panic_or(std::any::type_name::<S>(), "add_ref", 0, || {
+        intercom::ComBoxData::<S>::add_ref(combox)
+    })
 }
 
 #[doc(hidden)]
@@ -125,7 +136,116 @@ where
         self_vtable,
         std::any::type_name::<S>()
     );
-    intercom::ComBoxData::<S>::release(combox)
+    if let Some((outer, vtable)) = controlling_unknown(combox) {
+        return (vtable.release)(outer);
+    }
+    intercom::panic::catch_panic_or(std::any::type_name::<S>(), "release", 0, || {
+        intercom::ComBoxData::<S>::release(combox)
+    })
+}
+
+type IUnknownVTable = <dyn IUnknown as ComInterfaceVariant<AutomationTypeSystem>>::VTable;
+
+/// Gets the controlling `IUnknown` of an aggregated object.
+///
+/// Returns `None` if the object isn't aggregated, in which case the `IUnknown`
+/// methods are served by the object itself.
+pub(crate) unsafe fn controlling_unknown<S>(
+    combox: &intercom::ComBoxData<S>,
+) -> Option<(crate::raw::RawComPtr, &IUnknownVTable)>
+where
+    S: intercom::attributes::ComClass,
+{
+    combox
+        .outer()
+        .map(|outer| (outer, &**(outer as *const *const IUnknownVTable)))
+}
+
+/// Marker for the non-delegating `IUnknown` of aggregatable classes.
+///
+/// Aggregated objects delegate the `IUnknown` methods of their interfaces to
+/// the controlling outer object. The outer object holds the aggregated object
+/// through a separate non-delegating `IUnknown`, which serves the methods
+/// using the aggregated object itself.
+#[doc(hidden)]
+pub struct NonDelegatingIUnknown;
+
+/// Virtual table of the non-delegating `IUnknown` for the class `S`.
+#[doc(hidden)]
+pub struct NonDelegatingVTable<S>(std::marker::PhantomData<S>);
+
+impl<S> NonDelegatingVTable<S>
+where
+    S: intercom::attributes::ComClassInterface<NonDelegatingIUnknown, AutomationTypeSystem>
+        + intercom::attributes::ComClass,
+{
+    pub const VTABLE: IUnknownVTable = IUnknownVTable {
+        query_interface: non_delegating_query_interface::<S>,
+        add_ref: non_delegating_add_ref::<S>,
+        release: non_delegating_release::<S>,
+    };
+}
+
+unsafe extern "system" fn non_delegating_query_interface<S>(
+    self_vtable: crate::raw::RawComPtr,
+    riid: *const crate::GUID,
+    out: *mut *mut std::ffi::c_void,
+) -> HRESULT
+where
+    S: intercom::attributes::ComClassInterface<NonDelegatingIUnknown, AutomationTypeSystem>
+        + intercom::attributes::ComClass,
+{
+    let combox = S::get_box(self_vtable);
+    log::trace!(
+        "[{:p}, through {:p}] Serving non-delegating {}::query_interface",
+        combox,
+        self_vtable,
+        std::any::type_name::<S>()
+    );
+    intercom::panic::catch_panic(std::any::type_name::<S>(), "query_interface", || {
+        // The non-delegating IUnknown is the identity of the aggregated
+        // object. Any other interface will delegate back to the outer object.
+        let iunknown_iid = <dyn IUnknown as ComInterfaceVariant<AutomationTypeSystem>>::iid();
+        if !riid.is_null() && *riid == *iunknown_iid {
+            *out = self_vtable;
+            intercom::ComBoxData::<S>::add_ref(combox);
+            return crate::raw::S_OK;
+        }
+
+        // The reference for other interfaces is held through the returned
+        // interface pointer, which delegates to the outer object. Move the
+        // reference acquired by the query over to the outer object.
+        let hr = intercom::ComBoxData::<S>::query_interface(combox, riid, out);
+        if hr == crate::raw::S_OK {
+            if let Some((outer, vtable)) = controlling_unknown(combox) {
+                (vtable.add_ref)(outer);
+                intercom::ComBoxData::<S>::release(combox);
+            }
+        }
+        hr
+    })
+}
+
+unsafe extern "system" fn non_delegating_add_ref<S>(self_vtable: crate::raw::RawComPtr) -> u32
+where
+    S: intercom::attributes::ComClassInterface<NonDelegatingIUnknown, AutomationTypeSystem>
+        + intercom::attributes::ComClass,
+{
+    let combox = S::get_box(self_vtable);
+    intercom::panic::catch_panic_or(std::any::type_name::<S>(), "add_ref", 0, || {
+        intercom::ComBoxData::<S>::add_ref(combox)
+    })
+}
+
+unsafe extern "system" fn non_delegating_release<S>(self_vtable: crate::raw::RawComPtr) -> u32
+where
+    S: intercom::attributes::ComClassInterface<NonDelegatingIUnknown, AutomationTypeSystem>
+        + intercom::attributes::ComClass,
+{
+    let combox = S::get_box(self_vtable);
+    intercom::panic::catch_panic_or(std::any::type_name::<S>(), "release", 0, || {
+        intercom::ComBoxData::<S>::release(combox)
+    })
 }
 
 /// The `ISupportErrorInfo` COM interface.
@@ -193,3 +313,124 @@ pub mod isupporterrorinfo
         }
     }
 }
+
+/// The `IDispatch` COM interface.
+///
+/// Provides late bound access to the methods of an object for scripting
+/// languages and other clients that do not use the virtual tables directly.
+///
+/// Intercom implements the interface for the interfaces declared with
+/// `#[com_interface(dual)]`. See the [`dispatch`](crate::dispatch) module for
+/// the details.
+#[com_interface(
+    com_iid = "00020400-0000-0000-C000-000000000046",
+    raw_iid = "11131511-0000-0000-C000-000000000046"
+)]
+pub trait IDispatch: IUnknown
+{
+    /// Gets the number of type information interfaces the object provides.
+    ///
+    /// # Safety
+    ///
+    /// The `count` must be a valid pointer.
+    unsafe fn get_type_info_count(&self, count: *mut u32) -> crate::raw::HRESULT;
+
+    /// Gets the type information of the object.
+    ///
+    /// # Safety
+    ///
+    /// The `type_info` must be a valid pointer.
+    unsafe fn get_type_info(
+        &self,
+        index: u32,
+        lcid: u32,
+        type_info: *mut crate::raw::RawComPtr,
+    ) -> crate::raw::HRESULT;
+
+    /// Maps the member name and the parameter names into dispatch IDs.
+    ///
+    /// # Safety
+    ///
+    /// The `names` must point to `count` null terminated strings and the
+    /// `dispids` must have room for `count` dispatch IDs.
+    unsafe fn get_ids_of_names(
+        &self,
+        riid: crate::REFIID,
+        names: *mut *mut u16,
+        count: u32,
+        lcid: u32,
+        dispids: *mut crate::raw::DISPID,
+    ) -> crate::raw::HRESULT;
+
+    /// Invokes a member of the object.
+    ///
+    /// # Safety
+    ///
+    /// The `params` must be valid dispatch parameters. The remaining pointers
+    /// must be either valid or null.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn invoke(
+        &self,
+        dispid: crate::raw::DISPID,
+        riid: crate::REFIID,
+        lcid: u32,
+        flags: u16,
+        params: *mut crate::raw::DISPPARAMS,
+        result: *mut crate::raw::Variant<AutomationTypeSystem>,
+        exception: *mut crate::raw::EXCEPINFO,
+        arg_err: *mut u32,
+    ) -> crate::raw::HRESULT;
+}
+
+/// The `IWeakReference` COM interface.
+///
+/// Represents a weak reference to a COM object. The weak reference does not
+/// keep the object alive, but it can be resolved into a strong reference for
+/// as long as the object exists.
+#[com_interface(
+    com_iid = "00000037-0000-0000-C000-000000000046",
+    raw_iid = "11111148-0000-0000-C000-000000000046"
+)]
+pub trait IWeakReference
+{
+    /// Resolves the weak reference into a strong interface pointer.
+    ///
+    /// * `riid` - The `IID` of the interface to query from the object.
+    ///
+    /// Returns a null pointer if the object has already been destroyed.
+    ///
+    /// # Safety
+    ///
+    /// The REFIID must be a valid IID pointer.
+    unsafe fn resolve(&self, riid: crate::REFIID) -> crate::ComResult<crate::raw::RawComPtr>;
+}
+
+/// The `IWeakReferenceSource` COM interface.
+///
+/// Provides weak references to the current object. Intercom implements the
+/// interface automatically for all COM classes.
+#[com_interface(
+    com_iid = "00000038-0000-0000-C000-000000000046",
+    raw_iid = "11111149-0000-0000-C000-000000000046",
+    implemented_by = iweakreferencesource
+)]
+pub trait IWeakReferenceSource: IUnknown
+{
+    /// Gets a weak reference to the current object.
+    fn get_weak_reference(&self) -> crate::ComResult<crate::ComRc<dyn IWeakReference>>;
+}
+
+pub mod iweakreferencesource
+{
+    use crate::{combox::ComBoxData, ComRc, ComResult};
+
+    use super::IWeakReference;
+
+    /// Gets a weak reference to the object.
+    pub fn get_weak_reference<S>(this: &ComBoxData<S>) -> ComResult<ComRc<dyn IWeakReference>>
+    where
+        S: intercom::attributes::ComClass,
+    {
+        Ok(crate::weak::WeakReference::create(this))
+    }
+}
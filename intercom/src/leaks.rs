@@ -0,0 +1,223 @@
+//! Tracking of the live COM objects for detecting reference count leaks.
+//!
+//! The tracking is enabled with the `leak-tracking` feature. While enabled,
+//! every `ComBoxData` is registered on construction and unregistered once its
+//! last strong reference is released. The creation backtraces are captured
+//! when backtraces are enabled through the `RUST_BACKTRACE` or
+//! `RUST_LIB_BACKTRACE` environment variables.
+//!
+//! Without the feature no objects are tracked and the functions report an
+//! empty registry.
+//!
+//! The libraries export the registry to the COM clients through the
+//! `IntercomDumpLiveObjects` function implemented by `com_library!`.
+
+use crate::raw::HRESULT;
+use std::fmt;
+
+/// Details of a live COM object.
+#[derive(Debug, Clone)]
+pub struct LiveObject
+{
+    /// Rust type name of the object.
+    pub class_name: &'static str,
+
+    /// Address of the object data.
+    pub address: usize,
+
+    /// Strong reference count at the time the details were gathered.
+    pub ref_count: u32,
+
+    /// Backtrace of the object construction if one was captured.
+    pub backtrace: Option<String>,
+}
+
+impl fmt::Display for LiveObject
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(
+            f,
+            "[{:#x}] {} (references: {})",
+            self.address, self.class_name, self.ref_count
+        )?;
+        if let Some(backtrace) = &self.backtrace {
+            write!(f, "\nCreated at:\n{}", backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks whether the live objects are being tracked.
+pub fn is_enabled() -> bool
+{
+    cfg!(feature = "leak-tracking")
+}
+
+/// Gets the details of the objects that are currently alive.
+///
+/// The objects are listed in the order of their addresses.
+pub fn live_objects() -> Vec<LiveObject>
+{
+    registry::live_objects()
+}
+
+/// Asserts that there are no live objects.
+///
+/// # Panics
+///
+/// Panics and lists the live objects if there are any left.
+#[track_caller]
+pub fn assert_no_live_objects()
+{
+    let objects = live_objects();
+    if !objects.is_empty() {
+        panic!(
+            "{} live object(s) remaining:\n{}",
+            objects.len(),
+            format_objects(&objects)
+        );
+    }
+}
+
+/// Writes the live objects to the standard error stream.
+///
+/// Returns `S_OK` if there were no live objects, `S_FALSE` if there were and
+/// `E_NOTIMPL` if the `leak-tracking` feature is not enabled.
+pub fn dump_live_objects() -> HRESULT
+{
+    if !is_enabled() {
+        return crate::raw::E_NOTIMPL;
+    }
+
+    let objects = live_objects();
+    if objects.is_empty() {
+        return crate::raw::S_OK;
+    }
+
+    eprintln!(
+        "{} live object(s) remaining:\n{}",
+        objects.len(),
+        format_objects(&objects)
+    );
+    crate::raw::S_FALSE
+}
+
+fn format_objects(objects: &[LiveObject]) -> String
+{
+    objects
+        .iter()
+        .map(|obj| obj.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(feature = "leak-tracking")]
+pub(crate) use self::registry::{register, unregister};
+
+#[cfg(feature = "leak-tracking")]
+mod registry
+{
+    use super::LiveObject;
+    use std::backtrace::{Backtrace, BacktraceStatus};
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    struct Entry
+    {
+        class_name: &'static str,
+        ref_count: *const AtomicU32,
+        backtrace: Option<Backtrace>,
+    }
+
+    // The reference count pointer stays valid while the entry is registered
+    // as the objects are unregistered before their memory is released.
+    unsafe impl Send for Entry {}
+
+    static LIVE_OBJECTS: Mutex<BTreeMap<usize, Entry>> = Mutex::new(BTreeMap::new());
+
+    fn objects() -> std::sync::MutexGuard<'static, BTreeMap<usize, Entry>>
+    {
+        // Tracking should keep working even if a panic occurred while the
+        // registry was locked.
+        LIVE_OBJECTS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn register(address: usize, class_name: &'static str, ref_count: &AtomicU32)
+    {
+        let backtrace = Backtrace::capture();
+        let entry = Entry {
+            class_name,
+            ref_count,
+            backtrace: match backtrace.status() {
+                BacktraceStatus::Captured => Some(backtrace),
+                _ => None,
+            },
+        };
+        objects().insert(address, entry);
+    }
+
+    pub fn unregister(address: usize)
+    {
+        objects().remove(&address);
+    }
+
+    pub fn live_objects() -> Vec<LiveObject>
+    {
+        objects()
+            .iter()
+            .map(|(address, entry)| LiveObject {
+                class_name: entry.class_name,
+                address: *address,
+                ref_count: unsafe { (*entry.ref_count).load(Ordering::SeqCst) },
+                backtrace: entry.backtrace.as_ref().map(|bt| bt.to_string()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(not(feature = "leak-tracking"))]
+mod registry
+{
+    pub fn live_objects() -> Vec<super::LiveObject>
+    {
+        vec![]
+    }
+}
+
+#[cfg(all(test, feature = "leak-tracking"))]
+mod test
+{
+    use crate::*;
+
+    #[com_class(clsid = None)]
+    struct LeakTest;
+
+    fn find(address: usize) -> Option<leaks::LiveObject>
+    {
+        leaks::live_objects()
+            .into_iter()
+            .find(|obj| obj.address == address)
+    }
+
+    #[test]
+    fn live_objects_are_tracked()
+    {
+        let combox = ComBox::new(LeakTest);
+        let address = combox.as_ref() as *const _ as usize;
+
+        let obj = find(address).expect("Object was not tracked");
+        assert!(obj.class_name.ends_with("LeakTest"));
+        assert_eq!(obj.ref_count, 1);
+
+        let rc = ComRc::<dyn IUnknown>::from(&combox);
+        assert_eq!(find(address).unwrap().ref_count, 2);
+
+        drop(rc);
+        drop(combox);
+        assert!(find(address).is_none());
+    }
+}
@@ -81,6 +81,8 @@ mod comrc;
 pub use crate::comrc::*;
 mod comitf;
 pub use crate::comitf::*;
+mod weak;
+pub use crate::weak::ComWeak;
 mod strings;
 pub use crate::strings::*;
 mod guid;
@@ -88,8 +90,16 @@ pub use crate::guid::GUID;
 pub mod error;
 pub use crate::error::{load_error, store_error, ComError, ErrorValue};
 pub mod alloc;
+pub mod allocator;
+pub mod conformant;
+pub mod dispatch;
 pub mod interfaces;
+pub mod leaks;
+pub mod module;
+pub mod panic;
 pub mod runtime;
+pub mod safearray;
+pub mod threading;
 mod variant;
 pub use crate::variant::{Variant, VariantError};
 pub mod type_system;
@@ -127,6 +137,7 @@ pub mod registry
 com_module!(
     class intercom::alloc::Allocator,
     class intercom::error::ErrorStore,
+    interface intercom::IIntercomClassFactoryEx,
 );
 
 /// Raw COM pointer type.
@@ -148,7 +159,9 @@ pub mod raw
     use std::os::raw::c_void;
     use std::ptr::NonNull;
 
+    pub use crate::dispatch::raw::*;
     pub use crate::error::raw::*;
+    pub use crate::safearray::raw::*;
     pub use crate::type_system::{ForeignType, TypeSystem};
     pub use crate::variant::raw::*;
 
@@ -160,11 +173,23 @@ pub mod raw
         intercom_attributes::ExternType,
         intercom_attributes::ExternInput,
         intercom_attributes::ExternOutput,
-        intercom_attributes::ForeignType,
     )]
     #[repr(transparent)]
     pub struct BSTR(pub *mut u16);
 
+    impl ForeignType for BSTR
+    {
+        fn type_name() -> &'static str
+        {
+            "BSTR"
+        }
+
+        fn type_desc() -> crate::typelib::TypeDesc
+        {
+            crate::typelib::TypeDesc::String(crate::typelib::StringEncoding::Utf16)
+        }
+    }
+
     #[repr(transparent)]
     #[derive(PartialEq, Eq)]
     pub struct InterfacePtr<TS: TypeSystem, I: ?Sized>
@@ -238,6 +263,16 @@ pub mod raw
         {
             <I as ForeignType>::indirection_level() + 1
         }
+
+        fn type_desc() -> crate::typelib::TypeDesc
+        {
+            crate::typelib::TypeDesc::Interface {
+                name: <I as ForeignType>::type_name().into(),
+                iid: I::iid(TS::key())
+                    .cloned()
+                    .unwrap_or_else(crate::GUID::zero_guid),
+            }
+        }
     }
 }
 
@@ -262,7 +297,9 @@ pub const IID_IErrorInfo: GUID = GUID {
 pub use crate::interfaces::IUnknown;
 // pub use crate::interfaces::__IUnknown_AutomationVtbl as IUnknownVtbl;
 
+pub use crate::interfaces::IDispatch;
 pub use crate::interfaces::ISupportErrorInfo;
+pub use crate::interfaces::{IWeakReference, IWeakReferenceSource};
 // pub use crate::interfaces::__ISupportErrorInfo_AutomationVtbl as ISupportErrorInfoVtbl;
 
 /// Basic COM result type.
@@ -0,0 +1,123 @@
+//! Tracks whether the library is in use by the COM clients.
+//!
+//! The library may be unloaded once there are no live COM objects and no
+//! server locks acquired through `IClassFactory::lock_server`. The
+//! `DllCanUnloadNow` function implemented by `com_library!` reports the state
+//! to the COM infrastructure.
+//!
+//! Each library links its own copy of intercom so the counts only cover the
+//! objects created by the current library.
+
+use crate::raw::{HRESULT, S_FALSE, S_OK};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+static OBJECT_COUNT: AtomicUsize = AtomicUsize::new(0);
+static LOCK_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Set once the unload hook has completed. Using the library clears it.
+static UNLOADED: AtomicBool = AtomicBool::new(false);
+
+/// Serializes the unload hook invocations.
+static UNLOAD_HOOK: Mutex<()> = Mutex::new(());
+
+/// Gets the number of live COM objects in the library.
+///
+/// The objects remain alive while there are strong or weak references to
+/// them.
+pub fn object_count() -> usize
+{
+    OBJECT_COUNT.load(Ordering::SeqCst)
+}
+
+/// Gets the number of server locks held on the library.
+pub fn lock_count() -> usize
+{
+    LOCK_COUNT.load(Ordering::SeqCst)
+}
+
+/// Acquires a server lock that keeps the library loaded.
+pub fn lock()
+{
+    LOCK_COUNT.fetch_add(1, Ordering::SeqCst);
+    UNLOADED.store(false, Ordering::SeqCst);
+}
+
+/// Releases a server lock acquired with `lock`.
+///
+/// Releasing more locks than were acquired is ignored.
+pub fn unlock()
+{
+    let _ = LOCK_COUNT.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+        count.checked_sub(1)
+    });
+}
+
+/// Checks whether the library can be unloaded.
+pub fn can_unload() -> bool
+{
+    object_count() == 0 && lock_count() == 0
+}
+
+/// Checks whether the library can be unloaded and invokes the unload hook.
+///
+/// The hook is invoked the first time the library can be unloaded and again
+/// whenever the library has created objects or acquired server locks since
+/// the previous invocation. A hook that fails is retried on the next call.
+/// Returns `S_OK` only if the hook has completed and the library is still
+/// unused afterwards.
+pub fn can_unload_now<F>(on_unload: F) -> HRESULT
+where
+    F: FnOnce() -> HRESULT,
+{
+    if !can_unload() {
+        return S_FALSE;
+    }
+
+    let _guard = UNLOAD_HOOK.lock().unwrap_or_else(PoisonError::into_inner);
+    if !UNLOADED.swap(true, Ordering::SeqCst) {
+        let hr = on_unload();
+        if hr != S_OK {
+            UNLOADED.store(false, Ordering::SeqCst);
+            return hr;
+        }
+    }
+
+    // The hook may have created objects or acquired locks.
+    match can_unload() {
+        true => S_OK,
+        false => S_FALSE,
+    }
+}
+
+pub(crate) fn object_created()
+{
+    OBJECT_COUNT.fetch_add(1, Ordering::SeqCst);
+    UNLOADED.store(false, Ordering::SeqCst);
+}
+
+pub(crate) fn object_destroyed()
+{
+    OBJECT_COUNT.fetch_sub(1, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn server_locks()
+    {
+        let initial = lock_count();
+
+        lock();
+        lock();
+        assert_eq!(lock_count(), initial + 2);
+        assert!(!can_unload());
+
+        unlock();
+        unlock();
+        assert_eq!(lock_count(), initial);
+    }
+}
@@ -0,0 +1,284 @@
+//! Panic handling at the COM boundary.
+//!
+//! Unwinding across the `extern "system"` functions Intercom generates for
+//! the virtual tables is undefined behavior and will take down the host
+//! process in practice. Instead the generated functions run the user code
+//! through the functions in this module, which catch the panic, store the
+//! panic details as `IErrorInfo` and turn the panic into an error `HRESULT`.
+//!
+//! The library may observe the panics by specifying an `on_panic` hook in the
+//! `com_library!` macro:
+//!
+//! ```
+//! # use intercom::*;
+//! com_library!(on_panic = report_panic, class Calculator);
+//!
+//! fn report_panic(report: &intercom::panic::PanicReport)
+//! {
+//!     eprintln!("{}", report);
+//! }
+//!
+//! # #[com_class(Self)]
+//! # #[derive(Default)]
+//! # struct Calculator;
+//! # #[com_interface]
+//! # impl Calculator {}
+//! # fn main() -> () { }
+//! ```
+
+use crate::raw::{self, HRESULT};
+use crate::{ComError, ErrorValue};
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::panic::{AssertUnwindSafe, Location};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Once, RwLock};
+
+/// Details of a panic caught at the COM boundary.
+#[derive(Debug, Clone)]
+pub struct PanicReport
+{
+    /// Rust type name of the COM class serving the call.
+    pub class_name: &'static str,
+
+    /// Name of the method that panicked.
+    pub method_name: &'static str,
+
+    /// The panic message.
+    pub message: String,
+
+    /// Source location of the panic, if available.
+    pub location: Option<String>,
+
+    /// The `HRESULT` returned to the caller.
+    pub hresult: HRESULT,
+}
+
+impl std::fmt::Display for PanicReport
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        write!(
+            f,
+            "{}::{} panicked: {}",
+            self.class_name, self.method_name, self.message
+        )?;
+        if let Some(location) = &self.location {
+            write!(f, " at {}", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// Panic hook type accepted by `set_panic_handler`.
+pub type PanicHandler = fn(&PanicReport);
+
+static PANIC_HANDLER: RwLock<Option<PanicHandler>> = RwLock::new(None);
+static PANIC_HRESULT: AtomicI32 = AtomicI32::new(raw::E_UNEXPECTED.hr);
+static INSTALL_LOCATION_HOOK: Once = Once::new();
+
+thread_local! {
+    static CATCH_DEPTH: Cell<u32> = const { Cell::new(0) };
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Sets the handler invoked for every panic caught at the COM boundary.
+///
+/// The `com_library!` macro calls this automatically for the `on_panic`
+/// hook.
+pub fn set_panic_handler(handler: PanicHandler)
+{
+    *PANIC_HANDLER
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(handler);
+}
+
+/// Sets the `HRESULT` returned for calls that end in a panic.
+///
+/// Defaults to `E_UNEXPECTED`.
+pub fn set_panic_hresult(hr: HRESULT)
+{
+    PANIC_HRESULT.store(hr.hr, Ordering::Relaxed);
+}
+
+/// Gets the `HRESULT` returned for calls that end in a panic.
+pub fn panic_hresult() -> HRESULT
+{
+    HRESULT::new(PANIC_HRESULT.load(Ordering::Relaxed))
+}
+
+/// Invokes `f`, converting a possible panic into an error value.
+///
+/// The panic details are stored as the current `IErrorInfo` and reported to
+/// the panic handler before the error value is returned.
+pub fn catch_panic<R, F>(class_name: &'static str, method_name: &'static str, f: F) -> R
+where
+    R: ErrorValue,
+    F: FnOnce() -> R,
+{
+    match guarded_call(f) {
+        Ok(r) => r,
+        Err(payload) => {
+            let report = report_panic(class_name, method_name, payload);
+            let error = ComError::new_message(report.hresult, report.to_string());
+            R::from_error(crate::store_error(error))
+        }
+    }
+}
+
+/// Invokes `f`, returning `fallback` if the call panics.
+///
+/// Used for methods such as `IUnknown::add_ref` that have no way to report
+/// errors, but for which returning a dummy value is harmless.
+pub fn catch_panic_or<R, F>(
+    class_name: &'static str,
+    method_name: &'static str,
+    fallback: R,
+    f: F,
+) -> R
+where
+    F: FnOnce() -> R,
+{
+    match guarded_call(f) {
+        Ok(r) => r,
+        Err(payload) => {
+            report_panic(class_name, method_name, payload);
+            fallback
+        }
+    }
+}
+
+/// Invokes `f`, aborting the process if the call panics.
+///
+/// Infallible methods have no channel for reporting errors back to the
+/// caller. Instead of unwinding into foreign code the process is aborted
+/// after the panic has been reported to the panic handler.
+pub fn catch_panic_or_abort<R, F>(class_name: &'static str, method_name: &'static str, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    match guarded_call(f) {
+        Ok(r) => r,
+        Err(payload) => {
+            report_panic(class_name, method_name, payload);
+            std::process::abort();
+        }
+    }
+}
+
+fn guarded_call<R, F>(f: F) -> Result<R, Box<dyn Any + Send>>
+where
+    F: FnOnce() -> R,
+{
+    install_location_hook();
+
+    // The depth is used by the panic hook to figure out whether the panic
+    // location should be recorded for a report.
+    CATCH_DEPTH.with(|depth| depth.set(depth.get() + 1));
+    let result = std::panic::catch_unwind(AssertUnwindSafe(f));
+    CATCH_DEPTH.with(|depth| depth.set(depth.get() - 1));
+
+    result
+}
+
+fn report_panic(
+    class_name: &'static str,
+    method_name: &'static str,
+    payload: Box<dyn Any + Send>,
+) -> PanicReport
+{
+    let message = match payload.downcast::<String>() {
+        Ok(s) => *s,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(s) => s.to_string(),
+            Err(_) => "Box<dyn Any>".to_string(),
+        },
+    };
+
+    let report = PanicReport {
+        class_name,
+        method_name,
+        message,
+        location: PANIC_LOCATION.with(|location| location.borrow_mut().take()),
+        hresult: panic_hresult(),
+    };
+
+    crate::logging::error(|l| l(module_path!(), format_args!("{}", report)));
+
+    // Copy the handler out of the lock before calling it. This avoids
+    // deadlocking if the handler ends up setting a new handler.
+    let handler = *PANIC_HANDLER
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(handler) = handler {
+        // The handler is user code and might panic as well. There's nothing
+        // sensible we can do about that so the panic is ignored.
+        let _ = std::panic::catch_unwind(AssertUnwindSafe(|| handler(&report)));
+    }
+
+    report
+}
+
+/// Chains a panic hook that records the panic location.
+///
+/// The location is only available to the panic hook so it must be stored
+/// for `report_panic` to pick it up after the unwinding is done.
+fn install_location_hook()
+{
+    INSTALL_LOCATION_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if CATCH_DEPTH.with(Cell::get) > 0 {
+                let location = info.location().map(Location::to_string);
+                PANIC_LOCATION.with(|stored| *stored.borrow_mut() = location);
+            }
+            previous(info);
+        }));
+    });
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::*;
+
+    #[com_interface]
+    trait IPanicking
+    {
+        fn fallible(&self) -> ComResult<u32>;
+        fn hresult(&self) -> raw::HRESULT;
+    }
+
+    #[com_class(clsid = None, IPanicking)]
+    #[derive(Default)]
+    struct Panicking;
+
+    impl IPanicking for Panicking
+    {
+        fn fallible(&self) -> ComResult<u32>
+        {
+            panic!("Fallible panic")
+        }
+
+        fn hresult(&self) -> raw::HRESULT
+        {
+            panic!("HRESULT panic")
+        }
+    }
+
+    #[test]
+    fn panic_is_converted_to_error()
+    {
+        let rc: ComRc<dyn IPanicking> = ComRc::from(ComBox::new(Panicking));
+
+        let err = rc.fallible().unwrap_err();
+        assert_eq!(err.hresult, raw::E_UNEXPECTED);
+
+        let description = err.description().expect("Panic did not store error info");
+        assert!(description.contains("fallible"));
+        assert!(description.contains("Fallible panic"));
+        assert!(description.contains("panic.rs"));
+
+        assert_eq!(rc.hresult(), raw::E_UNEXPECTED);
+    }
+}
@@ -297,7 +297,7 @@ pub fn register_class(
         (
             format!("CLSID\\{}\\InprocServer32", cls.clsid),
             "ThreadingModel",
-            "Both".to_string(),
+            cls.threading_model.registry_value().to_string(),
         ),
         (format!("CLSID\\{}\\ProgID", cls.clsid), "", curver),
         (
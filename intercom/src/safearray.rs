@@ -0,0 +1,895 @@
+//! `SAFEARRAY` support for automation arrays.
+//!
+//! `Vec<T>` and `&[T]` parameters of the Automation type system are passed as
+//! one dimensional `SAFEARRAY`s with a zero lower bound. `&mut [T]` parameters
+//! are passed as `[in, out]` array pointers. The callee works on a copy of the
+//! elements, which is written back into the caller's array when the call
+//! completes. The Raw type system uses the conformant arrays in
+//! `intercom::conformant` instead.
+//!
+//! The element type must implement `SafeArrayElement`, which covers the
+//! primitive types, the strings, `Variant` and the interface pointers. The
+//! elements are converted through `Variant`, so arrays of any compatible
+//! element type are accepted as input.
+//!
+//! Arrays stored in a `Variant` are represented as `Variant::Array`, which
+//! is passed as a `VT_ARRAY | VT_VARIANT` array and accepts any supported
+//! element type when received.
+//!
+//! Windows provides the `SAFEARRAY` API in `oleaut32`. On other platforms
+//! Intercom implements the API using the same memory layout as the
+//! `intercom-cpp` headers so the arrays can be shared with C++ clients.
+
+use crate::attributes::ComInterface;
+use crate::raw::{var_type, SafeArray, SAFEARRAY, SAFEARRAYBOUND};
+use crate::type_system::{AutomationTypeSystem, ExternInput, ExternOutput, ExternType};
+use crate::typelib::{Primitive, StringEncoding, TypeDesc};
+use crate::{BString, ComError, ComRc, ComResult, Variant};
+use std::convert::TryFrom;
+use std::time::SystemTime;
+
+type RawVariant = crate::raw::Variant<AutomationTypeSystem>;
+
+/// A type that can be stored in a `SAFEARRAY`.
+pub trait SafeArrayElement: TryFrom<Variant> + Into<Variant>
+{
+    /// The `VARTYPE` of the array elements.
+    const VARTYPE: u16;
+
+    /// The name of the array type in the type library.
+    const ARRAY_TYPE_NAME: &'static str;
+
+    /// Describes the elements as they are stored in the array.
+    fn element_type_desc() -> TypeDesc;
+}
+
+macro_rules! safearray_element {
+    ( $t:ty, $vt:ident, $name:expr, $desc:expr ) => {
+        impl SafeArrayElement for $t
+        {
+            const VARTYPE: u16 = var_type::$vt;
+            const ARRAY_TYPE_NAME: &'static str = concat!("SAFEARRAY(", $name, ")");
+
+            fn element_type_desc() -> TypeDesc
+            {
+                $desc
+            }
+        }
+    };
+}
+
+safearray_element!(i8, I1, "i8", TypeDesc::Primitive(Primitive::I8));
+safearray_element!(u8, UI1, "u8", TypeDesc::Primitive(Primitive::U8));
+safearray_element!(i16, I2, "i16", TypeDesc::Primitive(Primitive::I16));
+safearray_element!(u16, UI2, "u16", TypeDesc::Primitive(Primitive::U16));
+safearray_element!(i32, I4, "i32", TypeDesc::Primitive(Primitive::I32));
+safearray_element!(u32, UI4, "u32", TypeDesc::Primitive(Primitive::U32));
+safearray_element!(i64, I8, "i64", TypeDesc::Primitive(Primitive::I64));
+safearray_element!(u64, UI8, "u64", TypeDesc::Primitive(Primitive::U64));
+safearray_element!(f32, R4, "f32", TypeDesc::Primitive(Primitive::F32));
+safearray_element!(f64, R8, "f64", TypeDesc::Primitive(Primitive::F64));
+safearray_element!(
+    bool,
+    BOOL,
+    "VARIANT_BOOL",
+    TypeDesc::Primitive(Primitive::VariantBool)
+);
+safearray_element!(
+    SystemTime,
+    DATE,
+    "DATE",
+    TypeDesc::Primitive(Primitive::Date)
+);
+safearray_element!(
+    String,
+    BSTR,
+    "BSTR",
+    TypeDesc::String(StringEncoding::Utf16)
+);
+safearray_element!(
+    BString,
+    BSTR,
+    "BSTR",
+    TypeDesc::String(StringEncoding::Utf16)
+);
+safearray_element!(Variant, VARIANT, "VARIANT", TypeDesc::Variant);
+
+impl<I: ComInterface + ?Sized> SafeArrayElement for ComRc<I>
+{
+    const VARTYPE: u16 = var_type::UNKNOWN;
+    const ARRAY_TYPE_NAME: &'static str = "SAFEARRAY(IUnknown*)";
+
+    /// The elements are stored as `IUnknown` pointers.
+    fn element_type_desc() -> TypeDesc
+    {
+        <dyn crate::IUnknown as crate::ForeignType>::type_desc()
+    }
+}
+
+impl<T: SafeArrayElement> SafeArray<T>
+{
+    /// Creates an array from the values.
+    ///
+    /// The caller owns the array and must destroy it.
+    pub fn from_vec(values: Vec<T>) -> ComResult<SafeArray<T>>
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        create_array(T::VARTYPE, values).map(|ptr| unsafe { SafeArray::new(ptr) })
+    }
+
+    /// Copies the elements of the array.
+    ///
+    /// A null array is treated as an empty one.
+    ///
+    /// # Safety
+    ///
+    /// The array must be a valid `SAFEARRAY` or null.
+    pub unsafe fn to_vec(&self) -> ComResult<Vec<T>>
+    {
+        read_array(self.ptr)?
+            .into_iter()
+            .map(|value| {
+                T::try_from(value).map_err(|_| ComError::new_hr(crate::raw::DISP_E_TYPEMISMATCH))
+            })
+            .collect()
+    }
+
+    /// Destroys the array and its elements.
+    ///
+    /// # Safety
+    ///
+    /// The array must be a valid `SAFEARRAY` or null and owned by the caller.
+    pub unsafe fn destroy(self) -> ComResult<()>
+    {
+        ComResult::from(os::SafeArrayDestroy(self.ptr))
+    }
+}
+
+/// Destroys a `SAFEARRAY` parameter when the call completes.
+pub struct SafeArrayLease(*mut SAFEARRAY);
+
+impl Drop for SafeArrayLease
+{
+    fn drop(&mut self)
+    {
+        unsafe {
+            os::SafeArrayDestroy(self.0);
+        }
+    }
+}
+
+impl<T: SafeArrayElement> ExternType<AutomationTypeSystem> for Vec<T>
+{
+    type ForeignType = SafeArray<T>;
+}
+
+unsafe impl<T: SafeArrayElement> ExternInput<AutomationTypeSystem> for Vec<T>
+{
+    type Lease = SafeArrayLease;
+    unsafe fn into_foreign_parameter(self) -> ComResult<(Self::ForeignType, Self::Lease)>
+    {
+        log::trace!("Vec::into_foreign_parameter");
+        let array = SafeArray::from_vec(self)?;
+        Ok((array, SafeArrayLease(array.ptr)))
+    }
+
+    type Owned = Self;
+    unsafe fn from_foreign_parameter(source: Self::ForeignType) -> ComResult<Self::Owned>
+    {
+        log::trace!("Vec::from_foreign_parameter");
+        source.to_vec()
+    }
+}
+
+unsafe impl<T: SafeArrayElement> ExternOutput<AutomationTypeSystem> for Vec<T>
+{
+    fn into_foreign_output(self) -> ComResult<Self::ForeignType>
+    {
+        log::trace!("Vec::into_foreign_output");
+        SafeArray::from_vec(self)
+    }
+
+    unsafe fn from_foreign_output(source: Self::ForeignType) -> ComResult<Self>
+    {
+        log::trace!("Vec::from_foreign_output");
+        let values = source.to_vec();
+        source.destroy()?;
+        values
+    }
+}
+
+impl<'a, T: SafeArrayElement + Clone> ExternType<AutomationTypeSystem> for &'a [T]
+{
+    type ForeignType = SafeArray<T>;
+}
+
+unsafe impl<'a, T: SafeArrayElement + Clone> ExternInput<AutomationTypeSystem> for &'a [T]
+{
+    type Lease = SafeArrayLease;
+    unsafe fn into_foreign_parameter(self) -> ComResult<(Self::ForeignType, Self::Lease)>
+    {
+        log::trace!("&[T]::into_foreign_parameter");
+        let array = SafeArray::from_vec(self.to_vec())?;
+        Ok((array, SafeArrayLease(array.ptr)))
+    }
+
+    type Owned = Vec<T>;
+    unsafe fn from_foreign_parameter(source: Self::ForeignType) -> ComResult<Self::Owned>
+    {
+        log::trace!("&[T]::from_foreign_parameter");
+        source.to_vec()
+    }
+}
+
+impl<'a, T: SafeArrayElement + Clone> ExternType<AutomationTypeSystem> for &'a mut [T]
+{
+    type ForeignType = *mut SafeArray<T>;
+}
+
+unsafe impl<'a, T: SafeArrayElement + Clone> ExternInput<AutomationTypeSystem> for &'a mut [T]
+{
+    type Lease = InOutArrayLease<'a, T>;
+    unsafe fn into_foreign_parameter(self) -> ComResult<(Self::ForeignType, Self::Lease)>
+    {
+        log::trace!("&mut [T]::into_foreign_parameter");
+        let array = Box::into_raw(Box::new(SafeArray::from_vec(self.to_vec())?));
+        Ok((
+            array,
+            InOutArrayLease {
+                values: self,
+                array,
+            },
+        ))
+    }
+
+    type Owned = InOutArray<T>;
+    unsafe fn from_foreign_parameter(source: Self::ForeignType) -> ComResult<Self::Owned>
+    {
+        log::trace!("&mut [T]::from_foreign_parameter");
+        if source.is_null() {
+            return Err(ComError::E_POINTER);
+        }
+        Ok(InOutArray {
+            values: (*source).to_vec()?,
+            target: source,
+        })
+    }
+}
+
+/// Copies the elements of an `[in, out]` array parameter back into the slice
+/// once the call completes.
+pub struct InOutArrayLease<'a, T: SafeArrayElement>
+{
+    values: &'a mut [T],
+    array: *mut SafeArray<T>,
+}
+
+impl<'a, T: SafeArrayElement> Drop for InOutArrayLease<'a, T>
+{
+    fn drop(&mut self)
+    {
+        unsafe {
+            // The callee may have replaced the array. The slice keeps its
+            // length so only the leading elements are copied.
+            let array = Box::from_raw(self.array);
+            match array.to_vec() {
+                Ok(values) => {
+                    for (dst, src) in self.values.iter_mut().zip(values) {
+                        *dst = src;
+                    }
+                }
+                Err(e) => log::warn!("Failed to read the [in, out] array: {}", e),
+            }
+            let _ = array.destroy();
+        }
+    }
+}
+
+/// The elements of an `[in, out]` array parameter.
+///
+/// The elements are written back into the caller's array when the value is
+/// dropped at the end of the call.
+pub struct InOutArray<T: SafeArrayElement>
+{
+    values: Vec<T>,
+    target: *mut SafeArray<T>,
+}
+
+impl<T: SafeArrayElement> std::ops::Deref for InOutArray<T>
+{
+    type Target = [T];
+    fn deref(&self) -> &[T]
+    {
+        &self.values
+    }
+}
+
+impl<T: SafeArrayElement> std::ops::DerefMut for InOutArray<T>
+{
+    fn deref_mut(&mut self) -> &mut [T]
+    {
+        &mut self.values
+    }
+}
+
+impl<T: SafeArrayElement> Drop for InOutArray<T>
+{
+    fn drop(&mut self)
+    {
+        // The caller keeps its original array if the new one cannot be
+        // created.
+        let values = std::mem::take(&mut self.values);
+        match SafeArray::from_vec(values) {
+            Ok(array) => unsafe {
+                let _ = std::ptr::replace(self.target, array).destroy();
+            },
+            Err(e) => log::warn!("Failed to write the [in, out] array: {}", e),
+        }
+    }
+}
+
+/// Creates a one dimensional array of `vt` elements from the values.
+pub(crate) fn create_array(vt: u16, values: Vec<Variant>) -> ComResult<*mut SAFEARRAY>
+{
+    let bound = SAFEARRAYBOUND {
+        cElements: values.len() as u32,
+        lLbound: 0,
+    };
+
+    unsafe {
+        let psa = os::SafeArrayCreate(vt, 1, &bound);
+        if psa.is_null() {
+            return Err(ComError::new_hr(crate::raw::E_OUTOFMEMORY));
+        }
+
+        let result = with_data(psa, |data, size| {
+            for (idx, value) in values.into_iter().enumerate() {
+                write_element(vt, data.add(idx * size), size, value)?;
+            }
+            Ok(())
+        });
+
+        // Destroying the array releases the elements written so far.
+        match result {
+            Ok(()) => Ok(psa),
+            Err(e) => {
+                os::SafeArrayDestroy(psa);
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Copies the elements of a one dimensional array.
+///
+/// # Safety
+///
+/// The array must be a valid `SAFEARRAY` or null.
+pub(crate) unsafe fn read_array(psa: *mut SAFEARRAY) -> ComResult<Vec<Variant>>
+{
+    if psa.is_null() {
+        return Ok(vec![]);
+    }
+    if (*psa).cDims != 1 {
+        return Err(ComError::E_INVALIDARG);
+    }
+
+    let mut vt = 0;
+    ComResult::from(os::SafeArrayGetVartype(psa, &mut vt))?;
+    let count = (*psa).rgsabound[0].cElements as usize;
+    with_data(psa, |data, size| {
+        (0..count)
+            .map(|idx| read_element(vt, data.add(idx * size), size))
+            .collect()
+    })
+}
+
+/// Destroys an array owned by the caller.
+///
+/// # Safety
+///
+/// The array must be a valid `SAFEARRAY` or null.
+pub(crate) unsafe fn destroy_array(psa: *mut SAFEARRAY) -> ComResult<()>
+{
+    ComResult::from(os::SafeArrayDestroy(psa))
+}
+
+/// Locks the array for the duration of the closure.
+unsafe fn with_data<R>(
+    psa: *mut SAFEARRAY,
+    f: impl FnOnce(*mut u8, usize) -> ComResult<R>,
+) -> ComResult<R>
+{
+    let mut data = std::ptr::null_mut();
+    ComResult::from(os::SafeArrayAccessData(psa, &mut data))?;
+    let result = f(data as *mut u8, (*psa).cbElements as usize);
+    os::SafeArrayUnaccessData(psa);
+    result
+}
+
+/// Moves the value into an uninitialized array element.
+unsafe fn write_element(vt: u16, dst: *mut u8, size: usize, value: Variant) -> ComResult<()>
+{
+    let value = RawVariant::try_from(value)?;
+    if vt == var_type::VARIANT {
+        std::ptr::write(dst as *mut RawVariant, value);
+        return Ok(());
+    }
+
+    // Dispatch pointers are valid IUnknown pointers.
+    let compatible =
+        value.vt.0 == vt || (vt == var_type::UNKNOWN && value.vt.0 == var_type::DISPATCH);
+    if !compatible || size > std::mem::size_of_val(&value.data) {
+        drop(Variant::from_raw(value));
+        return Err(ComError::new_hr(crate::raw::DISP_E_TYPEMISMATCH));
+    }
+
+    // The element takes the ownership of the value data.
+    std::ptr::copy_nonoverlapping(&value.data as *const _ as *const u8, dst, size);
+    Ok(())
+}
+
+/// Copies an array element.
+unsafe fn read_element(vt: u16, src: *const u8, size: usize) -> ComResult<Variant>
+{
+    if vt == var_type::VARIANT {
+        return Variant::from_raw_ref(&*(src as *const RawVariant));
+    }
+
+    let mut value = RawVariant::default();
+    if size > std::mem::size_of_val(&value.data) {
+        return Err(ComError::E_INVALIDARG);
+    }
+    value.vt = crate::raw::VariantType::new(vt);
+    std::ptr::copy_nonoverlapping(src, &mut value.data as *mut _ as *mut u8, size);
+    Variant::from_raw_ref(&value)
+}
+
+pub mod raw
+{
+    use crate::type_system::ForeignType;
+    use std::marker::PhantomData;
+    use std::os::raw::c_void;
+
+    /// The bounds of a `SAFEARRAY` dimension.
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug)]
+    #[allow(non_snake_case)]
+    #[allow(clippy::upper_case_acronyms)]
+    pub struct SAFEARRAYBOUND
+    {
+        pub cElements: u32,
+        pub lLbound: i32,
+    }
+
+    /// The `SAFEARRAY` descriptor.
+    ///
+    /// The bounds of the dimensions beyond the first one follow the
+    /// descriptor in memory.
+    #[repr(C)]
+    #[allow(non_snake_case)]
+    #[allow(clippy::upper_case_acronyms)]
+    pub struct SAFEARRAY
+    {
+        pub cDims: u16,
+        pub fFeatures: u16,
+        pub cbElements: u32,
+        pub cLocks: u32,
+        pub pvData: *mut c_void,
+        pub rgsabound: [SAFEARRAYBOUND; 1],
+    }
+
+    /// The array elements are `BSTR`s.
+    pub const FADF_BSTR: u16 = 0x100;
+
+    /// The array elements are `IUnknown` pointers.
+    pub const FADF_UNKNOWN: u16 = 0x200;
+
+    /// The array elements are `IDispatch` pointers.
+    pub const FADF_DISPATCH: u16 = 0x400;
+
+    /// The array elements are `VARIANT`s.
+    pub const FADF_VARIANT: u16 = 0x800;
+
+    /// The `VARTYPE` of the elements is stored before the descriptor.
+    pub const FADF_HAVEVARTYPE: u16 = 0x80;
+
+    /// A `SAFEARRAY` pointer with `T` elements.
+    #[repr(transparent)]
+    pub struct SafeArray<T>
+    {
+        pub ptr: *mut SAFEARRAY,
+        phantom: PhantomData<T>,
+    }
+
+    impl<T> SafeArray<T>
+    {
+        /// # Safety
+        ///
+        /// The pointer must be a valid `SAFEARRAY` with elements compatible
+        /// with `T` or null.
+        pub unsafe fn new(ptr: *mut SAFEARRAY) -> SafeArray<T>
+        {
+            SafeArray {
+                ptr,
+                phantom: PhantomData,
+            }
+        }
+
+        /// Creates a null array pointer.
+        pub fn null() -> SafeArray<T>
+        {
+            SafeArray {
+                ptr: std::ptr::null_mut(),
+                phantom: PhantomData,
+            }
+        }
+
+        pub fn is_null(&self) -> bool
+        {
+            self.ptr.is_null()
+        }
+    }
+
+    impl<T> Clone for SafeArray<T>
+    {
+        fn clone(&self) -> Self
+        {
+            *self
+        }
+    }
+
+    impl<T> Copy for SafeArray<T> {}
+
+    impl<T: super::SafeArrayElement> ForeignType for SafeArray<T>
+    {
+        fn type_name() -> &'static str
+        {
+            T::ARRAY_TYPE_NAME
+        }
+
+        fn type_desc() -> crate::typelib::TypeDesc
+        {
+            crate::typelib::TypeDesc::SafeArray(Box::new(T::element_type_desc()))
+        }
+    }
+}
+
+//////////////////////////////////////////
+// OS specific array allocation.
+
+#[cfg(windows)]
+mod os
+{
+    use crate::raw::{HRESULT, SAFEARRAY, SAFEARRAYBOUND};
+    use std::os::raw::c_void;
+
+    #[link(name = "oleaut32")]
+    extern "system" {
+        #[doc(hidden)]
+        pub fn SafeArrayCreate(vt: u16, dims: u32, bounds: *const SAFEARRAYBOUND)
+            -> *mut SAFEARRAY;
+
+        #[doc(hidden)]
+        pub fn SafeArrayDestroy(psa: *mut SAFEARRAY) -> HRESULT;
+
+        #[doc(hidden)]
+        pub fn SafeArrayGetVartype(psa: *mut SAFEARRAY, vt: *mut u16) -> HRESULT;
+
+        #[doc(hidden)]
+        pub fn SafeArrayAccessData(psa: *mut SAFEARRAY, data: *mut *mut c_void) -> HRESULT;
+
+        #[doc(hidden)]
+        pub fn SafeArrayUnaccessData(psa: *mut SAFEARRAY) -> HRESULT;
+    }
+}
+
+#[cfg(not(windows))]
+#[allow(non_snake_case)]
+mod os
+{
+    use super::RawVariant;
+    use crate::raw::*;
+    use crate::type_system::AutomationTypeSystem;
+    use crate::{BString, ComRc, Variant};
+    use std::os::raw::c_void;
+
+    // The descriptor is preceded by a header that holds the VARTYPE of the
+    // elements in its last four bytes. Matches intercom-cpp/src/posix/safearray.hpp.
+    const HEADER_SIZE: usize = 16;
+
+    fn element_size(vt: u16) -> Option<usize>
+    {
+        Some(match vt {
+            var_type::I1 | var_type::UI1 => 1,
+            var_type::I2 | var_type::UI2 | var_type::BOOL => 2,
+            var_type::I4 | var_type::UI4 | var_type::R4 | var_type::ERROR => 4,
+            var_type::INT | var_type::UINT => 4,
+            var_type::I8 | var_type::UI8 | var_type::R8 | var_type::CY | var_type::DATE => 8,
+            var_type::BSTR | var_type::UNKNOWN | var_type::DISPATCH => {
+                std::mem::size_of::<*mut c_void>()
+            }
+            var_type::VARIANT => std::mem::size_of::<RawVariant>(),
+            _ => return None,
+        })
+    }
+
+    fn features(vt: u16) -> u16
+    {
+        FADF_HAVEVARTYPE
+            | match vt {
+                var_type::BSTR => FADF_BSTR,
+                var_type::UNKNOWN => FADF_UNKNOWN,
+                var_type::DISPATCH => FADF_DISPATCH,
+                var_type::VARIANT => FADF_VARIANT,
+                _ => 0,
+            }
+    }
+
+    unsafe fn element_count(psa: *mut SAFEARRAY) -> usize
+    {
+        let bounds = (*psa).rgsabound.as_ptr();
+        (0..(*psa).cDims as usize)
+            .map(|dim| (*bounds.add(dim)).cElements as usize)
+            .product()
+    }
+
+    #[doc(hidden)]
+    pub unsafe fn SafeArrayCreate(
+        vt: u16,
+        dims: u32,
+        bounds: *const SAFEARRAYBOUND,
+    ) -> *mut SAFEARRAY
+    {
+        let size = match element_size(vt) {
+            Some(size) => size,
+            None => return std::ptr::null_mut(),
+        };
+        if dims == 0 || bounds.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        let descriptor_size = std::mem::size_of::<SAFEARRAY>()
+            + (dims as usize - 1) * std::mem::size_of::<SAFEARRAYBOUND>();
+        let buffer = libc::calloc(1, HEADER_SIZE + descriptor_size) as *mut u8;
+        if buffer.is_null() {
+            return std::ptr::null_mut();
+        }
+        *(buffer.add(HEADER_SIZE - 4) as *mut u32) = u32::from(vt);
+
+        let psa = buffer.add(HEADER_SIZE) as *mut SAFEARRAY;
+        (*psa).cDims = dims as u16;
+        (*psa).fFeatures = features(vt);
+        (*psa).cbElements = size as u32;
+        std::ptr::copy_nonoverlapping(bounds, (*psa).rgsabound.as_mut_ptr(), dims as usize);
+
+        // The elements start zeroed, which is the empty value of every
+        // supported element type.
+        (*psa).pvData = libc::calloc(element_count(psa).max(1), size);
+        if (*psa).pvData.is_null() {
+            libc::free(buffer as *mut c_void);
+            return std::ptr::null_mut();
+        }
+        psa
+    }
+
+    #[doc(hidden)]
+    pub unsafe fn SafeArrayDestroy(psa: *mut SAFEARRAY) -> HRESULT
+    {
+        if psa.is_null() {
+            return S_OK;
+        }
+        if (*psa).cLocks > 0 {
+            return DISP_E_ARRAYISLOCKED;
+        }
+
+        let features = (*psa).fFeatures;
+        let size = (*psa).cbElements as usize;
+        let data = (*psa).pvData as *mut u8;
+        for idx in 0..element_count(psa) {
+            let element = data.add(idx * size);
+            if features & FADF_BSTR != 0 {
+                drop(BString::from_ptr(*(element as *mut *mut u16)));
+            } else if features & (FADF_UNKNOWN | FADF_DISPATCH) != 0 {
+                let ptr = *(element as *mut *mut c_void);
+                if let Some(ptr) =
+                    InterfacePtr::<AutomationTypeSystem, dyn crate::IUnknown>::new(ptr)
+                {
+                    drop(ComRc::wrap(ptr));
+                }
+            } else if features & FADF_VARIANT != 0 {
+                let variant = std::ptr::read(element as *const RawVariant);
+                drop(Variant::from_raw(variant));
+            }
+        }
+
+        libc::free(data as *mut c_void);
+        libc::free((psa as *mut u8).sub(HEADER_SIZE) as *mut c_void);
+        S_OK
+    }
+
+    #[doc(hidden)]
+    pub unsafe fn SafeArrayGetVartype(psa: *mut SAFEARRAY, vt: *mut u16) -> HRESULT
+    {
+        if psa.is_null() || vt.is_null() {
+            return E_INVALIDARG;
+        }
+        if (*psa).fFeatures & FADF_HAVEVARTYPE == 0 {
+            return E_INVALIDARG;
+        }
+        *vt = *((psa as *mut u8).sub(4) as *const u32) as u16;
+        S_OK
+    }
+
+    #[doc(hidden)]
+    pub unsafe fn SafeArrayAccessData(psa: *mut SAFEARRAY, data: *mut *mut c_void) -> HRESULT
+    {
+        if psa.is_null() || data.is_null() {
+            return E_INVALIDARG;
+        }
+        (*psa).cLocks += 1;
+        *data = (*psa).pvData;
+        S_OK
+    }
+
+    #[doc(hidden)]
+    pub unsafe fn SafeArrayUnaccessData(psa: *mut SAFEARRAY) -> HRESULT
+    {
+        if psa.is_null() || (*psa).cLocks == 0 {
+            return E_UNEXPECTED;
+        }
+        (*psa).cLocks -= 1;
+        S_OK
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::raw;
+    use crate::*;
+
+    fn round_trip<T: SafeArrayElement>(values: Vec<T>) -> Vec<T>
+    {
+        unsafe {
+            let array = <Vec<T> as ExternOutput<AutomationTypeSystem>>::into_foreign_output(values)
+                .unwrap();
+            <Vec<T> as ExternOutput<AutomationTypeSystem>>::from_foreign_output(array).unwrap()
+        }
+    }
+
+    #[test]
+    fn primitive_arrays()
+    {
+        assert_eq!(round_trip(vec![1i32, -2, 3]), vec![1, -2, 3]);
+        assert_eq!(round_trip(vec![1u8, 2, 255]), vec![1, 2, 255]);
+        assert_eq!(round_trip(vec![1.5f64, -0.25]), vec![1.5, -0.25]);
+        assert_eq!(round_trip(vec![true, false]), vec![true, false]);
+        assert_eq!(round_trip(Vec::<i64>::new()), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn string_arrays()
+    {
+        let values = vec!["foo".to_string(), String::new(), "bar".to_string()];
+        assert_eq!(round_trip(values.clone()), values);
+    }
+
+    #[test]
+    fn variant_arrays()
+    {
+        let values = vec![
+            Variant::I32(1),
+            Variant::from("foo".to_string()),
+            Variant::Array(vec![Variant::F64(1.0), Variant::None]),
+        ];
+        let result = round_trip(values);
+        assert_eq!(i32::try_from(result[0].clone()).unwrap(), 1);
+        assert_eq!(String::try_from(result[1].clone()).unwrap(), "foo");
+        match &result[2] {
+            Variant::Array(inner) => {
+                assert_eq!(f64::try_from(inner[0].clone()).unwrap(), 1.0);
+                assert!(matches!(inner[1], Variant::None));
+            }
+            other => panic!("Expected an array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn variant_array_raw_round_trip()
+    {
+        let variant = Variant::from(vec![1i32, 2, 3]);
+        let raw_variant = RawVariant::try_from(variant).unwrap();
+        assert_eq!(raw_variant.vt.0, var_type::ARRAY | var_type::VARIANT);
+
+        let variant = unsafe { Variant::from_raw(raw_variant).unwrap() };
+        assert_eq!(Vec::<i32>::try_from(variant).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn typed_arrays_convert_elements()
+    {
+        // An I2 array is accepted where I4 elements are expected.
+        let array = SafeArray::<i16>::from_vec(vec![1, 2]).unwrap();
+        let array = unsafe { SafeArray::<i32>::new(array.ptr) };
+        let values = unsafe { array.to_vec() };
+        unsafe { array.destroy().unwrap() };
+        assert_eq!(values.unwrap(), vec![1, 2]);
+
+        let array = SafeArray::<String>::from_vec(vec!["foo".to_string()]).unwrap();
+        let array = unsafe { SafeArray::<i32>::new(array.ptr) };
+        let error = unsafe { array.to_vec() }.unwrap_err();
+        unsafe { array.destroy().unwrap() };
+        assert_eq!(error.hresult, raw::DISP_E_TYPEMISMATCH);
+    }
+
+    #[test]
+    fn null_array_is_empty()
+    {
+        let values = unsafe { SafeArray::<i32>::null().to_vec().unwrap() };
+        assert!(values.is_empty());
+    }
+
+    #[com_interface]
+    trait IValue
+    {
+        fn value(&self) -> i32;
+    }
+
+    #[com_class(clsid = None, IValue)]
+    struct Value(i32);
+
+    impl IValue for Value
+    {
+        fn value(&self) -> i32
+        {
+            self.0
+        }
+    }
+
+    #[test]
+    fn interface_arrays()
+    {
+        let values: Vec<ComRc<dyn IValue>> = vec![
+            ComRc::from(ComBox::new(Value(1))),
+            ComRc::from(ComBox::new(Value(2))),
+        ];
+        let result = round_trip(values.clone());
+        assert_eq!(
+            result.iter().map(|v| v.value()).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+
+    #[com_interface]
+    trait IInPlace
+    {
+        fn double(&self, values: &mut [f64]) -> ComResult<()>;
+    }
+
+    #[com_class(clsid = None, IInPlace)]
+    struct InPlace;
+
+    impl IInPlace for InPlace
+    {
+        fn double(&self, values: &mut [f64]) -> ComResult<()>
+        {
+            values.iter_mut().for_each(|v| *v *= 2.0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn mutable_slices_are_in_out_arrays()
+    {
+        // Call through the Automation interface only.
+        let in_place: ComRc<dyn IInPlace> = ComRc::from(ComBox::new(InPlace));
+        let automation =
+            ComItf::maybe_new(ComItf::ptr::<AutomationTypeSystem>(&in_place), None).unwrap();
+
+        let mut values = [1.0, -2.5];
+        automation.double(&mut values).unwrap();
+        assert_eq!(values, [2.0, -5.0]);
+    }
+}
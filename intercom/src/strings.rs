@@ -17,6 +17,7 @@ use crate::raw::BSTR;
 use crate::type_system::{
     AutomationTypeSystem, ExternInput, ExternOutput, ExternType, RawTypeSystem,
 };
+use crate::typelib::{StringEncoding, TypeDesc};
 
 #[derive(Debug)]
 pub struct FormatError;
@@ -133,6 +134,8 @@ impl BStr
     pub fn to_string(&self) -> Result<String, FormatError>
     {
         match self.len_bytes() {
+            // Empty strings may be represented by null pointers.
+            0 => Ok(String::new()),
             x if x % 2 == 0 => String::from_utf16(unsafe {
                 std::slice::from_raw_parts(self.as_ptr() as *const u16, x as usize / 2)
             })
@@ -552,6 +555,11 @@ unsafe impl ExternInput<AutomationTypeSystem> for String
 impl ExternType<RawTypeSystem> for String
 {
     type ForeignType = *mut c_char;
+
+    fn type_desc() -> TypeDesc
+    {
+        TypeDesc::String(StringEncoding::Utf8)
+    }
 }
 
 unsafe impl ExternInput<RawTypeSystem> for String
@@ -619,6 +627,11 @@ impl<'a> ExternType<AutomationTypeSystem> for &'a str
 impl<'a> ExternType<RawTypeSystem> for &'a str
 {
     type ForeignType = *mut c_char;
+
+    fn type_desc() -> TypeDesc
+    {
+        TypeDesc::String(StringEncoding::Utf8)
+    }
 }
 
 unsafe impl<'a> ExternInput<AutomationTypeSystem> for &'a str
@@ -668,6 +681,11 @@ impl ExternType<AutomationTypeSystem> for BString
 impl ExternType<RawTypeSystem> for BString
 {
     type ForeignType = *mut c_char;
+
+    fn type_desc() -> TypeDesc
+    {
+        TypeDesc::String(StringEncoding::Utf8)
+    }
 }
 
 unsafe impl ExternInput<AutomationTypeSystem> for BString
@@ -755,6 +773,11 @@ impl ExternType<AutomationTypeSystem> for CString
 impl ExternType<RawTypeSystem> for CString
 {
     type ForeignType = *mut c_char;
+
+    fn type_desc() -> TypeDesc
+    {
+        TypeDesc::String(StringEncoding::Utf8)
+    }
 }
 
 unsafe impl ExternInput<AutomationTypeSystem> for CString
@@ -843,6 +866,11 @@ impl<'a> ExternType<AutomationTypeSystem> for &'a CStr
 impl<'a> ExternType<RawTypeSystem> for &'a CStr
 {
     type ForeignType = *mut c_char;
+
+    fn type_desc() -> TypeDesc
+    {
+        TypeDesc::String(StringEncoding::Utf8)
+    }
 }
 
 unsafe impl<'a> ExternInput<AutomationTypeSystem> for &'a CStr
@@ -893,6 +921,11 @@ impl<'a> ExternType<AutomationTypeSystem> for &'a BStr
 impl<'a> ExternType<RawTypeSystem> for &'a BStr
 {
     type ForeignType = *mut c_char;
+
+    fn type_desc() -> TypeDesc
+    {
+        TypeDesc::String(StringEncoding::Utf8)
+    }
 }
 
 unsafe impl<'a> ExternInput<AutomationTypeSystem> for &'a BStr
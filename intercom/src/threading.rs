@@ -0,0 +1,377 @@
+//! Concurrency policies of the COM classes.
+//!
+//! COM clients may invoke the methods of an object from any thread and may
+//! re-enter the object through callbacks while a previous call is still in
+//! progress. The `#[com_class(threading = "...")]` option selects how the
+//! calls into the instances of the class are guarded:
+//!
+//! - `single` - The object may only be called on the thread that created it.
+//!   Calls from other threads fail with `RPC_E_WRONG_THREAD`.
+//! - `mutex` - The calls are serialized behind a lock. Calls from other
+//!   threads wait until the current call completes.
+//! - `free` - The object may be called from any thread concurrently. This is
+//!   the default.
+//!
+//! In every mode a call that would alias a `&mut self` method with another
+//! call into the same object is rejected with `E_ILLEGAL_METHOD_CALL`. This
+//! covers both the reentrant calls made through callbacks and, in the `free`
+//! mode, the concurrent calls from other threads.
+//!
+//! Methods returning a `ComResult` or an `HRESULT` report the rejection to the
+//! caller as an error. Other methods have no error channel. A rejected call
+//! into such a method panics, which aborts the process after the panic has
+//! been reported to the `on_panic` handler of the library. Methods that may be
+//! called from other threads or reentrantly should return an error type.
+
+use crate::{ComError, ComResult, ExternOutput, ExternType, ForeignType};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// Concurrency policy of a COM class.
+#[derive(ExternType, ExternOutput, ForeignType, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(C)]
+pub enum ThreadingModel
+{
+    /// The object is bound to the thread that created it.
+    Single,
+
+    /// The calls into the object are serialized.
+    Mutex,
+
+    /// The object may be called from any thread.
+    #[default]
+    Free,
+}
+
+impl ThreadingModel
+{
+    /// Gets the `ThreadingModel` value used in the registry and the
+    /// registration-free manifests.
+    pub fn registry_value(self) -> &'static str
+    {
+        match self {
+            ThreadingModel::Single => "Apartment",
+
+            // The free objects are registered as "Both" instead of "Free" so
+            // that the single threaded apartments call them directly. There
+            // are no proxies for the raw interfaces that the "Free" model
+            // would require for these calls.
+            ThreadingModel::Mutex | ThreadingModel::Free => "Both",
+        }
+    }
+}
+
+/// Sentinel borrow count for an exclusive `&mut self` call.
+const EXCLUSIVE: usize = usize::MAX;
+
+/// Identifies the current thread.
+///
+/// `std::thread::ThreadId` cannot be stored in an atomic so the threads are
+/// numbered separately. Zero is never used as a thread number.
+fn current_thread() -> usize
+{
+    static NEXT_THREAD: AtomicUsize = AtomicUsize::new(1);
+    thread_local! {
+        static THREAD: usize = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+    }
+    THREAD.with(|thread| *thread)
+}
+
+/// Tracks the calls in progress on a single object.
+pub struct CallState
+{
+    model: ThreadingModel,
+
+    /// The creating thread for `single` objects or the thread holding the
+    /// lock for `mutex` objects.
+    thread: AtomicUsize,
+
+    /// Number of the `&self` calls in progress or `EXCLUSIVE`.
+    borrows: AtomicUsize,
+
+    /// The lock serializing the calls into `mutex` objects.
+    lock: Option<Mutex<()>>,
+}
+
+impl CallState
+{
+    /// Creates the call state for an object created on the current thread.
+    pub fn new(model: ThreadingModel) -> CallState
+    {
+        CallState {
+            model,
+            thread: AtomicUsize::new(match model {
+                ThreadingModel::Single => current_thread(),
+                _ => 0,
+            }),
+            borrows: AtomicUsize::new(0),
+            lock: match model {
+                ThreadingModel::Mutex => Some(Mutex::new(())),
+                _ => None,
+            },
+        }
+    }
+
+    /// Enters a method call.
+    ///
+    /// The `exclusive` flag specifies whether the method takes `&mut self`.
+    /// The call is in progress until the returned guard is dropped.
+    pub fn enter(&self, exclusive: bool) -> ComResult<CallGuard<'_>>
+    {
+        let current = current_thread();
+        match self.model {
+            ThreadingModel::Single => {
+                if self.thread.load(Ordering::Relaxed) != current {
+                    return Err(ComError::RPC_E_WRONG_THREAD);
+                }
+                self.borrow(exclusive)
+            }
+            ThreadingModel::Mutex => {
+                // Waiting for the lock held by the current thread would
+                // deadlock.
+                if self.thread.load(Ordering::Acquire) == current {
+                    return Err(reentrant_call());
+                }
+
+                // The lock protects no data so a panic during an earlier
+                // call does not invalidate it.
+                let lock = self
+                    .lock
+                    .as_ref()
+                    .expect("Mutex objects have a lock")
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                self.thread.store(current, Ordering::Release);
+                Ok(CallGuard {
+                    state: self,
+                    exclusive: true,
+                    lock: Some(lock),
+                })
+            }
+            ThreadingModel::Free => self.borrow(exclusive),
+        }
+    }
+
+    fn borrow(&self, exclusive: bool) -> ComResult<CallGuard<'_>>
+    {
+        let acquired = if exclusive {
+            self.borrows
+                .compare_exchange(0, EXCLUSIVE, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        } else {
+            self.borrows
+                .fetch_update(Ordering::Acquire, Ordering::Relaxed, |count| match count {
+                    EXCLUSIVE => None,
+                    count => Some(count + 1),
+                })
+                .is_ok()
+        };
+
+        match acquired {
+            true => Ok(CallGuard {
+                state: self,
+                exclusive,
+                lock: None,
+            }),
+            false => Err(reentrant_call()),
+        }
+    }
+}
+
+fn reentrant_call() -> ComError
+{
+    ComError::E_ILLEGAL_METHOD_CALL
+        .with_message("The call conflicts with another call in progress on the object")
+}
+
+/// A method call in progress.
+///
+/// Dropping the guard completes the call.
+pub struct CallGuard<'a>
+{
+    state: &'a CallState,
+    exclusive: bool,
+    lock: Option<MutexGuard<'a, ()>>,
+}
+
+impl<'a> Drop for CallGuard<'a>
+{
+    fn drop(&mut self)
+    {
+        match self.lock.take() {
+            Some(lock) => {
+                // Clear the owner before releasing the lock for the next
+                // thread.
+                self.state.thread.store(0, Ordering::Release);
+                drop(lock);
+            }
+            None if self.exclusive => self.state.borrows.store(0, Ordering::Release),
+            None => {
+                self.state.borrows.fetch_sub(1, Ordering::Release);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn shared_calls_nest()
+    {
+        let state = CallState::new(ThreadingModel::Free);
+        let outer = state.enter(false).unwrap();
+        let inner = state.enter(false).unwrap();
+        assert_eq!(
+            state.enter(true).err().unwrap().hresult,
+            raw::E_ILLEGAL_METHOD_CALL
+        );
+
+        drop(inner);
+        drop(outer);
+        drop(state.enter(true).unwrap());
+    }
+
+    #[test]
+    fn exclusive_calls_reject_reentrancy()
+    {
+        for model in &[
+            ThreadingModel::Single,
+            ThreadingModel::Mutex,
+            ThreadingModel::Free,
+        ] {
+            let state = CallState::new(*model);
+            let call = state.enter(true).unwrap();
+            assert_eq!(
+                state.enter(false).err().unwrap().hresult,
+                raw::E_ILLEGAL_METHOD_CALL
+            );
+            drop(call);
+            drop(state.enter(false).unwrap());
+        }
+    }
+
+    #[test]
+    fn single_rejects_other_threads()
+    {
+        let state = CallState::new(ThreadingModel::Single);
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    assert_eq!(
+                        state.enter(false).err().unwrap().hresult,
+                        raw::RPC_E_WRONG_THREAD
+                    );
+                })
+                .join()
+                .unwrap();
+        });
+        drop(state.enter(false).unwrap());
+    }
+
+    #[test]
+    fn mutex_serializes_calls()
+    {
+        let state = CallState::new(ThreadingModel::Mutex);
+        let active = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        let _call = state.enter(false).unwrap();
+                        assert_eq!(active.fetch_add(1, Ordering::SeqCst), 0);
+                        std::thread::yield_now();
+                        active.fetch_sub(1, Ordering::SeqCst);
+                    }
+                });
+            }
+        });
+    }
+
+    #[com_interface]
+    trait IValue
+    {
+        fn get(&self) -> ComResult<u32>;
+        fn get_through(&self, other: &ComItf<dyn IValue>) -> ComResult<u32>;
+        fn touch(&self) -> raw::HRESULT;
+        fn touch_through(&self, other: &ComItf<dyn IValue>) -> ComResult<i32>;
+        fn peek(&self) -> u32;
+        fn set(&mut self, value: u32);
+    }
+
+    #[com_class(clsid = None, threading = "mutex", IValue)]
+    struct Value(u32);
+
+    impl IValue for Value
+    {
+        fn get(&self) -> ComResult<u32>
+        {
+            Ok(self.0)
+        }
+
+        fn get_through(&self, other: &ComItf<dyn IValue>) -> ComResult<u32>
+        {
+            other.get()
+        }
+
+        fn touch(&self) -> raw::HRESULT
+        {
+            raw::S_OK
+        }
+
+        fn touch_through(&self, other: &ComItf<dyn IValue>) -> ComResult<i32>
+        {
+            Ok(other.touch().hr)
+        }
+
+        fn peek(&self) -> u32
+        {
+            self.0
+        }
+
+        fn set(&mut self, value: u32)
+        {
+            self.0 = value;
+        }
+    }
+
+    #[test]
+    fn reentrant_com_calls_are_rejected()
+    {
+        let value: ComRc<dyn IValue> = ComRc::from(ComBox::new(Value(1)));
+        let other: ComRc<dyn IValue> = ComRc::from(ComBox::new(Value(2)));
+
+        assert_eq!(value.get_through(&other).unwrap(), 2);
+        assert_eq!(
+            value.get_through(&value).err().unwrap().hresult,
+            raw::E_ILLEGAL_METHOD_CALL
+        );
+        assert_eq!(value.get().unwrap(), 1);
+
+        // Methods returning a raw HRESULT report the rejection as well.
+        assert_eq!(value.touch_through(&other).unwrap(), raw::S_OK.hr);
+        assert_eq!(
+            value.touch_through(&value).unwrap(),
+            raw::E_ILLEGAL_METHOD_CALL.hr
+        );
+    }
+
+    #[test]
+    fn methods_without_error_channel_are_guarded()
+    {
+        // The calls that are not rejected succeed even though the methods
+        // could not report a rejection.
+        let value: ComRc<dyn IValue> = ComRc::from(ComBox::new(Value(1)));
+        let mut itf = ComItf::maybe_new(
+            ComItf::ptr::<type_system::AutomationTypeSystem>(&value),
+            ComItf::ptr::<type_system::RawTypeSystem>(&value),
+        )
+        .unwrap();
+        itf.set(5);
+        assert_eq!(itf.peek(), 5);
+        assert_eq!(value.get().unwrap(), 5);
+    }
+}
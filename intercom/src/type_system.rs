@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use crate::typelib::{PointerDirection, Primitive, TypeDesc};
 use crate::ComItf;
 
 #[derive(Debug, Clone, Copy, Hash, PartialOrd, PartialEq, Eq)]
@@ -103,12 +104,29 @@ pub trait ForeignType
     {
         0
     }
+
+    /// Describes the type for the type library.
+    ///
+    /// The types are described as structs by default.
+    fn type_desc() -> TypeDesc
+    {
+        TypeDesc::Struct(Self::type_name().into())
+    }
 }
 
 /// Specifies the raw COM type to use for the specific Rust type.
 pub trait ExternType<TS: TypeSystem>
 {
     type ForeignType: ForeignType;
+
+    /// Describes the foreign type for the type library.
+    ///
+    /// Types that share the foreign representation may refine the
+    /// description, such as the nullable interfaces or the UTF-8 strings.
+    fn type_desc() -> TypeDesc
+    {
+        <Self::ForeignType as ForeignType>::type_desc()
+    }
 }
 
 /// Defines a type that may be used as a parameter type in Intercom interfaces.
@@ -280,7 +298,7 @@ where
 /// A quick macro for implementing ExternInput/etc. for various basic types
 /// that should represent themselves.
 macro_rules! self_extern {
-    ( $t:ty ) => {
+    ( $t:ty => $desc:expr ) => {
         impl ForeignType for $t
         {
             /// The default name is the name of the type.
@@ -288,6 +306,11 @@ macro_rules! self_extern {
             {
                 stringify!($t)
             }
+
+            fn type_desc() -> TypeDesc
+            {
+                $desc
+            }
         }
 
         impl<TS: TypeSystem> ExternType<TS> for $t
@@ -354,33 +377,33 @@ macro_rules! self_extern {
 }
 
 // Define all types that should have built-in Self extern type.
-self_extern!(());
-self_extern!(i8);
-self_extern!(i16);
-self_extern!(i32);
-self_extern!(i64);
-self_extern!(isize);
-self_extern!(u8);
-self_extern!(u16);
-self_extern!(u32);
-self_extern!(u64);
-self_extern!(usize);
-self_extern!(f32);
-self_extern!(f64);
-self_extern!(bool);
+self_extern!(() => TypeDesc::Void);
+self_extern!(i8 => TypeDesc::Primitive(Primitive::I8));
+self_extern!(i16 => TypeDesc::Primitive(Primitive::I16));
+self_extern!(i32 => TypeDesc::Primitive(Primitive::I32));
+self_extern!(i64 => TypeDesc::Primitive(Primitive::I64));
+self_extern!(isize => TypeDesc::Primitive(Primitive::ISize));
+self_extern!(u8 => TypeDesc::Primitive(Primitive::U8));
+self_extern!(u16 => TypeDesc::Primitive(Primitive::U16));
+self_extern!(u32 => TypeDesc::Primitive(Primitive::U32));
+self_extern!(u64 => TypeDesc::Primitive(Primitive::U64));
+self_extern!(usize => TypeDesc::Primitive(Primitive::USize));
+self_extern!(f32 => TypeDesc::Primitive(Primitive::F32));
+self_extern!(f64 => TypeDesc::Primitive(Primitive::F64));
+self_extern!(bool => TypeDesc::Primitive(Primitive::Bool));
 
 use crate::raw::HRESULT;
-self_extern!(HRESULT);
+self_extern!(HRESULT => TypeDesc::Primitive(Primitive::Hresult));
 
 use crate::GUID;
-self_extern!(GUID);
+self_extern!(GUID => TypeDesc::Struct("GUID".into()));
 
-self_extern!(TypeSystemName);
+self_extern!(TypeSystemName => TypeDesc::Enum("TypeSystemName".into()));
 
-self_extern!(std::ffi::c_void);
+self_extern!(std::ffi::c_void => TypeDesc::Void);
 
 macro_rules! extern_ptr {
-    ( $mut:tt ) => {
+    ( $mut:tt => $direction:ident ) => {
         impl<TS: TypeSystem, TPtr: ForeignType + ?Sized> ExternType<TS> for *$mut TPtr
         {
             type ForeignType = Self;
@@ -453,12 +476,67 @@ macro_rules! extern_ptr {
             {
                 <TPtr as ForeignType>::indirection_level() + 1
             }
+
+            fn type_desc() -> TypeDesc
+            {
+                TypeDesc::pointer(
+                    <TPtr as ForeignType>::type_desc(),
+                    PointerDirection::$direction)
+            }
         }
     }
 }
 
-extern_ptr!(mut);
-extern_ptr!(const);
+extern_ptr!(mut => InOut);
+extern_ptr!(const => In);
+
+/// Foreign representation of a C-like enum.
+///
+/// The enum is passed as its discriminant. `#[derive(ExternType)]` uses the
+/// type for the enums so that the conversions from the foreign values can
+/// reject unknown discriminants.
+#[derive(Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct EnumRepr<T, R>
+{
+    pub value: R,
+    phantom: std::marker::PhantomData<T>,
+}
+
+impl<T, R> EnumRepr<T, R>
+{
+    pub fn new(value: R) -> Self
+    {
+        EnumRepr {
+            value,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T, R: Clone> Clone for EnumRepr<T, R>
+{
+    fn clone(&self) -> Self
+    {
+        EnumRepr::new(self.value.clone())
+    }
+}
+
+impl<T, R: Copy> Copy for EnumRepr<T, R> {}
+
+impl<T: ForeignType, R> ForeignType for EnumRepr<T, R>
+{
+    /// The foreign code refers to the enum by its name.
+    fn type_name() -> &'static str
+    {
+        T::type_name()
+    }
+
+    fn type_desc() -> TypeDesc
+    {
+        T::type_desc()
+    }
+}
 
 /// Defines the uninitialized values for out parameters when calling into
 /// Intercom interfaces.
@@ -17,6 +17,14 @@ impl TypeLib
                     let itf = Interface::from_comrc(&ComItf::query_interface(&ty)?)?;
                     TypeInfo::Interface(ComBox::new(itf))
                 }
+                TypeInfoKind::Struct => {
+                    let s = Struct::from_comrc(&ComItf::query_interface(&ty)?)?;
+                    TypeInfo::Struct(ComBox::new(s))
+                }
+                TypeInfoKind::Enum => {
+                    let e = Enum::from_comrc(&ComItf::query_interface(&ty)?)?;
+                    TypeInfo::Enum(ComBox::new(e))
+                }
             });
         }
 
@@ -50,6 +58,8 @@ impl CoClass
             name: ti.get_name()?.into(),
             clsid: ti.get_clsid()?,
             interfaces,
+            threading_model: ti.get_threading_model()?,
+            doc: type_doc(ti)?,
         })
     }
 }
@@ -60,15 +70,64 @@ impl Interface
     {
         let mut variants = vec![];
         for v in 0..ti.get_variant_count()? {
-            variants.push(ComBox::new(InterfaceVariant::from_comrc(
-                &ti.get_variant(v)?,
-            )?));
+            let mut variant = InterfaceVariant::from_comrc(&ti.get_variant(v)?)?;
+            let base = ti.get_base(variant.ts)?;
+            if !base.is_empty() {
+                variant.base = Some(base.into());
+            }
+            variants.push(ComBox::new(variant));
         }
 
         Ok(Interface {
             name: ti.get_name()?.into(),
             options: ti.get_options()?,
             variants,
+            doc: type_doc(ti)?,
+        })
+    }
+}
+
+impl Struct
+{
+    pub fn from_comrc(ti: &ComRc<dyn IIntercomStruct>) -> Result<Struct, TypeLibError>
+    {
+        let mut fields = vec![];
+        for f in 0..ti.get_field_count()? {
+            let (name, ty, offset) = ti.get_field(f)?;
+            fields.push(Field {
+                name: name.into(),
+                ty: parse_type_desc(&ty)?,
+                offset,
+            });
+        }
+
+        Ok(Struct {
+            name: ti.get_name()?.into(),
+            size: ti.get_size()?,
+            fields,
+            doc: type_doc(ti)?,
+        })
+    }
+}
+
+impl Enum
+{
+    pub fn from_comrc(ti: &ComRc<dyn IIntercomEnum>) -> Result<Enum, TypeLibError>
+    {
+        let mut variants = vec![];
+        for v in 0..ti.get_variant_count()? {
+            let (name, value) = ti.get_variant(v)?;
+            variants.push(EnumVariant {
+                name: name.into(),
+                value,
+            });
+        }
+
+        Ok(Enum {
+            name: ti.get_name()?.into(),
+            ty: ti.get_type()?.into(),
+            variants,
+            doc: type_doc(ti)?,
         })
     }
 }
@@ -88,6 +147,7 @@ impl InterfaceVariant
             ts: ti.get_type_system()?,
             iid: ti.get_iid()?,
             methods,
+            base: None,
         })
     }
 }
@@ -98,25 +158,48 @@ impl Method
     {
         let mut parameters = vec![];
         for p in 0..ti.get_parameter_count()? {
-            let (name, ty, indirection_level, direction) = ti.get_parameter(p)?;
+            let (name, ty, direction) = ti.get_parameter(p)?;
             parameters.push(Arg {
                 name: name.into(),
-                ty: ty.into(),
-                indirection_level,
+                ty: parse_type_desc(&ty)?,
                 direction,
+                doc: optional_doc(ti.get_parameter_doc(p)?),
             });
         }
 
-        let (return_ty, return_indirection_level) = ti.get_return_type()?;
         Ok(Method {
             name: ti.get_name()?.into(),
+            kind: ti.get_kind()?,
             return_type: Arg {
                 name: "".into(),
-                ty: return_ty.into(),
-                indirection_level: return_indirection_level,
+                ty: parse_type_desc(&ti.get_return_type()?)?,
                 direction: Direction::Return,
+                doc: None,
             },
             parameters,
+            doc: optional_doc(ti.get_doc()?),
         })
     }
 }
+
+fn parse_type_desc(ty: &str) -> Result<TypeDesc, TypeLibError>
+{
+    ty.parse().map_err(TypeLibError::InvalidTypeDesc)
+}
+
+/// Reads the documentation of the type through `IIntercomTypeInfo`.
+fn type_doc<I: crate::attributes::ComInterface + ?Sized>(
+    ti: &ComRc<I>,
+) -> Result<Option<Cow<'static, str>>, TypeLibError>
+{
+    let info: ComRc<dyn IIntercomTypeInfo> = ComItf::query_interface(ti)?;
+    Ok(optional_doc(info.get_doc()?))
+}
+
+fn optional_doc(doc: String) -> Option<Cow<'static, str>>
+{
+    match doc.is_empty() {
+        true => None,
+        false => Some(doc.into()),
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    com_class, com_interface, type_system::TypeSystemName, ComBox, ComError, ComItf, ComRc,
-    ComResult, ExternOutput, ExternType, ForeignType, GUID,
+    com_class, com_interface, threading::ThreadingModel, type_system::TypeSystemName, ComBox,
+    ComError, ComItf, ComRc, ComResult, ExternOutput, ExternType, ForeignType, GUID,
 };
 
 use std::borrow::Cow;
@@ -10,6 +10,9 @@ pub enum TypeLibError
 {
     #[fail(display = "COM error occurred: {}", _0)]
     ComError(ComError),
+
+    #[fail(display = "Invalid type descriptor: {}", _0)]
+    InvalidTypeDesc(String),
 }
 
 impl From<ComError> for TypeLibError
@@ -21,6 +24,8 @@ impl From<ComError> for TypeLibError
 }
 
 mod from_impls;
+mod type_desc;
+pub use type_desc::*;
 
 // pub mod raw;
 // use raw::*;
@@ -60,6 +65,8 @@ pub enum TypeInfo
 {
     Class(ComBox<CoClass>),
     Interface(ComBox<Interface>),
+    Struct(ComBox<Struct>),
+    Enum(ComBox<Enum>),
 }
 
 #[derive(ExternType, ExternOutput, ForeignType, Debug)]
@@ -68,6 +75,8 @@ pub enum TypeInfoKind
 {
     CoClass,
     Interface,
+    Struct,
+    Enum,
 }
 
 #[com_interface]
@@ -75,6 +84,11 @@ pub trait IIntercomTypeInfo
 {
     fn get_name(&self) -> ComResult<String>;
     fn get_kind(&self) -> ComResult<TypeInfoKind>;
+
+    /// Gets the documentation of the type.
+    ///
+    /// The documentation is empty if the type is not documented.
+    fn get_doc(&self) -> ComResult<String>;
 }
 
 // TypeInfo::CoClass
@@ -86,17 +100,21 @@ pub struct CoClass
     pub name: Cow<'static, str>,
     pub clsid: GUID,
     pub interfaces: Vec<InterfaceRef>,
+    pub threading_model: ThreadingModel,
+
+    /// Documentation of the class.
+    pub doc: Option<Cow<'static, str>>,
 }
 
 #[com_interface]
 pub trait IIntercomCoClass
 {
-    // FIXME: Support interface inheritance
     fn get_name(&self) -> ComResult<String>;
 
     fn get_clsid(&self) -> ComResult<GUID>;
     fn get_interface_count(&self) -> ComResult<u32>;
     fn get_interface_ref(&self, idx: u32, ts: TypeSystemName) -> ComResult<(String, GUID)>;
+    fn get_threading_model(&self) -> ComResult<ThreadingModel>;
 }
 
 // TypeInfo::Interface
@@ -108,6 +126,9 @@ pub struct Interface
     pub name: Cow<'static, str>,
     pub variants: Vec<ComBox<InterfaceVariant>>,
     pub options: InterfaceOptions,
+
+    /// Documentation of the interface.
+    pub doc: Option<Cow<'static, str>>,
 }
 
 #[derive(Debug, Clone, Default, ExternType, ExternOutput, ForeignType)]
@@ -115,6 +136,7 @@ pub struct Interface
 pub struct InterfaceOptions
 {
     pub class_impl_interface: bool,
+    pub dual: bool,
     pub __non_exhaustive: (),
 }
 
@@ -125,17 +147,26 @@ pub struct InterfaceVariant
     pub ts: TypeSystemName,
     pub iid: GUID,
     pub methods: Vec<ComBox<Method>>,
+
+    /// Name of the interface this variant derives from.
+    ///
+    /// `None` for interfaces that have no base interface.
+    pub base: Option<Cow<'static, str>>,
 }
 
 #[com_interface]
 pub trait IIntercomInterface
 {
-    // FIXME: Support interface inheritance
     fn get_name(&self) -> ComResult<String>;
     fn get_options(&self) -> ComResult<InterfaceOptions>;
 
     fn get_variant_count(&self) -> ComResult<u32>;
     fn get_variant(&self, idx: u32) -> ComResult<ComRc<dyn IIntercomInterfaceVariant>>;
+
+    /// Gets the name of the base interface of the given variant.
+    ///
+    /// The name is empty if the variant has no base interface.
+    fn get_base(&self, ts: TypeSystemName) -> ComResult<String>;
 }
 
 #[com_interface]
@@ -147,24 +178,106 @@ pub trait IIntercomInterfaceVariant
     fn get_method(&self, idx: u32) -> ComResult<ComRc<dyn IIntercomMethod>>;
 }
 
+// TypeInfo::Struct
+
+#[com_class(IIntercomTypeInfo, IIntercomStruct)]
+#[derive(Debug)]
+pub struct Struct
+{
+    pub name: Cow<'static, str>,
+
+    /// Size of the struct in bytes.
+    pub size: u32,
+    pub fields: Vec<Field>,
+
+    /// Documentation of the struct.
+    pub doc: Option<Cow<'static, str>>,
+}
+
+#[derive(Debug)]
+pub struct Field
+{
+    pub name: Cow<'static, str>,
+    pub ty: TypeDesc,
+
+    /// Offset of the field from the start of the struct in bytes.
+    pub offset: u32,
+}
+
+#[com_interface]
+pub trait IIntercomStruct
+{
+    fn get_name(&self) -> ComResult<String>;
+    fn get_size(&self) -> ComResult<u32>;
+    fn get_field_count(&self) -> ComResult<u32>;
+
+    /// Gets the name, the `TypeDesc` text and the offset of the field.
+    fn get_field(&self, idx: u32) -> ComResult<(String, String, u32)>;
+}
+
+// TypeInfo::Enum
+
+#[com_class(IIntercomTypeInfo, IIntercomEnum)]
+#[derive(Debug)]
+pub struct Enum
+{
+    pub name: Cow<'static, str>,
+
+    /// Integer type of the discriminants.
+    pub ty: Cow<'static, str>,
+    pub variants: Vec<EnumVariant>,
+
+    /// Documentation of the enum.
+    pub doc: Option<Cow<'static, str>>,
+}
+
+#[derive(Debug)]
+pub struct EnumVariant
+{
+    pub name: Cow<'static, str>,
+    pub value: i64,
+}
+
+#[com_interface]
+pub trait IIntercomEnum
+{
+    fn get_name(&self) -> ComResult<String>;
+    fn get_type(&self) -> ComResult<String>;
+    fn get_variant_count(&self) -> ComResult<u32>;
+    fn get_variant(&self, idx: u32) -> ComResult<(String, i64)>;
+}
+
 // Method
 
 #[com_class(IIntercomMethod)]
 #[derive(Debug)]
 pub struct Method
 {
+    /// Name of the method.
+    ///
+    /// The property accessors are named after the property.
     pub name: Cow<'static, str>,
+    pub kind: MethodKind,
     pub return_type: Arg,
     pub parameters: Vec<Arg>,
+
+    /// Documentation of the method.
+    pub doc: Option<Cow<'static, str>>,
 }
 
 #[derive(Debug)]
 pub struct Arg
 {
     pub name: Cow<'static, str>,
-    pub ty: Cow<'static, str>,
-    pub indirection_level: u32,
+
+    /// Type of the argument as it appears in the COM signature.
+    ///
+    /// The out parameters are pointers to the values.
+    pub ty: TypeDesc,
     pub direction: Direction,
+
+    /// Documentation of the argument.
+    pub doc: Option<Cow<'static, str>>,
 }
 
 #[derive(Debug, Clone, Copy, ExternType, ExternOutput, ForeignType, PartialEq, Eq)]
@@ -177,13 +290,45 @@ pub enum Direction
     Return,
 }
 
+/// The role of a method in the interface.
+#[derive(Debug, Clone, Copy, ExternType, ExternOutput, ForeignType, PartialEq, Eq)]
+#[repr(C)]
+pub enum MethodKind
+{
+    /// A plain method.
+    Method,
+
+    /// A property getter declared with `#[com_property]`.
+    PropertyGet,
+
+    /// A property setter declared with `#[com_property]`.
+    PropertyPut,
+}
+
 #[com_interface]
 pub trait IIntercomMethod
 {
     fn get_name(&self) -> ComResult<String>;
-    fn get_return_type(&self) -> ComResult<(String, u32)>;
+
+    /// Gets the return type in the text form of the `TypeDesc`.
+    fn get_return_type(&self) -> ComResult<String>;
     fn get_parameter_count(&self) -> ComResult<u32>;
-    fn get_parameter(&self, idx: u32) -> ComResult<(String, String, u32, Direction)>;
+
+    /// Gets the name, the `TypeDesc` text and the direction of the parameter.
+    fn get_parameter(&self, idx: u32) -> ComResult<(String, String, Direction)>;
+
+    /// Gets the role of the method.
+    fn get_kind(&self) -> ComResult<MethodKind>;
+
+    /// Gets the documentation of the method.
+    ///
+    /// The documentation is empty if the method is not documented.
+    fn get_doc(&self) -> ComResult<String>;
+
+    /// Gets the documentation of the parameter.
+    ///
+    /// The documentation is empty if the parameter is not documented.
+    fn get_parameter_doc(&self, idx: u32) -> ComResult<String>;
 }
 
 // Impls
@@ -209,6 +354,8 @@ impl IIntercomTypeLib for TypeLib
         Ok(match &self.types[idx as usize] {
             TypeInfo::Class(cls) => ComRc::from(cls),
             TypeInfo::Interface(itf) => ComRc::from(itf),
+            TypeInfo::Struct(s) => ComRc::from(s),
+            TypeInfo::Enum(e) => ComRc::from(e),
         })
     }
 }
@@ -224,6 +371,11 @@ impl IIntercomTypeInfo for CoClass
     {
         Ok(TypeInfoKind::CoClass)
     }
+
+    fn get_doc(&self) -> ComResult<String>
+    {
+        Ok(doc_string(&self.doc))
+    }
 }
 
 impl IIntercomCoClass for CoClass
@@ -253,6 +405,11 @@ impl IIntercomCoClass for CoClass
             },
         ))
     }
+
+    fn get_threading_model(&self) -> ComResult<ThreadingModel>
+    {
+        Ok(self.threading_model)
+    }
 }
 
 impl IIntercomTypeInfo for Interface
@@ -266,6 +423,11 @@ impl IIntercomTypeInfo for Interface
     {
         Ok(TypeInfoKind::Interface)
     }
+
+    fn get_doc(&self) -> ComResult<String>
+    {
+        Ok(doc_string(&self.doc))
+    }
 }
 
 impl IIntercomInterface for Interface
@@ -289,6 +451,20 @@ impl IIntercomInterface for Interface
     {
         Ok(ComRc::from(&self.variants[idx as usize]))
     }
+
+    fn get_base(&self, ts: TypeSystemName) -> ComResult<String>
+    {
+        let variant = self
+            .variants
+            .iter()
+            .find(|v| v.ts == ts)
+            .ok_or(ComError::E_INVALIDARG)?;
+        Ok(variant
+            .base
+            .as_ref()
+            .map(|b| b.to_string())
+            .unwrap_or_default())
+    }
 }
 
 impl IIntercomInterfaceVariant for InterfaceVariant
@@ -314,6 +490,90 @@ impl IIntercomInterfaceVariant for InterfaceVariant
     }
 }
 
+impl IIntercomTypeInfo for Struct
+{
+    fn get_name(&self) -> ComResult<String>
+    {
+        Ok(self.name.to_string())
+    }
+
+    fn get_kind(&self) -> ComResult<TypeInfoKind>
+    {
+        Ok(TypeInfoKind::Struct)
+    }
+
+    fn get_doc(&self) -> ComResult<String>
+    {
+        Ok(doc_string(&self.doc))
+    }
+}
+
+impl IIntercomStruct for Struct
+{
+    fn get_name(&self) -> ComResult<String>
+    {
+        Ok(self.name.to_string())
+    }
+
+    fn get_size(&self) -> ComResult<u32>
+    {
+        Ok(self.size)
+    }
+
+    fn get_field_count(&self) -> ComResult<u32>
+    {
+        Ok(self.fields.len() as u32)
+    }
+
+    fn get_field(&self, idx: u32) -> ComResult<(String, String, u32)>
+    {
+        let field = &self.fields[idx as usize];
+        Ok((field.name.to_string(), field.ty.to_string(), field.offset))
+    }
+}
+
+impl IIntercomTypeInfo for Enum
+{
+    fn get_name(&self) -> ComResult<String>
+    {
+        Ok(self.name.to_string())
+    }
+
+    fn get_kind(&self) -> ComResult<TypeInfoKind>
+    {
+        Ok(TypeInfoKind::Enum)
+    }
+
+    fn get_doc(&self) -> ComResult<String>
+    {
+        Ok(doc_string(&self.doc))
+    }
+}
+
+impl IIntercomEnum for Enum
+{
+    fn get_name(&self) -> ComResult<String>
+    {
+        Ok(self.name.to_string())
+    }
+
+    fn get_type(&self) -> ComResult<String>
+    {
+        Ok(self.ty.to_string())
+    }
+
+    fn get_variant_count(&self) -> ComResult<u32>
+    {
+        Ok(self.variants.len() as u32)
+    }
+
+    fn get_variant(&self, idx: u32) -> ComResult<(String, i64)>
+    {
+        let variant = &self.variants[idx as usize];
+        Ok((variant.name.to_string(), variant.value))
+    }
+}
+
 impl IIntercomMethod for Method
 {
     fn get_name(&self) -> ComResult<String>
@@ -321,42 +581,63 @@ impl IIntercomMethod for Method
         Ok(self.name.to_string())
     }
 
-    fn get_return_type(&self) -> ComResult<(String, u32)>
+    fn get_return_type(&self) -> ComResult<String>
     {
-        Ok((
-            self.return_type.ty.to_string(),
-            self.return_type.indirection_level,
-        ))
+        Ok(self.return_type.ty.to_string())
     }
 
     fn get_parameter_count(&self) -> ComResult<u32>
     {
         Ok(self.parameters.len() as u32)
     }
-    fn get_parameter(&self, idx: u32) -> ComResult<(String, String, u32, Direction)>
+    fn get_parameter(&self, idx: u32) -> ComResult<(String, String, Direction)>
     {
         let arg = &self.parameters[idx as usize];
-        Ok((
-            arg.name.to_string(),
-            arg.ty.to_string(),
-            arg.indirection_level,
-            arg.direction,
-        ))
+        Ok((arg.name.to_string(), arg.ty.to_string(), arg.direction))
+    }
+
+    fn get_kind(&self) -> ComResult<MethodKind>
+    {
+        Ok(self.kind)
+    }
+
+    fn get_doc(&self) -> ComResult<String>
+    {
+        Ok(doc_string(&self.doc))
+    }
+
+    fn get_parameter_doc(&self, idx: u32) -> ComResult<String>
+    {
+        Ok(doc_string(&self.parameters[idx as usize].doc))
     }
 }
 
 impl CoClass
 {
-    pub fn __new(name: Cow<'static, str>, clsid: GUID, interfaces: Vec<InterfaceRef>) -> Self
+    pub fn __new(
+        name: Cow<'static, str>,
+        clsid: GUID,
+        interfaces: Vec<InterfaceRef>,
+        threading_model: ThreadingModel,
+        doc: Option<Cow<'static, str>>,
+    ) -> Self
     {
         Self {
             name,
             clsid,
             interfaces,
+            threading_model,
+            doc,
         }
     }
 }
 
+/// Converts the optional documentation into the string passed through COM.
+fn doc_string(doc: &Option<Cow<'static, str>>) -> String
+{
+    doc.as_ref().map(|d| d.to_string()).unwrap_or_default()
+}
+
 impl TypeLib
 {
     pub fn __new(
@@ -369,10 +650,14 @@ impl TypeLib
         types.sort_by_key(|item| match item {
             TypeInfo::Class(cls) => ("class", cls.as_ref().name.to_string()),
             TypeInfo::Interface(itf) => ("itf", itf.as_ref().name.to_string()),
+            TypeInfo::Struct(s) => ("struct", s.as_ref().name.to_string()),
+            TypeInfo::Enum(e) => ("enum", e.as_ref().name.to_string()),
         });
         types.dedup_by_key(|item| match item {
             TypeInfo::Class(cls) => ("class", cls.as_ref().name.to_string()),
             TypeInfo::Interface(itf) => ("itf", itf.as_ref().name.to_string()),
+            TypeInfo::Struct(s) => ("struct", s.as_ref().name.to_string()),
+            TypeInfo::Enum(e) => ("enum", e.as_ref().name.to_string()),
         });
         TypeLib {
             name,
@@ -382,3 +667,319 @@ impl TypeLib
         }
     }
 }
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::attributes::{ComEnumTypeInfo, ComInterfaceTypeInfo, ComStructTypeInfo};
+    use crate::type_system::{self, RawTypeSystem as Raw};
+
+    /// Sample layout.
+    #[derive(ExternType, ForeignType)]
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct Sample
+    {
+        a: u8,
+        b: u32,
+        c: *const u16,
+        empty: (),
+    }
+
+    #[derive(ExternType, crate::ExternInput, ExternOutput, ForeignType, Debug, PartialEq)]
+    #[repr(u8)]
+    enum Sparse
+    {
+        First = 1,
+        Second,
+        Last = 200,
+    }
+
+    #[crate::com_interface]
+    trait IShape
+    {
+        fn area(&self) -> f64;
+    }
+
+    #[crate::com_interface(base = IShape)]
+    trait IPolygon: IShape
+    {
+        fn corners(&self) -> u32;
+    }
+
+    #[crate::com_interface(base = IPolygon)]
+    trait ISquare: IPolygon
+    {
+        fn side(&self) -> f64;
+    }
+
+    #[crate::com_interface]
+    trait IDescribed
+    {
+        fn name(&self, prefix: &str) -> ComResult<String>;
+        fn total(&self, values: &[u32]) -> ComResult<u32>;
+        fn find(&self, shape: Option<ComRc<dyn IShape>>) -> ComResult<ComRc<dyn IShape>>;
+    }
+
+    #[test]
+    fn methods_describe_their_types()
+    {
+        let types = <dyn IDescribed as ComInterfaceTypeInfo>::gather_type_info();
+        let itf = match &types[..] {
+            [TypeInfo::Interface(itf)] => itf.as_ref(),
+            _ => panic!("Expected an interface"),
+        };
+        let raw = itf
+            .variants
+            .iter()
+            .find(|v| v.ts == TypeSystemName::Raw)
+            .unwrap();
+        let signatures = raw
+            .methods
+            .iter()
+            .map(|m| {
+                let params = m
+                    .parameters
+                    .iter()
+                    .map(|p| format!("{:?} {}: {}", p.direction, p.name, p.ty))
+                    .collect::<Vec<_>>();
+                format!("{}({}) -> {}", m.name, params.join(", "), m.return_type.ty)
+            })
+            .collect::<Vec<_>>();
+
+        let shape_iid = <dyn IShape as crate::attributes::ComInterface>::iid(TypeSystemName::Raw);
+        let shape = format!("interface(IShape, {})", shape_iid.unwrap());
+        assert_eq!(
+            signatures,
+            vec![
+                "name(In prefix: string(utf8), Retval __out: pointer(out, string(utf8))) \
+                 -> HRESULT"
+                    .to_string(),
+                "total(In values: array(u32, values_len), In values_len: u32, \
+                 Retval __out: pointer(out, u32)) -> HRESULT"
+                    .to_string(),
+                format!(
+                    "find(In shape: nullable({0}), Retval __out: pointer(out, {0})) -> HRESULT",
+                    shape
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn interfaces_describe_their_base_chain()
+    {
+        let types = <dyn ISquare as ComInterfaceTypeInfo>::gather_type_info();
+        let bases = types
+            .iter()
+            .map(|t| match t {
+                TypeInfo::Interface(itf) => itf.as_ref(),
+                _ => panic!("Expected an interface"),
+            })
+            .flat_map(|itf| {
+                itf.variants
+                    .iter()
+                    .map(move |v| (itf.name.to_string(), v.base.as_ref().unwrap().to_string()))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bases,
+            vec![
+                ("ISquare".to_string(), "IPolygon".to_string()),
+                ("ISquare".to_string(), "IPolygon".to_string()),
+                ("IPolygon".to_string(), "IShape".to_string()),
+                ("IPolygon".to_string(), "IShape".to_string()),
+                ("IShape".to_string(), "IUnknown".to_string()),
+                ("IShape".to_string(), "IUnknown".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn interface_bases_survive_the_com_round_trip()
+    {
+        let types = <dyn IPolygon as ComInterfaceTypeInfo>::gather_type_info();
+        let itf = match &types[0] {
+            TypeInfo::Interface(itf) => ComRc::<dyn IIntercomInterface>::from(itf),
+            _ => panic!("Expected an interface"),
+        };
+
+        let itf = Interface::from_comrc(&itf).unwrap();
+        assert_eq!(itf.name, "IPolygon");
+        for variant in &itf.variants {
+            assert_eq!(variant.base.as_ref().unwrap(), "IShape");
+        }
+        assert_eq!(itf.get_base(TypeSystemName::Raw).unwrap(), "IShape");
+    }
+
+    #[crate::com_interface]
+    trait IGauge
+    {
+        #[crate::com_property]
+        fn get_level(&self) -> ComResult<f64>;
+
+        #[crate::com_property]
+        fn set_level(&mut self, level: f64) -> ComResult<()>;
+
+        fn reset(&mut self);
+    }
+
+    #[test]
+    fn properties_survive_the_com_round_trip()
+    {
+        let types = <dyn IGauge as ComInterfaceTypeInfo>::gather_type_info();
+        let itf = match &types[..] {
+            [TypeInfo::Interface(itf)] => ComRc::<dyn IIntercomInterface>::from(itf),
+            _ => panic!("Expected an interface"),
+        };
+
+        let itf = Interface::from_comrc(&itf).unwrap();
+        for variant in &itf.variants {
+            let methods = variant
+                .methods
+                .iter()
+                .map(|m| (m.name.to_string(), m.kind))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                methods,
+                vec![
+                    ("level".to_string(), MethodKind::PropertyGet),
+                    ("level".to_string(), MethodKind::PropertyPut),
+                    ("reset".to_string(), MethodKind::Method),
+                ]
+            );
+        }
+    }
+
+    /// A documented interface.
+    #[crate::com_interface]
+    trait IDocumented
+    {
+        /// Scales the value.
+        ///
+        /// # Arguments
+        ///
+        /// - `value` - The value to scale.
+        fn scale(&self, value: f64) -> f64;
+    }
+
+    /// A documented class.
+    #[crate::com_class(IDocumented)]
+    struct Documented;
+
+    impl IDocumented for Documented
+    {
+        fn scale(&self, value: f64) -> f64
+        {
+            value * 2.0
+        }
+    }
+
+    #[test]
+    fn docs_survive_the_com_round_trip()
+    {
+        let types = <Documented as crate::attributes::ComClassTypeInfo>::gather_type_info();
+        let cls = match &types[0] {
+            TypeInfo::Class(cls) => ComRc::<dyn IIntercomCoClass>::from(cls),
+            _ => panic!("Expected a class"),
+        };
+        let itf = match &types[1] {
+            TypeInfo::Interface(itf) => ComRc::<dyn IIntercomInterface>::from(itf),
+            _ => panic!("Expected an interface"),
+        };
+
+        let cls = CoClass::from_comrc(&cls).unwrap();
+        assert_eq!(cls.doc.unwrap(), "A documented class.");
+
+        let itf = Interface::from_comrc(&itf).unwrap();
+        assert_eq!(itf.doc.unwrap(), "A documented interface.");
+        for variant in &itf.variants {
+            let method = &variant.methods[0];
+            assert_eq!(method.doc.as_ref().unwrap(), "Scales the value.");
+            assert_eq!(
+                method.parameters[0].doc.as_ref().unwrap(),
+                "The value to scale."
+            );
+            assert_eq!(method.return_type.doc, None);
+        }
+    }
+
+    #[test]
+    fn enums_describe_their_discriminants()
+    {
+        let types = <Sparse as ComEnumTypeInfo>::gather_type_info();
+        let e = match &types[..] {
+            [TypeInfo::Enum(e)] => e.as_ref(),
+            _ => panic!("Expected an enum"),
+        };
+
+        assert_eq!(e.name, "Sparse");
+        assert_eq!(e.ty, "u8");
+        let variants = e
+            .variants
+            .iter()
+            .map(|v| (v.name.as_ref(), v.value))
+            .collect::<Vec<_>>();
+        assert_eq!(variants, vec![("First", 1), ("Second", 2), ("Last", 200)]);
+    }
+
+    #[test]
+    fn enums_reject_unknown_discriminants()
+    {
+        type Repr = type_system::EnumRepr<Sparse, u8>;
+
+        let foreign = type_system::ExternOutput::<Raw>::into_foreign_output(Sparse::Last);
+        assert_eq!(foreign.unwrap().value, 200);
+
+        unsafe {
+            let value = <Sparse as type_system::ExternInput<Raw>>::from_foreign_parameter;
+            assert_eq!(value(Repr::new(2)).unwrap(), Sparse::Second);
+            assert_eq!(
+                value(Repr::new(0)).unwrap_err().hresult,
+                crate::raw::E_INVALIDARG
+            );
+
+            let value = <Sparse as type_system::ExternOutput<Raw>>::from_foreign_output;
+            assert_eq!(
+                value(Repr::new(3)).unwrap_err().hresult,
+                crate::raw::E_INVALIDARG
+            );
+        }
+    }
+
+    #[test]
+    fn structs_describe_their_layout()
+    {
+        let types = <Sample as ComStructTypeInfo>::gather_type_info();
+        let s = match &types[..] {
+            [TypeInfo::Struct(s)] => s.as_ref(),
+            _ => panic!("Expected a struct"),
+        };
+
+        assert_eq!(s.name, "Sample");
+        assert_eq!(s.doc.as_deref(), Some("Sample layout."));
+        assert_eq!(s.size as usize, std::mem::size_of::<Sample>());
+
+        // The zero sized fields are omitted.
+        let fields = s
+            .fields
+            .iter()
+            .map(|f| (f.name.as_ref(), f.ty.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("a", TypeDesc::Primitive(Primitive::U8)),
+                ("b", TypeDesc::Primitive(Primitive::U32)),
+                (
+                    "c",
+                    TypeDesc::pointer(TypeDesc::Primitive(Primitive::U16), PointerDirection::In)
+                ),
+            ]
+        );
+        assert_eq!(s.fields[0].offset, 0);
+        assert_eq!(s.fields[1].offset, 4);
+        assert_eq!(s.fields[2].offset as usize, std::mem::size_of::<usize>());
+    }
+}
@@ -0,0 +1,390 @@
+//! Structured descriptions of the foreign types.
+//!
+//! The descriptors are produced by the `ExternType` implementations and
+//! describe the types as they appear in the COM signatures. The type library
+//! interfaces carry the descriptors in the text form produced by the
+//! `Display` implementation, which `FromStr` parses back.
+
+use crate::GUID;
+use std::borrow::Cow;
+use std::fmt;
+
+/// Description of a type in a COM signature.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeDesc
+{
+    /// No value. Appears as a return type or behind a pointer.
+    Void,
+
+    /// A primitive value.
+    Primitive(Primitive),
+
+    /// A string pointer.
+    String(StringEncoding),
+
+    /// A `VARIANT` value.
+    Variant,
+
+    /// An interface pointer.
+    Interface
+    {
+        name: Cow<'static, str>, iid: GUID
+    },
+
+    /// A `#[repr(C)]` struct passed by value.
+    Struct(Cow<'static, str>),
+
+    /// A C-like enum passed as its discriminant.
+    Enum(Cow<'static, str>),
+
+    /// A pointer to a conformant array.
+    ///
+    /// The length is the name of the parameter that holds the number of
+    /// elements.
+    Array
+    {
+        element: Box<TypeDesc>,
+        length: Cow<'static, str>,
+    },
+
+    /// A `SAFEARRAY` pointer.
+    SafeArray(Box<TypeDesc>),
+
+    /// A pointer to a value.
+    Pointer
+    {
+        pointee: Box<TypeDesc>,
+        direction: PointerDirection,
+    },
+
+    /// A pointer value that may be null.
+    Nullable(Box<TypeDesc>),
+}
+
+/// Primitive value types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primitive
+{
+    Bool,
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    ISize,
+    USize,
+    F32,
+    F64,
+    Hresult,
+
+    /// `VARIANT_BOOL` used by the automation containers.
+    VariantBool,
+
+    /// OLE automation `DATE`.
+    Date,
+}
+
+/// Encoding of the string values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringEncoding
+{
+    /// Length prefixed UTF-16 `BSTR`.
+    Utf16,
+
+    /// Null terminated UTF-8 string.
+    Utf8,
+}
+
+/// The data flow through a pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerDirection
+{
+    /// The callee only reads through the pointer.
+    In,
+
+    /// The callee writes the value for the caller.
+    Out,
+
+    /// The callee may both read and write the value.
+    InOut,
+}
+
+impl TypeDesc
+{
+    /// Describes a pointer to the value.
+    pub fn pointer(pointee: TypeDesc, direction: PointerDirection) -> TypeDesc
+    {
+        TypeDesc::Pointer {
+            pointee: Box::new(pointee),
+            direction,
+        }
+    }
+
+    /// Describes a conformant array of the elements.
+    pub fn array(element: TypeDesc, length: &'static str) -> TypeDesc
+    {
+        TypeDesc::Array {
+            element: Box::new(element),
+            length: length.into(),
+        }
+    }
+
+    /// Describes a value that may be null.
+    pub fn nullable(inner: TypeDesc) -> TypeDesc
+    {
+        match inner {
+            TypeDesc::Nullable(_) => inner,
+            inner => TypeDesc::Nullable(Box::new(inner)),
+        }
+    }
+}
+
+impl Primitive
+{
+    const ALL: [Primitive; 16] = [
+        Primitive::Bool,
+        Primitive::I8,
+        Primitive::U8,
+        Primitive::I16,
+        Primitive::U16,
+        Primitive::I32,
+        Primitive::U32,
+        Primitive::I64,
+        Primitive::U64,
+        Primitive::ISize,
+        Primitive::USize,
+        Primitive::F32,
+        Primitive::F64,
+        Primitive::Hresult,
+        Primitive::VariantBool,
+        Primitive::Date,
+    ];
+
+    /// The name of the type.
+    ///
+    /// The Rust types use the Rust names and the COM specific types use the
+    /// COM names.
+    pub fn name(self) -> &'static str
+    {
+        match self {
+            Primitive::Bool => "bool",
+            Primitive::I8 => "i8",
+            Primitive::U8 => "u8",
+            Primitive::I16 => "i16",
+            Primitive::U16 => "u16",
+            Primitive::I32 => "i32",
+            Primitive::U32 => "u32",
+            Primitive::I64 => "i64",
+            Primitive::U64 => "u64",
+            Primitive::ISize => "isize",
+            Primitive::USize => "usize",
+            Primitive::F32 => "f32",
+            Primitive::F64 => "f64",
+            Primitive::Hresult => "HRESULT",
+            Primitive::VariantBool => "VARIANT_BOOL",
+            Primitive::Date => "DATE",
+        }
+    }
+}
+
+impl fmt::Display for TypeDesc
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            TypeDesc::Void => write!(f, "void"),
+            TypeDesc::Primitive(p) => write!(f, "{}", p.name()),
+            TypeDesc::String(StringEncoding::Utf16) => write!(f, "string(utf16)"),
+            TypeDesc::String(StringEncoding::Utf8) => write!(f, "string(utf8)"),
+            TypeDesc::Variant => write!(f, "variant"),
+            TypeDesc::Interface { name, iid } => write!(f, "interface({}, {})", name, iid),
+            TypeDesc::Struct(name) => write!(f, "struct({})", name),
+            TypeDesc::Enum(name) => write!(f, "enum({})", name),
+            TypeDesc::Array { element, length } => write!(f, "array({}, {})", element, length),
+            TypeDesc::SafeArray(element) => write!(f, "safearray({})", element),
+            TypeDesc::Pointer { pointee, direction } => {
+                let direction = match direction {
+                    PointerDirection::In => "in",
+                    PointerDirection::Out => "out",
+                    PointerDirection::InOut => "inout",
+                };
+                write!(f, "pointer({}, {})", direction, pointee)
+            }
+            TypeDesc::Nullable(inner) => write!(f, "nullable({})", inner),
+        }
+    }
+}
+
+impl std::str::FromStr for TypeDesc
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let mut parser = Parser { input: s };
+        let desc = parser.type_desc()?;
+        match parser.input.trim() {
+            "" => Ok(desc),
+            rest => Err(format!("Unexpected '{}' in type '{}'", rest, s)),
+        }
+    }
+}
+
+/// Recursive descent parser for the text form of the descriptors.
+struct Parser<'a>
+{
+    input: &'a str,
+}
+
+impl<'a> Parser<'a>
+{
+    fn type_desc(&mut self) -> Result<TypeDesc, String>
+    {
+        let keyword = self.token()?;
+        if let Some(p) = Primitive::ALL.iter().find(|p| p.name() == keyword) {
+            return Ok(TypeDesc::Primitive(*p));
+        }
+
+        Ok(match keyword {
+            "void" => TypeDesc::Void,
+            "variant" => TypeDesc::Variant,
+            "string" => {
+                self.expect("(")?;
+                let encoding = match self.token()? {
+                    "utf16" => StringEncoding::Utf16,
+                    "utf8" => StringEncoding::Utf8,
+                    other => return Err(format!("Unknown string encoding '{}'", other)),
+                };
+                self.expect(")")?;
+                TypeDesc::String(encoding)
+            }
+            "interface" => {
+                self.expect("(")?;
+                let name = self.token()?.to_string();
+                self.expect(",")?;
+                let iid = GUID::parse(self.token()?)?;
+                self.expect(")")?;
+                TypeDesc::Interface {
+                    name: name.into(),
+                    iid,
+                }
+            }
+            "struct" | "enum" => {
+                self.expect("(")?;
+                let name = Cow::from(self.token()?.to_string());
+                self.expect(")")?;
+                match keyword {
+                    "struct" => TypeDesc::Struct(name),
+                    _ => TypeDesc::Enum(name),
+                }
+            }
+            "array" => {
+                self.expect("(")?;
+                let element = self.type_desc()?;
+                self.expect(",")?;
+                let length = self.token()?.to_string();
+                self.expect(")")?;
+                TypeDesc::Array {
+                    element: Box::new(element),
+                    length: length.into(),
+                }
+            }
+            "safearray" => {
+                self.expect("(")?;
+                let element = self.type_desc()?;
+                self.expect(")")?;
+                TypeDesc::SafeArray(Box::new(element))
+            }
+            "pointer" => {
+                self.expect("(")?;
+                let direction = match self.token()? {
+                    "in" => PointerDirection::In,
+                    "out" => PointerDirection::Out,
+                    "inout" => PointerDirection::InOut,
+                    other => return Err(format!("Unknown pointer direction '{}'", other)),
+                };
+                self.expect(",")?;
+                let pointee = self.type_desc()?;
+                self.expect(")")?;
+                TypeDesc::pointer(pointee, direction)
+            }
+            "nullable" => {
+                self.expect("(")?;
+                let inner = self.type_desc()?;
+                self.expect(")")?;
+                TypeDesc::Nullable(Box::new(inner))
+            }
+            other => return Err(format!("Unknown type '{}'", other)),
+        })
+    }
+
+    /// Takes the next word or punctuation character.
+    fn token(&mut self) -> Result<&'a str, String>
+    {
+        self.input = self.input.trim_start();
+        let len = match self.input.chars().next() {
+            None => return Err("Unexpected end of type".to_string()),
+            Some(c @ '(') | Some(c @ ')') | Some(c @ ',') => c.len_utf8(),
+            Some(_) => self.input.find(['(', ')', ',']).unwrap_or(self.input.len()),
+        };
+        let (token, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(token.trim_end())
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String>
+    {
+        match self.token()? {
+            token if token == expected => Ok(()),
+            token => Err(format!("Expected '{}', found '{}'", expected, token)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn type_descs_round_trip_through_text()
+    {
+        let descs = vec![
+            TypeDesc::Void,
+            TypeDesc::Primitive(Primitive::Hresult),
+            TypeDesc::String(StringEncoding::Utf8),
+            TypeDesc::Variant,
+            TypeDesc::pointer(
+                TypeDesc::nullable(TypeDesc::Interface {
+                    name: "IFoo".into(),
+                    iid: GUID::parse("12345678-1234-1234-1234-123456789ABC").unwrap(),
+                }),
+                PointerDirection::Out,
+            ),
+            TypeDesc::pointer(
+                TypeDesc::array(TypeDesc::Struct("Point".into()), "points_len"),
+                PointerDirection::Out,
+            ),
+            TypeDesc::SafeArray(Box::new(TypeDesc::Enum("Color".into()))),
+            TypeDesc::pointer(TypeDesc::Void, PointerDirection::InOut),
+        ];
+
+        for desc in descs {
+            let text = desc.to_string();
+            assert_eq!(text.parse::<TypeDesc>(), Ok(desc), "{}", text);
+        }
+    }
+
+    #[test]
+    fn invalid_type_descs_are_rejected()
+    {
+        assert!("".parse::<TypeDesc>().is_err());
+        assert!("i128".parse::<TypeDesc>().is_err());
+        assert!("pointer(sideways, i32)".parse::<TypeDesc>().is_err());
+        assert!("struct(Point".parse::<TypeDesc>().is_err());
+        assert!("i32 i32".parse::<TypeDesc>().is_err());
+    }
+}
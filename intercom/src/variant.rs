@@ -27,6 +27,8 @@ pub enum Variant
     String(IntercomString),
     SystemTime(SystemTime),
     IUnknown(ComRc<dyn IUnknown>),
+    IDispatch(ComRc<dyn IDispatch>),
+    Array(Vec<Variant>),
 }
 
 impl Variant
@@ -50,6 +52,8 @@ impl Variant
             Variant::SystemTime(..) => raw::var_type::DATE,
             Variant::Currency(..) => raw::var_type::CY,
             Variant::IUnknown(..) => raw::var_type::UNKNOWN,
+            Variant::IDispatch(..) => raw::var_type::DISPATCH,
+            Variant::Array(..) => raw::var_type::ARRAY | raw::var_type::VARIANT,
         }
     }
 
@@ -58,6 +62,17 @@ impl Variant
     /// The source variant must be a valid variant.
     pub unsafe fn from_raw<TS: TypeSystem>(src: raw::Variant<TS>) -> ComResult<Self>
     {
+        // The arrays are copied into the Variant and destroyed.
+        if src.vt.0 & raw::var_type::ARRAY != 0 {
+            let parray = match src.vt.0 & raw::var_type::BYREF {
+                0 => src.data.parray,
+                _ => *src.data.pparray,
+            };
+            let values = crate::safearray::read_array(parray);
+            crate::safearray::destroy_array(parray)?;
+            return Ok(Variant::Array(values?));
+        }
+
         Ok(if src.vt.0 & raw::var_type::BYREF == 0 {
             match src.vt.0 & raw::var_type::TYPEMASK {
                 raw::var_type::EMPTY | raw::var_type::NULL => Variant::None,
@@ -81,6 +96,10 @@ impl Variant
                     Some(ptr) => Variant::IUnknown(ComRc::wrap(ptr)),
                     None => Variant::None,
                 },
+                raw::var_type::DISPATCH => match src.data.pdispVal {
+                    Some(ptr) => Variant::IDispatch(ComRc::wrap(ptr)),
+                    None => Variant::None,
+                },
                 _ => return Err(ComError::E_NOTIMPL),
             }
         } else {
@@ -106,10 +125,38 @@ impl Variant
                     Some(ptr) => Variant::IUnknown(ComRc::wrap(ptr)),
                     None => Variant::None,
                 },
+                raw::var_type::DISPATCH => match *src.data.ppdispVal {
+                    Some(ptr) => Variant::IDispatch(ComRc::wrap(ptr)),
+                    None => Variant::None,
+                },
                 _ => return Err(ComError::E_NOTIMPL),
             }
         })
     }
+
+    /// Converts a raw variant owned by the caller.
+    ///
+    /// The strings are copied and the interfaces referenced, leaving the
+    /// source variant intact.
+    ///
+    /// # Safety
+    ///
+    /// The source variant must be a valid variant.
+    pub unsafe fn from_raw_ref<TS: TypeSystem>(src: &raw::Variant<TS>) -> ComResult<Self>
+    {
+        if src.vt.0 & raw::var_type::ARRAY != 0 {
+            let parray = match src.vt.0 & raw::var_type::BYREF {
+                0 => src.data.parray,
+                _ => *src.data.pparray,
+            };
+            return crate::safearray::read_array(parray).map(Variant::Array);
+        }
+
+        // The borrowed value shares the data of the source and must not be
+        // dropped.
+        let borrowed = std::mem::ManuallyDrop::new(Self::from_raw(*src)?);
+        Ok((*borrowed).clone())
+    }
 }
 
 impl Default for Variant
@@ -225,6 +272,11 @@ impl<TS: TypeSystem> TryFrom<Variant> for raw::Variant<TS>
             ),
 
             Variant::IUnknown(data) => {
+                // The interface might be held only for the other type system.
+                let data = match ComItf::ptr::<TS>(&data) {
+                    Some(_) => data,
+                    None => data.query_interface_ts::<TS, dyn IUnknown>()?,
+                };
                 let v = raw::Variant::new(
                     raw::VariantType::new(raw::var_type::UNKNOWN),
                     raw::VariantData {
@@ -238,6 +290,35 @@ impl<TS: TypeSystem> TryFrom<Variant> for raw::Variant<TS>
 
                 v
             }
+
+            Variant::IDispatch(data) => {
+                // The dispatch interface might be held only for the other
+                // type system.
+                let data = match ComItf::ptr::<TS>(&data) {
+                    Some(_) => data,
+                    None => data
+                        .as_iunknown()
+                        .query_interface_ts::<TS, dyn IDispatch>()?,
+                };
+                let v = raw::Variant::new(
+                    raw::VariantType::new(raw::var_type::DISPATCH),
+                    raw::VariantData {
+                        pdispVal: ComItf::ptr(&data),
+                    },
+                );
+
+                // The reference is moved into the raw variant.
+                std::mem::forget(data);
+
+                v
+            }
+
+            Variant::Array(data) => raw::Variant::new(
+                raw::VariantType::new(raw::var_type::ARRAY | raw::var_type::VARIANT),
+                raw::VariantData {
+                    parray: crate::safearray::create_array(raw::var_type::VARIANT, data)?,
+                },
+            ),
         })
     }
 }
@@ -513,6 +594,19 @@ impl<T: ComInterface + ?Sized> From<ComRc<T>> for Variant
     }
 }
 
+impl<T: ComInterface + ?Sized> TryFrom<Variant> for ComRc<T>
+{
+    type Error = ComError;
+    fn try_from(src: Variant) -> ComResult<ComRc<T>>
+    {
+        match src {
+            Variant::IUnknown(data) => ComItf::query_interface::<T>(&data),
+            Variant::IDispatch(data) => ComItf::query_interface::<T>(&data),
+            _ => Err(ComError::from(VariantError::from(&src))),
+        }
+    }
+}
+
 impl TryFrom<Variant> for f64
 {
     type Error = VariantError;
@@ -616,6 +710,29 @@ impl TryFrom<Variant> for CString
     }
 }
 
+impl<T: TryFrom<Variant>> TryFrom<Variant> for Vec<T>
+{
+    type Error = VariantError;
+    fn try_from(src: Variant) -> Result<Vec<T>, Self::Error>
+    {
+        match src {
+            Variant::Array(data) => data
+                .into_iter()
+                .map(|value| T::try_from(value).map_err(|_| VariantError))
+                .collect(),
+            _ => Err(VariantError::from(&src)),
+        }
+    }
+}
+
+impl<T: Into<Variant>> From<Vec<T>> for Variant
+{
+    fn from(src: Vec<T>) -> Self
+    {
+        Variant::Array(src.into_iter().map(Into::into).collect())
+    }
+}
+
 impl<T: Into<IntercomString>> From<T> for Variant
 {
     fn from(src: T) -> Self
@@ -627,8 +744,7 @@ impl<T: Into<IntercomString>> From<T> for Variant
 pub mod raw
 {
 
-    use super::intercom_attributes::ForeignType;
-    use crate::type_system::TypeSystem;
+    use crate::type_system::{ForeignType, TypeSystem};
     use std::time::{Duration, SystemTime};
 
     #[repr(C)]
@@ -763,8 +879,8 @@ pub mod raw
         pub date: VariantDate,
         pub bstrVal: *mut u16,
         pub punkVal: Option<crate::raw::InterfacePtr<TS, dyn crate::IUnknown>>,
-        //*pdispVal : ComItf<IDispatch>,
-        //parray : SafeArray,
+        pub pdispVal: Option<crate::raw::InterfacePtr<TS, dyn crate::IDispatch>>,
+        pub parray: *mut crate::raw::SAFEARRAY,
         pub pbVal: *mut i8,
         pub piVal: *mut i16,
         pub plVal: *mut i32,
@@ -777,8 +893,8 @@ pub mod raw
         pub pdate: *mut VariantDate,
         pub pbstrVal: *mut *mut u16,
         pub ppunkVal: *mut Option<crate::raw::InterfacePtr<TS, dyn crate::IUnknown>>,
-        //ppdispVal : *mut ComItf<IDispatch>,
-        //pparray : *mut SafeArray,
+        pub ppdispVal: *mut Option<crate::raw::InterfacePtr<TS, dyn crate::IDispatch>>,
+        pub pparray: *mut *mut crate::raw::SAFEARRAY,
         pub pvarVal: *mut Variant<TS>,
         pub byref: *mut std::os::raw::c_void,
         pub cVal: u8,
@@ -798,7 +914,7 @@ pub mod raw
     }
 
     #[repr(C)]
-    #[derive(Copy, Clone, ForeignType)]
+    #[derive(Copy, Clone)]
     pub struct Variant<TS: TypeSystem>
     {
         pub vt: VariantType,
@@ -808,6 +924,19 @@ pub mod raw
         pub data: VariantData<TS>,
     }
 
+    impl<TS: TypeSystem> ForeignType for Variant<TS>
+    {
+        fn type_name() -> &'static str
+        {
+            "Variant"
+        }
+
+        fn type_desc() -> crate::typelib::TypeDesc
+        {
+            crate::typelib::TypeDesc::Variant
+        }
+    }
+
     impl<TS: TypeSystem> Variant<TS>
     {
         pub fn new(vt: VariantType, data: VariantData<TS>) -> Variant<TS>
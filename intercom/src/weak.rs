@@ -0,0 +1,265 @@
+use super::*;
+use crate::attributes::{ComClass, ComInterface, ComInterfaceVariant, HasInterface};
+use crate::interfaces::{IWeakReference, IWeakReferenceSource};
+use crate::raw::RawComPtr;
+use crate::type_system::AutomationTypeSystem;
+use std::marker::PhantomData;
+
+/// Weak reference to a COM object.
+///
+/// The weak reference does not keep the object alive. Use `upgrade` to get
+/// a strong `ComRc` reference to the object while it still exists.
+///
+/// Weak references can be used to break reference cycles in object graphs,
+/// such as child objects that refer back to their parent.
+///
+/// The weak references are implemented through the `IWeakReferenceSource`
+/// interface, which every `#[com_class]` implements automatically.
+pub struct ComWeak<T: ComInterface + ?Sized>
+{
+    reference: ComRc<dyn IWeakReference>,
+    phantom: PhantomData<T>,
+}
+
+impl<T: ComInterface + ?Sized> ComWeak<T>
+{
+    /// Attempts to get a strong reference to the object.
+    ///
+    /// Returns `Ok(None)` if the object has already been destroyed. Fails
+    /// with `E_NOINTERFACE` if the object is alive but does not implement
+    /// the interface `T`.
+    pub fn upgrade(&self) -> ComResult<Option<ComRc<T>>>
+    {
+        let iid = <dyn IUnknown as ComInterfaceVariant<AutomationTypeSystem>>::iid();
+        let unknown = unsafe {
+            let ptr = self.reference.resolve(iid)?;
+
+            // The resolved pointer holds a reference already.
+            match raw::InterfacePtr::<AutomationTypeSystem, dyn IUnknown>::new(ptr) {
+                Some(ptr) => ComRc::wrap(ptr),
+                None => return Ok(None),
+            }
+        };
+        ComItf::query_interface::<T>(&unknown).map(Some)
+    }
+}
+
+impl<T: ComInterface + ?Sized> Clone for ComWeak<T>
+{
+    fn clone(&self) -> Self
+    {
+        ComWeak {
+            reference: self.reference.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ComInterface + ?Sized> std::fmt::Debug for ComWeak<T>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+    {
+        write!(f, "ComWeak({:?})", self.reference)
+    }
+}
+
+impl<I: ComInterface + ?Sized, T: HasInterface<I>> From<&ComBox<T>> for ComWeak<I>
+{
+    fn from(combox: &ComBox<T>) -> Self
+    {
+        ComWeak {
+            reference: WeakReference::create(combox.as_ref()),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ComInterface + ?Sized> ComRc<T>
+{
+    /// Creates a weak reference to the object.
+    ///
+    /// Fails with `E_NOINTERFACE` if the object does not implement
+    /// `IWeakReferenceSource`.
+    pub fn downgrade(this: &ComRc<T>) -> ComResult<ComWeak<T>>
+    {
+        let source: ComRc<dyn IWeakReferenceSource> = ComItf::query_interface(this)?;
+        Ok(ComWeak {
+            reference: source.get_weak_reference()?,
+            phantom: PhantomData,
+        })
+    }
+}
+
+/// `IWeakReference` implementation for the intercom COM classes.
+///
+/// The `ComBoxData` type is erased so that the weak references of all the
+/// classes share the same COM class.
+#[com_class(clsid = None, IWeakReference)]
+pub(crate) struct WeakReference
+{
+    data: *mut std::ffi::c_void,
+    resolve: unsafe fn(*mut std::ffi::c_void, REFIID) -> ComResult<RawComPtr>,
+    release: unsafe fn(*mut std::ffi::c_void),
+}
+
+impl WeakReference
+{
+    /// Creates a new weak reference to the object.
+    pub(crate) fn create<T: ComClass>(data: &ComBoxData<T>) -> ComRc<dyn IWeakReference>
+    {
+        unsafe { ComBoxData::add_weak(data) };
+        ComRc::from(ComBox::new(WeakReference {
+            data: data as *const ComBoxData<T> as *mut std::ffi::c_void,
+            resolve: resolve::<T>,
+            release: release::<T>,
+        }))
+    }
+}
+
+impl IWeakReference for WeakReference
+{
+    unsafe fn resolve(&self, riid: REFIID) -> ComResult<RawComPtr>
+    {
+        (self.resolve)(self.data, riid)
+    }
+}
+
+impl Drop for WeakReference
+{
+    fn drop(&mut self)
+    {
+        unsafe { (self.release)(self.data) };
+    }
+}
+
+/// Queries the `riid` interface from the object if it is still alive.
+unsafe fn resolve<T: ComClass>(data: *mut std::ffi::c_void, riid: REFIID) -> ComResult<RawComPtr>
+{
+    // Keep the object alive for the duration of the query.
+    let data = data as *mut ComBoxData<T>;
+    if !ComBoxData::try_add_ref(&*data) {
+        return Ok(std::ptr::null_mut());
+    }
+
+    // Aggregated objects must hand out references through the outer object
+    // so that the reference counts stay balanced.
+    let mut out = std::ptr::null_mut();
+    let hr = match crate::interfaces::controlling_unknown(&*data) {
+        Some((outer, vtable)) => (vtable.query_interface)(outer, riid, &mut out),
+        None => ComBoxData::query_interface(&*data, riid, &mut out),
+    };
+    ComBoxData::release(data);
+
+    match hr {
+        raw::S_OK => Ok(out),
+        e => Err(e.into()),
+    }
+}
+
+/// Releases the weak reference held on the object.
+unsafe fn release<T: ComClass>(data: *mut std::ffi::c_void)
+{
+    ComBoxData::release_weak(data as *mut ComBoxData<T>);
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[com_interface]
+    trait INode
+    {
+        fn get_value(&self) -> u32;
+    }
+
+    #[com_class(clsid = None, INode)]
+    struct Node
+    {
+        value: u32,
+        drops: &'static AtomicU32,
+    }
+
+    impl INode for Node
+    {
+        fn get_value(&self) -> u32
+        {
+            self.value
+        }
+    }
+
+    impl Drop for Node
+    {
+        fn drop(&mut self)
+        {
+            self.drops.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn upgrade_while_alive()
+    {
+        static DROPS: AtomicU32 = AtomicU32::new(0);
+        let combox = ComBox::new(Node {
+            value: 10,
+            drops: &DROPS,
+        });
+        let weak: ComWeak<dyn INode> = ComWeak::from(&combox);
+
+        let strong = weak.upgrade().unwrap().expect("Object was alive");
+        assert_eq!(strong.get_value(), 10);
+        drop(strong);
+
+        drop(combox);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+        assert!(weak.upgrade().unwrap().is_none());
+    }
+
+    #[test]
+    fn downgrade_through_com()
+    {
+        static DROPS: AtomicU32 = AtomicU32::new(0);
+        let rc: ComRc<dyn INode> = ComRc::from(ComBox::new(Node {
+            value: 20,
+            drops: &DROPS,
+        }));
+        let weak = ComRc::downgrade(&rc).unwrap();
+        let weak_clone = weak.clone();
+
+        assert_eq!(weak.upgrade().unwrap().unwrap().get_value(), 20);
+
+        drop(rc);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+        assert!(weak.upgrade().unwrap().is_none());
+        assert!(weak_clone.upgrade().unwrap().is_none());
+    }
+
+    #[com_interface]
+    trait IUnsupported
+    {
+        fn get_count(&self) -> u32;
+    }
+
+    // Claim the interface without listing it in the com_class so the object
+    // refuses the query.
+    impl crate::attributes::HasInterface<dyn IUnsupported> for Node {}
+
+    #[test]
+    fn upgrade_unsupported_interface()
+    {
+        static DROPS: AtomicU32 = AtomicU32::new(0);
+        let combox = ComBox::new(Node {
+            value: 30,
+            drops: &DROPS,
+        });
+        let weak: ComWeak<dyn IUnsupported> = ComWeak::from(&combox);
+
+        let err = weak.upgrade().unwrap_err();
+        assert_eq!(err.hresult, raw::E_NOINTERFACE);
+
+        drop(combox);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+        assert!(weak.upgrade().unwrap().is_none());
+    }
+}
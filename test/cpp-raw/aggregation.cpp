@@ -0,0 +1,139 @@
+#include <cstdint>
+
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+namespace
+{
+    // Minimal outer object that aggregates an inner Intercom object and
+    // exposes the inner IAggregatedObject interface as its own.
+    class Outer : public IUnknown
+    {
+    public:
+
+        intercom::HRESULT INTERCOM_CC QueryInterface(
+            intercom::REFIID riid,
+            void** out
+        ) override
+        {
+            if( riid == IID_IUnknown )
+            {
+                *out = static_cast< IUnknown* >( this );
+                AddRef();
+                return intercom::SC_OK;
+            }
+
+            if( riid == IID_IAggregatedObject_Automation && inner != nullptr )
+                return inner->QueryInterface( riid, out );
+
+            *out = nullptr;
+            return intercom::EC_NOINTERFACE;
+        }
+
+        intercom::REF_COUNT_32 INTERCOM_CC AddRef() override
+        {
+            return ++ref_count;
+        }
+
+        intercom::REF_COUNT_32 INTERCOM_CC Release() override
+        {
+            return --ref_count;
+        }
+
+        IUnknown* inner = nullptr;
+        intercom::REF_COUNT_32 ref_count = 1;
+    };
+}
+
+TEST_CASE( "aggregation" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    SECTION( "Aggregatable class can be aggregated" )
+    {
+        intercom::RawInterface< intercom::IClassFactory > factory;
+        REQUIRE( intercom::detail::get_class_factory(
+                CLSID_AggregatedObject, &factory ) == intercom::SC_OK );
+
+        Outer outer;
+
+        SECTION( "Aggregation requires IUnknown" )
+        {
+            void* ptr = nullptr;
+            intercom::HRESULT hr = factory->CreateInstance(
+                    &outer, IID_IAggregatedObject_Automation, &ptr );
+
+            REQUIRE( hr == intercom::EC_NOAGGREGATION );
+            REQUIRE( ptr == nullptr );
+        }
+
+        IUnknown* inner = nullptr;
+        intercom::HRESULT hr = factory->CreateInstance(
+                &outer, IID_IUnknown, reinterpret_cast< void** >( &inner ) );
+        REQUIRE( hr == intercom::SC_OK );
+        REQUIRE( inner != nullptr );
+        outer.inner = inner;
+
+        // Creating the inner object must not touch the outer reference count.
+        REQUIRE( outer.ref_count == 1 );
+
+        IAggregatedObject_Automation* pAggregated = nullptr;
+        hr = outer.QueryInterface(
+                IID_IAggregatedObject_Automation,
+                reinterpret_cast< void** >( &pAggregated ) );
+        REQUIRE( hr == intercom::SC_OK );
+        REQUIRE( pAggregated != nullptr );
+        REQUIRE( pAggregated->GetValue() == 42 );
+
+        SECTION( "Reference counting is delegated to the outer object" )
+        {
+            REQUIRE( outer.ref_count == 2 );
+            REQUIRE( pAggregated->GetInnerRefCount() == 1 );
+
+            pAggregated->AddRef();
+            REQUIRE( outer.ref_count == 3 );
+            REQUIRE( pAggregated->GetInnerRefCount() == 1 );
+
+            pAggregated->Release();
+            REQUIRE( outer.ref_count == 2 );
+        }
+
+        SECTION( "IUnknown of the aggregated interface is the outer object" )
+        {
+            IUnknown* pUnknown = nullptr;
+            hr = pAggregated->QueryInterface(
+                    IID_IUnknown, reinterpret_cast< void** >( &pUnknown ) );
+            REQUIRE( hr == intercom::SC_OK );
+            REQUIRE( pUnknown == static_cast< IUnknown* >( &outer ) );
+            pUnknown->Release();
+        }
+
+        pAggregated->Release();
+        REQUIRE( outer.ref_count == 1 );
+
+        // Releasing the non-delegating IUnknown destroys the inner object.
+        REQUIRE( inner->Release() == 0 );
+        outer.inner = nullptr;
+    }
+
+    SECTION( "Non-aggregatable class rejects the outer object" )
+    {
+        intercom::RawInterface< intercom::IClassFactory > factory;
+        REQUIRE( intercom::detail::get_class_factory(
+                CLSID_PrimitiveOperations, &factory ) == intercom::SC_OK );
+
+        Outer outer;
+        IUnknown* inner = nullptr;
+        intercom::HRESULT hr = factory->CreateInstance(
+                &outer, IID_IUnknown, reinterpret_cast< void** >( &inner ) );
+
+        REQUIRE( hr == intercom::EC_NOAGGREGATION );
+        REQUIRE( inner == nullptr );
+        REQUIRE( outer.ref_count == 1 );
+    }
+
+    UninitializeRuntime();
+}
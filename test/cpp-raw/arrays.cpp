@@ -0,0 +1,265 @@
+#include <cstdint>
+#include <cstring>
+#include <string>
+
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+namespace
+{
+    intercom::BSTR make_bstr( const std::u16string& value )
+    {
+        intercom::BSTR bstr = intercom::allocate_bstr( static_cast< uint32_t >( value.size() ) );
+        std::memcpy( bstr, value.data(), value.size() * sizeof( char16_t ) );
+        return bstr;
+    }
+
+    template< typename T >
+    T* access( intercom::SAFEARRAY* psa )
+    {
+        void* data = nullptr;
+        REQUIRE( intercom::SafeArrayAccessData( psa, &data ) == intercom::SC_OK );
+        return static_cast< T* >( data );
+    }
+}
+
+TEST_CASE( "arrays" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    IArrayTests_Automation* pTests = nullptr;
+    intercom::HRESULT hr = CreateInstance(
+            CLSID_ArrayTests,
+            IID_IArrayTests_Automation,
+            &pTests );
+    REQUIRE( hr == intercom::SC_OK );
+
+    SECTION( "Arrays can be passed as parameters" )
+    {
+        intercom::SAFEARRAY* psa = intercom::SafeArrayCreateVector( intercom::VT_I4, 0, 4 );
+        REQUIRE( psa != nullptr );
+
+        int32_t* data = access< int32_t >( psa );
+        for( int32_t i = 0; i < 4; ++i )
+            data[ i ] = i + 1;
+        REQUIRE( intercom::SafeArrayUnaccessData( psa ) == intercom::SC_OK );
+
+        int32_t sum = 0;
+        REQUIRE( pTests->Sum( psa, &sum ) == intercom::SC_OK );
+        REQUIRE( sum == 10 );
+
+        // The caller retains the ownership of the array.
+        REQUIRE( intercom::SafeArrayDestroy( psa ) == intercom::SC_OK );
+    }
+
+    SECTION( "Arrays can be returned" )
+    {
+        intercom::SAFEARRAY* psa = nullptr;
+        REQUIRE( pTests->Range( 3, &psa ) == intercom::SC_OK );
+        REQUIRE( psa != nullptr );
+
+        intercom::VARTYPE vt = intercom::VT_EMPTY;
+        REQUIRE( intercom::SafeArrayGetVartype( psa, &vt ) == intercom::SC_OK );
+        REQUIRE( vt == intercom::VT_I4 );
+        REQUIRE( intercom::SafeArrayGetDim( psa ) == 1 );
+
+        intercom::LONG lower = -1;
+        intercom::LONG upper = -1;
+        REQUIRE( intercom::SafeArrayGetLBound( psa, 1, &lower ) == intercom::SC_OK );
+        REQUIRE( intercom::SafeArrayGetUBound( psa, 1, &upper ) == intercom::SC_OK );
+        REQUIRE( lower == 0 );
+        REQUIRE( upper == 2 );
+
+        int32_t* data = access< int32_t >( psa );
+        REQUIRE( data[ 0 ] == 0 );
+        REQUIRE( data[ 1 ] == 1 );
+        REQUIRE( data[ 2 ] == 2 );
+
+        // Locked arrays cannot be destroyed.
+        REQUIRE( intercom::SafeArrayDestroy( psa ) == intercom::EC_ARRAYISLOCKED );
+        REQUIRE( intercom::SafeArrayUnaccessData( psa ) == intercom::SC_OK );
+        REQUIRE( intercom::SafeArrayDestroy( psa ) == intercom::SC_OK );
+    }
+
+    SECTION( "String arrays are passed as BSTR arrays" )
+    {
+        intercom::SAFEARRAY* psa = intercom::SafeArrayCreateVector( intercom::VT_BSTR, 0, 2 );
+        intercom::BSTR* data = access< intercom::BSTR >( psa );
+        data[ 0 ] = make_bstr( u"foo" );
+        data[ 1 ] = make_bstr( u"bar" );
+        REQUIRE( intercom::SafeArrayUnaccessData( psa ) == intercom::SC_OK );
+
+        intercom::BSTR separator = make_bstr( u", " );
+        intercom::BSTR joined = nullptr;
+        REQUIRE( pTests->Join( psa, separator, &joined ) == intercom::SC_OK );
+        REQUIRE( std::u16string( joined ) == u"foo, bar" );
+
+        intercom::free_bstr( joined );
+        intercom::free_bstr( separator );
+        REQUIRE( intercom::SafeArrayDestroy( psa ) == intercom::SC_OK );
+
+        intercom::BSTR value = make_bstr( u"a,b,c" );
+        intercom::SAFEARRAY* parts = nullptr;
+        REQUIRE( pTests->Split( value, &parts ) == intercom::SC_OK );
+
+        intercom::LONG upper = -1;
+        REQUIRE( intercom::SafeArrayGetUBound( parts, 1, &upper ) == intercom::SC_OK );
+        REQUIRE( upper == 2 );
+
+        intercom::BSTR* part_data = access< intercom::BSTR >( parts );
+        REQUIRE( std::u16string( part_data[ 0 ] ) == u"a" );
+        REQUIRE( std::u16string( part_data[ 2 ] ) == u"c" );
+        REQUIRE( intercom::SafeArrayUnaccessData( parts ) == intercom::SC_OK );
+
+        intercom::free_bstr( value );
+        REQUIRE( intercom::SafeArrayDestroy( parts ) == intercom::SC_OK );
+    }
+
+    SECTION( "Variant arrays hold mixed values" )
+    {
+        intercom::SAFEARRAY* psa = intercom::SafeArrayCreateVector( intercom::VT_VARIANT, 0, 2 );
+        intercom::VARIANT* data = access< intercom::VARIANT >( psa );
+        data[ 0 ].vt = intercom::VT_I4;
+        data[ 0 ].lVal = 42;
+        data[ 1 ].vt = intercom::VT_BSTR;
+        data[ 1 ].bstrVal = make_bstr( u"value" );
+        REQUIRE( intercom::SafeArrayUnaccessData( psa ) == intercom::SC_OK );
+
+        intercom::SAFEARRAY* reversed = nullptr;
+        REQUIRE( pTests->ReverseVariants( psa, &reversed ) == intercom::SC_OK );
+        REQUIRE( intercom::SafeArrayDestroy( psa ) == intercom::SC_OK );
+
+        intercom::VARIANT* result = access< intercom::VARIANT >( reversed );
+        REQUIRE( result[ 0 ].vt == intercom::VT_BSTR );
+        REQUIRE( std::u16string( result[ 0 ].bstrVal ) == u"value" );
+        REQUIRE( result[ 1 ].vt == intercom::VT_I4 );
+        REQUIRE( result[ 1 ].lVal == 42 );
+        REQUIRE( intercom::SafeArrayUnaccessData( reversed ) == intercom::SC_OK );
+
+        // Destroying the array clears the variants.
+        REQUIRE( intercom::SafeArrayDestroy( reversed ) == intercom::SC_OK );
+    }
+
+    SECTION( "Arrays can be stored in variants" )
+    {
+        intercom::VARIANT v;
+        intercom::VariantInit( &v );
+        REQUIRE( v.vt == intercom::VT_EMPTY );
+
+        v.vt = intercom::VT_ARRAY | intercom::VT_I4;
+        v.parray = intercom::SafeArrayCreateVector( intercom::VT_I4, 0, 8 );
+        REQUIRE( intercom::VariantClear( &v ) == intercom::SC_OK );
+        REQUIRE( v.vt == intercom::VT_EMPTY );
+    }
+
+    SECTION( "Mutable slices are passed as in/out arrays" )
+    {
+        intercom::SAFEARRAY* psa = intercom::SafeArrayCreateVector( intercom::VT_R8, 0, 3 );
+        REQUIRE( psa != nullptr );
+
+        double* data = access< double >( psa );
+        data[ 0 ] = 1.0;
+        data[ 1 ] = 2.5;
+        data[ 2 ] = -1.0;
+        REQUIRE( intercom::SafeArrayUnaccessData( psa ) == intercom::SC_OK );
+
+        REQUIRE( pTests->Scale( &psa, 2.0 ) == intercom::SC_OK );
+        REQUIRE( psa != nullptr );
+
+        // The callee replaces the array with the modified values.
+        data = access< double >( psa );
+        REQUIRE( data[ 0 ] == 2.0 );
+        REQUIRE( data[ 1 ] == 5.0 );
+        REQUIRE( data[ 2 ] == -2.0 );
+        REQUIRE( intercom::SafeArrayUnaccessData( psa ) == intercom::SC_OK );
+        REQUIRE( intercom::SafeArrayDestroy( psa ) == intercom::SC_OK );
+    }
+
+    REQUIRE( pTests->Release() == 0 );
+
+    UninitializeRuntime();
+}
+
+TEST_CASE( "conformant arrays" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    IArrayTests_Raw* pTests = nullptr;
+    intercom::HRESULT hr = CreateInstance(
+            CLSID_ArrayTests,
+            IID_IArrayTests_Raw,
+            &pTests );
+    REQUIRE( hr == intercom::SC_OK );
+
+    IAllocator_Automation* pAllocator = nullptr;
+    REQUIRE( CreateInstance(
+            CLSID_Allocator,
+            IID_IAllocator_Automation,
+            &pAllocator ) == intercom::SC_OK );
+
+    SECTION( "Arrays are passed as a pointer and a length" )
+    {
+        int32_t values[] = { 1, 2, 3, 4 };
+        int32_t sum = 0;
+        REQUIRE( pTests->Sum( values, 4, &sum ) == intercom::SC_OK );
+        REQUIRE( sum == 10 );
+
+        // Empty arrays may be passed as null.
+        REQUIRE( pTests->Sum( nullptr, 0, &sum ) == intercom::SC_OK );
+        REQUIRE( sum == 0 );
+        REQUIRE( pTests->Sum( nullptr, 1, &sum ) == intercom::EC_POINTER );
+    }
+
+    SECTION( "Returned arrays are freed with the allocator" )
+    {
+        int32_t* values = nullptr;
+        uint32_t len = 0;
+        REQUIRE( pTests->Range( 3, &values, &len ) == intercom::SC_OK );
+        REQUIRE( len == 3 );
+        REQUIRE( values[ 0 ] == 0 );
+        REQUIRE( values[ 2 ] == 2 );
+        pAllocator->Free( values );
+    }
+
+    SECTION( "String elements are converted" )
+    {
+        char foo[] = "foo";
+        char bar[] = "bar";
+        char separator[] = "-";
+        char* values[] = { foo, bar };
+        char* joined = nullptr;
+        REQUIRE( pTests->Join( values, 2, separator, &joined ) == intercom::SC_OK );
+        REQUIRE( std::string( joined ) == "foo-bar" );
+        pAllocator->Free( joined );
+
+        char input[] = "a,bc";
+        char** parts = nullptr;
+        uint32_t len = 0;
+        REQUIRE( pTests->Split( input, &parts, &len ) == intercom::SC_OK );
+        REQUIRE( len == 2 );
+        REQUIRE( std::string( parts[ 0 ] ) == "a" );
+        REQUIRE( std::string( parts[ 1 ] ) == "bc" );
+
+        // The caller owns both the elements and the array.
+        pAllocator->Free( parts[ 0 ] );
+        pAllocator->Free( parts[ 1 ] );
+        pAllocator->Free( parts );
+    }
+
+    SECTION( "Mutable slices are modified in place" )
+    {
+        double values[] = { 1.0, 2.0 };
+        REQUIRE( pTests->Scale( values, 2, 0.5 ) == intercom::SC_OK );
+        REQUIRE( values[ 0 ] == 0.5 );
+        REQUIRE( values[ 1 ] == 1.0 );
+    }
+
+    REQUIRE( pAllocator->Release() == 0 );
+    REQUIRE( pTests->Release() == 0 );
+
+    UninitializeRuntime();
+}
@@ -0,0 +1,176 @@
+#include <cstdint>
+#include <string>
+#include <vector>
+
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+namespace
+{
+    // Dispatch calls pass the reserved IID_NULL.
+    const intercom::IID IID_NONE = { 0, 0, 0, { 0, 0, 0, 0, 0, 0, 0, 0 } };
+
+    DISPID get_dispid( IDispatch* pDispatch, const char16_t* name )
+    {
+        intercom::OLECHAR* names[] = { const_cast< intercom::OLECHAR* >( name ) };
+        DISPID dispid = 0;
+        intercom::HRESULT hr = pDispatch->GetIDsOfNames(
+                IID_NONE, names, 1, 0, &dispid );
+        return hr == intercom::SC_OK ? dispid : DISPID_UNKNOWN;
+    }
+
+    // Invokes a method with the arguments in the parameter order.
+    intercom::HRESULT invoke(
+        IDispatch* pDispatch,
+        DISPID dispid,
+        std::vector< intercom::VARIANT > args,
+        intercom::VARIANT* pResult,
+        EXCEPINFO* pException = nullptr,
+        intercom::UINT* pArgErr = nullptr
+    )
+    {
+        // The dispatch arguments are passed in the reverse order.
+        std::vector< intercom::VARIANT > reversed( args.rbegin(), args.rend() );
+        DISPPARAMS params = {
+                reversed.data(), nullptr, static_cast< intercom::UINT >( reversed.size() ), 0 };
+        return pDispatch->Invoke(
+                dispid, IID_NONE, 0, DISPATCH_METHOD,
+                &params, pResult, pException, pArgErr );
+    }
+
+    intercom::VARIANT i4( int32_t value )
+    {
+        intercom::VARIANT v = { 0 };
+        v.vt = intercom::VT_I4;
+        v.lVal = value;
+        return v;
+    }
+
+    intercom::VARIANT bstr( intercom::BSTR value )
+    {
+        intercom::VARIANT v = { 0 };
+        v.vt = intercom::VT_BSTR;
+        v.bstrVal = value;
+        return v;
+    }
+}
+
+TEST_CASE( "dispatch" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    IDispatchTests_Automation* pTests = nullptr;
+    intercom::HRESULT hr = CreateInstance(
+            CLSID_DispatchTests,
+            IID_IDispatchTests_Automation,
+            &pTests );
+    REQUIRE( hr == intercom::SC_OK );
+
+    IDispatch* pDispatch = nullptr;
+    REQUIRE( pTests->QueryInterface(
+            IID_IDispatch, reinterpret_cast< void** >( &pDispatch ) ) == intercom::SC_OK );
+
+    SECTION( "Dual interfaces are callable through the virtual table" )
+    {
+        REQUIRE( pTests->Add( 1, 2 ) == 3 );
+    }
+
+    SECTION( "Names are resolved in the method order" )
+    {
+        REQUIRE( get_dispid( pDispatch, u"Add" ) == 1 );
+        REQUIRE( get_dispid( pDispatch, u"Concat" ) == 2 );
+        REQUIRE( get_dispid( pDispatch, u"get_value" ) == 3 );
+        REQUIRE( get_dispid( pDispatch, u"CREATECHILD" ) == 4 );
+        REQUIRE( get_dispid( pDispatch, u"Missing" ) == DISPID_UNKNOWN );
+
+        intercom::UINT count = 1;
+        REQUIRE( pDispatch->GetTypeInfoCount( &count ) == intercom::SC_OK );
+        REQUIRE( count == 0 );
+    }
+
+    SECTION( "Invoke converts the arguments" )
+    {
+        intercom::VARIANT result = { 0 };
+        REQUIRE( invoke( pDispatch, 1, { i4( 10 ), i4( 20 ) }, &result ) == intercom::SC_OK );
+        REQUIRE( result.vt == intercom::VT_I4 );
+        REQUIRE( result.lVal == 30 );
+
+        IAllocator_Automation* pAllocator = nullptr;
+        REQUIRE( CreateInstance(
+                CLSID_Allocator,
+                IID_IAllocator_Automation,
+                &pAllocator ) == intercom::SC_OK );
+
+        intercom::BSTR a = pAllocator->AllocBstr(
+                const_cast< uint16_t* >( reinterpret_cast< const uint16_t* >( u"foo" ) ), 3 );
+        intercom::BSTR b = pAllocator->AllocBstr(
+                const_cast< uint16_t* >( reinterpret_cast< const uint16_t* >( u"bar" ) ), 3 );
+        REQUIRE( invoke( pDispatch, 2, { bstr( a ), bstr( b ) }, &result ) == intercom::SC_OK );
+        REQUIRE( result.vt == intercom::VT_BSTR );
+        REQUIRE( std::u16string( result.bstrVal ) == u"foobar" );
+
+        // The caller retains the ownership of the arguments.
+        REQUIRE( std::u16string( a ) == u"foo" );
+
+        pAllocator->FreeBstr( result.bstrVal );
+        pAllocator->FreeBstr( a );
+        pAllocator->FreeBstr( b );
+        REQUIRE( pAllocator->Release() == 0 );
+    }
+
+    SECTION( "Invoke rejects bad arguments" )
+    {
+        intercom::VARIANT result = { 0 };
+        REQUIRE( invoke( pDispatch, 1, { i4( 10 ) }, &result ) == intercom::EC_BADPARAMCOUNT );
+        REQUIRE( invoke( pDispatch, 99, {}, &result ) == intercom::EC_MEMBERNOTFOUND );
+
+        intercom::VARIANT wrong = { 0 };
+        wrong.vt = intercom::VT_BOOL;
+        intercom::UINT arg_err = 99;
+        REQUIRE( invoke( pDispatch, 1, { wrong, i4( 1 ) }, &result, nullptr, &arg_err )
+                == intercom::EC_TYPEMISMATCH );
+        REQUIRE( arg_err == 1 );
+    }
+
+    SECTION( "Invoke reports errors as exceptions" )
+    {
+        DISPID dispid = get_dispid( pDispatch, u"Fail" );
+        intercom::VARIANT result = { 0 };
+        EXCEPINFO exception = { 0 };
+        REQUIRE( invoke( pDispatch, dispid, {}, &result, &exception ) == intercom::EC_EXCEPTION );
+        REQUIRE( exception.scode == 0x80070005 );
+        REQUIRE( std::u16string( exception.bstrDescription ) == u"Dispatch failure" );
+
+        IAllocator_Automation* pAllocator = nullptr;
+        REQUIRE( CreateInstance(
+                CLSID_Allocator,
+                IID_IAllocator_Automation,
+                &pAllocator ) == intercom::SC_OK );
+        pAllocator->FreeBstr( exception.bstrDescription );
+        pAllocator->FreeBstr( exception.bstrSource );
+        REQUIRE( pAllocator->Release() == 0 );
+    }
+
+    SECTION( "Dispatch objects can be returned in variants" )
+    {
+        intercom::VARIANT result = { 0 };
+        REQUIRE( invoke( pDispatch, 4, { i4( 7 ) }, &result ) == intercom::SC_OK );
+        REQUIRE( result.vt == intercom::VT_DISPATCH );
+        REQUIRE( result.pdispVal != nullptr );
+
+        intercom::VARIANT value = { 0 };
+        REQUIRE( invoke( result.pdispVal, 3, {}, &value ) == intercom::SC_OK );
+        REQUIRE( value.vt == intercom::VT_I4 );
+        REQUIRE( value.lVal == 7 );
+
+        REQUIRE( result.pdispVal->Release() == 0 );
+    }
+
+    REQUIRE( pDispatch->Release() == 1 );
+    REQUIRE( pTests->Release() == 0 );
+
+    UninitializeRuntime();
+}
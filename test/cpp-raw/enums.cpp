@@ -0,0 +1,50 @@
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+TEST_CASE( "enums" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    IEnumTests_Raw* pTests = nullptr;
+    intercom::HRESULT hr = CreateInstance(
+            CLSID_EnumTests,
+            IID_IEnumTests_Raw,
+            &pTests );
+    REQUIRE( hr == intercom::SC_OK );
+
+    SECTION( "Enums are passed as their discriminants" )
+    {
+        Color color = Color::Red;
+        REQUIRE( pTests->NextColor( Color::Blue, &color ) == intercom::SC_OK );
+        REQUIRE( color == Color::Red );
+        REQUIRE( pTests->NextColor( Color::Red, &color ) == intercom::SC_OK );
+        REQUIRE( color == Color::Green );
+    }
+
+    SECTION( "Explicit discriminants are preserved" )
+    {
+        REQUIRE( static_cast< uint32_t >( Status::Pending ) == 10 );
+        REQUIRE( static_cast< uint32_t >( Status::Running ) == 11 );
+        REQUIRE( static_cast< uint32_t >( Status::Done ) == 100 );
+
+        uint32_t code = 0;
+        REQUIRE( pTests->StatusCode( Status::Running, &code ) == intercom::SC_OK );
+        REQUIRE( code == 11 );
+    }
+
+    SECTION( "Unknown discriminants are rejected" )
+    {
+        Color color = Color::Red;
+        REQUIRE( pTests->NextColor( static_cast< Color >( 3 ), &color ) == intercom::EC_INVALIDARG );
+
+        uint32_t code = 0;
+        REQUIRE( pTests->StatusCode( static_cast< Status >( 12 ), &code ) == intercom::EC_INVALIDARG );
+    }
+
+    REQUIRE( pTests->Release() == 0 );
+
+    UninitializeRuntime();
+}
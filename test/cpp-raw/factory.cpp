@@ -0,0 +1,77 @@
+#include <cstdint>
+
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+namespace
+{
+    intercom::VARIANT make_i4( int32_t value )
+    {
+        intercom::VARIANT v = { 0 };
+        v.vt = intercom::VT_I4;
+        v.lVal = value;
+        return v;
+    }
+}
+
+TEST_CASE( "factory" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    intercom::RawInterface< intercom::IClassFactory > factory;
+    REQUIRE( intercom::detail::get_class_factory(
+            CLSID_ConfiguredObject, &factory ) == intercom::SC_OK );
+
+    SECTION( "IClassFactory creates the object without arguments" )
+    {
+        IConfiguredObject_Raw* pObject = nullptr;
+        intercom::HRESULT hr = factory->CreateInstance(
+                nullptr, IID_IConfiguredObject_Raw,
+                reinterpret_cast< void** >( &pObject ) );
+        REQUIRE( hr == intercom::SC_OK );
+        REQUIRE( pObject != nullptr );
+        REQUIRE( pObject->GetValue() == 1 );
+
+        REQUIRE( pObject->Release() == 0 );
+    }
+
+    SECTION( "IIntercomClassFactoryEx passes the arguments" )
+    {
+        IIntercomClassFactoryEx_Raw* pFactoryEx = nullptr;
+        intercom::HRESULT hr = factory->QueryInterface(
+                IID_IIntercomClassFactoryEx_Raw,
+                reinterpret_cast< void** >( &pFactoryEx ) );
+        REQUIRE( hr == intercom::SC_OK );
+        REQUIRE( pFactoryEx != nullptr );
+
+        intercom::IID iid = IID_IConfiguredObject_Raw;
+
+        SECTION( "Valid arguments" )
+        {
+            IConfiguredObject_Raw* pObject = nullptr;
+            hr = pFactoryEx->CreateInstanceWith(
+                    make_i4( 10 ), &iid, reinterpret_cast< void** >( &pObject ) );
+            REQUIRE( hr == intercom::SC_OK );
+            REQUIRE( pObject != nullptr );
+            REQUIRE( pObject->GetValue() == 10 );
+
+            REQUIRE( pObject->Release() == 0 );
+        }
+
+        SECTION( "Factory errors are returned to the caller" )
+        {
+            IConfiguredObject_Raw* pObject = nullptr;
+            hr = pFactoryEx->CreateInstanceWith(
+                    make_i4( -1 ), &iid, reinterpret_cast< void** >( &pObject ) );
+            REQUIRE( hr == intercom::EC_INVALIDARG );
+            REQUIRE( pObject == nullptr );
+        }
+
+        pFactoryEx->Release();
+    }
+
+    UninitializeRuntime();
+}
@@ -0,0 +1,48 @@
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+TEST_CASE( "inheritance" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    IParrot_Raw* pParrot = nullptr;
+    intercom::HRESULT hr = CreateInstance(
+            CLSID_Parrot,
+            IID_IParrot_Raw,
+            &pParrot );
+    REQUIRE( hr == intercom::SC_OK );
+
+    SECTION( "Derived interfaces call their own methods" )
+    {
+        uint32_t words = 0;
+        REQUIRE( pParrot->Words( &words ) == intercom::SC_OK );
+        REQUIRE( words == 42 );
+    }
+
+    SECTION( "Derived interfaces call the methods of their bases" )
+    {
+        double wingspan = 0;
+        REQUIRE( pParrot->Wingspan( &wingspan ) == intercom::SC_OK );
+        REQUIRE( wingspan == 0.5 );
+
+        uint32_t legs = 0;
+        REQUIRE( pParrot->Legs( &legs ) == intercom::SC_OK );
+        REQUIRE( legs == 2 );
+    }
+
+    SECTION( "Derived interfaces convert to their bases" )
+    {
+        IAnimal_Raw* pAnimal = pParrot;
+
+        uint32_t legs = 0;
+        REQUIRE( pAnimal->Legs( &legs ) == intercom::SC_OK );
+        REQUIRE( legs == 2 );
+    }
+
+    REQUIRE( pParrot->Release() == 0 );
+
+    UninitializeRuntime();
+}
@@ -0,0 +1,59 @@
+#include <cstdint>
+
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+// Exported by com_library! on every platform but not declared by the
+// Windows SDK or the generated headers.
+extern "C" intercom::HRESULT INTERCOM_CC IntercomDumpLiveObjects();
+
+TEST_CASE( "leaks" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    IModuleState_Automation* pState = nullptr;
+    intercom::HRESULT hr = CreateInstance(
+            CLSID_ModuleState,
+            IID_IModuleState_Automation,
+            &pState );
+    REQUIRE( hr == intercom::SC_OK );
+    REQUIRE( pState != nullptr );
+
+    SECTION( "Live objects are reported" )
+    {
+        IConfiguredObject_Automation* pObject = nullptr;
+        hr = CreateInstance(
+                CLSID_ConfiguredObject,
+                IID_IConfiguredObject_Automation,
+                &pObject );
+        REQUIRE( hr == intercom::SC_OK );
+
+        REQUIRE( IntercomDumpLiveObjects() == intercom::SC_FALSE );
+
+        REQUIRE( pObject->Release() == 0 );
+    }
+
+    SECTION( "Released objects are not reported" )
+    {
+        // Other test cases may leave objects alive so compare the counts
+        // instead of expecting an empty registry.
+        const uint32_t initial = pState->GetLiveObjectCount();
+
+        IConfiguredObject_Automation* pObject = nullptr;
+        hr = CreateInstance(
+                CLSID_ConfiguredObject,
+                IID_IConfiguredObject_Automation,
+                &pObject );
+        REQUIRE( hr == intercom::SC_OK );
+        REQUIRE( pState->GetLiveObjectCount() == initial + 1 );
+
+        REQUIRE( pObject->Release() == 0 );
+        REQUIRE( pState->GetLiveObjectCount() == initial );
+    }
+
+    REQUIRE( pState->Release() == 0 );
+    UninitializeRuntime();
+}
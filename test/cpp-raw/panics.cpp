@@ -0,0 +1,80 @@
+#include <cstdint>
+#include <string>
+using std::char_traits;
+
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+TEST_CASE( "panics" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    IPanicTests_Automation* pPanicTests = nullptr;
+    intercom::HRESULT hr = CreateInstance(
+            CLSID_PanicTests,
+            IID_IPanicTests_Automation,
+            &pPanicTests );
+    REQUIRE( hr == intercom::SC_OK );
+    REQUIRE( pPanicTests != nullptr );
+
+    IErrorStore_Automation* pErrorStore = nullptr;
+    hr = CreateInstance(
+            CLSID_ErrorStore,
+            IID_IErrorStore_Automation,
+            &pErrorStore );
+    REQUIRE( hr == intercom::SC_OK );
+    REQUIRE( pErrorStore != nullptr );
+
+    IAllocator_Automation* pAllocator = nullptr;
+    hr = CreateInstance(
+            CLSID_Allocator,
+            IID_IAllocator_Automation,
+            &pAllocator );
+    REQUIRE( hr == intercom::SC_OK );
+    REQUIRE( pAllocator != nullptr );
+
+    uint32_t panic_count = pPanicTests->GetPanicCount();
+
+    SECTION( "Panic in a method returning Result is converted to E_UNEXPECTED" )
+    {
+        const char16_t* msg = u"Panic message";
+        intercom::BSTR bstrMsg = pAllocator->AllocBstr(
+                const_cast< uint16_t* >( reinterpret_cast< const uint16_t* >( msg ) ),
+                static_cast< uint32_t >( char_traits<char16_t>::length( msg ) ) );
+
+        uint32_t out = 0;
+        hr = pPanicTests->PanicResult( bstrMsg, OUT &out );
+        REQUIRE( hr == intercom::EC_UNEXPECTED );
+
+        IErrorInfo* pErrorInfo = nullptr;
+        hr = pErrorStore->GetErrorInfo( &pErrorInfo );
+        REQUIRE( hr == intercom::SC_OK );
+        REQUIRE( pErrorInfo != nullptr );
+
+        intercom::BSTR bstrDescription = nullptr;
+        hr = pErrorInfo->GetDescription( &bstrDescription );
+        REQUIRE( hr == intercom::SC_OK );
+        REQUIRE( bstrDescription != nullptr );
+
+        pAllocator->FreeBstr( bstrDescription );
+        pAllocator->FreeBstr( bstrMsg );
+        pErrorInfo->Release();
+
+        REQUIRE( pPanicTests->GetPanicCount() == panic_count + 1 );
+    }
+
+    SECTION( "Panic in a method returning HRESULT is converted to E_UNEXPECTED" )
+    {
+        REQUIRE( pPanicTests->PanicHresult() == intercom::EC_UNEXPECTED );
+        REQUIRE( pPanicTests->GetPanicCount() == panic_count + 1 );
+    }
+
+    pAllocator->Release();
+    pErrorStore->Release();
+    REQUIRE( pPanicTests->Release() == 0 );
+
+    UninitializeRuntime();
+}
@@ -0,0 +1,50 @@
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+TEST_CASE( "properties" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    IThermostat_Raw* pThermostat = nullptr;
+    intercom::HRESULT hr = CreateInstance(
+            CLSID_Thermostat,
+            IID_IThermostat_Raw,
+            &pThermostat );
+    REQUIRE( hr == intercom::SC_OK );
+
+    SECTION( "Getters return the property value" )
+    {
+        double target = 0;
+        REQUIRE( pThermostat->get_Target( &target ) == intercom::SC_OK );
+        REQUIRE( target == 20.0 );
+    }
+
+    SECTION( "Setters change the property value" )
+    {
+        REQUIRE( pThermostat->put_Target( 22.5 ) == intercom::SC_OK );
+
+        double target = 0;
+        REQUIRE( pThermostat->get_Target( &target ) == intercom::SC_OK );
+        REQUIRE( target == 22.5 );
+
+        uint32_t changes = 0;
+        REQUIRE( pThermostat->get_Changes( &changes ) == intercom::SC_OK );
+        REQUIRE( changes == 1 );
+    }
+
+    SECTION( "Setters report errors" )
+    {
+        REQUIRE( pThermostat->put_Target( -300.0 ) == intercom::EC_INVALIDARG );
+
+        double target = 0;
+        REQUIRE( pThermostat->get_Target( &target ) == intercom::SC_OK );
+        REQUIRE( target == 20.0 );
+    }
+
+    REQUIRE( pThermostat->Release() == 0 );
+
+    UninitializeRuntime();
+}
@@ -0,0 +1,49 @@
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+TEST_CASE( "structs" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    IStructTests_Raw* pTests = nullptr;
+    intercom::HRESULT hr = CreateInstance(
+            CLSID_StructTests,
+            IID_IStructTests_Raw,
+            &pTests );
+    REQUIRE( hr == intercom::SC_OK );
+
+    SECTION( "Structs can be passed by value" )
+    {
+        Point point = { 1.0, 2.0 };
+        Point translated = { 0.0, 0.0 };
+        REQUIRE( pTests->Translate( point, 0.5, -1.0, &translated ) == intercom::SC_OK );
+        REQUIRE( translated.x == 1.5 );
+        REQUIRE( translated.y == 1.0 );
+    }
+
+    SECTION( "Structs can contain other structs" )
+    {
+        Point position = { 3.0, 4.0 };
+        Marker marker = {};
+        REQUIRE( pTests->CreateMarker( 7, position, &marker ) == intercom::SC_OK );
+        REQUIRE( marker.visible );
+        REQUIRE( marker.id == 7 );
+        REQUIRE( marker.position.x == 3.0 );
+        REQUIRE( marker.position.y == 4.0 );
+    }
+
+    SECTION( "Structs can be modified through pointers" )
+    {
+        Marker marker = { true, 1, { 0.0, 0.0 } };
+        REQUIRE( pTests->Hide( &marker ) == intercom::SC_OK );
+        REQUIRE( marker.visible == false );
+        REQUIRE( marker.id == 1 );
+    }
+
+    REQUIRE( pTests->Release() == 0 );
+
+    UninitializeRuntime();
+}
@@ -0,0 +1,102 @@
+#include <cstdint>
+#include <thread>
+#include <vector>
+
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+TEST_CASE( "threading" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    SECTION( "Single threaded objects reject calls from other threads" )
+    {
+        IThreadingTests_Automation* pObject = nullptr;
+        intercom::HRESULT hr = CreateInstance(
+                CLSID_SingleThreaded,
+                IID_IThreadingTests_Automation,
+                &pObject );
+        REQUIRE( hr == intercom::SC_OK );
+
+        uint32_t value = 0;
+        REQUIRE( pObject->Add( 5, &value ) == intercom::SC_OK );
+        REQUIRE( value == 5 );
+
+        intercom::HRESULT thread_hr = intercom::SC_OK;
+        std::thread other( [&]() {
+            uint32_t ignored = 0;
+            thread_hr = pObject->GetValue( &ignored );
+        } );
+        other.join();
+        REQUIRE( thread_hr == intercom::EC_WRONG_THREAD );
+
+        REQUIRE( pObject->GetValue( &value ) == intercom::SC_OK );
+        REQUIRE( value == 5 );
+
+        REQUIRE( pObject->Release() == 0 );
+    }
+
+    SECTION( "Mutex objects serialize calls" )
+    {
+        IThreadingTests_Automation* pObject = nullptr;
+        intercom::HRESULT hr = CreateInstance(
+                CLSID_MutexThreaded,
+                IID_IThreadingTests_Automation,
+                &pObject );
+        REQUIRE( hr == intercom::SC_OK );
+
+        const int thread_count = 4;
+        const int call_count = 100;
+        std::vector< std::thread > threads;
+        for( int i = 0; i < thread_count; i++ )
+        {
+            threads.emplace_back( [&]() {
+                for( int j = 0; j < call_count; j++ )
+                {
+                    uint32_t ignored = 0;
+                    pObject->Add( 1, &ignored );
+                }
+            } );
+        }
+        for( auto& thread : threads )
+            thread.join();
+
+        uint32_t value = 0;
+        REQUIRE( pObject->GetValue( &value ) == intercom::SC_OK );
+        REQUIRE( value == thread_count * call_count );
+
+        REQUIRE( pObject->Release() == 0 );
+    }
+
+    SECTION( "Reentrant calls are rejected" )
+    {
+        const intercom::CLSID* classes[] = { &CLSID_SingleThreaded, &CLSID_MutexThreaded };
+        for( const intercom::CLSID* clsid : classes )
+        {
+            IThreadingTests_Automation* pObject = nullptr;
+            IThreadingTests_Automation* pOther = nullptr;
+            REQUIRE( CreateInstance(
+                    *clsid, IID_IThreadingTests_Automation, &pObject ) == intercom::SC_OK );
+            REQUIRE( CreateInstance(
+                    *clsid, IID_IThreadingTests_Automation, &pOther ) == intercom::SC_OK );
+
+            uint32_t value = 0;
+            REQUIRE( pOther->Add( 2, &value ) == intercom::SC_OK );
+            REQUIRE( pObject->AddThrough( pOther, &value ) == intercom::SC_OK );
+            REQUIRE( value == 2 );
+
+            REQUIRE( pObject->AddThrough( pObject, &value ) == intercom::EC_ILLEGAL_METHOD_CALL );
+
+            REQUIRE( pObject->GetValue( &value ) == intercom::SC_OK );
+            REQUIRE( value == 2 );
+
+            REQUIRE( pObject->Release() == 0 );
+            REQUIRE( pOther->Release() == 0 );
+        }
+    }
+
+    UninitializeRuntime();
+}
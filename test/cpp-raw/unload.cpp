@@ -0,0 +1,118 @@
+#include <cstdint>
+
+#include "../cpp-utility/os.hpp"
+#include "../dependencies/catch.hpp"
+
+#include "testlib.hpp"
+
+#ifndef _MSC_VER
+extern "C" intercom::HRESULT DllCanUnloadNow();
+#endif
+
+TEST_CASE( "unload" )
+{
+    // Initialize COM.
+    InitializeRuntime();
+
+    IModuleState_Automation* pState = nullptr;
+    intercom::HRESULT hr = CreateInstance(
+            CLSID_ModuleState,
+            IID_IModuleState_Automation,
+            &pState );
+    REQUIRE( hr == intercom::SC_OK );
+    REQUIRE( pState != nullptr );
+
+    SECTION( "Live objects prevent unloading" )
+    {
+        REQUIRE( DllCanUnloadNow() == intercom::SC_FALSE );
+    }
+
+    SECTION( "Objects are counted while alive" )
+    {
+        const uint32_t initial = pState->GetObjectCount();
+
+        IConfiguredObject_Automation* pObject = nullptr;
+        hr = CreateInstance(
+                CLSID_ConfiguredObject,
+                IID_IConfiguredObject_Automation,
+                &pObject );
+        REQUIRE( hr == intercom::SC_OK );
+        REQUIRE( pState->GetObjectCount() == initial + 1 );
+
+        pObject->Release();
+        REQUIRE( pState->GetObjectCount() == initial );
+    }
+
+    SECTION( "Server locks are counted" )
+    {
+        intercom::RawInterface< intercom::IClassFactory > factory;
+        REQUIRE( intercom::detail::get_class_factory(
+                CLSID_ConfiguredObject, &factory ) == intercom::SC_OK );
+
+        const uint32_t initial = pState->GetLockCount();
+
+        REQUIRE( factory->LockServer( true ) == intercom::SC_OK );
+        REQUIRE( pState->GetLockCount() == initial + 1 );
+
+        REQUIRE( factory->LockServer( false ) == intercom::SC_OK );
+        REQUIRE( pState->GetLockCount() == initial );
+    }
+
+    const uint32_t unload_count = pState->GetUnloadCount();
+    REQUIRE( pState->Release() == 0 );
+
+    SECTION( "Unused library can be unloaded" )
+    {
+        // COM polls DllCanUnloadNow repeatedly without unloading the library
+        // in between. on_unload must be invoked only once.
+        REQUIRE( DllCanUnloadNow() == intercom::SC_OK );
+        REQUIRE( DllCanUnloadNow() == intercom::SC_OK );
+
+        IModuleState_Automation* pOther = nullptr;
+        REQUIRE( CreateInstance(
+                CLSID_ModuleState,
+                IID_IModuleState_Automation,
+                &pOther ) == intercom::SC_OK );
+        REQUIRE( pOther->GetUnloadCount() == 1 );
+        pOther->Release();
+    }
+
+    SECTION( "on_unload is not invoked while the library is in use" )
+    {
+        IModuleState_Automation* pOther = nullptr;
+        REQUIRE( CreateInstance(
+                CLSID_ModuleState,
+                IID_IModuleState_Automation,
+                &pOther ) == intercom::SC_OK );
+
+        REQUIRE( DllCanUnloadNow() == intercom::SC_FALSE );
+        REQUIRE( pOther->GetUnloadCount() == unload_count );
+        pOther->Release();
+    }
+
+    SECTION( "on_unload is invoked again after the library has been used" )
+    {
+        REQUIRE( DllCanUnloadNow() == intercom::SC_OK );
+
+        IModuleState_Automation* pOther = nullptr;
+        REQUIRE( CreateInstance(
+                CLSID_ModuleState,
+                IID_IModuleState_Automation,
+                &pOther ) == intercom::SC_OK );
+        REQUIRE( pOther->GetUnloadCount() == unload_count + 1 );
+        pOther->Release();
+
+        // Creating the object above re-armed the hook.
+        REQUIRE( DllCanUnloadNow() == intercom::SC_OK );
+        REQUIRE( DllCanUnloadNow() == intercom::SC_OK );
+
+        REQUIRE( CreateInstance(
+                CLSID_ModuleState,
+                IID_IModuleState_Automation,
+                &pOther ) == intercom::SC_OK );
+        REQUIRE( pOther->GetUnloadCount() == unload_count + 2 );
+        pOther->Release();
+    }
+
+    UninitializeRuntime();
+}
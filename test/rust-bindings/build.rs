@@ -0,0 +1,29 @@
+//! Links the tests to the test library the bindings were generated from.
+
+use std::env;
+use std::path::PathBuf;
+
+fn main()
+{
+    let profile = env::var("PROFILE").unwrap();
+    let target_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("../target")
+        .join(profile);
+
+    if env::var("CARGO_CFG_TARGET_OS").unwrap() == "windows" {
+        // Cargo names the import library "test_lib.dll.lib" while the linker
+        // looks for "test_lib.lib".
+        let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+        std::fs::copy(
+            target_dir.join("test_lib.dll.lib"),
+            out_dir.join("test_lib.lib"),
+        )
+        .expect("The test_lib must be built before the bindings are tested");
+        println!("cargo:rustc-link-search=native={}", out_dir.display());
+    } else {
+        println!("cargo:rustc-link-search=native={}", target_dir.display());
+        println!("cargo:rustc-link-arg=-Wl,-rpath,{}", target_dir.display());
+    }
+
+    println!("cargo:rerun-if-changed=generated/test_lib.rs");
+}
@@ -0,0 +1,13 @@
+//! Tests for the Rust bindings generated from the test library.
+//!
+//! The bindings are generated by the `intercom-cli rust` command into the
+//! `generated` directory before building the tests.
+
+#[allow(clippy::all)]
+pub mod test_lib
+{
+    include!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/generated/test_lib.rs"
+    ));
+}
@@ -0,0 +1,136 @@
+//! Calls the test library through the generated bindings.
+
+use intercom::{raw, ComItf, ComRc};
+use rust_bindings::test_lib::*;
+
+#[test]
+fn primitives()
+{
+    let ops = create_primitive_operations().unwrap();
+
+    assert_eq!(ops.i32(1), !2);
+    assert_eq!(ops.u64(1), !2);
+    assert_eq!(ops.f64(4.0), 0.25);
+}
+
+#[test]
+fn results()
+{
+    let ops = create_result_operations().unwrap();
+
+    assert!(ops.s_ok().is_ok());
+    assert_eq!(ops.not_impl().unwrap_err().hresult, raw::E_NOTIMPL);
+    assert_eq!(ops.sqrt(16.0).unwrap(), 4.0);
+    assert_eq!(ops.sqrt(-1.0).unwrap_err().hresult, raw::E_INVALIDARG);
+    assert_eq!(ops.tuple(0x0001_0002).unwrap(), (1, 2));
+}
+
+#[test]
+fn strings()
+{
+    let strings = create_string_tests().unwrap();
+
+    for i in 0..3 {
+        let s = strings.index_to_string(i).unwrap();
+        assert_eq!(strings.string_to_index(&s).unwrap(), i);
+    }
+}
+
+#[test]
+fn arrays()
+{
+    let arrays = create_array_tests().unwrap();
+
+    assert_eq!(arrays.sum(&[1, 2, 3]).unwrap(), 6);
+    assert_eq!(arrays.range(3).unwrap(), vec![0, 1, 2]);
+    assert_eq!(
+        arrays
+            .join(&["a".to_owned(), "b".to_owned()], ", ")
+            .unwrap(),
+        "a, b"
+    );
+    assert_eq!(
+        arrays.split("a,b").unwrap(),
+        vec!["a".to_owned(), "b".to_owned()]
+    );
+
+    let mut values = [1.0, 2.0];
+    arrays.scale(&mut values, 2.0).unwrap();
+    assert_eq!(values, [2.0, 4.0]);
+}
+
+#[test]
+fn structs_and_enums()
+{
+    let structs = create_struct_tests().unwrap();
+    let point = structs
+        .translate(Point { x: 1.0, y: 2.0 }, 1.0, 1.0)
+        .unwrap();
+    assert_eq!((point.x, point.y), (2.0, 3.0));
+
+    let mut marker = structs.create_marker(5, point).unwrap();
+    assert!(marker.visible);
+    assert_eq!(marker.id, 5);
+    structs.hide(&mut marker).unwrap();
+    assert!(!marker.visible);
+
+    let enums = create_enum_tests().unwrap();
+    assert_eq!(enums.next_color(Color::Blue).unwrap(), Color::Red);
+    assert_eq!(enums.status_code(Status::Done).unwrap(), 100);
+}
+
+#[test]
+fn properties()
+{
+    let thermostat = create_thermostat().unwrap();
+
+    assert_eq!(thermostat.get_target().unwrap(), 20.0);
+    thermostat.set_target(22.5).unwrap();
+    assert_eq!(thermostat.get_target().unwrap(), 22.5);
+    assert_eq!(
+        thermostat.set_target(-300.0).unwrap_err().hresult,
+        raw::E_INVALIDARG
+    );
+    assert_eq!(thermostat.get_changes().unwrap(), 1);
+}
+
+#[test]
+fn inheritance()
+{
+    let animal = create_parrot().unwrap();
+    assert_eq!(animal.legs().unwrap(), 2);
+
+    let parrot: ComRc<dyn IParrot> = ComItf::query_interface(&animal).unwrap();
+    assert_eq!(parrot.legs().unwrap(), 2);
+    assert_eq!(parrot.wingspan().unwrap(), 0.5);
+    assert_eq!(parrot.words().unwrap(), 42);
+}
+
+#[test]
+fn returned_interfaces()
+{
+    let creator = create_class_creator().unwrap();
+
+    let root = creator.create_root(1).unwrap();
+    assert_eq!(root.get_id().unwrap(), 1);
+    assert_eq!(root.get_parent_id().unwrap(), 0);
+}
+
+#[test]
+fn imported_interfaces()
+{
+    let counter = create_imported_counter().unwrap();
+
+    assert_eq!(counter.add(2).unwrap(), 2);
+    assert_eq!(counter.add(3).unwrap(), 5);
+    assert_eq!(counter.total(), 5);
+    assert_eq!(counter.split(2.5).unwrap(), (2, 0.5));
+
+    counter.set_name("imported").unwrap();
+    assert_eq!(counter.get_name().unwrap(), "imported");
+
+    let peer: ComRc<dyn IImportedPeer> = ComItf::query_interface(&counter).unwrap();
+    assert_eq!(peer.merge(&counter).unwrap(), 10);
+    assert!(peer.peer(Some(&counter)).is_ok());
+    assert_eq!(peer.peer(None).unwrap_err().hresult, raw::E_POINTER);
+}
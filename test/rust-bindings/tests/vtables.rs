@@ -0,0 +1,130 @@
+//! Compares the type info of the generated traits against the type library
+//! of the test library.
+//!
+//! The type info describes the vtables of both type systems so matching type
+//! info means the generated traits lay out the same vtables as the original
+//! interfaces.
+
+use intercom::attributes::ComInterfaceTypeInfo;
+use intercom::raw::{InterfacePtr, RawComPtr, HRESULT};
+use intercom::type_system::{AutomationTypeSystem, TypeSystemName};
+use intercom::typelib::{IIntercomTypeLib, Interface, TypeInfo, TypeLib};
+use intercom::ComRc;
+use rust_bindings::test_lib::*;
+
+#[link(name = "test_lib")]
+extern "system" {
+    #[link_name = "IntercomTypeLib"]
+    fn intercom_type_lib(ts: TypeSystemName, out: *mut RawComPtr) -> HRESULT;
+}
+
+fn library_type_lib() -> TypeLib
+{
+    let typelib = unsafe {
+        let mut ptr = std::ptr::null_mut();
+        assert_eq!(
+            intercom_type_lib(TypeSystemName::Automation, &mut ptr),
+            intercom::raw::S_OK
+        );
+        let ptr = InterfacePtr::<AutomationTypeSystem, dyn IIntercomTypeLib>::new(ptr).unwrap();
+        ComRc::wrap(ptr)
+    };
+    TypeLib::from_comrc(&typelib).unwrap()
+}
+
+/// Describes the vtables of the interface in comparable form.
+fn vtables(itf: &Interface) -> Vec<String>
+{
+    let mut lines = vec![format!("{} dual: {}", itf.name, itf.options.dual)];
+    for variant in &itf.variants {
+        lines.push(format!(
+            "{:?} {} : {:?}",
+            variant.ts, variant.iid, variant.base
+        ));
+        for method in &variant.methods {
+            let params = method
+                .parameters
+                .iter()
+                .map(|p| format!("{:?} {}: {}", p.direction, p.name, p.ty))
+                .collect::<Vec<_>>();
+            lines.push(format!(
+                "    {:?} {}({}) -> {}",
+                method.kind,
+                method.name,
+                params.join(", "),
+                method.return_type.ty
+            ));
+        }
+    }
+    lines
+}
+
+fn find_interface<'a>(types: &'a [TypeInfo], name: &str) -> Option<&'a Interface>
+{
+    types.iter().find_map(|t| match t {
+        TypeInfo::Interface(itf) if itf.name == name => Some(&**itf),
+        _ => None,
+    })
+}
+
+macro_rules! assert_same_vtables {
+    ($lib:expr, $($itf:ident),* $(,)?) => {
+        $(
+            let generated = <dyn $itf as ComInterfaceTypeInfo>::gather_type_info();
+            let generated = find_interface(&generated, stringify!($itf))
+                .expect(concat!("No type info for ", stringify!($itf)));
+            let original = find_interface(&$lib.types, stringify!($itf))
+                .expect(concat!(stringify!($itf), " is not in the library"));
+            assert_eq!(vtables(generated), vtables(original));
+        )*
+    };
+}
+
+#[test]
+fn generated_vtables_match_the_library()
+{
+    let lib = library_type_lib();
+    assert_same_vtables!(
+        lib,
+        AggregatedObject,
+        AllocTests,
+        ClassCreator,
+        ConfiguredObject,
+        CreatedClass,
+        ErrorTests,
+        IAllocator,
+        IAnimal,
+        IArrayTests,
+        IBird,
+        ICallback,
+        IDispatchTests,
+        IEnumTests,
+        IErrorSource,
+        IErrorStore,
+        IImportedCounter,
+        IImportedPeer,
+        IIntercomClassFactoryEx,
+        INullableInterface,
+        IOnlyInterface,
+        IOutputMemoryTests,
+        IParent,
+        IParrot,
+        IRefCount,
+        ISharedInterface,
+        IStringTests,
+        IStructTests,
+        IThermostat,
+        IThreadingTests,
+        IVariantInterface,
+        ModuleState,
+        NullableTests,
+        PanicTests,
+        PrimitiveOperations,
+        RefCountOperations,
+        ResultOperations,
+        StatefulOperations,
+        TypeSystemCaller,
+        UnicodeConversion,
+        VariantTests,
+    );
+}
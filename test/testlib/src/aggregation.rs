@@ -0,0 +1,20 @@
+use intercom::*;
+
+#[com_class(aggregatable = true, AggregatedObject)]
+#[derive(Default)]
+pub struct AggregatedObject;
+
+#[com_interface]
+impl AggregatedObject
+{
+    pub fn get_value(&self) -> u32
+    {
+        42
+    }
+
+    pub fn get_inner_ref_count(&self) -> u32
+    {
+        let combox = unsafe { ComBoxData::of(self) };
+        combox.get_ref_count()
+    }
+}
@@ -0,0 +1,51 @@
+use intercom::*;
+
+#[com_interface]
+pub trait IArrayTests
+{
+    fn sum(&self, values: &[i32]) -> ComResult<i32>;
+    fn range(&self, count: i32) -> ComResult<Vec<i32>>;
+    fn join(&self, values: Vec<String>, separator: &str) -> ComResult<String>;
+    fn split(&self, value: &str) -> ComResult<Vec<String>>;
+    fn reverse_variants(&self, values: Vec<Variant>) -> ComResult<Vec<Variant>>;
+    fn scale(&self, values: &mut [f64], factor: f64) -> ComResult<()>;
+}
+
+#[com_class(IArrayTests)]
+#[derive(Default)]
+pub struct ArrayTests;
+
+impl IArrayTests for ArrayTests
+{
+    fn sum(&self, values: &[i32]) -> ComResult<i32>
+    {
+        Ok(values.iter().sum())
+    }
+
+    fn range(&self, count: i32) -> ComResult<Vec<i32>>
+    {
+        Ok((0..count).collect())
+    }
+
+    fn join(&self, values: Vec<String>, separator: &str) -> ComResult<String>
+    {
+        Ok(values.join(separator))
+    }
+
+    fn split(&self, value: &str) -> ComResult<Vec<String>>
+    {
+        Ok(value.split(',').map(String::from).collect())
+    }
+
+    fn reverse_variants(&self, mut values: Vec<Variant>) -> ComResult<Vec<Variant>>
+    {
+        values.reverse();
+        Ok(values)
+    }
+
+    fn scale(&self, values: &mut [f64], factor: f64) -> ComResult<()>
+    {
+        values.iter_mut().for_each(|v| *v *= factor);
+        Ok(())
+    }
+}
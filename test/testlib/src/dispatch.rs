@@ -0,0 +1,47 @@
+use intercom::*;
+
+#[com_interface(dual)]
+pub trait IDispatchTests
+{
+    fn add(&self, a: i32, b: i32) -> i32;
+    fn concat(&self, a: &str, b: String) -> ComResult<String>;
+    fn get_value(&self) -> ComResult<i32>;
+    fn create_child(&self, value: i32) -> ComResult<Variant>;
+    fn fail(&self) -> ComResult<()>;
+}
+
+#[com_class(IDispatchTests)]
+#[derive(Default)]
+pub struct DispatchTests
+{
+    value: i32,
+}
+
+impl IDispatchTests for DispatchTests
+{
+    fn add(&self, a: i32, b: i32) -> i32
+    {
+        a + b
+    }
+
+    fn concat(&self, a: &str, b: String) -> ComResult<String>
+    {
+        Ok(format!("{}{}", a, b))
+    }
+
+    fn get_value(&self) -> ComResult<i32>
+    {
+        Ok(self.value)
+    }
+
+    fn create_child(&self, value: i32) -> ComResult<Variant>
+    {
+        let child: ComRc<dyn IDispatchTests> = ComRc::from(ComBox::new(DispatchTests { value }));
+        Ok(Variant::IDispatch(ComItf::query_interface(&child)?))
+    }
+
+    fn fail(&self) -> ComResult<()>
+    {
+        Err(ComError::E_ACCESSDENIED.with_message("Dispatch failure"))
+    }
+}
@@ -0,0 +1,47 @@
+use intercom::*;
+
+#[derive(ExternType, ExternInput, ExternOutput, ForeignType, Clone, Copy, Debug)]
+#[repr(C)]
+pub enum Color
+{
+    Red,
+    Green,
+    Blue,
+}
+
+#[derive(ExternType, ExternInput, ExternOutput, ForeignType, Clone, Copy, Debug)]
+#[repr(u32)]
+pub enum Status
+{
+    Pending = 10,
+    Running,
+    Done = 100,
+}
+
+#[com_interface]
+pub trait IEnumTests
+{
+    fn next_color(&self, color: Color) -> ComResult<Color>;
+    fn status_code(&self, status: Status) -> ComResult<u32>;
+}
+
+#[com_class(IEnumTests)]
+#[derive(Default)]
+pub struct EnumTests;
+
+impl IEnumTests for EnumTests
+{
+    fn next_color(&self, color: Color) -> ComResult<Color>
+    {
+        Ok(match color {
+            Color::Red => Color::Green,
+            Color::Green => Color::Blue,
+            Color::Blue => Color::Red,
+        })
+    }
+
+    fn status_code(&self, status: Status) -> ComResult<u32>
+    {
+        Ok(status as u32)
+    }
+}
@@ -0,0 +1,28 @@
+use intercom::*;
+
+#[com_class(factory = create_configured_object, ConfiguredObject)]
+pub struct ConfiguredObject
+{
+    value: i32,
+}
+
+#[com_interface]
+impl ConfiguredObject
+{
+    pub fn get_value(&self) -> i32
+    {
+        self.value
+    }
+}
+
+/// Creates the object from the initialization arguments.
+///
+/// Objects created without arguments get the value 1.
+fn create_configured_object(args: Variant) -> ComResult<ConfiguredObject>
+{
+    match args {
+        Variant::None => Ok(ConfiguredObject { value: 1 }),
+        Variant::I32(value) if value >= 0 => Ok(ConfiguredObject { value }),
+        _ => Err(ComError::E_INVALIDARG),
+    }
+}
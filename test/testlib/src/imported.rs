@@ -0,0 +1,58 @@
+use intercom::*;
+use std::cell::{Cell, RefCell};
+
+com_import!("idl/imported.idl");
+
+#[com_class(IImportedCounter, IImportedPeer)]
+#[derive(Default)]
+pub struct ImportedCounter
+{
+    total: Cell<i32>,
+    name: RefCell<String>,
+}
+
+impl IImportedCounter for ImportedCounter
+{
+    fn add(&self, amount: i32) -> ComResult<i32>
+    {
+        self.total.set(self.total.get() + amount);
+        Ok(self.total.get())
+    }
+
+    fn split(&self, value: f64) -> ComResult<(i32, f64)>
+    {
+        Ok((value.trunc() as i32, value.fract()))
+    }
+
+    fn get_name(&self) -> ComResult<String>
+    {
+        Ok(self.name.borrow().clone())
+    }
+
+    fn set_name(&self, name: &str) -> ComResult<()>
+    {
+        *self.name.borrow_mut() = name.to_string();
+        Ok(())
+    }
+
+    fn total(&self) -> u32
+    {
+        self.total.get() as u32
+    }
+}
+
+impl IImportedPeer for ImportedCounter
+{
+    fn merge(&self, other: &ComItf<dyn IImportedCounter>) -> ComResult<i32>
+    {
+        self.add(other.total() as i32)
+    }
+
+    fn peer(&self, other: Option<&ComItf<dyn IImportedCounter>>) -> ComResult<ComRc<dyn IUnknown>>
+    {
+        match other {
+            Some(other) => Ok(other.as_iunknown().into()),
+            None => Err(ComError::E_POINTER),
+        }
+    }
+}
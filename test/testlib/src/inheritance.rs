@@ -0,0 +1,47 @@
+use intercom::*;
+
+#[com_interface]
+pub trait IAnimal
+{
+    fn legs(&self) -> ComResult<u32>;
+}
+
+#[com_interface(base = IAnimal)]
+pub trait IBird: IAnimal
+{
+    fn wingspan(&self) -> ComResult<f64>;
+}
+
+#[com_interface(base = IBird)]
+pub trait IParrot: IBird
+{
+    fn words(&self) -> ComResult<u32>;
+}
+
+#[com_class(IAnimal, IBird, IParrot)]
+#[derive(Default)]
+pub struct Parrot;
+
+impl IAnimal for Parrot
+{
+    fn legs(&self) -> ComResult<u32>
+    {
+        Ok(2)
+    }
+}
+
+impl IBird for Parrot
+{
+    fn wingspan(&self) -> ComResult<f64>
+    {
+        Ok(0.5)
+    }
+}
+
+impl IParrot for Parrot
+{
+    fn words(&self) -> ComResult<u32>
+    {
+        Ok(42)
+    }
+}
@@ -6,22 +6,37 @@ use intercom::*;
 extern crate chrono;
 extern crate winapi;
 
+pub mod aggregation;
 pub mod alloc;
+pub mod arrays;
+pub mod dispatch;
+pub mod enums;
 pub mod error_info;
+pub mod factory;
+pub mod imported;
+pub mod inheritance;
 pub mod interface_params;
 pub mod nullable_parameters;
 pub mod output_memory;
+pub mod panics;
 pub mod primitive;
+pub mod properties;
 pub mod result;
 pub mod return_interfaces;
 pub mod stateful;
 pub mod strings;
+pub mod structs;
+pub mod threading;
 pub mod type_system_callbacks;
 pub mod unicode;
+pub mod unload;
 pub mod variant;
 
 // Declare available COM classes.
 com_library! {
+    on_panic = panics::on_panic,
+    on_unload = unload::on_unload,
+
     module return_interfaces,
     module nullable_parameters,
 
@@ -37,6 +52,24 @@ com_library! {
     class variant::VariantImpl,
     class unicode::UnicodeConversion,
     class output_memory::OutputMemoryTests,
+    class panics::PanicTests,
+    class aggregation::AggregatedObject,
+    class factory::ConfiguredObject,
+    class unload::ModuleState,
+    class threading::SingleThreaded,
+    class threading::MutexThreaded,
+    class dispatch::DispatchTests,
+    class arrays::ArrayTests,
+    class structs::StructTests,
+    class enums::EnumTests,
+    class inheritance::Parrot,
+    class properties::Thermostat,
+    class imported::ImportedCounter,
+
+    struct structs::Point,
+    struct structs::Marker,
+    enum enums::Color,
+    enum enums::Status,
 
     interface IOnlyInterface,
 
@@ -0,0 +1,33 @@
+use intercom::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+static PANIC_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// Panic hook registered through `com_library!`.
+pub fn on_panic(_report: &intercom::panic::PanicReport)
+{
+    PANIC_COUNT.fetch_add(1, Ordering::SeqCst);
+}
+
+#[com_class(PanicTests)]
+#[derive(Default)]
+pub struct PanicTests;
+
+#[com_interface]
+impl PanicTests
+{
+    pub fn panic_result(&self, msg: &str) -> ComResult<u32>
+    {
+        panic!("{}", msg);
+    }
+
+    pub fn panic_hresult(&self) -> raw::HRESULT
+    {
+        panic!("Panic with HRESULT return value");
+    }
+
+    pub fn get_panic_count(&self) -> u32
+    {
+        PANIC_COUNT.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,65 @@
+use intercom::*;
+
+/// Temperature control exposed through properties.
+#[com_interface]
+pub trait IThermostat
+{
+    /// Target temperature in degrees Celsius.
+    #[com_property]
+    fn get_target(&self) -> ComResult<f64>;
+
+    /// Sets the target temperature.
+    ///
+    /// # Arguments
+    ///
+    /// - `target` - Temperature in degrees Celsius; must not be below absolute zero.
+    #[com_property]
+    fn set_target(&mut self, target: f64) -> ComResult<()>;
+
+    /// Number of times the target has been changed.
+    #[com_property]
+    fn get_changes(&self) -> ComResult<u32>;
+}
+
+#[com_class(IThermostat)]
+pub struct Thermostat
+{
+    target: f64,
+    changes: u32,
+}
+
+impl Default for Thermostat
+{
+    fn default() -> Self
+    {
+        Thermostat {
+            target: 20.0,
+            changes: 0,
+        }
+    }
+}
+
+impl IThermostat for Thermostat
+{
+    fn get_target(&self) -> ComResult<f64>
+    {
+        Ok(self.target)
+    }
+
+    fn set_target(&mut self, target: f64) -> ComResult<()>
+    {
+        // Nothing gets colder than the absolute zero.
+        if target < -273.15 {
+            return Err(ComError::E_INVALIDARG);
+        }
+
+        self.target = target;
+        self.changes += 1;
+        Ok(())
+    }
+
+    fn get_changes(&self) -> ComResult<u32>
+    {
+        Ok(self.changes)
+    }
+}
@@ -0,0 +1,65 @@
+use intercom::*;
+
+#[derive(ExternType, ExternInput, ExternOutput, ForeignType, Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Point
+{
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(ExternType, ExternInput, ExternOutput, ForeignType, Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Marker
+{
+    pub visible: bool,
+    pub id: u32,
+    pub position: Point,
+}
+
+#[com_interface]
+pub trait IStructTests
+{
+    fn translate(&self, point: Point, dx: f64, dy: f64) -> ComResult<Point>;
+    fn create_marker(&self, id: u32, position: Point) -> ComResult<Marker>;
+
+    /// Hides the marker in place.
+    ///
+    /// # Safety
+    ///
+    /// The marker must be null or point to a valid `Marker`.
+    unsafe fn hide(&self, marker: *mut Marker) -> ComResult<()>;
+}
+
+#[com_class(IStructTests)]
+#[derive(Default)]
+pub struct StructTests;
+
+impl IStructTests for StructTests
+{
+    fn translate(&self, point: Point, dx: f64, dy: f64) -> ComResult<Point>
+    {
+        Ok(Point {
+            x: point.x + dx,
+            y: point.y + dy,
+        })
+    }
+
+    fn create_marker(&self, id: u32, position: Point) -> ComResult<Marker>
+    {
+        Ok(Marker {
+            visible: true,
+            id,
+            position,
+        })
+    }
+
+    unsafe fn hide(&self, marker: *mut Marker) -> ComResult<()>
+    {
+        match marker.as_mut() {
+            Some(marker) => marker.visible = false,
+            None => return Err(ComError::E_POINTER),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use intercom::*;
+
+#[com_interface]
+pub trait IThreadingTests
+{
+    fn get_value(&self) -> ComResult<u32>;
+    fn add(&mut self, value: u32) -> ComResult<u32>;
+    fn add_through(&mut self, other: &ComItf<dyn IThreadingTests>) -> ComResult<u32>;
+}
+
+#[com_class(threading = "single", IThreadingTests)]
+#[derive(Default)]
+pub struct SingleThreaded
+{
+    value: u32,
+}
+
+#[com_class(threading = "mutex", IThreadingTests)]
+#[derive(Default)]
+pub struct MutexThreaded
+{
+    value: u32,
+}
+
+impl IThreadingTests for SingleThreaded
+{
+    fn get_value(&self) -> ComResult<u32>
+    {
+        Ok(self.value)
+    }
+
+    fn add(&mut self, value: u32) -> ComResult<u32>
+    {
+        let current = self.value;
+        std::thread::yield_now();
+        self.value = current + value;
+        Ok(self.value)
+    }
+
+    fn add_through(&mut self, other: &ComItf<dyn IThreadingTests>) -> ComResult<u32>
+    {
+        self.value += other.get_value()?;
+        Ok(self.value)
+    }
+}
+
+impl IThreadingTests for MutexThreaded
+{
+    fn get_value(&self) -> ComResult<u32>
+    {
+        Ok(self.value)
+    }
+
+    fn add(&mut self, value: u32) -> ComResult<u32>
+    {
+        // Split the update so that unguarded concurrent calls would lose
+        // increments.
+        let current = self.value;
+        std::thread::yield_now();
+        self.value = current + value;
+        Ok(self.value)
+    }
+
+    fn add_through(&mut self, other: &ComItf<dyn IThreadingTests>) -> ComResult<u32>
+    {
+        self.value += other.get_value()?;
+        Ok(self.value)
+    }
+}
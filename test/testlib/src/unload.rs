@@ -0,0 +1,38 @@
+use intercom::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+static UNLOAD_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// Unload hook registered through `com_library!`.
+pub fn on_unload()
+{
+    UNLOAD_COUNT.fetch_add(1, Ordering::SeqCst);
+}
+
+#[com_class(ModuleState)]
+#[derive(Default)]
+pub struct ModuleState;
+
+#[com_interface]
+impl ModuleState
+{
+    pub fn get_object_count(&self) -> u32
+    {
+        intercom::module::object_count() as u32
+    }
+
+    pub fn get_lock_count(&self) -> u32
+    {
+        intercom::module::lock_count() as u32
+    }
+
+    pub fn get_unload_count(&self) -> u32
+    {
+        UNLOAD_COUNT.load(Ordering::SeqCst)
+    }
+
+    pub fn get_live_object_count(&self) -> u32
+    {
+        intercom::leaks::live_objects().len() as u32
+    }
+}